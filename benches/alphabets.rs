@@ -0,0 +1,69 @@
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+use bio::alphabets::dna;
+use bio::alphabets::normalize_uppercase;
+
+fn soft_masked_seq() -> Vec<u8> {
+    b"ACGTacgtNNnnGATTACAgattaca"
+        .iter()
+        .cycle()
+        .take(1_000_000)
+        .cloned()
+        .collect()
+}
+
+#[bench]
+fn bench_normalize_uppercase(b: &mut Bencher) {
+    let mut seq = soft_masked_seq();
+    b.iter(|| {
+        normalize_uppercase(&mut seq);
+        test::black_box(&seq);
+    });
+}
+
+#[bench]
+fn bench_uppercase_lookup_table(b: &mut Bencher) {
+    let mut table = [0; 256];
+    for (i, a) in table.iter_mut().enumerate() {
+        *a = (i as u8).to_ascii_uppercase();
+    }
+    let mut seq = soft_masked_seq();
+    b.iter(|| {
+        for a in seq.iter_mut() {
+            *a = table[*a as usize];
+        }
+        test::black_box(&seq);
+    });
+}
+
+fn chromosome() -> Vec<u8> {
+    b"GATTACAgattacaNNRYACGT"
+        .iter()
+        .cycle()
+        .take(10_000_000)
+        .cloned()
+        .collect()
+}
+
+#[bench]
+fn bench_complement_slice(b: &mut Bencher) {
+    let seq = chromosome();
+    let mut comp = vec![0; seq.len()];
+    b.iter(|| {
+        dna::complement_slice(&seq, &mut comp);
+        test::black_box(&comp);
+    });
+}
+
+#[bench]
+fn bench_complement_per_byte(b: &mut Bencher) {
+    let seq = chromosome();
+    b.iter(|| {
+        let comp: Vec<u8> = seq.iter().map(|&a| dna::complement(a)).collect();
+        test::black_box(comp);
+    });
+}
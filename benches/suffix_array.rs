@@ -10,3 +10,33 @@ use bio::data_structures::suffix_array::*;
 fn bench_suffix_array(b: &mut Bencher) {
     b.iter(|| suffix_array(b"GCCTTAACATTATTACGCCTA$"));
 }
+
+/// A pseudo-random DNA text of 4 MB, large enough for parallel construction.
+#[cfg(feature = "parallel")]
+fn random_text() -> Vec<u8> {
+    let mut x: u64 = 1;
+    let mut text: Vec<u8> = (0..1 << 22)
+        .map(|_| {
+            x = x
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            b"ACGT"[(x >> 62) as usize]
+        })
+        .collect();
+    text.push(b'$');
+    text
+}
+
+#[cfg(feature = "parallel")]
+#[bench]
+fn bench_suffix_array_parallel_1(b: &mut Bencher) {
+    let text = random_text();
+    b.iter(|| suffix_array_parallel(&text, 1));
+}
+
+#[cfg(feature = "parallel")]
+#[bench]
+fn bench_suffix_array_parallel_4(b: &mut Bencher) {
+    let text = random_text();
+    b.iter(|| suffix_array_parallel(&text, 4));
+}
@@ -16,8 +16,9 @@
 //! ```
 
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 
-use crate::alphabets::Alphabet;
+use crate::alphabets::{Alphabet, SymbolSet};
 
 /// The DNA alphabet (uppercase and lowercase).
 pub fn alphabet() -> Alphabet {
@@ -34,25 +35,244 @@ pub fn iupac_alphabet() -> Alphabet {
     Alphabet::new(b"ACGTRYSWKMBDHVNZacgtryswkmbdhvnz")
 }
 
+/// The symbols of `alphabet`.
+pub const SYMBOLS: SymbolSet = SymbolSet::new(b"ACGTacgt");
+
+/// The symbols of `n_alphabet`.
+pub const N_SYMBOLS: SymbolSet = SymbolSet::new(b"ACGTNacgtn");
+
+/// The symbols of `iupac_alphabet`.
+pub const IUPAC_SYMBOLS: SymbolSet = SymbolSet::new(b"ACGTRYSWKMBDHVNZacgtryswkmbdhvnz");
+
+const COMPLEMENT: [u8; 256] = {
+    let mut comp = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        comp[i] = i as u8;
+        i += 1;
+    }
+    let (codes, complements) = (b"AGCTYRWSKMDVHBN", b"TCGARYWSMKHBDVN");
+    let mut i = 0;
+    while i < codes.len() {
+        comp[codes[i] as usize] = complements[i];
+        comp[codes[i] as usize + 32] = complements[i] + 32; // lowercase variants
+        i += 1;
+    }
+    comp
+};
+
+/// The IUPAC DNA alphabet with the gap symbols `-` and `.` (uppercase and lowercase), e.g.
+/// for aligned sequences.
+pub fn gapped_alphabet() -> Alphabet {
+    Alphabet::new(&b"ACGTRYSWKMBDHVNZacgtryswkmbdhvnz-."[..])
+}
+
 lazy_static! {
-    static ref COMPLEMENT: [u8; 256] = {
-        let mut comp = [0; 256];
-        for (v, a) in comp.iter_mut().enumerate() {
+    static ref IDENTITY: [u8; 256] = {
+        let mut identity = [0; 256];
+        for (v, a) in identity.iter_mut().enumerate() {
             *a = v as u8;
         }
-        for (&a, &b) in b"AGCTYRWSKMDVHBN".iter().zip(b"TCGARYWSMKHBDVN".iter()) {
-            comp[a as usize] = b;
-            comp[a as usize + 32] = b + 32;  // lowercase variants
-        }
-        comp
+        identity
     };
 }
 
+/// Return the concrete bases that are represented by the given IUPAC code, preserving the
+/// case (e.g. `R` yields `AG` and `n` yields `acgt`). Any other symbol, including the concrete
+/// bases themselves, is returned as is.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// assert_eq!(dna::iupac_expand(b'R'), b"AG");
+/// assert_eq!(dna::iupac_expand(b'n'), b"acgt");
+/// assert_eq!(dna::iupac_expand(b'C'), b"C");
+/// ```
+pub fn iupac_expand(a: u8) -> &'static [u8] {
+    match a {
+        b'R' => b"AG",
+        b'Y' => b"CT",
+        b'S' => b"CG",
+        b'W' => b"AT",
+        b'K' => b"GT",
+        b'M' => b"AC",
+        b'B' => b"CGT",
+        b'D' => b"AGT",
+        b'H' => b"ACT",
+        b'V' => b"ACG",
+        b'N' => b"ACGT",
+        b'r' => b"ag",
+        b'y' => b"ct",
+        b's' => b"cg",
+        b'w' => b"at",
+        b'k' => b"gt",
+        b'm' => b"ac",
+        b'b' => b"cgt",
+        b'd' => b"agt",
+        b'h' => b"act",
+        b'v' => b"acg",
+        b'n' => b"acgt",
+        _ => &IDENTITY[a as usize..a as usize + 1],
+    }
+}
+
+/// Bitmasks of the concrete bases (A = 1, C = 2, G = 4, T = 8) represented by each IUPAC
+/// code in both cases, and 0 for any other symbol.
+const IUPAC_MASKS: [u8; 256] = {
+    let codes = b"ACGTRYSWKMBDHVN";
+    let masks = [1, 2, 4, 8, 5, 10, 6, 9, 12, 3, 14, 13, 11, 7, 15];
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < codes.len() {
+        table[codes[i] as usize] = masks[i];
+        table[codes[i].to_ascii_lowercase() as usize] = masks[i];
+        i += 1;
+    }
+    table
+};
+
+/// Check whether the two given symbols can denote the same base under IUPAC semantics,
+/// ignoring the case (e.g. `R` is compatible with `A`, `g` and `N`, but not with `C`). Any
+/// other symbol is only compatible with itself.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// assert!(dna::iupac_compatible(b'R', b'a'));
+/// assert!(dna::iupac_compatible(b'N', b'Y'));
+/// assert!(!dna::iupac_compatible(b'R', b'C'));
+/// assert!(dna::iupac_compatible(b'$', b'$'));
+/// ```
+pub fn iupac_compatible(a: u8, b: u8) -> bool {
+    let (mask_a, mask_b) = (IUPAC_MASKS[a as usize], IUPAC_MASKS[b as usize]);
+    if mask_a == 0 || mask_b == 0 {
+        a == b
+    } else {
+        mask_a & mask_b != 0
+    }
+}
+
+/// Number of replacement positions recorded by `SanitizeReport`.
+const MAX_REPORTED_POSITIONS: usize = 10;
+
+/// Summary of the symbols replaced (or removed) during sanitization.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// Number of replacements per replaced symbol.
+    pub counts: BTreeMap<u8, usize>,
+    /// Positions of the first (at most 10) replacements in the original sequence.
+    pub positions: Vec<usize>,
+    /// Number of removed gap symbols (`-`), only used by `sanitize_owned`.
+    pub gaps: usize,
+}
+
+impl SanitizeReport {
+    /// Return the total number of replaced symbols.
+    pub fn replaced(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Check whether the sequence was left unchanged.
+    pub fn is_clean(&self) -> bool {
+        self.counts.is_empty() && self.gaps == 0
+    }
+
+    fn record(&mut self, pos: usize, a: u8) {
+        *self.counts.entry(a).or_insert(0) += 1;
+        if self.positions.len() < MAX_REPORTED_POSITIONS {
+            self.positions.push(pos);
+        }
+    }
+}
+
+/// Return the replacement of a symbol outside of the IUPAC DNA alphabet.
+fn sanitized(a: u8) -> u8 {
+    if a.is_ascii_lowercase() {
+        b'n'
+    } else {
+        b'N'
+    }
+}
+
+/// Replace any symbol of given sequence that is not in `iupac_alphabet` by `N` (or `n` for
+/// lowercase letters), returning which symbols have been replaced.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let mut seq = b"ACG-T*ACx1".to_vec();
+/// let report = dna::sanitize(&mut seq);
+/// assert_eq!(seq, b"ACGNTNACnN");
+/// assert_eq!(report.replaced(), 4);
+/// assert_eq!(report.positions, [3, 5, 8, 9]);
+/// ```
+pub fn sanitize(seq: &mut [u8]) -> SanitizeReport {
+    let mut report = SanitizeReport::default();
+    for (i, a) in seq.iter_mut().enumerate() {
+        if !IUPAC_SYMBOLS.contains(*a) {
+            report.record(i, *a);
+            *a = sanitized(*a);
+        }
+    }
+
+    report
+}
+
+/// Like `sanitize`, but remove gap symbols (`-`) instead of replacing them, e.g. for
+/// aligned sequences. Reported positions refer to the given sequence.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let (seq, report) = dna::sanitize_owned(b"AC--GT-x");
+/// assert_eq!(seq, b"ACGTn");
+/// assert_eq!(report.gaps, 3);
+/// assert_eq!(report.positions, [7]);
+/// ```
+pub fn sanitize_owned(seq: &[u8]) -> (Vec<u8>, SanitizeReport) {
+    let mut report = SanitizeReport::default();
+    let mut sanitized_seq = Vec::with_capacity(seq.len());
+    for (i, &a) in seq.iter().enumerate() {
+        if a == b'-' {
+            report.gaps += 1;
+        } else if !IUPAC_SYMBOLS.contains(a) {
+            report.record(i, a);
+            sanitized_seq.push(sanitized(a));
+        } else {
+            sanitized_seq.push(a);
+        }
+    }
+
+    (sanitized_seq, report)
+}
+
 /// Return complement of given DNA alphabet character (IUPAC alphabet supported).
-pub fn complement(a: u8) -> u8 {
+pub const fn complement(a: u8) -> u8 {
     COMPLEMENT[a as usize]
 }
 
+/// Write the complement of `src` to `dst` (IUPAC alphabet supported), without allocating.
+/// Panics if the slices differ in length.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let mut comp = [0; 7];
+/// dna::complement_slice(b"GATTaca", &mut comp);
+/// assert_eq!(&comp, b"CTAAtgt");
+/// ```
+pub fn complement_slice(src: &[u8], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len(), "Expecting slices of equal length.");
+    for (b, &a) in dst.iter_mut().zip(src) {
+        *b = COMPLEMENT[a as usize];
+    }
+}
+
 /// Calculate reverse complement of given text (IUPAC alphabet supported).
 pub fn revcomp<C, T>(text: T) -> Vec<u8>
 where
@@ -65,3 +285,234 @@ where
         .map(|a| complement(*a.borrow()))
         .collect()
 }
+
+/// Transcribe the given DNA text into RNA, i.e. replace T by U (preserving the case).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// assert_eq!(dna::transcribe(b"GATTaca"), b"GAUUaca");
+/// ```
+pub fn transcribe<C, T>(text: T) -> Vec<u8>
+where
+    C: Borrow<u8>,
+    T: IntoIterator<Item = C>,
+{
+    text.into_iter()
+        .map(|a| match *a.borrow() {
+            b'T' => b'U',
+            b't' => b'u',
+            a => a,
+        })
+        .collect()
+}
+
+/// Complement the given text in place, without reversing it (IUPAC alphabet supported).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let mut seq = b"GATtacaN".to_vec();
+/// dna::comp_inplace(&mut seq);
+/// assert_eq!(seq, b"CTAatgtN");
+/// ```
+pub fn comp_inplace(text: &mut [u8]) {
+    for a in text.iter_mut() {
+        *a = complement(*a);
+    }
+}
+
+/// Calculate reverse complement of given text in place (IUPAC alphabet supported).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let mut seq = b"GATTACA".to_vec();
+/// dna::revcomp_inplace(&mut seq);
+/// assert_eq!(seq, b"TGTAATC");
+/// ```
+pub fn revcomp_inplace(text: &mut [u8]) {
+    text.reverse();
+    comp_inplace(text);
+}
+
+/// Iterate lazily over the reverse complement of given text (IUPAC alphabet supported).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// let rc: Vec<u8> = dna::revcomp_iter(b"GATTACA").collect();
+/// assert_eq!(rc, b"TGTAATC");
+/// ```
+pub fn revcomp_iter(text: &[u8]) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+    text.iter().rev().map(|&a| complement(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODES: [(u8, &[u8]); 15] = [
+        (b'A', b"A"),
+        (b'C', b"C"),
+        (b'G', b"G"),
+        (b'T', b"T"),
+        (b'R', b"AG"),
+        (b'Y', b"CT"),
+        (b'S', b"CG"),
+        (b'W', b"AT"),
+        (b'K', b"GT"),
+        (b'M', b"AC"),
+        (b'B', b"CGT"),
+        (b'D', b"AGT"),
+        (b'H', b"ACT"),
+        (b'V', b"ACG"),
+        (b'N', b"ACGT"),
+    ];
+
+    #[test]
+    fn test_iupac_alphabet() {
+        let alphabet = iupac_alphabet();
+        for &(code, _) in CODES.iter() {
+            assert!(alphabet.is_word([code, code.to_ascii_lowercase()]));
+        }
+    }
+
+    #[test]
+    fn test_iupac_expand() {
+        for &(code, bases) in CODES.iter() {
+            assert_eq!(iupac_expand(code), bases);
+            assert_eq!(
+                iupac_expand(code.to_ascii_lowercase()),
+                &bases.to_ascii_lowercase()[..]
+            );
+        }
+        assert_eq!(iupac_expand(b'$'), b"$");
+    }
+
+    #[test]
+    fn test_iupac_compatible() {
+        for &(a, bases_a) in CODES.iter() {
+            for &(b, bases_b) in CODES.iter() {
+                let expected = bases_a.iter().any(|base| bases_b.contains(base));
+                for &(x, y) in &[
+                    (a, b),
+                    (a.to_ascii_lowercase(), b),
+                    (a, b.to_ascii_lowercase()),
+                    (a.to_ascii_lowercase(), b.to_ascii_lowercase()),
+                ] {
+                    assert_eq!(
+                        iupac_compatible(x, y),
+                        expected,
+                        "{} {}",
+                        x as char,
+                        y as char
+                    );
+                }
+            }
+        }
+
+        let is_code = |a: u8| {
+            CODES
+                .iter()
+                .any(|&(code, _)| code == a.to_ascii_uppercase())
+        };
+        for a in 0..=u8::MAX {
+            for b in 0..=u8::MAX {
+                if !is_code(a) || !is_code(b) {
+                    assert_eq!(iupac_compatible(a, b), a == b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_revcomp_inplace() {
+        let text = b"ACGTRYSWKMBDHVNacgtryswkmbdhvn";
+        // odd and even lengths
+        for len in 0..=text.len() {
+            let mut seq = text[..len].to_vec();
+            revcomp_inplace(&mut seq);
+            assert_eq!(seq, revcomp(&text[..len]));
+            assert_eq!(revcomp_iter(&text[..len]).collect::<Vec<u8>>(), seq);
+            revcomp_inplace(&mut seq);
+            assert_eq!(seq, &text[..len]);
+
+            comp_inplace(&mut seq);
+            assert!(revcomp_iter(&text[..len]).rev().eq(seq.iter().cloned()));
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut seq = b"ACGTRYKMbdhvNZz".to_vec();
+        assert!(sanitize(&mut seq).is_clean());
+        assert_eq!(seq, b"ACGTRYKMbdhvNZz");
+
+        let mut seq = b"AC1G-uT*.x\nXYZ0123456789".to_vec();
+        let report = sanitize(&mut seq);
+        assert_eq!(seq, b"ACNGNnTNNnNNYZNNNNNNNNNN");
+        assert_eq!(report.replaced(), 18);
+        assert_eq!(report.counts[&b'1'], 2);
+        assert_eq!(report.counts[&b'-'], 1);
+        assert_eq!(report.counts.len(), 17);
+        assert_eq!(report.positions, [2, 4, 5, 7, 8, 9, 10, 11, 14, 15]);
+        assert_eq!(report.gaps, 0);
+    }
+
+    #[test]
+    fn test_sanitize_owned() {
+        let (seq, report) = sanitize_owned(b"--ACG-T*a-c--uN---");
+        assert_eq!(seq, b"ACGTNacnN");
+        assert_eq!(report.gaps, 9);
+        assert_eq!(report.replaced(), 2);
+        assert_eq!(report.positions, [7, 13]);
+        assert!(!report.is_clean());
+
+        let (seq, report) = sanitize_owned(b"ACGT");
+        assert_eq!(seq, b"ACGT");
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_complement() {
+        for &(code, bases) in CODES.iter() {
+            let comp = complement(code);
+            let comp_bases: Vec<u8> = iupac_expand(comp).to_vec();
+            let mut expected: Vec<u8> = bases.iter().map(|&a| complement(a)).collect();
+            expected.sort_unstable();
+            assert_eq!(comp_bases, expected);
+            assert_eq!(
+                complement(code.to_ascii_lowercase()),
+                comp.to_ascii_lowercase()
+            );
+        }
+        for a in 0..=u8::MAX {
+            assert_eq!(complement(complement(a)), a);
+        }
+
+        let src: Vec<u8> = (0..=u8::MAX).cycle().take(1000).collect();
+        for len in &[0, 1, 63, 64, 65, 200, 1000] {
+            let mut dst = vec![0; *len];
+            complement_slice(&src[..*len], &mut dst);
+            assert!(dst
+                .iter()
+                .cloned()
+                .eq(src[..*len].iter().map(|&a| complement(a))));
+        }
+    }
+
+    #[test]
+    fn test_gapped() {
+        let seq = b"-ACG.T--a.";
+        assert!(gapped_alphabet().is_word(seq));
+        assert!(!iupac_alphabet().is_word(seq));
+        assert_eq!(revcomp(seq), b".t--A.CGT-");
+        assert_eq!(complement(b'-'), b'-');
+        assert_eq!(complement(b'.'), b'.');
+    }
+}
@@ -15,18 +15,26 @@
 //! ```
 
 use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
 use std::mem;
+use std::ops::Range;
+use std::str::FromStr;
 
 use bit_set::BitSet;
 use vec_map::VecMap;
 
+use crate::data_structures::bitenc::BitEnc;
+
 pub mod dna;
 pub mod protein;
 pub mod rna;
+pub mod translation;
 
 pub type SymbolRanks = VecMap<u8>;
 
 /// Representation of an alphabet.
+#[derive(Clone, PartialEq, Eq)]
 pub struct Alphabet {
     pub symbols: BitSet,
 }
@@ -49,6 +57,59 @@ impl Alphabet {
         self.symbols.insert(a as usize);
     }
 
+    /// Check if given symbol is part of the alphabet.
+    pub fn contains(&self, a: u8) -> bool {
+        self.symbols.contains(a as usize)
+    }
+
+    /// Iterate over the symbols of the alphabet in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.symbols.iter().map(|a| a as u8)
+    }
+
+    /// Return the alphabet of all symbols contained in this or the other alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::{dna, Alphabet};
+    /// // the alphabet of an FMD-index text
+    /// let alphabet = dna::n_alphabet().union(&Alphabet::new(b"$"));
+    /// assert!(alphabet.is_word(b"ACGTN$NACGT$"));
+    /// ```
+    pub fn union(&self, other: &Alphabet) -> Alphabet {
+        let mut symbols = self.symbols.clone();
+        symbols.union_with(&other.symbols);
+
+        Alphabet { symbols }
+    }
+
+    /// Return the alphabet of all symbols contained in both this and the other alphabet.
+    pub fn intersection(&self, other: &Alphabet) -> Alphabet {
+        let mut symbols = self.symbols.clone();
+        symbols.intersect_with(&other.symbols);
+
+        Alphabet { symbols }
+    }
+
+    /// Return the alphabet of all symbols contained in this but not the other alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::{dna, Alphabet};
+    /// let alphabet = dna::n_alphabet()
+    ///     .union(&Alphabet::new(b"-"))
+    ///     .difference(&Alphabet::new(b"Nn"));
+    /// assert_eq!(alphabet, "-ACGTacgt".parse().unwrap());
+    /// ```
+    pub fn difference(&self, other: &Alphabet) -> Alphabet {
+        let mut symbols = self.symbols.clone();
+        symbols.difference_with(&other.symbols);
+
+        Alphabet { symbols }
+    }
+
     /// Check if given text is a word over the alphabet.
     pub fn is_word<C, T>(&self, text: T) -> bool
     where
@@ -59,6 +120,78 @@ impl Alphabet {
             .all(|c| self.symbols.contains(*c.borrow() as usize))
     }
 
+    /// Return the set of symbols as a bitmap, for fast membership tests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// let symbols = dna::alphabet().bitmap();
+    /// assert!(symbols.contains(b'A'));
+    /// assert!(!symbols.contains(b'N'));
+    /// assert_eq!(symbols, dna::SYMBOLS);
+    /// ```
+    pub fn bitmap(&self) -> SymbolSet {
+        let mut bits = [0; 4];
+        for a in self.iter() {
+            bits[a as usize / 64] |= 1 << (a % 64);
+        }
+
+        SymbolSet(bits)
+    }
+
+    /// Return the alphabet containing the symbols of this one in both ASCII cases.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// let alphabet = Alphabet::new(b"ACGT").case_insensitive();
+    /// assert!(alphabet.is_word(b"ACGTacgt"));
+    /// ```
+    pub fn case_insensitive(&self) -> Alphabet {
+        let mut symbols = self.symbols.clone();
+        for a in self.symbols.iter() {
+            symbols.insert((a as u8).to_ascii_lowercase() as usize);
+            symbols.insert((a as u8).to_ascii_uppercase() as usize);
+        }
+
+        Alphabet { symbols }
+    }
+
+    /// Return the position and value of the first symbol of given text that is not part of
+    /// the alphabet.
+    pub fn find_invalid<C, T>(&self, text: T) -> Option<(usize, u8)>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+    {
+        text.into_iter()
+            .map(|c| *c.borrow())
+            .enumerate()
+            .find(|&(_, c)| !self.symbols.contains(c as usize))
+    }
+
+    /// Check if given text is a word over the alphabet, reporting the first symbol that is
+    /// not part of the alphabet otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets;
+    /// let alphabet = alphabets::dna::alphabet();
+    /// assert!(alphabet.validate(b"GATTACA").is_ok());
+    /// let err = alphabet.validate(b"GATXACA").unwrap_err();
+    /// assert_eq!((err.symbol, err.pos), (b'X', 3));
+    /// assert_eq!(err.context, "GATXACA");
+    /// ```
+    pub fn validate(&self, text: &[u8]) -> Result<(), InvalidSymbol> {
+        match self.find_invalid(text) {
+            Some((pos, symbol)) => Err(InvalidSymbol::new(text, pos, symbol)),
+            None => Ok(()),
+        }
+    }
+
     /// Return lexicographically maximal symbol.
     pub fn max_symbol(&self) -> Option<u8> {
         self.symbols.iter().max().map(|a| a as u8)
@@ -75,6 +208,204 @@ impl Alphabet {
     }
 }
 
+/// A set of symbols, stored as a bitmap of 256 bits for branch-free membership tests. Unlike
+/// `Alphabet`, it can be constructed in constant expressions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolSet([u64; 4]);
+
+impl SymbolSet {
+    /// Create the set of the given symbols.
+    pub const fn new(symbols: &[u8]) -> Self {
+        let mut bits = [0; 4];
+        let mut i = 0;
+        while i < symbols.len() {
+            bits[symbols[i] as usize / 64] |= 1 << (symbols[i] % 64);
+            i += 1;
+        }
+
+        SymbolSet(bits)
+    }
+
+    /// Check if given symbol is in the set.
+    #[inline]
+    pub fn contains(&self, a: u8) -> bool {
+        self.0[a as usize / 64] >> (a % 64) & 1 == 1
+    }
+
+    /// Check if given text consists of symbols of the set only.
+    pub fn is_word(&self, text: &[u8]) -> bool {
+        text.iter().all(|&a| self.contains(a))
+    }
+
+    /// Return the position and value of the first symbol of given text that is not in the
+    /// set.
+    pub fn find_invalid(&self, text: &[u8]) -> Option<(usize, u8)> {
+        text.iter()
+            .position(|&a| !self.contains(a))
+            .map(|pos| (pos, text[pos]))
+    }
+
+    /// Return the alphabet of the symbols in the set.
+    pub fn to_alphabet(&self) -> Alphabet {
+        Alphabet::new((0..=u8::MAX).filter(|&a| self.contains(a)))
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = std::convert::Infallible;
+
+    /// Create an alphabet from the bytes of the given string, e.g. `"ACGTN$".parse()`.
+    fn from_str(symbols: &str) -> Result<Self, Self::Err> {
+        Ok(Alphabet::new(symbols.as_bytes()))
+    }
+}
+
+impl fmt::Debug for Alphabet {
+    /// Print the symbols in increasing order, escaping non-printable ones.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbols: String = self
+            .iter()
+            .flat_map(std::ascii::escape_default)
+            .map(char::from)
+            .collect();
+        f.debug_struct("Alphabet")
+            .field("symbols", &symbols)
+            .finish()
+    }
+}
+
+/// Convert the given sequence to uppercase in place. Use `MaskedIntervals` to keep the
+/// soft-masked (lowercase) regions. This is a plain ASCII conversion, which the compiler
+/// vectorizes, and benchmarked to be several times faster than a lookup table (see
+/// `benches/alphabets.rs`).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets;
+/// let mut seq = b"ACGTacgtNn".to_vec();
+/// alphabets::normalize_uppercase(&mut seq);
+/// assert_eq!(seq, b"ACGTACGTNN");
+/// ```
+pub fn normalize_uppercase(seq: &mut [u8]) {
+    seq.make_ascii_uppercase();
+}
+
+/// Remove the gap symbols `-` and `.` from the given aligned sequence. Returns the ungapped
+/// sequence and, for each of its positions, the corresponding column of the aligned
+/// sequence, e.g. to project hits in the ungapped sequence back onto the alignment.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets;
+/// let (seq, columns) = alphabets::degap(b"-AC..G-T");
+/// assert_eq!(seq, b"ACGT");
+/// assert_eq!(columns, [1, 2, 5, 7]);
+/// ```
+pub fn degap(seq: &[u8]) -> (Vec<u8>, Vec<usize>) {
+    seq.iter()
+        .enumerate()
+        .filter(|&(_, &a)| a != b'-' && a != b'.')
+        .map(|(column, &a)| (a, column))
+        .unzip()
+}
+
+/// The maximal runs of lowercase (i.e. soft-masked) symbols of a sequence, ordered by
+/// position, which allows to restore the masking after `normalize_uppercase`.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::{self, MaskedIntervals};
+/// let mut seq = b"ACgtACGTaa".to_vec();
+/// let masked = MaskedIntervals::new(&seq);
+/// assert_eq!(masked.intervals, [2..4, 8..10]);
+///
+/// alphabets::normalize_uppercase(&mut seq);
+/// masked.apply(&mut seq);
+/// assert_eq!(seq, b"ACgtACGTaa");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaskedIntervals {
+    pub intervals: Vec<Range<usize>>,
+}
+
+impl MaskedIntervals {
+    /// Record the lowercase runs of the given sequence.
+    pub fn new(seq: &[u8]) -> Self {
+        let mut intervals: Vec<Range<usize>> = Vec::new();
+        for (i, a) in seq.iter().enumerate() {
+            if !a.is_ascii_lowercase() {
+                continue;
+            }
+            match intervals.last_mut() {
+                Some(interval) if interval.end == i => interval.end += 1,
+                _ => intervals.push(i..i + 1),
+            }
+        }
+
+        MaskedIntervals { intervals }
+    }
+
+    /// Return the number of masked symbols.
+    pub fn masked_len(&self) -> usize {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    /// Convert the recorded runs of the given sequence to lowercase.
+    pub fn apply(&self, seq: &mut [u8]) {
+        for interval in &self.intervals {
+            seq[interval.clone()].make_ascii_lowercase();
+        }
+    }
+}
+
+/// Number of symbols shown on either side of an invalid symbol in `InvalidSymbol::context`.
+const CONTEXT_LEN: usize = 10;
+
+/// Error for a symbol of a text not being part of an alphabet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidSymbol {
+    /// The invalid symbol.
+    pub symbol: u8,
+    /// The position of the invalid symbol in the text.
+    pub pos: usize,
+    /// The text around the invalid symbol, with non-printable symbols escaped.
+    pub context: String,
+}
+
+impl InvalidSymbol {
+    pub(crate) fn new(text: &[u8], pos: usize, symbol: u8) -> Self {
+        let window = &text[pos.saturating_sub(CONTEXT_LEN)..text.len().min(pos + CONTEXT_LEN + 1)];
+        let context = window
+            .iter()
+            .flat_map(|&c| std::ascii::escape_default(c))
+            .map(char::from)
+            .collect();
+
+        InvalidSymbol {
+            symbol,
+            pos,
+            context,
+        }
+    }
+}
+
+impl fmt::Display for InvalidSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "symbol '{}' at position {} is not part of the alphabet (in \"{}\")",
+            std::ascii::escape_default(self.symbol),
+            self.pos,
+            self.context
+        )
+    }
+}
+
+impl Error for InvalidSymbol {}
+
 /// Tools based on transforming the alphabet symbols to their lexicographical ranks.
 #[derive(Serialize, Deserialize)]
 pub struct RankTransform {
@@ -94,7 +425,40 @@ impl RankTransform {
 
     /// Get the rank of symbol `a`.
     pub fn get(&self, a: u8) -> u8 {
-        *self.ranks.get(a as usize).expect("Unexpected character.")
+        self.rank(a)
+    }
+
+    /// Get the rank of symbol `a`, i.e. the number of smaller symbols in the alphabet.
+    /// Panics if `a` is not in the alphabet.
+    pub fn rank(&self, a: u8) -> u8 {
+        match self.ranks.get(a as usize) {
+            Some(&r) => r,
+            None => panic!("Unexpected symbol {:?} not in alphabet.", a as char),
+        }
+    }
+
+    /// Get the symbol of the given rank, i.e. the inverse of `rank`. Panics if the rank is not
+    /// smaller than the alphabet size.
+    pub fn symbol(&self, rank: u8) -> u8 {
+        match self.ranks.keys().nth(rank as usize) {
+            Some(a) => a as u8,
+            None => panic!(
+                "Rank {} out of range for alphabet of size {}.",
+                rank,
+                self.ranks.len()
+            ),
+        }
+    }
+
+    /// Return the number of bits needed to store a symbol rank, i.e. log2(|A|) rounded up.
+    pub fn bits_per_symbol(&self) -> u32 {
+        (self.ranks.len() as f32).log2().ceil() as u32
+    }
+
+    /// Return the number of distinct encodings of q-grams as returned by `qgrams`, i.e. the
+    /// size of a table indexed by them.
+    pub fn frame_size(&self, q: u32) -> usize {
+        1 << (q * self.bits_per_symbol())
     }
 
     /// Transform a given `text`.
@@ -103,14 +467,7 @@ impl RankTransform {
         C: Borrow<u8>,
         T: IntoIterator<Item = C>,
     {
-        text.into_iter()
-            .map(|c| {
-                *self
-                    .ranks
-                    .get(*c.borrow() as usize)
-                    .expect("Unexpected character in text.")
-            })
-            .collect()
+        text.into_iter().map(|c| self.rank(*c.borrow())).collect()
     }
 
     /// Iterate over q-grams (substrings of length q) of given `text`. The q-grams are encoded
@@ -122,7 +479,7 @@ impl RankTransform {
         C: Borrow<u8>,
         T: IntoIterator<Item = C>,
     {
-        let bits = (self.ranks.len() as f32).log2().ceil() as u32;
+        let bits = self.bits_per_symbol();
         assert!(
             (bits * q) as usize <= mem::size_of::<usize>() * 8,
             "Expecting q to be smaller than usize / log2(|A|)"
@@ -151,6 +508,85 @@ impl RankTransform {
     }
 }
 
+/// A fixed-width bit encoding of the symbols of an alphabet by their ranks, using the
+/// minimal number of bits per symbol.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::{Alphabet, Encoding};
+/// let encoding = Encoding::for_alphabet(&Alphabet::new(b"ACGTN$"));
+/// assert_eq!(encoding.width(), 3);
+/// let bitenc = encoding.encode(b"GATTACA$").unwrap();
+/// assert_eq!(encoding.decode(&bitenc), b"GATTACA$");
+/// assert!(encoding.encode(b"GATU").is_err());
+/// ```
+pub struct Encoding {
+    ranks: RankTransform,
+    width: usize,
+    fallback: Option<u8>,
+}
+
+impl Encoding {
+    /// Create the encoding for the given alphabet.
+    pub fn for_alphabet(alphabet: &Alphabet) -> Self {
+        let ranks = RankTransform::new(alphabet);
+        let width = (ranks.bits_per_symbol() as usize).max(1);
+        assert!(width <= 8, "Only encoding widths up to 8 supported");
+
+        Encoding {
+            ranks,
+            width,
+            fallback: None,
+        }
+    }
+
+    /// Encode symbols that are not in the alphabet like the given symbol of the alphabet
+    /// (e.g. `N`), instead of failing on them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::{Alphabet, Encoding};
+    /// let encoding = Encoding::for_alphabet(&Alphabet::new(b"ACGTN")).fallback(b'N');
+    /// let bitenc = encoding.encode(b"ACRYT").unwrap();
+    /// assert_eq!(encoding.decode(&bitenc), b"ACNNT");
+    /// ```
+    pub fn fallback(mut self, symbol: u8) -> Self {
+        self.fallback = Some(self.ranks.rank(symbol));
+        self
+    }
+
+    /// Return the number of bits per symbol.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the code of the given symbol, or `None` if it cannot be encoded.
+    pub fn rank(&self, a: u8) -> Option<u8> {
+        self.ranks.ranks.get(a as usize).cloned().or(self.fallback)
+    }
+
+    /// Encode the given text, failing on the first symbol not in the alphabet unless a
+    /// fallback is set.
+    pub fn encode(&self, text: &[u8]) -> Result<BitEnc, InvalidSymbol> {
+        let mut bitenc = BitEnc::with_capacity(self.width, text.len());
+        for (pos, &a) in text.iter().enumerate() {
+            match self.rank(a) {
+                Some(r) => bitenc.push(r),
+                None => return Err(InvalidSymbol::new(text, pos, a)),
+            }
+        }
+
+        Ok(bitenc)
+    }
+
+    /// Decode the given encoded text.
+    pub fn decode(&self, bitenc: &BitEnc) -> Vec<u8> {
+        bitenc.iter().map(|r| self.ranks.symbol(r)).collect()
+    }
+}
+
 /// Iterator over q-grams.
 pub struct QGrams<'a, C, T>
 where
@@ -195,13 +631,246 @@ where
     }
 }
 
-#[cfg(tests)]
+#[cfg(test)]
 mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, StdRng};
+
     #[test]
     fn test_serde() {
         use serde::{Deserialize, Serialize};
-        fn impls_serde_traits<S: Serialize + Deserialize>() {}
+        fn impls_serde_traits<S: Serialize + for<'de> Deserialize<'de>>() {}
 
         impls_serde_traits::<RankTransform>();
     }
+
+    fn check_roundtrip(alphabet: &Alphabet, text: &[u8]) {
+        let ranks = RankTransform::new(alphabet);
+        let transformed = ranks.transform(text);
+        assert!(transformed.iter().all(|&r| (r as usize) < alphabet.len()));
+        let restored: Vec<u8> = transformed.iter().map(|&r| ranks.symbol(r)).collect();
+        assert_eq!(restored, text);
+        for (r, a) in alphabet.symbols.iter().enumerate() {
+            assert_eq!(ranks.rank(a as u8), r as u8);
+            assert_eq!(ranks.symbol(r as u8), a as u8);
+        }
+    }
+
+    #[test]
+    fn test_rank_transform() {
+        check_roundtrip(&dna::alphabet(), b"GATTACAgattaca");
+        check_roundtrip(&protein::alphabet(), b"MKVLAAGIVGLLLAWESSQ");
+        // non-contiguous symbols
+        check_roundtrip(
+            &Alphabet::new([0, 7, b'$', 200, 255]),
+            &[255, 0, 200, 7, b'$', 0],
+        );
+
+        let ranks = RankTransform::new(&dna::alphabet());
+        assert_eq!(ranks.transform(b"ACGTacgt"), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(ranks.bits_per_symbol(), 3);
+        assert_eq!(ranks.frame_size(2), 64);
+        let ranks = RankTransform::new(&protein::alphabet());
+        assert_eq!(ranks.bits_per_symbol(), 6);
+        let ranks = RankTransform::new(&Alphabet::new(b"A"));
+        assert_eq!(ranks.bits_per_symbol(), 0);
+        assert_eq!(ranks.frame_size(3), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in alphabet")]
+    fn test_rank_transform_unexpected_symbol() {
+        RankTransform::new(&dna::alphabet()).transform(b"ACGU");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_rank_transform_unexpected_rank() {
+        RankTransform::new(&dna::alphabet()).symbol(8);
+    }
+
+    #[test]
+    fn test_validate() {
+        let alphabet = dna::alphabet();
+        assert!(alphabet.validate(b"").is_ok());
+        assert!(alphabet.validate(b"ACGTacgt").is_ok());
+        assert_eq!(alphabet.find_invalid(b"ACGTacgt"), None);
+
+        let text = b"NACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTACGT";
+        let err = alphabet.validate(text).unwrap_err();
+        assert_eq!((err.symbol, err.pos), (b'N', 0));
+        assert_eq!(err.context, "NACGTACGTAC");
+        assert_eq!(alphabet.find_invalid(text.iter()), Some((0, b'N')));
+
+        let mut text = text.to_vec();
+        text[0] = b'A';
+        text[25] = b'\n';
+        let err = alphabet.validate(&text).unwrap_err();
+        assert_eq!((err.symbol, err.pos), (b'\n', 25));
+        assert_eq!(err.context, "GTACGTACGT\\nCGTACGTACG");
+        assert_eq!(
+            err.to_string(),
+            "symbol '\\n' at position 25 is not part of the alphabet (in \"GTACGTACGT\\nCGTACGTACG\")"
+        );
+
+        text[25] = b'C';
+        let last = text.len() - 1;
+        text[last] = b'-';
+        let err = alphabet.validate(&text).unwrap_err();
+        assert_eq!((err.symbol, err.pos), (b'-', last));
+        assert_eq!(err.context, "CGTACGTACG-");
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let alphabet = Alphabet::new(b"ACGT$").case_insensitive();
+        assert!(alphabet.is_word(b"acgt"));
+        assert!(alphabet.is_word(b"ACGT$"));
+        assert_eq!(alphabet.len(), 9);
+        assert_eq!(dna::alphabet().case_insensitive().len(), 8);
+    }
+
+    #[test]
+    fn test_normalize_uppercase() {
+        let mut all: Vec<u8> = (0..=u8::MAX).collect();
+        normalize_uppercase(&mut all);
+        for (a, b) in (0..=u8::MAX).zip(all) {
+            assert_eq!(a.to_ascii_uppercase(), b);
+        }
+
+        for seq in [&b""[..], b"ACGT", b"acgt", b"aCGtnNNnnA", b"NNNNacgt$"] {
+            let masked = MaskedIntervals::new(seq);
+            assert_eq!(
+                masked.masked_len(),
+                seq.iter().filter(|a| a.is_ascii_lowercase()).count()
+            );
+            let mut normalized = seq.to_vec();
+            normalize_uppercase(&mut normalized);
+            assert!(!normalized.iter().any(|a| a.is_ascii_lowercase()));
+            masked.apply(&mut normalized);
+            assert_eq!(normalized, seq);
+        }
+        assert_eq!(
+            MaskedIntervals::new(b"aCGtnNNnnA").intervals,
+            [0..1, 3..5, 7..9]
+        );
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let dna = dna::alphabet();
+        let rna = rna::alphabet();
+        assert_eq!(dna.union(&rna), "ACGTUacgtu".parse().unwrap());
+        assert_eq!(dna.intersection(&rna), "ACGacg".parse().unwrap());
+        assert_eq!(dna.difference(&rna), "Tt".parse().unwrap());
+        assert_eq!(rna.difference(&dna), "Uu".parse().unwrap());
+        assert_eq!(
+            dna::iupac_alphabet().intersection(&protein::alphabet()),
+            "ACDGHKMNRSTVWYacdghkmnrstvwy".parse().unwrap()
+        );
+        assert!(dna.difference(&dna.union(&rna)).is_empty());
+        assert_eq!(dna.union(&dna), dna);
+
+        assert!(dna.contains(b'A'));
+        assert!(!dna.contains(b'U'));
+        assert_eq!(dna.iter().collect::<Vec<u8>>(), b"ACGTacgt");
+        assert_eq!(dna.len(), 8);
+    }
+
+    #[test]
+    fn test_debug() {
+        let alphabet = Alphabet::new(b"T$\tA");
+        assert_eq!(
+            format!("{:?}", alphabet),
+            "Alphabet { symbols: \"\\\\t$AT\" }"
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let widths = [
+            (Alphabet::new(b"ACGT"), 2),
+            (Alphabet::new(b"ACGTN$"), 3),
+            (
+                protein::alphabet().intersection(&Alphabet::new(b'A'..=b'Z')),
+                5,
+            ),
+            (Alphabet::new(b"A"), 1),
+        ];
+        let mut rng = StdRng::from_seed(&[29]);
+        for (alphabet, width) in &widths {
+            let encoding = Encoding::for_alphabet(alphabet);
+            assert_eq!(encoding.width(), *width);
+            let symbols: Vec<u8> = alphabet.iter().collect();
+            for _ in 0..50 {
+                let text: Vec<u8> = (0..rng.gen_range(0, 100))
+                    .map(|_| *rng.choose(&symbols).unwrap())
+                    .collect();
+                let bitenc = encoding.encode(&text).unwrap();
+                assert_eq!(bitenc.len(), text.len());
+                assert_eq!(encoding.decode(&bitenc), text);
+            }
+        }
+
+        let encoding = Encoding::for_alphabet(&Alphabet::new(b"ACGT"));
+        let err = encoding.encode(b"ACGTN").err().unwrap();
+        assert_eq!((err.symbol, err.pos), (b'N', 4));
+    }
+
+    #[test]
+    fn test_degap() {
+        assert_eq!(degap(b""), (vec![], vec![]));
+        assert_eq!(degap(b"--.-"), (vec![], vec![]));
+        assert_eq!(degap(b"ACGT"), (b"ACGT".to_vec(), vec![0, 1, 2, 3]));
+        // leading, internal and trailing gaps
+        let aligned = b"..-AC-G.-TTa--";
+        let (seq, columns) = degap(aligned);
+        assert_eq!(seq, b"ACGTTa");
+        assert_eq!(columns, [3, 4, 6, 9, 10, 11]);
+        for (i, &column) in columns.iter().enumerate() {
+            assert_eq!(aligned[column], seq[i]);
+        }
+    }
+
+    #[test]
+    fn test_symbol_sets() {
+        let builtin = [
+            (dna::alphabet(), dna::SYMBOLS),
+            (dna::n_alphabet(), dna::N_SYMBOLS),
+            (dna::iupac_alphabet(), dna::IUPAC_SYMBOLS),
+            (rna::alphabet(), rna::SYMBOLS),
+            (rna::n_alphabet(), rna::N_SYMBOLS),
+            (rna::iupac_alphabet(), rna::IUPAC_SYMBOLS),
+            (protein::alphabet(), protein::SYMBOLS),
+            (protein::iupac_alphabet(), protein::IUPAC_SYMBOLS),
+        ];
+        for (alphabet, symbols) in &builtin {
+            assert_eq!(alphabet.bitmap(), *symbols);
+            assert_eq!(&symbols.to_alphabet(), alphabet);
+            for a in 0..=u8::MAX {
+                assert_eq!(symbols.contains(a), alphabet.is_word([a]));
+            }
+        }
+        let symbols = SymbolSet::new(&[0, 63, 64, 255]);
+        assert!(symbols.is_word(&[255, 0, 64, 63]));
+        assert_eq!(symbols.find_invalid(&[0, 64, 65]), Some((2, 65)));
+        assert_eq!(symbols.find_invalid(&[0, 64]), None);
+    }
+
+    #[test]
+    fn test_qgrams() {
+        let ranks = RankTransform::new(&Alphabet::new(b"ACGT"));
+        let qgrams: Vec<usize> = ranks.qgrams(2, b"ACGTA").collect();
+        assert_eq!(qgrams, [0b0001, 0b0110, 0b1011, 0b1100]);
+        assert!(qgrams.iter().all(|&qgram| qgram < ranks.frame_size(2)));
+
+        // equal q-grams get equal keys, also over a non-contiguous alphabet
+        let ranks = RankTransform::new(&Alphabet::new([3, 90, 250]));
+        let text = [3, 250, 90, 3, 250, 90, 90];
+        let qgrams: Vec<usize> = ranks.qgrams(3, &text).collect();
+        assert_eq!(qgrams.len(), 5);
+        assert_eq!(qgrams[0], qgrams[3]);
+        assert_ne!(qgrams[0], qgrams[1]);
+        assert!(qgrams.iter().all(|&qgram| qgram < ranks.frame_size(3)));
+    }
 }
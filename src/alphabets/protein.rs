@@ -12,15 +12,35 @@
 //! let alphabet = alphabets::protein::alphabet();
 //! assert!(alphabet.is_word(b"DEQsga"));
 //! assert!(!alphabet.is_word(b"BzJ"));
+//! assert!(alphabets::protein::iupac_alphabet().is_word(b"BzJ*"));
 //! ```
 
-use crate::alphabets::Alphabet;
+use crate::alphabets::{Alphabet, SymbolSet};
 
 /// Returns the standard protein alphabet, containing the 20 common amino acids.
 pub fn alphabet() -> Alphabet {
     Alphabet::new(&b"ARNDCEQGHILKMFPSTWYVarndceqghilkmfpstwyv"[..])
 }
 
+/// Returns the IUPAC protein alphabet, adding the ambiguity codes B (D or N), Z (E or Q),
+/// J (I or L) and X (any), selenocysteine (U), pyrrolysine (O) and the stop symbol `*`.
+pub fn iupac_alphabet() -> Alphabet {
+    Alphabet::new(&b"ARNDCEQGHILKMFPSTWYVBZJXUOarndceqghilkmfpstwyvbzjxuo*"[..])
+}
+
+/// The symbols of `alphabet`.
+pub const SYMBOLS: SymbolSet = SymbolSet::new(b"ARNDCEQGHILKMFPSTWYVarndceqghilkmfpstwyv");
+
+/// The symbols of `iupac_alphabet`.
+pub const IUPAC_SYMBOLS: SymbolSet =
+    SymbolSet::new(b"ARNDCEQGHILKMFPSTWYVBZJXUOarndceqghilkmfpstwyvbzjxuo*");
+
+/// Returns the IUPAC protein alphabet with the gap symbols `-` and `.`, e.g. for aligned
+/// sequences.
+pub fn gapped_alphabet() -> Alphabet {
+    Alphabet::new(&b"ARNDCEQGHILKMFPSTWYVBZJXUOarndceqghilkmfpstwyvbzjxuo*-."[..])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +64,16 @@ mod tests {
     fn number_is_no_word() {
         assert!(!alphabet().is_word(b"42"));
     }
+
+    #[test]
+    fn iupac_is_word() {
+        assert!(iupac_alphabet().is_word(b"MKVLUOxbz*"));
+        assert!(!iupac_alphabet().is_word(b"MK-"));
+    }
+
+    #[test]
+    fn gapped_is_word() {
+        assert!(gapped_alphabet().is_word(b"MK-V.L*"));
+        assert!(!iupac_alphabet().is_word(b"MK-V.L*"));
+    }
 }
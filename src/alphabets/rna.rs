@@ -17,7 +17,7 @@
 
 use std::borrow::Borrow;
 
-use crate::alphabets::Alphabet;
+use crate::alphabets::{Alphabet, SymbolSet};
 
 /// The RNA alphabet (uppercase and lowercase).
 pub fn alphabet() -> Alphabet {
@@ -34,6 +34,15 @@ pub fn iupac_alphabet() -> Alphabet {
     Alphabet::new(b"ACGURYSWKMBDHVNZacguryswkmbdhvnz")
 }
 
+/// The symbols of `alphabet`.
+pub const SYMBOLS: SymbolSet = SymbolSet::new(b"ACGUacgu");
+
+/// The symbols of `n_alphabet`.
+pub const N_SYMBOLS: SymbolSet = SymbolSet::new(b"ACGUNacgun");
+
+/// The symbols of `iupac_alphabet`.
+pub const IUPAC_SYMBOLS: SymbolSet = SymbolSet::new(b"ACGURYSWKMBDHVNZacguryswkmbdhvnz");
+
 lazy_static! {
     static ref COMPLEMENT: [u8; 256] = {
         let mut comp = [0; 256];
@@ -66,9 +75,32 @@ where
         .collect()
 }
 
+/// Reverse transcribe the given RNA text into DNA, i.e. replace U by T (preserving the case).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::rna;
+/// assert_eq!(rna::reverse_transcribe(b"GAUUaca"), b"GATTaca");
+/// ```
+pub fn reverse_transcribe<C, T>(text: T) -> Vec<u8>
+where
+    C: Borrow<u8>,
+    T: IntoIterator<Item = C>,
+{
+    text.into_iter()
+        .map(|a| match *a.borrow() {
+            b'U' => b'T',
+            b'u' => b't',
+            a => a,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alphabets::dna;
 
     #[test]
     fn is_word() {
@@ -93,5 +125,19 @@ mod tests {
     #[test]
     fn test_reverse_complement() {
         assert_eq!(revcomp(b"GAUUACA"), b"UGUAAUC");
+        assert_eq!(revcomp(b"gauNaca"), b"uguNauc");
+        let text = b"ACGUNacgunRYKMBDHV";
+        assert_eq!(revcomp(revcomp(text)), text);
+    }
+
+    #[test]
+    fn test_transcription() {
+        let dna_text = b"GATTACAnNtgca";
+        let rna_text = dna::transcribe(dna_text);
+        assert_eq!(rna_text, b"GAUUACAnNugca");
+        assert!(n_alphabet().is_word(&rna_text));
+        assert_eq!(reverse_transcribe(&rna_text), dna_text);
+        // complementing commutes with transcription
+        assert_eq!(revcomp(&rna_text), dna::transcribe(dna::revcomp(dna_text)));
     }
 }
@@ -0,0 +1,371 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Genetic codes and the translation between DNA and protein sequences.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::translation::{back_translate, CodonTable};
+//! let table = CodonTable::standard();
+//! assert_eq!(table.amino_acid(b"ATG"), Some(b'M'));
+//! // a degenerate DNA pattern matching all codons of each amino acid
+//! assert_eq!(back_translate(b"MKL*", &table), b"ATGAARYTNTRR");
+//! ```
+
+use crate::alphabets::dna;
+
+/// The bases in the order used by NCBI genetic code strings.
+const BASES: &[u8; 4] = b"TCAG";
+
+/// The IUPAC code of each set of bases, given as bitmask with A = 1, C = 2, G = 4, T = 8.
+const IUPAC_CODES: &[u8; 16] = b"-ACMGRSVTWYHKDBN";
+
+/// The NCBI genetic codes supported by `CodonTable::from_ncbi_id`, given as id, amino acids
+/// and starts.
+const NCBI_TABLES: [(u32, &str, &str); 6] = [
+    (
+        1,
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M---------------M----------------------------",
+    ),
+    (
+        2,
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        "----------**--------------------MMMM----------**---M------------",
+    ),
+    (
+        4,
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--MM------**-------M------------MMMM---------------M------------",
+    ),
+    (
+        5,
+        "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        "---M------**--------------------MMMM---------------M------------",
+    ),
+    (
+        6,
+        "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "--------------*--------------------M----------------------------",
+    ),
+    (
+        11,
+        "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        "---M------**--*----M------------MMMM---------------M------------",
+    ),
+];
+
+quick_error! {
+    #[derive(Debug, PartialEq)]
+    pub enum CodonTableError {
+        InvalidLength(len: usize) {
+            description("genetic code string does not cover all 64 codons")
+            display("expecting 64 symbols in genetic code string, found {}", len)
+        }
+        UnknownId(id: u32) {
+            description("unsupported NCBI genetic code id")
+            display("NCBI genetic code {} is not supported", id)
+        }
+    }
+}
+
+/// A genetic code, assigning an amino acid (or the stop symbol `*`) to each codon and
+/// marking the codons that can serve as start codons.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodonTable {
+    amino_acids: [u8; 64],
+    starts: [bool; 64],
+}
+
+impl CodonTable {
+    /// The standard genetic code (NCBI translation table 1).
+    pub fn standard() -> Self {
+        CodonTable::from_ncbi_id(1).unwrap()
+    }
+
+    /// Return the genetic code of the given NCBI translation table id. Supported are the
+    /// standard code (1), the vertebrate mitochondrial code (2), the mold, protozoan and
+    /// coelenterate mitochondrial code (4), the invertebrate mitochondrial code (5), the
+    /// ciliate nuclear code (6) and the bacterial, archaeal and plant plastid code (11).
+    pub fn from_ncbi_id(id: u32) -> Result<Self, CodonTableError> {
+        match NCBI_TABLES.iter().find(|table| table.0 == id) {
+            Some(&(_, amino_acids, starts)) => CodonTable::from_ncbi(amino_acids, starts),
+            None => Err(CodonTableError::UnknownId(id)),
+        }
+    }
+
+    /// Create a genetic code from the NCBI-style strings of amino acids and starts, listing
+    /// the codons in the order TTT, TTC, TTA, TTG, TCT, ..., GGG. Codons marked with `M` in
+    /// the starts string are start codons.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::translation::CodonTable;
+    /// // NCBI translation table 2, the vertebrate mitochondrial code
+    /// let table = CodonTable::from_ncbi(
+    ///     "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+    ///     "----------**--------------------MMMM----------**---M------------",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(table.amino_acid(b"AGA"), Some(b'*'));
+    /// assert!(table.is_start(b"ATA"));
+    /// ```
+    pub fn from_ncbi(amino_acids: &str, starts: &str) -> Result<Self, CodonTableError> {
+        for s in &[amino_acids, starts] {
+            if s.len() != 64 {
+                return Err(CodonTableError::InvalidLength(s.len()));
+            }
+        }
+        let mut table = CodonTable {
+            amino_acids: [0; 64],
+            starts: [false; 64],
+        };
+        table.amino_acids.copy_from_slice(amino_acids.as_bytes());
+        for (start, &c) in table.starts.iter_mut().zip(starts.as_bytes()) {
+            *start = c == b'M';
+        }
+
+        Ok(table)
+    }
+
+    /// Return the amino acid encoded by the given codon of concrete bases (case-insensitive,
+    /// with U being treated as T), or `None` if it is not such a codon.
+    pub fn amino_acid(&self, codon: &[u8]) -> Option<u8> {
+        codon_index(codon).map(|i| self.amino_acids[i])
+    }
+
+    /// Check whether the given codon of concrete bases is a start codon.
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.starts[i])
+    }
+
+    /// Return the amino acid encoded by the given codon, which may contain IUPAC codes
+    /// (case-insensitive, with U being treated as T). An ambiguous codon is translated if all
+    /// of the codons it represents encode the same amino acid (e.g. `GCN` yields `A`), and to
+    /// `X` otherwise.
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        let mut amino_acid = None;
+        if codon.len() == 3 {
+            let bases = |a: u8| match a.to_ascii_uppercase() {
+                b'U' => b"T",
+                a => dna::iupac_expand(a),
+            };
+            for &a in bases(codon[0]) {
+                for &b in bases(codon[1]) {
+                    for &c in bases(codon[2]) {
+                        match (self.amino_acid(&[a, b, c]), amino_acid) {
+                            (Some(x), None) => amino_acid = Some(x),
+                            (Some(x), Some(y)) if x == y => (),
+                            _ => return b'X',
+                        }
+                    }
+                }
+            }
+        }
+
+        amino_acid.unwrap_or(b'X')
+    }
+
+    /// Return all codons encoding the given amino acid (or the stop symbol `*`),
+    /// case-insensitive.
+    pub fn codons(&self, amino_acid: u8) -> Vec<[u8; 3]> {
+        let amino_acid = amino_acid.to_ascii_uppercase();
+        (0..64)
+            .filter(|&i| self.amino_acids[i] == amino_acid)
+            .map(|i| [BASES[i / 16], BASES[i / 4 % 4], BASES[i % 4]])
+            .collect()
+    }
+}
+
+/// Return the index of the given codon of concrete bases in the NCBI order.
+fn codon_index(codon: &[u8]) -> Option<usize> {
+    if codon.len() != 3 {
+        return None;
+    }
+    codon.iter().try_fold(0, |i, &a| {
+        let base = match a.to_ascii_uppercase() {
+            b'U' => b'T',
+            base => base,
+        };
+        BASES.iter().position(|&b| b == base).map(|r| 4 * i + r)
+    })
+}
+
+/// Return the bitmask of a concrete base as used by `IUPAC_CODES`.
+fn base_mask(a: u8) -> usize {
+    match a {
+        b'A' => 1,
+        b'C' => 2,
+        b'G' => 4,
+        _ => 8,
+    }
+}
+
+/// Translate the given DNA sequence (see `CodonTable::translate_codon`) codon by codon,
+/// ignoring a trailing partial codon.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::translation::{translate, CodonTable};
+/// let table = CodonTable::standard();
+/// assert_eq!(translate(b"ATGGCNtggNNNTAAGC", &table), b"MAWX*");
+/// ```
+pub fn translate(dna: &[u8], table: &CodonTable) -> Vec<u8> {
+    dna.chunks_exact(3)
+        .map(|codon| table.translate_codon(codon))
+        .collect()
+}
+
+/// Translate the given DNA sequence in all six reading frames, i.e. starting at offsets
+/// 0, 1 and 2 of the sequence and of its reverse complement (in this order).
+pub fn six_frame_translations(dna: &[u8], table: &CodonTable) -> [Vec<u8>; 6] {
+    let revcomp = dna::revcomp(dna);
+    let frame = |seq: &[u8], offset: usize| translate(seq.get(offset..).unwrap_or(&[]), table);
+
+    [
+        frame(dna, 0),
+        frame(dna, 1),
+        frame(dna, 2),
+        frame(&revcomp, 0),
+        frame(&revcomp, 1),
+        frame(&revcomp, 2),
+    ]
+}
+
+/// Back-translate the given peptide into a degenerate DNA pattern of three times its
+/// length. Each amino acid (or stop symbol `*`) is replaced by the minimal degenerate
+/// codon matching all of its codons, i.e. the IUPAC code of the bases at each codon
+/// position (e.g. `YTN` for leucine). Since positions are combined independently, the
+/// pattern can also match codons of other amino acids. Symbols not encoded by the table
+/// (e.g. `X`) yield `NNN`.
+pub fn back_translate(peptide: &[u8], table: &CodonTable) -> Vec<u8> {
+    let mut pattern = Vec::with_capacity(3 * peptide.len());
+    for &amino_acid in peptide {
+        let mut masks = [0; 3];
+        for codon in table.codons(amino_acid) {
+            for (mask, &a) in masks.iter_mut().zip(&codon) {
+                *mask |= base_mask(a);
+            }
+        }
+        if masks[0] == 0 {
+            masks = [15; 3];
+        }
+        pattern.extend(masks.iter().map(|&mask| IUPAC_CODES[mask]));
+    }
+
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    #[test]
+    fn test_codon_table() {
+        let table = CodonTable::standard();
+        assert_eq!(table.amino_acid(b"TTT"), Some(b'F'));
+        assert_eq!(table.amino_acid(b"tgg"), Some(b'W'));
+        assert_eq!(table.amino_acid(b"UAA"), Some(b'*'));
+        assert_eq!(table.amino_acid(b"GGG"), Some(b'G'));
+        assert_eq!(table.amino_acid(b"GGN"), None);
+        assert_eq!(table.amino_acid(b"GG"), None);
+        assert!(table.is_start(b"ATG"));
+        assert!(table.is_start(b"TTG"));
+        assert!(!table.is_start(b"ATA"));
+        assert_eq!(table.codons(b'W'), [*b"TGG"]);
+        assert_eq!(table.codons(b'*'), [*b"TAA", *b"TAG", *b"TGA"]);
+        assert_eq!(table.codons(b'X'), Vec::<[u8; 3]>::new());
+        assert_eq!(
+            CodonTable::from_ncbi("FFLL", "----"),
+            Err(CodonTableError::InvalidLength(4))
+        );
+    }
+
+    #[test]
+    fn test_ncbi_tables() {
+        for &(id, _, _) in NCBI_TABLES.iter() {
+            let table = CodonTable::from_ncbi_id(id).unwrap();
+            assert_eq!(table.amino_acid(b"ATG"), Some(b'M'));
+            assert!(table.is_start(b"ATG") || id == 6);
+        }
+        // deviations from the standard code
+        let mito = CodonTable::from_ncbi_id(2).unwrap();
+        assert_eq!(translate(b"TGAATAAGG", &mito), b"WM*");
+        assert_eq!(translate(b"TGAATAAGG", &CodonTable::standard()), b"*IR");
+        let ciliate = CodonTable::from_ncbi_id(6).unwrap();
+        assert_eq!(translate(b"TAATAGTGA", &ciliate), b"QQ*");
+        assert_eq!(
+            CodonTable::from_ncbi_id(7),
+            Err(CodonTableError::UnknownId(7))
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        let table = CodonTable::standard();
+        // beginning of human preproinsulin (INS)
+        let dna = b"ATGGCCCTGTGGATGCGCCTCCTGCCCCTGCTGGCGCTGCTGGCCCTCTGGGGACCTGACCCAGCCGCAGCC";
+        assert_eq!(translate(dna, &table), b"MALWMRLLPLLALLALWGPDPAAA");
+        assert_eq!(
+            translate(&dna.to_ascii_lowercase(), &table),
+            b"MALWMRLLPLLALLALWGPDPAAA"
+        );
+        assert_eq!(translate(&b"AUGGCC"[..], &table), b"MA");
+        // trailing partial codons
+        assert_eq!(translate(&dna[..dna.len() - 1], &table).len(), 23);
+        assert_eq!(translate(b"AT", &table), b"");
+        // ambiguous codons
+        assert_eq!(table.translate_codon(b"GCN"), b'A');
+        assert_eq!(table.translate_codon(b"YTR"), b'L');
+        assert_eq!(table.translate_codon(b"TAR"), b'*');
+        assert_eq!(table.translate_codon(b"TTN"), b'X');
+        assert_eq!(table.translate_codon(b"NNN"), b'X');
+        assert_eq!(table.translate_codon(b"GC-"), b'X');
+    }
+
+    #[test]
+    fn test_six_frame_translations() {
+        let table = CodonTable::standard();
+        let frames = six_frame_translations(b"ATGGCCTAAGC", &table);
+        assert_eq!(frames[0], b"MA*");
+        assert_eq!(frames[1], b"WPK");
+        assert_eq!(frames[2], b"GLS");
+        // reverse complement GCTTAGGCCAT
+        assert_eq!(frames[3], b"A*A");
+        assert_eq!(frames[4], b"LRP");
+        assert_eq!(frames[5], b"LGH");
+        let frames = six_frame_translations(b"AT", &table);
+        assert!(frames.iter().all(|frame| frame.is_empty()));
+    }
+
+    #[test]
+    fn test_back_translate() {
+        let table = CodonTable::standard();
+        assert_eq!(back_translate(b"", &table), b"");
+        assert_eq!(back_translate(b"LSRx", &table), b"YTNWSNMGNNNN");
+
+        // every coding sequence of a peptide matches its back-translation
+        let mut rng = StdRng::from_seed(&[31]);
+        for peptide in &[&b"MKVLAAGIVGLLLAWESSQ*"[..], b"MFLSYCWPHQRITNKVADEG*"] {
+            let pattern = back_translate(peptide, &table);
+            assert_eq!(pattern.len(), 3 * peptide.len());
+            for _ in 0..20 {
+                let seq: Vec<u8> = peptide
+                    .iter()
+                    .flat_map(|&a| *rng.choose(&table.codons(a)).unwrap())
+                    .collect();
+                assert_eq!(&translate(&seq, &table), peptide);
+                assert!(seq
+                    .iter()
+                    .zip(&pattern)
+                    .all(|(&a, &b)| dna::iupac_compatible(a, b)));
+            }
+        }
+    }
+}
@@ -1,12 +1,17 @@
 //! Efficient container for locations annotated across a set of named
 //! reference sequences.
+use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::io;
+use std::ops::Range;
 
 use crate::data_structures::interval_tree;
 use crate::data_structures::interval_tree::{IntervalTree, IntervalTreeIterator};
-use crate::utils::Interval;
+use crate::io::{bed, gff};
+use crate::utils::{Interval, Strand};
 use bio_types::annot::loc::Loc;
+use bio_types::strand;
 
 /// Efficient container for querying annotations, using `HashMap` and
 /// `IntervalTree`.
@@ -21,13 +26,16 @@ use bio_types::annot::loc::Loc;
 /// annotation or the query.
 ///
 /// Thus, the overlapping annotations identified by querying a
-/// `AnnotMap` may need further filtering.
+/// `AnnotMap` may need further filtering. Annotations also keep their
+/// strand, so that `find_stranded` can restrict queries to one strand.
+///
+/// Empty locations are not stored, since they never overlap a query.
 #[derive(Debug, Clone)]
 pub struct AnnotMap<R, T>
 where
     R: Hash + Eq,
 {
-    refid_itrees: HashMap<R, IntervalTree<isize, T>>,
+    refid_itrees: HashMap<R, IntervalTree<isize, (Strand, T)>>,
 }
 
 impl<R, T> AnnotMap<R, T>
@@ -71,13 +79,42 @@ where
     where
         R: Eq + Hash + Clone,
         L: Loc<RefID = R>,
+        L::Strand: Into<strand::Strand> + Copy,
     {
-        let itree = self
-            .refid_itrees
-            .entry(location.refid().clone())
-            .or_insert_with(IntervalTree::new);
         let rng = location.start()..(location.start() + (location.length() as isize));
-        itree.insert(rng, data);
+        let strand = location.strand().into().into();
+        self.insert(location.refid().clone(), rng, strand, data);
+    }
+
+    /// Inserts an object into the container at the given range of the given
+    /// reference sequence, on the given strand. Panics if the range has
+    /// end < start.
+    ///
+    /// ```
+    /// use bio::data_structures::annot_map::AnnotMap;
+    /// use bio::utils::Strand;
+    /// let mut genes = AnnotMap::new();
+    /// genes.insert("chrX".to_owned(), 461829..462426, Strand::Forward, "TMA22");
+    /// genes.insert("chrX".to_owned(), 462000..463000, Strand::Reverse, "antisense");
+    /// let hits: Vec<_> = genes.find_range("chrX", 462400..462500).collect();
+    /// assert_eq!(hits.len(), 2);
+    /// let hits: Vec<_> = genes
+    ///     .find_stranded("chrX", 462400..462500, Strand::Reverse)
+    ///     .map(|e| *e.data())
+    ///     .collect();
+    /// assert_eq!(hits, ["antisense"]);
+    /// ```
+    pub fn insert(&mut self, refid: R, range: Range<isize>, strand: Strand, data: T) {
+        assert!(
+            range.start <= range.end,
+            "Cannot insert a range with end < start"
+        );
+        if range.start < range.end {
+            self.refid_itrees
+                .entry(refid)
+                .or_insert_with(IntervalTree::new)
+                .insert(range, (strand, data));
+        }
     }
 
     /// Creates an `Iterator` that will visit all entries that overlap
@@ -116,15 +153,106 @@ where
             let itree_iter = itree.find(interval);
             AnnotMapIterator {
                 itree_iter: Some(itree_iter),
-                refid: location.refid(),
+                refid: Some(location.refid()),
+                strand: None,
             }
         } else {
             AnnotMapIterator {
                 itree_iter: None,
-                refid: location.refid(),
+                refid: None,
+                strand: None,
             }
         }
     }
+
+    /// Creates an `Iterator` that will visit all entries that overlap the
+    /// given range of the given reference sequence, regardless of their
+    /// strand. Nothing is found for unknown reference sequences.
+    pub fn find_range<Q>(&self, refid: &Q, range: Range<isize>) -> AnnotMapIterator<'_, R, T>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.refid_itrees.get_key_value(refid) {
+            Some((refid, itree)) => AnnotMapIterator {
+                itree_iter: Some(itree.find(range)),
+                refid: Some(refid),
+                strand: None,
+            },
+            None => AnnotMapIterator {
+                itree_iter: None,
+                refid: None,
+                strand: None,
+            },
+        }
+    }
+
+    /// Creates an `Iterator` that will visit all entries on the given strand
+    /// that overlap the given range of the given reference sequence.
+    pub fn find_stranded<Q>(
+        &self,
+        refid: &Q,
+        range: Range<isize>,
+        strand: Strand,
+    ) -> AnnotMapIterator<'_, R, T>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        AnnotMapIterator {
+            strand: Some(strand),
+            ..self.find_range(refid, range)
+        }
+    }
+}
+
+impl<T> AnnotMap<String, T> {
+    fn extend_from_records<I, E, F>(&mut self, records: I, location: F) -> Result<(), E>
+    where
+        I: Iterator<Item = Result<T, E>>,
+        F: Fn(&T) -> (String, Range<isize>, Strand),
+    {
+        for record in records {
+            let record = record?;
+            let (refid, range, strand) = location(&record);
+            self.insert(refid, range, strand, record);
+        }
+        Ok(())
+    }
+}
+
+impl AnnotMap<String, bed::Record> {
+    /// Inserts all records of a BED reader at their location. Records
+    /// without a strand column are on an unknown strand.
+    pub fn extend_from_bed<Rd: io::Read>(
+        &mut self,
+        reader: &mut bed::Reader<Rd>,
+    ) -> Result<(), bed::Error> {
+        self.extend_from_records(reader.records(), |record| {
+            (
+                record.chrom().to_owned(),
+                record.start() as isize..record.end() as isize,
+                record.feature_strand().unwrap_or(Strand::Unknown),
+            )
+        })
+    }
+}
+
+impl AnnotMap<String, gff::Record> {
+    /// Inserts all records of a GFF reader at their location, converted to
+    /// 0-based, half-open coordinates.
+    pub fn extend_from_gff<Rd: io::Read>(
+        &mut self,
+        reader: &mut gff::Reader<Rd>,
+    ) -> Result<(), gff::Error> {
+        self.extend_from_records(reader.records(), |record| {
+            (
+                record.seqname().to_owned(),
+                *record.start() as isize - 1..*record.end() as isize,
+                record.strand(),
+            )
+        })
+    }
 }
 
 impl<R, T> AnnotMap<R, T>
@@ -140,13 +268,13 @@ where
     /// `data` is the data to be inserted based on its location.
     ///
     /// Equivalent to inserting `data` at `data.contig()`.
-    pub fn insert_loc(&mut self, data: T) {
-        let itree = self
-            .refid_itrees
-            .entry(data.refid().clone())
-            .or_insert_with(IntervalTree::new);
+    pub fn insert_loc(&mut self, data: T)
+    where
+        T::Strand: Into<strand::Strand> + Copy,
+    {
         let rng = data.start()..(data.start() + (data.length() as isize));
-        itree.insert(rng, data);
+        let strand = data.strand().into().into();
+        self.insert(data.refid().clone(), rng, strand, data);
     }
 }
 
@@ -156,7 +284,7 @@ pub struct Entry<'a, R, T>
 where
     R: Eq + Hash,
 {
-    itree_entry: interval_tree::Entry<'a, isize, T>,
+    itree_entry: interval_tree::Entry<'a, isize, (Strand, T)>,
     refid: &'a R,
 }
 
@@ -166,7 +294,12 @@ where
 {
     /// Returns a reference to the data value in the `AnnotMap`.
     pub fn data(&self) -> &'a T {
-        self.itree_entry.data()
+        &self.itree_entry.data().1
+    }
+
+    /// Returns the strand of the annotation.
+    pub fn strand(&self) -> Strand {
+        self.itree_entry.data().0
     }
 
     /// Returns a reference to the interval spanned by the annotation.
@@ -188,8 +321,9 @@ pub struct AnnotMapIterator<'a, R, T>
 where
     R: Eq + Hash,
 {
-    itree_iter: Option<IntervalTreeIterator<'a, isize, T>>,
-    refid: &'a R,
+    itree_iter: Option<IntervalTreeIterator<'a, isize, (Strand, T)>>,
+    refid: Option<&'a R>,
+    strand: Option<Strand>,
 }
 
 impl<'a, R, T> Iterator for AnnotMapIterator<'a, R, T>
@@ -200,16 +334,13 @@ where
     type Item = Entry<'a, R, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.itree_iter {
-            Some(ref mut iter) => match iter.next() {
-                Some(next_itree) => Some(Entry {
-                    itree_entry: next_itree,
-                    refid: self.refid,
-                }),
-                None => None,
-            },
-            None => None,
-        }
+        let iter = self.itree_iter.as_mut()?;
+        let strand = self.strand;
+        let next_itree = iter.find(|entry| strand.is_none_or(|strand| entry.data().0 == strand))?;
+        Some(Entry {
+            itree_entry: next_itree,
+            refid: self.refid?,
+        })
     }
 }
 
@@ -313,4 +444,116 @@ mod tests {
         hits.sort();
         assert_eq!(hits, vec!["c", "d"]);
     }
+
+    #[test]
+    fn stranded() {
+        let mut genes = AnnotMap::new();
+        genes.insert("chr1".to_owned(), 100..200, Strand::Forward, "sense");
+        genes.insert("chr1".to_owned(), 150..250, Strand::Reverse, "antisense");
+        genes.insert("chr1".to_owned(), 180..190, Strand::Unknown, "unknown");
+        genes.insert("chr1".to_owned(), 300..300, Strand::Forward, "empty");
+        let find = |strand: Option<Strand>, range: Range<isize>| -> Vec<&str> {
+            let mut hits: Vec<&str> = match strand {
+                Some(strand) => genes
+                    .find_stranded("chr1", range, strand)
+                    .map(|e| *e.data())
+                    .collect(),
+                None => genes.find_range("chr1", range).map(|e| *e.data()).collect(),
+            };
+            hits.sort();
+            hits
+        };
+        assert_eq!(find(None, 160..170), ["antisense", "sense"]);
+        assert_eq!(find(Some(Strand::Forward), 160..170), ["sense"]);
+        assert_eq!(find(Some(Strand::Reverse), 160..170), ["antisense"]);
+        assert_eq!(find(Some(Strand::Reverse), 100..150), Vec::<&str>::new());
+        assert_eq!(find(Some(Strand::Unknown), 0..1000), ["unknown"]);
+        assert_eq!(find(None, 290..310), Vec::<&str>::new());
+        let entry = genes.find_range("chr1", 240..241).next().unwrap();
+        assert_eq!(
+            (entry.refid().as_str(), entry.strand()),
+            ("chr1", Strand::Reverse)
+        );
+
+        assert_eq!(genes.find_range("chr2", 0..1000).count(), 0);
+        assert_eq!(
+            genes
+                .find_stranded("chr2", 0..1000, Strand::Forward)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn insert_at_keeps_strand() {
+        let mut genes: AnnotMap<String, String> = AnnotMap::new();
+        genes.insert_at(
+            "TMA19".to_owned(),
+            &Contig::new("chrXI".to_owned(), 334412, 504, ReqStrand::Reverse),
+        );
+        let hits: Vec<_> = genes
+            .find_stranded("chrXI", 334500..334600, Strand::Reverse)
+            .collect();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            genes
+                .find_stranded("chrXI", 334500..334600, Strand::Forward)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn from_bed() {
+        let bed = b"chr1\t100\t200\tgeneA\t0\t+
+chr1\t150\t250\tgeneB\t0\t-
+chr2\t100\t200
+";
+        let mut genes = AnnotMap::new();
+        genes
+            .extend_from_bed(&mut bed::Reader::new(&bed[..]))
+            .unwrap();
+        let hits: Vec<_> = genes
+            .find_stranded("chr1", 190..210, Strand::Reverse)
+            .map(|e| e.data().name().unwrap())
+            .collect();
+        assert_eq!(hits, ["geneB"]);
+        assert_eq!(genes.find_range("chr1", 199..200).count(), 2);
+        assert_eq!(genes.find_range("chr1", 200..201).count(), 1);
+        let hit = genes.find_range("chr2", 0..150).next().unwrap();
+        assert_eq!(hit.strand(), Strand::Unknown);
+    }
+
+    #[test]
+    fn from_gff() {
+        let gff = b"chr1\tsrc\tgene\t1000\t5000\t.\t+\t.\tID=gene1
+chr1\tsrc\texon\t1000\t1200\t.\t+\t.\tParent=tx1
+chr1\tsrc\texon\t4000\t5000\t.\t+\t.\tParent=tx1
+chr1\tsrc\texon\t3000\t3500\t.\t-\t.\tParent=tx2
+";
+        let mut features = AnnotMap::new();
+        features
+            .extend_from_gff(&mut gff::Reader::new(&gff[..], gff::GffType::GFF3))
+            .unwrap();
+        let exons = |range: Range<isize>, strand: Strand| -> Vec<(u64, u64)> {
+            let mut exons: Vec<_> = features
+                .find_stranded("chr1", range, strand)
+                .map(|e| e.data())
+                .filter(|record| record.feature_type() == "exon")
+                .map(|record| (*record.start(), *record.end()))
+                .collect();
+            exons.sort();
+            exons
+        };
+        // 1-based inclusive coordinates: position 1200 is the 0-based position 1199
+        assert_eq!(exons(1199..1200, Strand::Forward), [(1000, 1200)]);
+        assert_eq!(exons(1200..3999, Strand::Forward), []);
+        assert_eq!(
+            exons(0..10000, Strand::Forward),
+            [(1000, 1200), (4000, 5000)]
+        );
+        assert_eq!(exons(3400..4100, Strand::Reverse), [(3000, 3500)]);
+        // the gene overlaps everything in between
+        assert_eq!(features.find_range("chr1", 2000..2001).count(), 1);
+    }
 }
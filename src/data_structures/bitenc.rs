@@ -157,6 +157,11 @@ impl BitEnc {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
+
+    /// Return the number of bits per value.
+    pub fn width(&self) -> usize {
+        self.width
+    }
 }
 
 /// Iterator over values of a bitencoded sequence (values will be unpacked into bytes).
@@ -7,12 +7,25 @@
 //! The implementation is based on the lecture notes
 //! "Algorithmen auf Sequenzen", Kopczynski, Marschall, Martin and Rahmann, 2008 - 2015.
 
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::iter::repeat;
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+#[cfg(feature = "parallel")]
+use std::thread;
 
 use crate::alphabets::Alphabet;
-use crate::data_structures::suffix_array::RawSuffixArray;
+use crate::data_structures::suffix_array::{
+    self, cmp_suffixes, construct_suffix_array_with_sentinel,
+};
+use crate::data_structures::wavelet_tree::WaveletTree;
 use crate::utils::prescan;
 use bytecount;
+use num_traits::{cast, NumCast};
 
 pub type BWT = Vec<u8>;
 pub type BWTSlice = [u8];
@@ -37,47 +50,631 @@ pub type BWTFind = Vec<usize>;
 /// let bwt = bwt(text, &pos);
 /// assert_eq!(bwt, b"ATTATTCAGGACCC$CTTTCAA");
 /// ```
-pub fn bwt(text: &[u8], pos: &RawSuffixArray) -> BWT {
+pub fn bwt<SA: suffix_array::SuffixArray>(text: &[u8], pos: &SA) -> BWT {
     assert_eq!(text.len(), pos.len());
     let n = text.len();
-    let mut bwt: BWT = repeat(0).take(n).collect();
-    for r in 0..n {
-        let p = pos[r];
-        bwt[r] = if p > 0 { text[p - 1] } else { text[n - 1] };
+    (0..n)
+        .map(|r| match pos.get(r).unwrap() {
+            0 => text[n - 1],
+            p => text[p - 1],
+        })
+        .collect()
+}
+
+/// Calculate Burrows-Wheeler-Transform of the given text of length n, without requiring
+/// the caller to construct the suffix array. For texts shorter than `u32::MAX`, the suffix
+/// array is built internally with 32-bit positions and dropped right away, which halves
+/// the peak memory compared to `bwt(text, &suffix_array(text))`.
+/// Complexity: O(n).
+///
+/// # Arguments
+///
+/// * `text` - the text ended by sentinel symbol (being lexicographically smallest)
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::bwt_from_text;
+/// let bwt = bwt_from_text(b"GCCTTAACATTATTACGCCTA$");
+/// assert_eq!(bwt, b"ATTATTCAGGACCC$CTTTCAA");
+/// ```
+pub fn bwt_from_text(text: &[u8]) -> BWT {
+    bwt_sorted_by(text, text)
+}
+
+/// Calculate the BWT of the given text like `bwt_from_text`, for a text over arbitrary bytes
+/// that is terminated by the given sentinel, which need not be the lexicographically smallest
+/// symbol (see `suffix_array::suffix_array_with_sentinel`).
+///
+/// # Arguments
+///
+/// * `text` - the text ended by the sentinel
+/// * `sentinel` - the sentinel symbol
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::bwt_from_text_with_sentinel;
+/// let bwt = bwt_from_text_with_sentinel(&[2, 0, 1, 0xff, 0, 0, 0xff], 0xff).unwrap();
+/// assert_eq!(bwt, [0, 1, 0, 0xff, 2, 0, 0xff]);
+/// ```
+pub fn bwt_from_text_with_sentinel(
+    text: &[u8],
+    sentinel: u8,
+) -> Result<BWT, suffix_array::SuffixArrayError> {
+    suffix_array::check_sentinel_positions(text, sentinel)?;
+
+    Ok(bwt_sorted_by_with_sentinel(text, text))
+}
+
+/// Calculate the BWT of the given text, with the suffixes being ordered by the
+/// suffixes of `sort_text` (e.g. the uppercased text for case-insensitive indexes).
+pub(crate) fn bwt_sorted_by(text: &[u8], sort_text: &[u8]) -> BWT {
+    suffix_array::check_sentinel(sort_text);
+    bwt_sorted_by_with_sentinel(text, sort_text)
+}
+
+/// Calculate the BWT like `bwt_sorted_by`, ordering the last symbol of `sort_text` before all
+/// other symbols regardless of its value.
+pub(crate) fn bwt_sorted_by_with_sentinel(text: &[u8], sort_text: &[u8]) -> BWT {
+    assert_eq!(text.len(), sort_text.len());
+    if text.len() < u32::MAX as usize {
+        bwt_generic(
+            text,
+            &construct_suffix_array_with_sentinel::<u32>(sort_text),
+        )
+    } else {
+        bwt_generic(
+            text,
+            &construct_suffix_array_with_sentinel::<usize>(sort_text),
+        )
+    }
+}
+
+fn bwt_generic<I: NumCast + Copy>(text: &[u8], pos: &[I]) -> BWT {
+    let n = text.len();
+    pos.iter()
+        .map(|&p| {
+            let p: usize = cast(p).unwrap();
+            if p > 0 {
+                text[p - 1]
+            } else {
+                text[n - 1]
+            }
+        })
+        .collect()
+}
+
+/// Calculate the Burrows-Wheeler-Transform of the text given as a sequence of chunks and
+/// write it to the given writer, without constructing the suffix array. Instead, the
+/// suffixes are partitioned into buckets of at most `block_size` suffixes sharing a common
+/// prefix. The text positions are distributed into their buckets in a temporary file (see
+/// `std::env::temp_dir`), from which the buckets are read, sorted and written out one after
+/// another in lexicographical order. The chunks are written to a temporary file as well as
+/// they are produced, such that the text is held in memory only once. Besides the text, only
+/// O(block_size) positions are held in memory at any time.
+///
+/// Time complexity: O(n * h^2 + n log b * l) for block size b, height h of the trie of
+/// prefixes shared by more than b suffixes, and average length l of the longest common
+/// prefixes of the suffixes. This is suited for texts without long repeats (e.g. long runs
+/// of N should be split or masked).
+///
+/// # Arguments
+///
+/// * `text_chunks` - the text ended by sentinel symbol (being lexicographically smallest)
+/// * `block_size` - the maximum number of suffixes sorted at once
+/// * `writer` - the writer receiving the BWT
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::bwt_to_writer;
+///
+/// let chunks = vec![b"GCCTTAACAT".to_vec(), b"TATTACGCCTA$".to_vec()];
+/// let mut bwt = Vec::new();
+/// bwt_to_writer(chunks, 4, &mut bwt).unwrap();
+/// assert_eq!(bwt, b"ATTATTCAGGACCC$CTTTCAA");
+/// ```
+pub fn bwt_to_writer<T, W>(text_chunks: T, block_size: usize, writer: W) -> io::Result<()>
+where
+    T: IntoIterator<Item = Vec<u8>>,
+    W: Write,
+{
+    assert!(block_size > 0, "Expecting positive block size.");
+    // spool the chunks to a scratch file as they are produced, such that the text is
+    // allocated once with its final length instead of growing while the chunks are held
+    let mut spool = ScratchFile::new()?;
+    let mut n = 0;
+    for chunk in text_chunks {
+        spool.file.write_all(&chunk)?;
+        n += chunk.len();
+    }
+    if n == 0 {
+        return Ok(());
+    }
+    let mut text = vec![0; n];
+    spool.file.seek(SeekFrom::Start(0))?;
+    spool.file.read_exact(&mut text)?;
+    drop(spool);
+
+    write_bwt_buckets(&text, block_size, writer).map(|_| ())
+}
+
+/// Write the BWT of the given non-empty text bucket by bucket (see `bwt_to_writer`), and
+/// return the maximum number of text positions held in memory at once.
+fn write_bwt_buckets<W: Write>(text: &[u8], block_size: usize, mut writer: W) -> io::Result<usize> {
+    let n = text.len();
+    let sentinel = text[n - 1];
+    let bwt_symbol = |p: usize| if p > 0 { text[p - 1] } else { text[n - 1] };
+    let trie = PrefixTrie::new(text, block_size);
+
+    // buckets in lexicographical order, and the offset of each bucket in the scratch file
+    let order = trie.buckets_in_order();
+    let mut offsets = vec![0; trie.buckets.len()];
+    let mut offset = 0;
+    for &b in &order {
+        offsets[b] = offset;
+        offset += trie.buckets[b].size;
+    }
+
+    // distribute the positions into their buckets, buffering up to block_size positions in
+    // total
+    let mut scratch = ScratchFile::new()?;
+    let capacity = (block_size / trie.buckets.len()).max(1);
+    let mut buffers = vec![Vec::new(); trie.buckets.len()];
+    let mut written = vec![0; trie.buckets.len()];
+    let mut peak = 0;
+    for p in 0..n {
+        let b = match trie.walk(text, p) {
+            PrefixChild::Bucket(b) => b,
+            _ => unreachable!("all prefixes have been refined"),
+        };
+        buffers[b].push(p);
+        if buffers[b].len() == capacity {
+            peak = peak.max(buffers.iter().map(Vec::len).sum());
+            scratch.write_positions(offsets[b] + written[b], &buffers[b])?;
+            written[b] += capacity;
+            buffers[b].clear();
+        }
+    }
+    peak = peak.max(buffers.iter().map(Vec::len).sum());
+    for (b, buffer) in buffers.iter().enumerate() {
+        scratch.write_positions(offsets[b] + written[b], buffer)?;
+    }
+    drop(buffers);
+
+    let mut symbols = Vec::with_capacity(block_size.min(n));
+    for &b in &order {
+        let bucket = &trie.buckets[b];
+        if bucket.sentinel {
+            // suffixes ending at a sentinel are ordered by decreasing position, i.e. the
+            // positions are read backwards in chunks of at most block_size
+            let mut end = bucket.size;
+            while end > 0 {
+                let start = end.saturating_sub(block_size);
+                let positions = scratch.read_positions(offsets[b] + start, end - start)?;
+                peak = peak.max(positions.len());
+                symbols.clear();
+                symbols.extend(positions.into_iter().rev().map(bwt_symbol));
+                writer.write_all(&symbols)?;
+                end = start;
+            }
+        } else {
+            let mut positions = scratch.read_positions(offsets[b], bucket.size)?;
+            debug_assert!(positions.len() <= block_size);
+            peak = peak.max(positions.len());
+            positions.sort_unstable_by(|&p, &q| cmp_suffixes(text, sentinel, p, q));
+            symbols.clear();
+            symbols.extend(positions.into_iter().map(bwt_symbol));
+            writer.write_all(&symbols)?;
+        }
+    }
+
+    Ok(peak)
+}
+
+/// Trie of the prefixes shared by more than block size suffixes, partitioning the suffixes
+/// of a text into buckets for `bwt_to_writer`.
+struct PrefixTrie {
+    nodes: Vec<PrefixNode>,
+    buckets: Vec<SuffixBucket>,
+}
+
+/// A prefix of the trie, the children of which are yet to be determined if `None`.
+struct PrefixNode {
+    len: usize,
+    children: Option<Vec<PrefixChild>>,
+}
+
+/// The suffixes starting with the prefix of a trie node followed by a given symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PrefixChild {
+    Empty,
+    Node(usize),
+    Bucket(usize),
+}
+
+/// A bucket of suffixes, either sharing their prefix up to a sentinel, or having to be
+/// sorted.
+struct SuffixBucket {
+    size: usize,
+    sentinel: bool,
+}
+
+impl PrefixTrie {
+    /// Build the trie with one pass over the text per trie level.
+    fn new(text: &[u8], block_size: usize) -> Self {
+        let sentinel = text[text.len() - 1];
+        let mut trie = PrefixTrie {
+            nodes: vec![PrefixNode {
+                len: 0,
+                children: None,
+            }],
+            buckets: Vec::new(),
+        };
+        let mut pending = vec![0];
+        while !pending.is_empty() {
+            // count the suffixes starting with each pending prefix by their next symbol
+            let mut slots = vec![usize::MAX; trie.nodes.len()];
+            for (slot, &j) in pending.iter().enumerate() {
+                slots[j] = slot;
+            }
+            let mut counts = vec![[0; 256]; pending.len()];
+            for p in 0..text.len() {
+                if let PrefixChild::Node(j) = trie.walk(text, p) {
+                    counts[slots[j]][text[p + trie.nodes[j].len] as usize] += 1;
+                }
+            }
+
+            let mut refined = Vec::new();
+            for (&j, counts) in pending.iter().zip(&counts) {
+                let mut children = vec![PrefixChild::Empty; 256];
+                // bucket of consecutive symbols that is still being filled
+                let mut group: Option<usize> = None;
+                for (c, &count) in counts.iter().enumerate() {
+                    if count == 0 {
+                        continue;
+                    }
+                    children[c] = if c as u8 == sentinel {
+                        group = None;
+                        trie.bucket(count, true)
+                    } else if count > block_size {
+                        group = None;
+                        trie.nodes.push(PrefixNode {
+                            len: trie.nodes[j].len + 1,
+                            children: None,
+                        });
+                        refined.push(trie.nodes.len() - 1);
+                        PrefixChild::Node(trie.nodes.len() - 1)
+                    } else {
+                        match group {
+                            Some(b) if trie.buckets[b].size + count <= block_size => {
+                                trie.buckets[b].size += count;
+                                PrefixChild::Bucket(b)
+                            }
+                            _ => {
+                                let bucket = trie.bucket(count, false);
+                                if let PrefixChild::Bucket(b) = bucket {
+                                    group = Some(b);
+                                }
+                                bucket
+                            }
+                        }
+                    };
+                }
+                trie.nodes[j].children = Some(children);
+            }
+            pending = refined;
+        }
+
+        trie
+    }
+
+    /// Add a bucket of the given size.
+    fn bucket(&mut self, size: usize, sentinel: bool) -> PrefixChild {
+        self.buckets.push(SuffixBucket { size, sentinel });
+        PrefixChild::Bucket(self.buckets.len() - 1)
+    }
+
+    /// Follow the suffix at position p down the trie, returning its bucket or the pending
+    /// node it ends in. As the text ends with the sentinel, which is never refined, the
+    /// suffix does not end before.
+    fn walk(&self, text: &[u8], p: usize) -> PrefixChild {
+        let mut j = 0;
+        loop {
+            let node = &self.nodes[j];
+            match node.children {
+                None => return PrefixChild::Node(j),
+                Some(ref children) => match children[text[p + node.len] as usize] {
+                    PrefixChild::Node(child) => j = child,
+                    child => return child,
+                },
+            }
+        }
+    }
+
+    /// The buckets in lexicographical order of their suffixes.
+    fn buckets_in_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.buckets.len());
+        // depth-first traversal, visiting the children of each node by increasing symbol
+        let mut stack = vec![(0, 0)];
+        while let Some((j, c)) = stack.pop() {
+            let children = self.nodes[j].children.as_ref().unwrap();
+            if c == children.len() {
+                continue;
+            }
+            stack.push((j, c + 1));
+            match children[c] {
+                PrefixChild::Node(child) => stack.push((child, 0)),
+                PrefixChild::Bucket(b) if order.last() != Some(&b) => order.push(b),
+                _ => (),
+            }
+        }
+
+        order
+    }
+}
+
+/// A temporary file storing text positions with 64 bit, removed when dropped.
+struct ScratchFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl ScratchFile {
+    /// Create a new file in the temporary directory.
+    fn new() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "bio-bwt-{}-{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, AtomicOrdering::SeqCst)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        Ok(ScratchFile { path, file })
+    }
+
+    /// Write the given positions, starting at the i-th stored position.
+    fn write_positions(&mut self, i: usize, positions: &[usize]) -> io::Result<()> {
+        let bytes: Vec<u8> = positions
+            .iter()
+            .flat_map(|&p| (p as u64).to_le_bytes())
+            .collect();
+        self.file.seek(SeekFrom::Start(i as u64 * 8))?;
+        self.file.write_all(&bytes)
+    }
+
+    /// Read len positions, starting at the i-th stored position.
+    fn read_positions(&mut self, i: usize, len: usize) -> io::Result<Vec<usize>> {
+        let mut bytes = vec![0; len * 8];
+        self.file.seek(SeekFrom::Start(i as u64 * 8))?;
+        self.file.read_exact(&mut bytes)?;
+
+        Ok(bytes
+            .chunks(8)
+            .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as usize)
+            .collect())
     }
+}
 
-    bwt
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
 }
 
 /// Calculate the inverse of a BWT of length n, which is the original text.
 /// Complexity: O(n).
 ///
-/// This only works if the last sentinel in the original text is unique
-/// and lexicographically the smallest.
+/// The sentinel is expected to be the lexicographically smallest symbol. The text may
+/// contain multiple sentinels separating sequences, which are restored in their original
+/// order, given that the suffix array orders the sentinels by decreasing text position
+/// (as `suffix_array` does).
 ///
 /// # Arguments
 ///
 /// * `bwt` - the BWT
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::{bwt, invert_bwt};
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let text = b"ACGT$GATTACA$";
+/// let bwt = bwt(text, &suffix_array(text));
+/// assert_eq!(invert_bwt(&bwt), &text[..]);
+/// ```
 pub fn invert_bwt(bwt: &BWTSlice) -> Vec<u8> {
+    let sentinel = match bwt.iter().min() {
+        Some(&sentinel) => sentinel,
+        None => return Vec::new(),
+    };
     let alphabet = Alphabet::new(bwt);
     let n = bwt.len();
-    let bwtfind = bwtfind(bwt, &alphabet);
-    let mut inverse = Vec::with_capacity(n);
+    // bwtfind is inverse to the LF-mapping
+    let mut lf = vec![0; n];
+    for (r, &p) in bwtfind(bwt, &alphabet).iter().enumerate() {
+        lf[p] = r;
+    }
 
-    let mut r = bwtfind[0];
-    for _ in 0..n {
-        r = bwtfind[r];
-        inverse.push(bwt[r]);
+    // Row j is the sentinel ending the j-th last sequence. Walk backwards over each
+    // sequence until reaching the row preceded by a sentinel.
+    let mut inverse = Vec::with_capacity(n);
+    let sentinel_count = bytecount::count(bwt, sentinel);
+    for mut r in 0..sentinel_count {
+        inverse.push(sentinel);
+        while bwt[r] != sentinel {
+            inverse.push(bwt[r]);
+            r = lf[r];
+        }
     }
+    inverse.reverse();
 
     inverse
 }
 
+/// Map each sentinel of a text over multiple sentinel-terminated sequences to the sequence
+/// it terminates, in the order of the sentinels in the suffix array and hence in the first
+/// rows of the BWT. Entry j is the index of the sequence whose sentinel is the j-th smallest
+/// suffix. Since `suffix_array` orders the sentinels by decreasing text position, the j-th
+/// sentinel then terminates the j-th last sequence, not the j-th one. This is also the order
+/// in which the LF-mapping walks through the sequences (see `invert_bwt`). The sentinel is
+/// the last symbol of the text, and its positions are sorted once like their suffixes,
+/// such that neither the suffix array nor a sample of it is needed.
+/// Complexity: O(n + m log m) for m sequences.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::sentinel_map;
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let text = b"ACGTACGT$GA$TTAGCA$";
+/// let map = sentinel_map(text);
+/// assert_eq!(map, [2, 1, 0]);
+/// // the suffix at row 1 is the sentinel of the sequence GA
+/// assert_eq!(suffix_array(text)[1], 11);
+/// ```
+pub fn sentinel_map(text: &[u8]) -> Vec<usize> {
+    let sentinel = match text.last() {
+        Some(&sentinel) => sentinel,
+        None => return Vec::new(),
+    };
+    let ends: Vec<usize> = text
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| c == sentinel)
+        .map(|(i, _)| i)
+        .collect();
+    let mut map: Vec<usize> = (0..ends.len()).collect();
+    map.sort_unstable_by(|&i, &j| cmp_suffixes(text, sentinel, ends[i], ends[j]));
+
+    map
+}
+
+/// Merge the BWTs of two texts into the BWT of their concatenation, such that an index can
+/// be extended by a new batch of sequences without rebuilding it from scratch. Both texts
+/// have to end with the sentinel. The sequence boundaries are kept: the sentinels of the
+/// second text follow those of the first one in the concatenation and are hence ordered
+/// before them (see `invert_bwt`).
+///
+/// The rows of both BWTs are interleaved by iteratively refining the interleaving by the
+/// LF-mapping until it is stable (Holt and McMillan, 2014).
+/// Complexity: O(n * l) for the length l of the longest common prefix between a suffix of
+/// the first and a suffix of the second text.
+///
+/// # Arguments
+///
+/// * `bwt_a` - the BWT of the first text
+/// * `bwt_b` - the BWT of the second text
+/// * `alphabet` - the alphabet of both texts
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::bwt::{bwt, merge};
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let (a, b) = (b"GCCTTAACAT$", b"TATTACGCCTA$");
+/// let merged = merge(
+///     &bwt(a, &suffix_array(a)),
+///     &bwt(b, &suffix_array(b)),
+///     &dna::n_alphabet(),
+/// );
+///
+/// let text = b"GCCTTAACAT$TATTACGCCTA$";
+/// assert_eq!(merged, bwt(text, &suffix_array(text)));
+/// ```
+pub fn merge(bwt_a: &BWTSlice, bwt_b: &BWTSlice, alphabet: &Alphabet) -> BWT {
+    merge_by(bwt_a, bwt_b, alphabet, false)
+}
+
+/// Merge two BWTs like `merge`, ordering the suffixes case-insensitively if
+/// `case_insensitive` is given (see `Occ::new_case_insensitive`).
+pub(crate) fn merge_by(
+    bwt_a: &BWTSlice,
+    bwt_b: &BWTSlice,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+) -> BWT {
+    if bwt_a.is_empty() || bwt_b.is_empty() {
+        return [bwt_a, bwt_b].concat();
+    }
+    let fold = |c: u8| {
+        if case_insensitive {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        }
+    };
+    let sentinel = *bwt_a.iter().chain(bwt_b).min().unwrap();
+    let n = bwt_a.len() + bwt_b.len();
+    let m = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.") as usize
+        + 1;
+
+    // first row of the suffixes starting with each symbol
+    let mut starts = vec![0; m];
+    for &c in bwt_a.iter().chain(bwt_b) {
+        starts[fold(c) as usize] += 1;
+    }
+    prescan(&mut starts[..], 0, |a, b| a + b);
+    let sentinels_b = bytecount::count(bwt_b, sentinel);
+
+    // whether row r of the merged BWT is a row of bwt_b, starting with all rows of bwt_a
+    // followed by all rows of bwt_b
+    let mut from_b: Vec<bool> = (0..n).map(|r| r >= bwt_a.len()).collect();
+    loop {
+        // the sentinels are ordered by decreasing text position, hence those of bwt_b come
+        // first, while the remaining rows are ordered by the rows of the following suffixes
+        let mut next = vec![false; n];
+        for is_b in next.iter_mut().take(sentinels_b) {
+            *is_b = true;
+        }
+        let mut rows = starts.clone();
+        let (mut a, mut b) = (bwt_a.iter(), bwt_b.iter());
+        for &is_b in &from_b {
+            let c = if is_b { b.next() } else { a.next() };
+            let c = *c.unwrap();
+            if c != sentinel {
+                let row = &mut rows[fold(c) as usize];
+                next[*row] = is_b;
+                *row += 1;
+            }
+        }
+        if next == from_b {
+            break;
+        }
+        from_b = next;
+    }
+
+    let (mut a, mut b) = (bwt_a.iter(), bwt_b.iter());
+    from_b
+        .into_iter()
+        .map(|is_b| {
+            if is_b {
+                *b.next().unwrap()
+            } else {
+                *a.next().unwrap()
+            }
+        })
+        .collect()
+}
+
 /// An occurrence array implementation.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Occ {
-    occ: Vec<Vec<usize>>,
+    occ: Counts,
     k: u32,
+    case_insensitive: bool,
 }
 
 impl Occ {
@@ -87,27 +684,331 @@ impl Occ {
     /// Alphabet size is determined on the fly from the BWT.
     /// For large texts, it is therefore advisable to transform
     /// the text before calculating the BWT (see alphabets::rank_transform).
+    /// Counts are stored with 32 bit, unless the BWT is longer than `u32::MAX`.
+    /// With the `parallel` feature, chunks of the BWT are counted in parallel.
     ///
     /// # Arguments
     ///
     /// * `bwt` - the BWT
     /// * `k` - the sampling rate: every k-th entry will be stored
     pub fn new(bwt: &BWTSlice, k: u32, alphabet: &Alphabet) -> Self {
-        let n = bwt.len();
+        Self::build(bwt, k, alphabet, false, is_wide(bwt))
+    }
+
+    /// Calculate occ array with sampling from BWT of length n like `new`, counting chunks of
+    /// the BWT of the given size in parallel. The result is identical to `new` for any chunk
+    /// size; `new` itself uses one chunk per available core.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `k` - the sampling rate: every k-th entry will be stored
+    /// * `chunk_size` - the number of BWT symbols counted by one thread
+    #[cfg(feature = "parallel")]
+    pub fn new_parallel(bwt: &BWTSlice, k: u32, alphabet: &Alphabet, chunk_size: usize) -> Self {
+        Occ {
+            occ: sampled_counts_parallel(bwt, k, alphabet, false, is_wide(bwt), chunk_size),
+            k,
+            case_insensitive: false,
+        }
+    }
+
+    /// Calculate occ array with sampling from a BWT read from the given reader (e.g. as
+    /// written by `bwt_to_writer`), without holding the BWT in memory. The result is
+    /// identical to `Occ::new`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the reader providing the BWT
+    /// * `k` - the sampling rate: every k-th entry will be stored
+    /// * `alphabet` - the alphabet of the BWT
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt_to_writer, Occ};
+    ///
+    /// let mut bwt = Vec::new();
+    /// bwt_to_writer(vec![b"GCCTTAACATTATTACGCCTA$".to_vec()], 8, &mut bwt).unwrap();
+    /// let occ = Occ::from_reader(&bwt[..], 3, &dna::n_alphabet()).unwrap();
+    /// assert_eq!(occ.get(&bwt, 14, b'C'), 4);
+    /// ```
+    pub fn from_reader<R: Read>(mut reader: R, k: u32, alphabet: &Alphabet) -> io::Result<Self> {
         let m = alphabet
             .max_symbol()
             .expect("Expecting non-empty alphabet.") as usize
             + 1;
-        let mut occ = Vec::with_capacity(n / k as usize);
-        let mut curr_occ: Vec<usize> = repeat(0).take(m).collect();
-        for (i, &c) in bwt.iter().enumerate() {
-            curr_occ[c as usize] += 1;
-            if i % k as usize == 0 {
-                occ.push(curr_occ.clone());
+        let mut occ = Counts::U32(Vec::new());
+        let mut curr_occ = vec![0; m];
+        let mut buffer = vec![0; 1 << 16];
+        let mut i = 0;
+        loop {
+            let len = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            for &c in &buffer[..len] {
+                if i == u32::MAX as usize {
+                    // counts may exceed 32 bit from here on, like with `Occ::new`
+                    if let Counts::U32(narrow) = occ {
+                        occ = Counts::U64(
+                            narrow
+                                .into_iter()
+                                .map(|counts| counts.into_iter().map(|c| c as usize).collect())
+                                .collect(),
+                        );
+                    }
+                }
+                curr_occ[c as usize] += 1;
+                if i % k as usize == 0 {
+                    match occ {
+                        Counts::U32(ref mut occ) => {
+                            occ.push(curr_occ.iter().map(|&count| count as u32).collect())
+                        }
+                        Counts::U64(ref mut occ) => occ.push(curr_occ.clone()),
+                        Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => {
+                            unreachable!()
+                        }
+                    }
+                }
+                i += 1;
             }
         }
 
-        Occ { occ, k }
+        Ok(Occ {
+            occ,
+            k,
+            case_insensitive: false,
+        })
+    }
+
+    /// Calculate occ array with sampling from BWT of length n, counting upper and lowercase
+    /// variants of a symbol as the same symbol (e.g. for soft-masked texts).
+    /// The BWT has to be constructed from a suffix array that orders the text
+    /// case-insensitively, i.e., the suffix array of the uppercased text.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `k` - the sampling rate: every k-th entry will be stored
+    pub fn new_case_insensitive(bwt: &BWTSlice, k: u32, alphabet: &Alphabet) -> Self {
+        Self::build(bwt, k, alphabet, true, is_wide(bwt))
+    }
+
+    /// Calculate occ array backed by a wavelet tree over the BWT instead of sampled counts
+    /// (see module `wavelet_tree` for the trade-off). This is advisable for larger alphabets,
+    /// e.g. proteins.
+    /// Time complexity: O(n log A).
+    /// Space complexity: O(n log A) bits with A being the number of distinct symbols.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::protein;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"MKVLAAGIVGLLLAQPAMA$";
+    /// let alphabet = protein::alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new_wavelet_tree(&bwt);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// let interval = fm.backward_search(b"LA".iter());
+    /// assert_eq!(interval.occ(&sa), [3, 12]);
+    /// ```
+    pub fn new_wavelet_tree(bwt: &BWTSlice) -> Self {
+        Self::build_wavelet_tree(bwt, false)
+    }
+
+    /// Calculate occ array backed by a wavelet tree, counting upper and lowercase variants
+    /// of a symbol as their uppercase variant if `case_insensitive` is given.
+    pub(crate) fn build_wavelet_tree(bwt: &BWTSlice, case_insensitive: bool) -> Self {
+        let wt = if case_insensitive {
+            WaveletTree::new(&bwt.to_ascii_uppercase())
+        } else {
+            WaveletTree::new(bwt)
+        };
+
+        Occ {
+            occ: Counts::WaveletTree(wt),
+            k: 1,
+            case_insensitive,
+        }
+    }
+
+    /// Calculate occ array backed by a 2-bit packed BWT with popcount-based rank queries
+    /// instead of sampled counts (see `PackedDnaRank`). This is advisable for DNA texts.
+    /// Symbols other than A, C, G and T (e.g. the sentinel and N) are supported, but should
+    /// be rare.
+    /// Time complexity: O(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new_packed_dna(&bwt);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// let interval = fm.backward_search(b"TTA".iter());
+    /// assert_eq!(interval.occ(&sa), [3, 12, 9]);
+    /// ```
+    pub fn new_packed_dna(bwt: &BWTSlice) -> Self {
+        Self::build_packed_dna(bwt, false)
+    }
+
+    /// Calculate occ array backed by a 2-bit packed BWT, counting lowercase bases as their
+    /// uppercase variant if `case_insensitive` is given.
+    pub(crate) fn build_packed_dna(bwt: &BWTSlice, case_insensitive: bool) -> Self {
+        let packed = if case_insensitive {
+            PackedDnaRank::new(&bwt.to_ascii_uppercase())
+        } else {
+            PackedDnaRank::new(bwt)
+        };
+
+        Occ {
+            occ: Counts::PackedDna(packed),
+            k: 1,
+            case_insensitive,
+        }
+    }
+
+    /// Calculate occ array backed by a run-length encoded BWT (see `RlBwt`) instead of
+    /// sampled counts. This needs memory proportional to the number of runs, which is
+    /// advisable for highly repetitive texts like collections of similar genomes.
+    /// Time complexity: O(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `k` - the sampling rate: symbol counts are stored for every k-th run
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"ACGTACGTACGTACGT$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new_run_length(&bwt, 4);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// let interval = fm.backward_search(b"GTAC".iter());
+    /// assert_eq!(interval.occ(&sa), [10, 6, 2]);
+    /// ```
+    pub fn new_run_length(bwt: &BWTSlice, k: usize) -> Self {
+        Self::build_run_length(bwt, k, false)
+    }
+
+    /// Calculate occ array backed by a run-length encoded BWT, counting lowercase symbols as
+    /// their uppercase variant if `case_insensitive` is given.
+    pub(crate) fn build_run_length(bwt: &BWTSlice, k: usize, case_insensitive: bool) -> Self {
+        let rlbwt = if case_insensitive {
+            RlBwt::new(&bwt.to_ascii_uppercase(), k)
+        } else {
+            RlBwt::new(bwt, k)
+        };
+
+        Occ {
+            occ: Counts::RunLength(rlbwt),
+            k: 1,
+            case_insensitive,
+        }
+    }
+
+    /// Calculate occ array, storing the checkpoint counts with 64 instead of 32 bit if `wide`
+    /// is given.
+    fn build(
+        bwt: &BWTSlice,
+        k: u32,
+        alphabet: &Alphabet,
+        case_insensitive: bool,
+        wide: bool,
+    ) -> Self {
+        Occ {
+            occ: sampled_counts(bwt, k, alphabet, case_insensitive, wide),
+            k,
+            case_insensitive,
+        }
+    }
+
+    /// Whether upper and lowercase variants of a symbol are counted as the same symbol.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// The sampling rate k: counts are stored for every k-th position of the BWT.
+    /// This is 1 for occ arrays that are not backed by sampled counts.
+    pub fn sampling_rate(&self) -> u32 {
+        self.k
+    }
+
+    /// Number of stored checkpoints, or 0 if the occ array is not backed by sampled counts
+    /// (see `new_wavelet_tree`, `new_packed_dna` and `new_run_length`).
+    pub fn num_checkpoints(&self) -> usize {
+        match self.occ {
+            Counts::U32(ref occ) => occ.len(),
+            Counts::U64(ref occ) => occ.len(),
+            Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => 0,
+        }
+    }
+
+    /// Return the i-th checkpoint, i.e. the counts of all symbols in bwt[..i * k + 1],
+    /// indexed by symbol up to the largest symbol of the alphabet. For case-insensitive
+    /// occ arrays, lowercase symbols are counted as their uppercase variant.
+    /// Returns `None` if there is no i-th checkpoint (see `num_checkpoints`), which is always
+    /// the case if the occ array is not backed by sampled counts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// use bio::data_structures::bwt::Occ;
+    ///
+    /// let bwt = b"ACCA$ACTA";
+    /// let occ = Occ::new(bwt, 4, &Alphabet::new(b"$ACT"));
+    /// assert_eq!(occ.num_checkpoints(), 3);
+    /// assert_eq!(occ.checkpoint(1).unwrap()[b'C' as usize], 2);
+    /// assert_eq!(occ.checkpoint(2).unwrap()[b'A' as usize], 4);
+    /// assert_eq!(occ.checkpoint(3), None);
+    /// ```
+    pub fn checkpoint(&self, i: usize) -> Option<Vec<usize>> {
+        match self.occ {
+            Counts::U32(ref occ) => occ
+                .get(i)
+                .map(|counts| counts.iter().map(|&count| count as usize).collect()),
+            Counts::U64(ref occ) => occ.get(i).cloned(),
+            Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => None,
+        }
     }
 
     /// Get occurrence count of symbol a in BWT[..r+1].
@@ -135,34 +1036,774 @@ impl Occ {
         // https://github.com/rust-bio/rust-bio/pull/74
         // https://github.com/rust-bio/rust-bio/pull/76
 
+        let folded = || {
+            if self.case_insensitive {
+                a.to_ascii_uppercase()
+            } else {
+                a
+            }
+        };
+        match self.occ {
+            Counts::WaveletTree(ref wt) => return wt.rank(folded(), r),
+            Counts::PackedDna(ref packed) => return packed.rank(folded(), r),
+            Counts::RunLength(ref rlbwt) => return rlbwt.rank(folded(), r),
+            _ => (),
+        }
+
         // self.k is our sampling rate, so find our last sampled checkpoint
         let i = r / self.k as usize;
-        let checkpoint = self.occ[i][a as usize];
 
         // find the portion of the BWT past the checkpoint which we need to count
         let start = (i * self.k as usize) + 1;
         let end = r + 1;
 
+        if self.case_insensitive && a.is_ascii_alphabetic() {
+            let (upper, lower) = (a.to_ascii_uppercase(), a.to_ascii_lowercase());
+            let checkpoint = self.occ.get(i, upper);
+            let count = bytecount::count(&bwt[start..end], upper)
+                + bytecount::count(&bwt[start..end], lower);
+            return checkpoint + (count as usize);
+        }
+
+        let checkpoint = self.occ.get(i, a);
+
         // count all the matching bytes b/t the closest checkpoint and our desired lookup
         let count = bytecount::count(&bwt[start..end], a);
 
         // return the sampled checkpoint for this character + the manual count we just did
         checkpoint + (count as usize)
     }
-}
 
-/// Calculate the less array for a given BWT. Complexity O(n).
-pub fn less(bwt: &BWTSlice, alphabet: &Alphabet) -> Less {
-    let m = alphabet
-        .max_symbol()
-        .expect("Expecting non-empty alphabet.") as usize
-        + 2;
-    let mut less: Less = repeat(0).take(m).collect();
-    for &c in bwt.iter() {
-        less[c as usize] += 1;
+    /// Get occurrences of the given symbols in bwt[..r+1] at once, indexed by symbol. For
+    /// sampled counts, this needs a single pass from the closest checkpoint, instead of one
+    /// per symbol as with `get`, and counts all symbols. Otherwise, only the given symbols
+    /// are counted.
+    /// Complexity: O(k + A) with the alphabet size A.
+    pub fn get_all(&self, bwt: &BWTSlice, r: usize, symbols: &[u8]) -> [usize; 256] {
+        self.get_all_bounds(bwt, r + 1, r + 1, symbols).1
+    }
+
+    /// Get occurrences of the given symbols in bwt[..lower] and bwt[..upper] at once, indexed
+    /// by symbol, i.e. the counts at both ends of the interval of rows `lower..upper`. For
+    /// sampled counts, the counts at the upper end continue from those at the lower end if
+    /// both share a checkpoint.
+    ///
+    /// This is used by `FMDIndex::backward_ext`, which needs the counts of all DNA symbols at
+    /// both ends of a bi-interval.
+    pub fn get_all_bounds(
+        &self,
+        bwt: &BWTSlice,
+        lower: usize,
+        upper: usize,
+        symbols: &[u8],
+    ) -> ([usize; 256], [usize; 256]) {
+        assert!(lower <= upper, "Expecting lower <= upper.");
+        let (mut lower_counts, mut upper_counts) = ([0; 256], [0; 256]);
+        let k = self.k as usize;
+        match self.occ {
+            Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => {
+                for &a in symbols {
+                    if lower > 0 {
+                        lower_counts[a as usize] = self.get(bwt, lower - 1, a);
+                    }
+                    if upper > 0 {
+                        upper_counts[a as usize] = self.get(bwt, upper - 1, a);
+                    }
+                }
+                return (lower_counts, upper_counts);
+            }
+            Counts::U32(_) | Counts::U64(_) => (),
+        }
+
+        if lower > 0 {
+            self.count_from_checkpoint(bwt, lower - 1, &mut lower_counts);
+        }
+        if upper > 0 {
+            if lower > 0 && (lower - 1) / k == (upper - 1) / k {
+                upper_counts = lower_counts;
+                for &c in &bwt[lower..upper] {
+                    upper_counts[c as usize] += 1;
+                }
+            } else {
+                self.count_from_checkpoint(bwt, upper - 1, &mut upper_counts);
+            }
+        }
+        if self.case_insensitive {
+            fold_case(&mut lower_counts);
+            fold_case(&mut upper_counts);
+        }
+
+        (lower_counts, upper_counts)
+    }
+
+    /// Write the occurrences of all symbols in bwt[..r+1] into `counts`, counting lowercase
+    /// symbols past the checkpoint separately for case-insensitive counts.
+    fn count_from_checkpoint(&self, bwt: &BWTSlice, r: usize, counts: &mut [usize; 256]) {
+        let i = r / self.k as usize;
+        match self.occ {
+            Counts::U32(ref occ) => {
+                for (count, &c) in counts.iter_mut().zip(occ[i].iter()) {
+                    *count = c as usize;
+                }
+            }
+            Counts::U64(ref occ) => counts[..occ[i].len()].copy_from_slice(&occ[i]),
+            Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => {
+                unreachable!("only sampled counts have checkpoints")
+            }
+        }
+        for &c in &bwt[(i * self.k as usize) + 1..r + 1] {
+            counts[c as usize] += 1;
+        }
+    }
+}
+
+/// Add the separately counted lowercase symbols to their uppercase variant, and give both
+/// variants the same count.
+fn fold_case(counts: &mut [usize; 256]) {
+    for c in b'a'..=b'z' {
+        let (upper, lower) = (c.to_ascii_uppercase() as usize, c as usize);
+        counts[upper] += counts[lower];
+        counts[lower] = counts[upper];
+    }
+}
+
+/// Counts of an `Occ`, either sampled checkpoints stored with 32 bit whenever the BWT is
+/// short enough, a wavelet tree, a packed DNA BWT or a run-length encoded BWT. The
+/// representation is externally tagged, such that it can be deserialized from formats that
+/// are not self-describing (e.g. bincode).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Counts {
+    U32(Vec<Vec<u32>>),
+    U64(Vec<Vec<usize>>),
+    WaveletTree(WaveletTree),
+    PackedDna(PackedDnaRank),
+    RunLength(RlBwt),
+}
+
+impl Counts {
+    /// Get the count of symbol a at the i-th checkpoint.
+    fn get(&self, i: usize, a: u8) -> usize {
+        match *self {
+            Counts::U32(ref occ) => occ[i][a as usize] as usize,
+            Counts::U64(ref occ) => occ[i][a as usize],
+            Counts::WaveletTree(_) | Counts::PackedDna(_) | Counts::RunLength(_) => {
+                unreachable!("only sampled counts have checkpoints")
+            }
+        }
+    }
+}
+
+/// Whether the counts of the given BWT may exceed 32 bit.
+fn is_wide(bwt: &BWTSlice) -> bool {
+    bwt.len() > u32::MAX as usize
+}
+
+/// Calculate the checkpoint counts of the given BWT, with 64 instead of 32 bit if `wide` is
+/// given.
+#[cfg(not(feature = "parallel"))]
+fn sampled_counts(
+    bwt: &BWTSlice,
+    k: u32,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+    wide: bool,
+) -> Counts {
+    if wide {
+        Counts::U64(checkpoints(bwt, k, alphabet, case_insensitive))
+    } else {
+        Counts::U32(checkpoints(bwt, k, alphabet, case_insensitive))
+    }
+}
+
+/// Calculate the checkpoint counts of the given BWT, with 64 instead of 32 bit if `wide` is
+/// given, counting one chunk of the BWT per available core in parallel.
+#[cfg(feature = "parallel")]
+fn sampled_counts(
+    bwt: &BWTSlice,
+    k: u32,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+    wide: bool,
+) -> Counts {
+    sampled_counts_parallel(
+        bwt,
+        k,
+        alphabet,
+        case_insensitive,
+        wide,
+        parallel_chunk_size(bwt.len()),
+    )
+}
+
+/// Calculate the checkpoint counts of the given BWT, with 64 instead of 32 bit if `wide` is
+/// given, counting chunks of the given size in parallel.
+#[cfg(feature = "parallel")]
+fn sampled_counts_parallel(
+    bwt: &BWTSlice,
+    k: u32,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+    wide: bool,
+    chunk_size: usize,
+) -> Counts {
+    if chunk_size >= bwt.len() {
+        // a single chunk, no need to spawn threads
+        if wide {
+            Counts::U64(checkpoints(bwt, k, alphabet, case_insensitive))
+        } else {
+            Counts::U32(checkpoints(bwt, k, alphabet, case_insensitive))
+        }
+    } else if wide {
+        Counts::U64(checkpoints_parallel(
+            bwt,
+            k,
+            alphabet,
+            case_insensitive,
+            chunk_size,
+        ))
+    } else {
+        Counts::U32(checkpoints_parallel(
+            bwt,
+            k,
+            alphabet,
+            case_insensitive,
+            chunk_size,
+        ))
+    }
+}
+
+/// Minimal number of BWT symbols counted by one thread.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_CHUNK_SIZE: usize = 1 << 20;
+
+/// Size of the chunks counted in parallel, such that there is one chunk per available core.
+#[cfg(feature = "parallel")]
+fn parallel_chunk_size(n: usize) -> usize {
+    let threads = thread::available_parallelism().map_or(1, |t| t.get());
+    n.div_ceil(threads).max(MIN_PARALLEL_CHUNK_SIZE)
+}
+
+/// Count the occurrences of all symbols below m in the given BWT.
+fn symbol_counts(bwt: &BWTSlice, m: usize, case_insensitive: bool) -> Vec<usize> {
+    let mut counts = vec![0; m];
+    for &c in bwt {
+        let c = if case_insensitive {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        };
+        counts[c as usize] += 1;
+    }
+
+    counts
+}
+
+/// Count the occurrences of all symbols below m in the given BWT, counting chunks of the
+/// given size in parallel.
+#[cfg(feature = "parallel")]
+fn symbol_counts_parallel(
+    bwt: &BWTSlice,
+    m: usize,
+    case_insensitive: bool,
+    chunk_size: usize,
+) -> Vec<usize> {
+    let partials: Vec<Vec<usize>> = thread::scope(|scope| {
+        let handles: Vec<_> = bwt
+            .chunks(chunk_size.max(1))
+            .map(|chunk| scope.spawn(move || symbol_counts(chunk, m, case_insensitive)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut counts = vec![0; m];
+    for partial in partials {
+        for (count, p) in counts.iter_mut().zip(partial) {
+            *count += p;
+        }
+    }
+
+    counts
+}
+
+/// Calculate the counts of all symbols in bwt[..i+1] for every k-th position i, counting
+/// chunks of the given size in parallel. The result is identical to `checkpoints`.
+#[cfg(feature = "parallel")]
+fn checkpoints_parallel<T: NumCast + Copy + Send>(
+    bwt: &BWTSlice,
+    k: u32,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+    chunk_size: usize,
+) -> Vec<Vec<T>> {
+    let k = k as usize;
+    let m = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.") as usize
+        + 1;
+    // align chunks to the sampling rate, such that checkpoints are at the same offsets in
+    // every chunk
+    let chunk_size = chunk_size.max(1).div_ceil(k) * k;
+    let chunks: Vec<&BWTSlice> = bwt.chunks(chunk_size).collect();
+
+    // first pass: totals per chunk
+    let totals: Vec<Vec<usize>> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| scope.spawn(move || symbol_counts(chunk, m, case_insensitive)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    // counts preceding each chunk
+    let mut offsets = Vec::with_capacity(chunks.len());
+    let mut curr_occ = vec![0; m];
+    for total in &totals {
+        offsets.push(curr_occ.clone());
+        for (count, t) in curr_occ.iter_mut().zip(total) {
+            *count += t;
+        }
+    }
+
+    // second pass: checkpoints per chunk, starting from the preceding counts
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .zip(offsets)
+            .map(|(chunk, mut curr_occ)| {
+                scope.spawn(move || {
+                    let mut occ: Vec<Vec<T>> = Vec::with_capacity(chunk.len().div_ceil(k));
+                    for (i, &c) in chunk.iter().enumerate() {
+                        let c = if case_insensitive {
+                            c.to_ascii_uppercase()
+                        } else {
+                            c
+                        };
+                        curr_occ[c as usize] += 1;
+                        if i % k == 0 {
+                            occ.push(curr_occ.iter().map(|&count| cast(count).unwrap()).collect());
+                        }
+                    }
+                    occ
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+/// Calculate the counts of all symbols in bwt[..i+1] for every k-th position i.
+fn checkpoints<T: NumCast + Copy>(
+    bwt: &BWTSlice,
+    k: u32,
+    alphabet: &Alphabet,
+    case_insensitive: bool,
+) -> Vec<Vec<T>> {
+    let n = bwt.len();
+    let m = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.") as usize
+        + 1;
+    let mut occ = Vec::with_capacity(n / k as usize);
+    let mut curr_occ: Vec<usize> = repeat(0).take(m).collect();
+    for (i, &c) in bwt.iter().enumerate() {
+        let c = if case_insensitive {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        };
+        curr_occ[c as usize] += 1;
+        if i % k as usize == 0 {
+            occ.push(curr_occ.iter().map(|&count| cast(count).unwrap()).collect());
+        }
+    }
+
+    occ
+}
+
+/// A run-length compressed BWT, storing the symbol and start of each run of equal symbols
+/// together with the symbol counts at every k-th run. For repetitive texts (e.g. collections
+/// of similar sequences), the number of runs r is much smaller than the length n of the BWT.
+/// Space complexity: O(r + r / k * A) with A being the number of distinct symbols.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::RlBwt;
+///
+/// let rlbwt = RlBwt::new(b"AAACC$GGGA", 2);
+/// assert_eq!(rlbwt.len(), 10);
+/// assert_eq!(rlbwt.runs(), 5);
+/// assert_eq!(rlbwt.symbol_at(4), b'C');
+/// assert_eq!(rlbwt.rank(b'A', 8), 3);
+/// assert_eq!(rlbwt.rank(b'A', 9), 4);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RlBwt {
+    heads: Vec<u8>,
+    starts: Vec<usize>,
+    symbols: Vec<u8>,
+    occ: Vec<usize>,
+    k: usize,
+    len: usize,
+}
+
+impl RlBwt {
+    /// Compress the given BWT. Complexity: O(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `k` - the sampling rate: symbol counts are stored for every k-th run
+    pub fn new(bwt: &BWTSlice, k: usize) -> Self {
+        assert!(k > 0, "Expecting a sampling rate of at least 1.");
+        let mut heads = Vec::new();
+        let mut starts = Vec::new();
+        for (r, &c) in bwt.iter().enumerate() {
+            if heads.last() != Some(&c) {
+                heads.push(c);
+                starts.push(r);
+            }
+        }
+        let symbols: Vec<u8> = Alphabet::new(bwt).symbols.iter().map(|c| c as u8).collect();
+
+        // counts of all symbols in the runs preceding each k-th run
+        let mut occ = Vec::with_capacity((heads.len() / k + 1) * symbols.len());
+        let mut curr_occ = vec![0; symbols.len()];
+        for (j, &c) in heads.iter().enumerate() {
+            if j % k == 0 {
+                occ.extend_from_slice(&curr_occ);
+            }
+            let end = starts.get(j + 1).cloned().unwrap_or(bwt.len());
+            curr_occ[symbols.binary_search(&c).unwrap()] += end - starts[j];
+        }
+
+        RlBwt {
+            heads,
+            starts,
+            symbols,
+            occ,
+            k,
+            len: bwt.len(),
+        }
+    }
+
+    /// Length of the BWT.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the BWT is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of runs of equal symbols.
+    pub fn runs(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Approximate number of bytes allocated on the heap.
+    pub fn heap_size(&self) -> usize {
+        self.heads.len()
+            + self.starts.len() * mem::size_of::<usize>()
+            + self.symbols.len()
+            + self.occ.len() * mem::size_of::<usize>()
+    }
+
+    /// Return the symbol at position r of the BWT. Complexity: O(log r).
+    pub fn symbol_at(&self, r: usize) -> u8 {
+        self.heads[self.run(r)]
+    }
+
+    /// Get the occurrences of symbol a in bwt[..r+1], like `Occ::get`.
+    /// Complexity: O(log r + k).
+    pub fn rank(&self, a: u8, r: usize) -> usize {
+        let j = self.run(r);
+        let i = j / self.k;
+        let mut count = match self.symbols.binary_search(&a) {
+            Ok(rank) => self.occ[i * self.symbols.len() + rank],
+            Err(_) => return 0,
+        };
+        for t in i * self.k..j {
+            if self.heads[t] == a {
+                count += self.starts[t + 1] - self.starts[t];
+            }
+        }
+        if self.heads[j] == a {
+            count += r - self.starts[j] + 1;
+        }
+
+        count
+    }
+
+    /// Decompress into the plain BWT.
+    pub fn decompress(&self) -> BWT {
+        let mut bwt = Vec::with_capacity(self.len);
+        for (j, &c) in self.heads.iter().enumerate() {
+            let end = self.starts.get(j + 1).cloned().unwrap_or(self.len);
+            bwt.resize(end, c);
+        }
+
+        bwt
+    }
+
+    /// Index of the run containing position r.
+    fn run(&self, r: usize) -> usize {
+        assert!(r < self.len, "Expecting position within the BWT.");
+        match self.starts.binary_search(&r) {
+            Ok(j) => j,
+            Err(j) => j - 1,
+        }
+    }
+}
+
+/// Rank support for DNA BWTs, storing the BWT with 2 bit per symbol in blocks of 64 symbols
+/// together with the counts of A, C, G and T preceding each block. The occurrences of a base
+/// within a block are counted by a single popcount on the masked bits of the block. All other
+/// symbols (e.g. the sentinel and N) are rare, and their positions are stored in an
+/// exception list instead.
+/// Space complexity: O(n / 4 + n / 64 * 4 words + e) for e exceptions.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::PackedDnaRank;
+///
+/// let rank = PackedDnaRank::new(b"ATTATTCAGGACCC$CTTTCAA");
+/// assert_eq!(rank.len(), 22);
+/// assert_eq!(rank.rank(b'C', 14), 4);
+/// assert_eq!(rank.rank(b'$', 14), 1);
+/// assert_eq!(rank.rank(b'A', 21), 6);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackedDnaRank {
+    /// high and low bits of the codes of each block, exceptions being encoded as A
+    blocks: Vec<[u64; 2]>,
+    /// counts of the codes preceding each block
+    counts: Vec<[usize; 4]>,
+    /// sorted positions of all exceptions
+    exceptions: Vec<usize>,
+    /// sorted positions of the exceptions, per exception symbol
+    exception_symbols: Vec<(u8, Vec<usize>)>,
+    len: usize,
+}
+
+impl PackedDnaRank {
+    /// Pack the given BWT. Complexity: O(n).
+    pub fn new(bwt: &BWTSlice) -> Self {
+        let n_blocks = bwt.len().div_ceil(64);
+        let mut blocks = Vec::with_capacity(n_blocks);
+        let mut counts = Vec::with_capacity(n_blocks);
+        let mut exceptions = Vec::new();
+        let mut exception_symbols: Vec<(u8, Vec<usize>)> = Vec::new();
+        let mut curr_counts = [0; 4];
+        for (b, chunk) in bwt.chunks(64).enumerate() {
+            counts.push(curr_counts);
+            let (mut hi, mut lo) = (0u64, 0u64);
+            for (i, &c) in chunk.iter().enumerate() {
+                let code = match dna_code(c) {
+                    Some(code) => code,
+                    None => {
+                        let pos = b * 64 + i;
+                        exceptions.push(pos);
+                        match exception_symbols.iter_mut().find(|&&mut (a, _)| a == c) {
+                            Some(&mut (_, ref mut positions)) => positions.push(pos),
+                            None => exception_symbols.push((c, vec![pos])),
+                        }
+                        0
+                    }
+                };
+                hi |= ((code >> 1) as u64) << i;
+                lo |= ((code & 1) as u64) << i;
+                curr_counts[code] += 1;
+            }
+            blocks.push([hi, lo]);
+        }
+
+        PackedDnaRank {
+            blocks,
+            counts,
+            exceptions,
+            exception_symbols,
+            len: bwt.len(),
+        }
+    }
+
+    /// Length of the BWT.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the BWT is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the occurrences of symbol a in bwt[..r+1], like `Occ::get`.
+    /// Complexity: O(1) for A, C, G and T, O(log e) otherwise.
+    pub fn rank(&self, a: u8, r: usize) -> usize {
+        assert!(r < self.len, "Expecting position within the BWT.");
+        let code = match dna_code(a) {
+            Some(code) => code,
+            None => {
+                return match self.exception_symbols.iter().find(|&&(c, _)| c == a) {
+                    Some((_, positions)) => positions.partition_point(|&p| p <= r),
+                    None => 0,
+                };
+            }
+        };
+
+        let (block, offset) = (r / 64, r % 64);
+        let [hi, lo] = self.blocks[block];
+        let hi = if code & 2 != 0 { hi } else { !hi };
+        let lo = if code & 1 != 0 { lo } else { !lo };
+        // select the positions up to and including the offset
+        let mask = u64::MAX >> (63 - offset);
+        let count = self.counts[block][code] + (hi & lo & mask).count_ones() as usize;
+        if code == 0 {
+            // exceptions are encoded as A
+            count - self.exceptions.partition_point(|&p| p <= r)
+        } else {
+            count
+        }
+    }
+}
+
+/// The 2-bit code of an uppercase DNA base.
+fn dna_code(a: u8) -> Option<usize> {
+    match a {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+/// Count the occurrences of all symbols below m in the BWT.
+#[cfg(not(feature = "parallel"))]
+fn total_counts(bwt: &BWTSlice, m: usize, case_insensitive: bool) -> Vec<usize> {
+    symbol_counts(bwt, m, case_insensitive)
+}
+
+/// Count the occurrences of all symbols below m in the BWT, counting chunks in parallel.
+#[cfg(feature = "parallel")]
+fn total_counts(bwt: &BWTSlice, m: usize, case_insensitive: bool) -> Vec<usize> {
+    symbol_counts_parallel(bwt, m, case_insensitive, parallel_chunk_size(bwt.len()))
+}
+
+/// Calculate the less array for a given BWT. Complexity O(n).
+/// With the `parallel` feature, chunks of the BWT are counted in parallel.
+pub fn less(bwt: &BWTSlice, alphabet: &Alphabet) -> Less {
+    let m = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.") as usize
+        + 2;
+    let mut less: Less = total_counts(bwt, m, false);
+    // calculate +-prescan
+    prescan(&mut less[..], 0, |a, b| a + b);
+
+    less
+}
+
+/// Calculate the less array for a given BWT like `less`, counting chunks of the BWT of the
+/// given size in parallel.
+#[cfg(feature = "parallel")]
+pub fn less_parallel(bwt: &BWTSlice, alphabet: &Alphabet, chunk_size: usize) -> Less {
+    let m = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.") as usize
+        + 2;
+    let mut less: Less = symbol_counts_parallel(bwt, m, false, chunk_size);
+    // calculate +-prescan
+    prescan(&mut less[..], 0, |a, b| a + b);
+
+    less
+}
+
+/// Calculate the less array for a given BWT like `less`, for a text that is terminated by the
+/// given sentinel, which is counted as smaller than all other symbols regardless of its value
+/// (see `suffix_array::suffix_array_with_sentinel`). Complexity O(n).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::Alphabet;
+/// use bio::data_structures::bwt::{bwt_from_text_with_sentinel, less_with_sentinel};
+///
+/// let text = [2, 0, 1, 0xff, 0, 0, 0xff];
+/// let bwt = bwt_from_text_with_sentinel(&text, 0xff).unwrap();
+/// let less = less_with_sentinel(&bwt, &Alphabet::new(&[0, 1, 2]), 0xff);
+/// assert_eq!((less[0], less[1], less[2], less[0xff]), (2, 5, 6, 0));
+/// ```
+pub fn less_with_sentinel(bwt: &BWTSlice, alphabet: &Alphabet, sentinel: u8) -> Less {
+    let max_symbol = alphabet
+        .max_symbol()
+        .expect("Expecting non-empty alphabet.")
+        .max(sentinel);
+    let mut less: Less = total_counts(bwt, max_symbol as usize + 2, false);
+    // calculate +-prescan
+    prescan(&mut less[..], 0, |a, b| a + b);
+    order_sentinel_first(&mut less, sentinel);
+
+    less
+}
+
+/// Calculate the less array like `less_case_insensitive`, for a text that is terminated by the
+/// given sentinel (see `less_with_sentinel`).
+pub(crate) fn less_case_insensitive_with_sentinel(
+    bwt: &BWTSlice,
+    alphabet: &Alphabet,
+    sentinel: u8,
+) -> Less {
+    case_insensitive_less(bwt, alphabet, Some(sentinel))
+}
+
+/// Adjust a less array calculated in byte order, such that the sentinel is counted as smaller
+/// than all other symbols. If no symbol is smaller than the sentinel anyway, the array is
+/// left unchanged.
+fn order_sentinel_first(less: &mut Less, sentinel: u8) {
+    let sentinel = sentinel as usize;
+    if less[sentinel] == 0 {
+        return;
+    }
+    let count = less[sentinel + 1] - less[sentinel];
+    for l in &mut less[..sentinel] {
+        *l += count;
+    }
+    less[sentinel] = 0;
+}
+
+/// Calculate the less array for a given BWT, counting upper and lowercase variants of a
+/// symbol as the same symbol (see `Occ::new_case_insensitive`). Complexity O(n).
+pub fn less_case_insensitive(bwt: &BWTSlice, alphabet: &Alphabet) -> Less {
+    case_insensitive_less(bwt, alphabet, None)
+}
+
+/// Calculate the case-insensitive less array, with the given sentinel counted first.
+fn case_insensitive_less(bwt: &BWTSlice, alphabet: &Alphabet, sentinel: Option<u8>) -> Less {
+    let max_symbol = alphabet
+        .symbols
+        .iter()
+        .map(|a| (a as u8).to_ascii_lowercase())
+        .max()
+        .expect("Expecting non-empty alphabet.");
+    let m = max_symbol.max(sentinel.unwrap_or(0)) as usize + 2;
+    let mut less: Less = total_counts(bwt, m, true);
+    // calculate +-prescan
+    prescan(&mut less[..], 0, |a, b| a + b);
+    if let Some(sentinel) = sentinel {
+        order_sentinel_first(&mut less, sentinel);
+    }
+    // lowercase symbols share the bucket of their uppercase variant
+    for c in b'a'..=b'z' {
+        if (c as usize) < m && Some(c) != sentinel {
+            less[c as usize] = less[c.to_ascii_uppercase() as usize];
+        }
     }
-    // calculate +-prescan
-    prescan(&mut less[..], 0, |a, b| a + b);
 
     less
 }
@@ -183,9 +1824,13 @@ pub fn bwtfind(bwt: &BWTSlice, alphabet: &Alphabet) -> BWTFind {
 
 #[cfg(test)]
 mod tests {
-    use super::{bwt, bwtfind, invert_bwt, Occ};
-    use crate::alphabets::Alphabet;
+    use super::{
+        bwt, bwt_from_text, bwt_to_writer, bwtfind, invert_bwt, less_case_insensitive, merge,
+        merge_by, sentinel_map, write_bwt_buckets, Counts, Occ, PackedDnaRank, RlBwt,
+    };
+    use crate::alphabets::{dna, Alphabet};
     use crate::data_structures::suffix_array::suffix_array;
+    use rand::{Rng, SeedableRng, StdRng};
 
     #[test]
     fn test_bwtfind() {
@@ -206,13 +1851,420 @@ mod tests {
         assert_eq!(inverse, text);
     }
 
+    #[test]
+    fn test_invert_bwt_random_texts() {
+        let mut rng = StdRng::from_seed(&[42]);
+        for _ in 0..200 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 5) {
+                for _ in 0..rng.gen_range(0, 50) {
+                    text.push(*rng.choose(b"ACGT").unwrap());
+                }
+                text.push(b'$');
+            }
+            let pos = suffix_array(&text);
+            let bwt = bwt(&text, &pos);
+            assert_eq!(invert_bwt(&bwt), text);
+        }
+    }
+
+    #[test]
+    fn test_occ_wide() {
+        let mut rng = StdRng::from_seed(&[11]);
+        let mut text: Vec<u8> = (0..5000)
+            .map(|_| *rng.choose(b"ACGTacgt").unwrap())
+            .collect();
+        text.push(b'$');
+        let bwt = bwt(&text, &suffix_array(&text));
+        let alphabet = Alphabet::new(b"$ACGTacgt");
+
+        for &case_insensitive in &[false, true] {
+            let narrow = Occ::build(&bwt, 7, &alphabet, case_insensitive, false);
+            let wide = Occ::build(&bwt, 7, &alphabet, case_insensitive, true);
+            match (&narrow.occ, &wide.occ) {
+                (Counts::U32(_), Counts::U64(_)) => (),
+                _ => panic!("unexpected checkpoint width"),
+            }
+            for r in 0..bwt.len() {
+                assert_eq!(
+                    narrow.get_all(&bwt, r, b"$ACGTacgt")[..],
+                    wide.get_all(&bwt, r, b"$ACGTacgt")[..]
+                );
+                for &a in b"$ACGTacgt" {
+                    assert_eq!(narrow.get(&bwt, r, a), wide.get(&bwt, r, a));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_counts() {
+        use super::{
+            checkpoints, checkpoints_parallel, less, less_parallel, symbol_counts,
+            symbol_counts_parallel,
+        };
+
+        let mut rng = StdRng::from_seed(&[5]);
+        let mut text: Vec<u8> = (0..3_000_000)
+            .map(|_| *rng.choose(b"ACGTNacgtn").unwrap())
+            .collect();
+        text.push(b'$');
+        let bwt = bwt_from_text(&text);
+        let alphabet = Alphabet::new(b"$ACGTNacgtn");
+        let m = alphabet.max_symbol().unwrap() as usize + 1;
+
+        for &(len, chunk_sizes) in &[
+            (1000, &[1, 31, 999][..]),
+            (bwt.len(), &[65_537, 500_000, bwt.len() + 1][..]),
+        ] {
+            let bwt = &bwt[..len];
+            for &case_insensitive in &[false, true] {
+                let expected: Vec<Vec<u32>> = checkpoints(bwt, 32, &alphabet, case_insensitive);
+                let counts = symbol_counts(bwt, m, case_insensitive);
+                for &chunk_size in chunk_sizes {
+                    let occ: Vec<Vec<u32>> =
+                        checkpoints_parallel(bwt, 32, &alphabet, case_insensitive, chunk_size);
+                    assert!(occ == expected);
+                    assert_eq!(
+                        symbol_counts_parallel(bwt, m, case_insensitive, chunk_size),
+                        counts
+                    );
+                }
+                if !case_insensitive {
+                    let occ = Occ::new(bwt, 32, &alphabet);
+                    let less = less(bwt, &alphabet);
+                    for &chunk_size in chunk_sizes {
+                        assert!(Occ::new_parallel(bwt, 32, &alphabet, chunk_size) == occ);
+                        assert_eq!(less_parallel(bwt, &alphabet, chunk_size), less);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bwt_to_writer() {
+        let mut rng = StdRng::from_seed(&[17]);
+        let mut text = Vec::new();
+        while text.len() < 2_000_000 {
+            let len = rng.gen_range(0, 20_000);
+            text.extend((0..len).map(|_| *rng.choose(b"ACGTN").unwrap()));
+            text.push(b'$');
+        }
+        // repeated sequences, and more sequences ending with the same prefix than fit into
+        // a block
+        for _ in 0..20 {
+            text.extend_from_slice(b"GATTACA$");
+        }
+        text.extend_from_slice(b"$$");
+        let expected = bwt(&text, &suffix_array(&text));
+        let alphabet = Alphabet::new(b"$ACGTN");
+
+        let chunks = text.chunks(65_536).map(|chunk| chunk.to_vec());
+        let mut streamed = Vec::new();
+        bwt_to_writer(chunks, text.len() + 1, &mut streamed).unwrap();
+        assert!(streamed == expected);
+
+        // no more than the block size of positions is held in memory at once
+        for &block_size in &[4096, 100_000] {
+            let mut streamed = Vec::new();
+            let peak = write_bwt_buckets(&text, block_size, &mut streamed).unwrap();
+            assert!(streamed == expected);
+            assert!(peak <= block_size);
+        }
+
+        let text = &text[text.len() - 5000..];
+        let expected_short = bwt(text, &suffix_array(text));
+        for &block_size in &[1, 7] {
+            let mut streamed = Vec::new();
+            bwt_to_writer(vec![text.to_vec()], block_size, &mut streamed).unwrap();
+            assert_eq!(streamed, expected_short);
+        }
+
+        for &k in &[1, 32] {
+            let occ = Occ::from_reader(&expected[..], k, &alphabet).unwrap();
+            assert!(occ.occ == Occ::new(&expected, k, &alphabet).occ);
+        }
+
+        let mut streamed = Vec::new();
+        bwt_to_writer(Vec::new(), 3, &mut streamed).unwrap();
+        assert!(streamed.is_empty());
+    }
+
+    #[test]
+    fn test_packed_dna_rank() {
+        let mut rng = StdRng::from_seed(&[29]);
+        let alphabet = Alphabet::new(b"$ACGTN");
+        for &len in &[1, 63, 64, 65, 1000, 20_000] {
+            let mut text: Vec<u8> = (0..len)
+                .map(|_| {
+                    if rng.gen_range(0, 50) == 0 {
+                        b'N'
+                    } else {
+                        *rng.choose(b"ACGT").unwrap()
+                    }
+                })
+                .collect();
+            for _ in 0..3 {
+                let pos = rng.gen_range(0, text.len());
+                text.insert(pos, b'$');
+            }
+            text.push(b'$');
+            let bwt = bwt(&text, &suffix_array(&text));
+            let occ = Occ::new(&bwt, 32, &alphabet);
+            let packed = PackedDnaRank::new(&bwt);
+            assert_eq!(packed.len(), bwt.len());
+
+            let n = bwt.len();
+            let mut positions: Vec<usize> = (0..n.min(70)).chain(n.saturating_sub(70)..n).collect();
+            positions.extend((0..500).map(|_| rng.gen_range(0, n)));
+            for &r in &positions {
+                for &a in b"$ACGTN" {
+                    assert_eq!(packed.rank(a, r), occ.get(&bwt, r, a));
+                }
+                assert_eq!(packed.rank(b'X', r), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sentinel_map() {
+        let text = b"ACGTACGTTT$GA$TTAGCA$";
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let map = sentinel_map(text);
+        assert_eq!(map, [2, 1, 0]);
+
+        // walking the LF-mapping backwards from each sentinel row passes through the
+        // sequence it terminates
+        let seqs: Vec<&[u8]> = text[..text.len() - 1].split(|&c| c == b'$').collect();
+        let alphabet = Alphabet::new(b"$ACGT");
+        let mut lf = vec![0; bwt.len()];
+        for (r, &p) in bwtfind(&bwt, &alphabet).iter().enumerate() {
+            lf[p] = r;
+        }
+        for (row, &seq) in map.iter().enumerate() {
+            let mut walked = Vec::new();
+            let mut r = row;
+            while bwt[r] != b'$' {
+                walked.push(bwt[r]);
+                r = lf[r];
+            }
+            walked.reverse();
+            assert_eq!(walked, seqs[seq]);
+        }
+
+        // a sentinel other than `$`, with the sentinel rows of the suffix array
+        let text = b"AC#G#TTAG#";
+        let sa = suffix_array(text);
+        let ends = [2, 4, 9];
+        let map = sentinel_map(text);
+        for (row, &seq) in map.iter().enumerate() {
+            assert_eq!(sa[row], ends[seq]);
+        }
+
+        assert!(sentinel_map(b"").is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut rng = StdRng::from_seed(&[23]);
+        let alphabet = Alphabet::new(b"$ACGTacgt");
+        let random_text = |rng: &mut StdRng, symbols: &[u8]| {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 4) {
+                let len = rng.gen_range(0, 60);
+                text.extend((0..len).map(|_| *rng.choose(symbols).unwrap()));
+                text.push(b'$');
+            }
+            text
+        };
+        for i in 0..300 {
+            let case_insensitive = i % 2 == 1;
+            let symbols: &[u8] = if case_insensitive {
+                b"ACGTacgt"
+            } else {
+                b"ACGT"
+            };
+            let a = random_text(&mut rng, symbols);
+            let b = random_text(&mut rng, symbols);
+            let text = [&a[..], &b[..]].concat();
+            let (bwt_a, bwt_b, expected) = if case_insensitive {
+                let sa = |text: &[u8]| suffix_array(&text.to_ascii_uppercase());
+                (bwt(&a, &sa(&a)), bwt(&b, &sa(&b)), bwt(&text, &sa(&text)))
+            } else {
+                (
+                    bwt(&a, &suffix_array(&a)),
+                    bwt(&b, &suffix_array(&b)),
+                    bwt(&text, &suffix_array(&text)),
+                )
+            };
+            let merged = merge_by(&bwt_a, &bwt_b, &alphabet, case_insensitive);
+            assert_eq!(merged, expected);
+            if !case_insensitive {
+                assert_eq!(invert_bwt(&merged), text);
+            }
+        }
+        assert_eq!(merge(b"", b"T$A", &alphabet), b"T$A");
+    }
+
+    #[test]
+    fn test_occ_get_all() {
+        let mut rng = StdRng::from_seed(&[3]);
+        let alphabet = Alphabet::new(b"$ACGTNacgtn");
+        let symbols = b"$ACGTNacgtn";
+        for _ in 0..20 {
+            let text: Vec<u8> = (0..rng.gen_range(1, 500))
+                .map(|_| *rng.choose(b"ACGTNacgtn").unwrap())
+                .chain(std::iter::once(b'$'))
+                .collect();
+            let bwt = bwt(&text, &suffix_array(&text));
+            let k = rng.gen_range(1, 40);
+            for occ in &[
+                Occ::new(&bwt, k, &alphabet),
+                Occ::new_case_insensitive(&bwt, k, &alphabet),
+                Occ::new_wavelet_tree(&bwt),
+                Occ::new_packed_dna(&bwt),
+                Occ::new_run_length(&bwt, k as usize),
+                Occ::build_wavelet_tree(&bwt, true),
+            ] {
+                for r in 0..bwt.len() {
+                    let counts = occ.get_all(&bwt, r, symbols);
+                    for &a in symbols {
+                        assert_eq!(counts[a as usize], occ.get(&bwt, r, a));
+                    }
+                }
+                // interval bounds sharing a checkpoint or not
+                for _ in 0..100 {
+                    let lower = rng.gen_range(0, bwt.len() + 1);
+                    let upper = rng.gen_range(lower, (lower + 2 * k as usize).min(bwt.len()) + 1);
+                    let (lower_counts, upper_counts) =
+                        occ.get_all_bounds(&bwt, lower, upper, symbols);
+                    for &a in symbols {
+                        let count = |end: usize| {
+                            if end == 0 {
+                                0
+                            } else {
+                                occ.get(&bwt, end - 1, a)
+                            }
+                        };
+                        assert_eq!(lower_counts[a as usize], count(lower));
+                        assert_eq!(upper_counts[a as usize], count(upper));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rlbwt() {
+        let reads = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG$CCGCCCACTTCGACCTCCCA\
+                      AAGTGCTGGGATTACAGGCATAAGCCACCACGCC$CGAAGTGGGCGGATCACTTGAGGTCAGGAGTTGGAGACT\
+                      AGCCTGGCCAACACGATGAAACCCCGTCTCTAATA$TATTAGAGACGGGGTTTCATCGTGTTGGCCAGGCTAGT\
+                      CTCCAACTCCTGACCTCAAGTGATCCGCCCACTTCG$";
+        let mut rng = StdRng::from_seed(&[5]);
+        let unit: Vec<u8> = (0..1000).map(|_| *rng.choose(b"ACGT").unwrap()).collect();
+        let mut repeated = unit.repeat(50);
+        repeated.push(b'$');
+
+        let alphabet = dna::n_alphabet();
+        for text in [&reads[..], &repeated].iter() {
+            let bwt = bwt(text, &suffix_array(text));
+            let occ = Occ::new(&bwt, 32, &alphabet);
+            let rlbwt = RlBwt::new(&bwt, 4);
+            assert_eq!(rlbwt.len(), bwt.len());
+            assert_eq!(rlbwt.decompress(), bwt);
+            for r in 0..bwt.len() {
+                assert_eq!(rlbwt.symbol_at(r), bwt[r]);
+                for &a in b"$ACGTN" {
+                    assert_eq!(rlbwt.rank(a, r), occ.get(&bwt, r, a));
+                }
+            }
+        }
+
+        // the repeated text compresses into at most a few runs per unit symbol
+        let bwt = bwt(&repeated, &suffix_array(&repeated));
+        let rlbwt = RlBwt::new(&bwt, 8);
+        assert!(rlbwt.runs() < 2 * unit.len());
+        assert!(rlbwt.heap_size() < bwt.len());
+    }
+
+    #[test]
+    fn test_bwt_from_text() {
+        let mut rng = StdRng::from_seed(&[7]);
+        let mut texts: Vec<Vec<u8>> = vec![b"$".to_vec(), b"ACGT$TT$$".to_vec()];
+        for &len in [10, 1000, 300_000].iter() {
+            let mut text: Vec<u8> = (0..len).map(|_| *rng.choose(b"ACGTN").unwrap()).collect();
+            text.push(b'$');
+            texts.push(text);
+        }
+        for text in &texts {
+            assert_eq!(bwt_from_text(text), bwt(text, &suffix_array(text)));
+        }
+    }
+
     #[test]
     fn test_occ() {
         let bwt = vec![1u8, 3u8, 3u8, 1u8, 2u8, 0u8];
         let alphabet = Alphabet::new(&[0u8, 1u8, 2u8, 3u8]);
         let occ = Occ::new(&bwt, 3, &alphabet);
-        assert_eq!(occ.occ, [[0, 1, 0, 0], [0, 2, 0, 2]]);
+        assert_eq!(
+            occ.occ,
+            Counts::U32(vec![vec![0, 1, 0, 0], vec![0, 2, 0, 2]])
+        );
         assert_eq!(occ.get(&bwt, 4, 2u8), 1);
         assert_eq!(occ.get(&bwt, 4, 3u8), 2);
     }
+
+    #[test]
+    fn test_occ_accessors() {
+        let bwt = bwt_from_text(b"GCCTTAACATTATTACGCCTA$");
+        let alphabet = Alphabet::new(b"$ACGT");
+        for &k in &[1, 3, 32] {
+            let occ = Occ::new(&bwt, k, &alphabet);
+            assert_eq!(occ.sampling_rate(), k);
+            assert_eq!(occ.num_checkpoints(), bwt.len().div_ceil(k as usize));
+            for i in 0..occ.num_checkpoints() {
+                let checkpoint = occ.checkpoint(i).unwrap();
+                assert_eq!(checkpoint.len(), b'T' as usize + 1);
+                for &a in b"$ACGT" {
+                    assert_eq!(checkpoint[a as usize], occ.get(&bwt, i * k as usize, a));
+                }
+            }
+
+            let cloned = occ.clone();
+            assert_eq!(cloned, occ);
+            assert_ne!(Occ::new(&bwt, k + 1, &alphabet), occ);
+            assert_ne!(Occ::new_wavelet_tree(&bwt), occ);
+        }
+
+        let wide = Occ::build(&bwt, 3, &alphabet, false, true);
+        assert_eq!(
+            wide.checkpoint(4),
+            Occ::new(&bwt, 3, &alphabet).checkpoint(4)
+        );
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        assert_eq!(occ.checkpoint(occ.num_checkpoints()), None);
+        assert_eq!(Occ::new_packed_dna(&bwt).checkpoint(0), None);
+        assert_eq!(Occ::new_packed_dna(&bwt).num_checkpoints(), 0);
+        assert_eq!(Occ::new_packed_dna(&bwt).sampling_rate(), 1);
+        assert!(format!("{:?}", Occ::new(&bwt, 3, &alphabet)).starts_with("Occ"));
+    }
+
+    #[test]
+    fn test_occ_case_insensitive() {
+        let bwt = b"aTA$ctC".to_vec();
+        let alphabet = Alphabet::new(b"ACTact");
+        let occ = Occ::new_case_insensitive(&bwt, 2, &alphabet);
+        assert_eq!(occ.get(&bwt, 6, b'A'), 2);
+        assert_eq!(occ.get(&bwt, 6, b'c'), 2);
+        assert_eq!(occ.get(&bwt, 3, b't'), 1);
+
+        let less = less_case_insensitive(&bwt, &alphabet);
+        assert_eq!(less[b'A' as usize], 1);
+        assert_eq!(less[b'a' as usize], 1);
+        assert_eq!(less[b'C' as usize], 3);
+        assert_eq!(less[b't' as usize], 5);
+    }
 }
@@ -9,6 +9,32 @@
 //!
 //! ## Generate
 //!
+//! The recommended way to obtain an index is `FMIndexBuilder`, which takes care of
+//! constructing the suffix array, BWT, less and occurrence arrays in the right order and
+//! validates the sampling parameters.
+//!
+//! ```
+//! use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+//! use bio::alphabets::dna;
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA$";
+//! let alphabet = dna::n_alphabet();
+//! let fm = FMIndexBuilder::new()
+//!     .text(text)
+//!     .alphabet(&alphabet)
+//!     .occ_sampling(3)
+//!     .sa_sampling(2)
+//!     .build()
+//!     .unwrap();
+//!
+//! let interval = fm.backward_search(b"TTA".iter());
+//! let mut positions = fm.positions_from_interval(&interval);
+//! positions.sort();
+//! assert_eq!(positions, [3, 9, 12]);
+//! ```
+//!
+//! The individual parts can also be constructed manually.
+//!
 //! ```
 //! use bio::data_structures::bwt::{bwt, less, Occ};
 //! use bio::data_structures::fmindex::{FMIndex, FMIndexable};
@@ -54,13 +80,25 @@
 //! ```
 
 use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::io;
 use std::iter::DoubleEndedIterator;
+use std::ops::Deref;
 
-use crate::alphabets::dna;
-use crate::data_structures::bwt::{Less, Occ, BWT};
-use crate::data_structures::suffix_array::SuffixArray;
+use bio_types::strand::ReqStrand;
+
+use crate::alphabets::{dna, Alphabet, SymbolSet};
+use crate::data_structures::bwt::{
+    bwt_sorted_by_with_sentinel, less, less_case_insensitive, less_case_insensitive_with_sentinel,
+    less_with_sentinel, merge_by, sentinel_map, Less, Occ, BWT,
+};
+use crate::data_structures::suffix_array::{self, SuffixArray};
+use crate::io::fasta;
 use std::mem::swap;
 
+/// The symbols of FMD-index texts, i.e. the DNA alphabet with N and the sentinel.
+const FMD_SYMBOLS: SymbolSet = SymbolSet::new(b"ACGTNacgtn$");
+
 /// A suffix array interval.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Interval {
@@ -94,23 +132,22 @@ pub trait FMIndexable {
     /// # Example
     ///
     /// ```
-    /// use bio::data_structures::bwt::{bwt, less, Occ};
-    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
-    /// use bio::data_structures::suffix_array::suffix_array;
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
     /// use bio::alphabets::dna;
     ///
     /// let text = b"GCCTTAACATTATTACGCCTA$";
     /// let alphabet = dna::n_alphabet();
-    /// let sa = suffix_array(text);
-    /// let bwt = bwt(text, &sa);
-    /// let less = less(&bwt, &alphabet);
-    /// let occ = Occ::new(&bwt, 3, &alphabet);
-    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    /// let fm = FMIndexBuilder::new()
+    ///     .text(text)
+    ///     .alphabet(&alphabet)
+    ///     .occ_sampling(3)
+    ///     .build()
+    ///     .unwrap();
     ///
     /// let pattern = b"TTA";
     /// let sai = fm.backward_search(pattern.iter());
     ///
-    /// let positions = sai.occ(&sa);
+    /// let positions = fm.positions_from_interval(&sai);
     ///
     /// assert_eq!(positions, [3, 12, 9]);
     /// ```
@@ -118,17 +155,195 @@ pub trait FMIndexable {
         &self,
         pattern: P,
     ) -> Interval {
-        let (mut l, mut r) = (0, self.bwt().len() - 1);
+        // the upper bound is exclusive, which avoids underflows for absent symbols
+        let (mut l, mut r) = (0, self.bwt().len());
         for &a in pattern.rev() {
             let less = self.less(a);
             l = less + if l > 0 { self.occ(l - 1, a) } else { 0 };
-            r = less + self.occ(r, a) - 1;
+            r = less + if r > 0 { self.occ(r - 1, a) } else { 0 };
         }
 
-        Interval {
-            lower: l,
-            upper: r + 1,
+        Interval { lower: l, upper: r }
+    }
+
+    /// Count the exact occurrences of the given pattern in the text, without locating them.
+    /// The backward search stops as soon as the interval becomes empty.
+    /// Complexity: O(m).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+    /// use bio::alphabets::dna;
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let fm = FMIndexBuilder::new()
+    ///     .text(b"GCCTTAACATTATTACGCCTA$")
+    ///     .alphabet(&alphabet)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(fm.count_occurrences(b"TTA"), 3);
+    /// assert_eq!(fm.count_occurrences(b"GGG"), 0);
+    /// ```
+    fn count_occurrences(&self, pattern: &[u8]) -> usize {
+        let (mut l, mut r) = (0, self.bwt().len());
+        for &a in pattern.iter().rev() {
+            let less = self.less(a);
+            l = less + if l > 0 { self.occ(l - 1, a) } else { 0 };
+            r = less + if r > 0 { self.occ(r - 1, a) } else { 0 };
+            if l >= r {
+                return 0;
+            }
+        }
+
+        r - l
+    }
+
+    /// Check whether the given pattern occurs in the text, stopping as soon as the
+    /// backward search interval becomes empty. Complexity: O(m).
+    fn contains(&self, pattern: &[u8]) -> bool {
+        self.count_occurrences(pattern) > 0
+    }
+
+    /// Perform backward search for a pattern that may contain IUPAC ambiguity codes
+    /// (e.g. `R`, `Y`, `N`), yielding the disjoint suffix array intervals of all exact
+    /// occurrences of any concrete pattern the codes expand to (see
+    /// `alphabets::dna::iupac_expand`). Intervals are sorted and adjacent intervals are merged.
+    ///
+    /// At each step, every interval is extended by all bases compatible with the current
+    /// pattern symbol. Since intervals are disjoint, there are never more than n of them,
+    /// but for patterns consisting mostly of `N`, their number approaches the number of
+    /// distinct k-mers of the text, and the search becomes O(m * min(4^m, n)).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+    /// use bio::alphabets::dna;
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let fm = FMIndexBuilder::new()
+    ///     .text(b"ACATGGACGT$")
+    ///     .alphabet(&alphabet)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut positions: Vec<usize> = fm
+    ///     .backward_search_ambig(b"ACRT")
+    ///     .iter()
+    ///     .flat_map(|interval| fm.positions_from_interval(interval))
+    ///     .collect();
+    /// positions.sort();
+    /// assert_eq!(positions, [0, 6]);
+    /// ```
+    fn backward_search_ambig(&self, pattern: &[u8]) -> Vec<Interval> {
+        if self.bwt().is_empty() {
+            return Vec::new();
+        }
+        let mut intervals = vec![Interval {
+            lower: 0,
+            upper: self.bwt().len(),
+        }];
+        // the number of occurrences of b before the given row
+        let occ = |r: usize, b: u8| r.checked_sub(1).map_or(0, |r| self.occ(r, b));
+        for &a in pattern.iter().rev() {
+            let mut extended: Vec<Interval> = Vec::new();
+            for interval in &intervals {
+                for &b in dna::iupac_expand(a) {
+                    let less = self.less(b);
+                    let lower = less + occ(interval.lower, b);
+                    let upper = less + occ(interval.upper, b);
+                    if lower < upper {
+                        extended.push(Interval { lower, upper });
+                    }
+                }
+            }
+            extended.sort_by_key(|interval| interval.lower);
+
+            intervals.clear();
+            for interval in extended {
+                match intervals.last_mut() {
+                    Some(last) if last.upper == interval.lower => last.upper = interval.upper,
+                    _ => intervals.push(interval),
+                }
+            }
+            if intervals.is_empty() {
+                break;
+            }
+        }
+
+        intervals
+    }
+
+    /// Return the exclusive upper bound of the suffix array interval of the suffixes starting
+    /// with symbol a, i.e., the number of suffixes starting with a symbol smaller or equal
+    /// to a. Unlike `less(a + 1)`, this does not rely on `a + 1` having an entry in the less
+    /// array that succeeds a, which does not hold for the lowercase 'z' of a case-insensitive
+    /// index or for the symbol 255. Complexity: O(k) with the occ sampling rate k.
+    fn next_symbol_bound(&self, a: u8) -> usize {
+        let n = self.bwt().len();
+        self.less(a) + if n > 0 { self.occ(n - 1, a) } else { 0 }
+    }
+
+    /// Perform the LF-mapping, i.e. return the row of the suffix preceding the suffix at
+    /// row `r` in the text. Complexity: O(k) with the occ sampling rate k.
+    fn lf(&self, r: usize) -> usize {
+        let a = self.bwt()[r];
+        self.less(a) + if r > 0 { self.occ(r - 1, a) } else { 0 }
+    }
+
+    /// Perform the inverse of the LF-mapping, i.e. return the row of the suffix following
+    /// the suffix at row `r` in the text. This also holds for case-insensitive indexes and
+    /// custom sentinels, whose symbols are not ordered by their byte value.
+    /// Complexity: O(A + k log n) with the alphabet size A.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let fm = FMIndexBuilder::new()
+    ///     .text(b"GCCTTAACATTATTACGCCTA$")
+    ///     .alphabet(&alphabet)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// for r in 0..fm.bwt().len() {
+    ///     assert_eq!(fm.psi(fm.lf(r)), r);
+    /// }
+    /// ```
+    fn psi(&self, r: usize) -> usize {
+        let n = self.bwt().len();
+        assert!(r < n, "Expecting row within the BWT.");
+        // the first symbol of the suffix at row r is the one whose bucket starts at the
+        // largest bucket start not after r. Symbols that do not occur have empty buckets
+        // sharing that start, and case-insensitive indexes let both variants of a symbol
+        // share a bucket.
+        let start = (0..=255u8)
+            .map(|a| self.less(a))
+            .filter(|&l| l <= r)
+            .max()
+            .unwrap();
+        let a = (0..=255u8)
+            .rev()
+            .find(|&a| self.less(a) == start && self.next_symbol_bound(a) > r)
+            .unwrap();
+        // find the row with the (r - less(a))-th occurrence of a in the BWT
+        let rank = r - self.less(a);
+        let (mut lower, mut upper) = (0, n - 1);
+        while lower < upper {
+            let mid = lower + (upper - lower) / 2;
+            if self.occ(mid, a) > rank {
+                upper = mid;
+            } else {
+                lower = mid + 1;
+            }
         }
+
+        lower
     }
 }
 
@@ -148,7 +363,13 @@ impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndexable
         self.occ.borrow().get(self.bwt.borrow(), r, a)
     }
     fn less(&self, a: u8) -> usize {
-        self.less.borrow()[a as usize]
+        // symbols beyond the less array are larger than all symbols of the text
+        let less: &Less = self.less.borrow();
+        if (a as usize) < less.len() {
+            less[a as usize]
+        } else {
+            self.bwt.borrow().len()
+        }
     }
     /// Provide a reference to the underlying BWT.
     fn bwt(&self) -> &BWT {
@@ -167,255 +388,1948 @@ impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndex<DBWT, DL
     pub fn new(bwt: DBWT, less: DLess, occ: DOcc) -> Self {
         FMIndex { bwt, less, occ }
     }
-}
-
-/// A bi-interval on suffix array of the forward and reverse strand of a DNA text.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct BiInterval {
-    lower: usize,
-    lower_rev: usize,
-    size: usize,
-    match_size: usize,
-}
 
-impl BiInterval {
-    pub fn forward(&self) -> Interval {
-        Interval {
-            upper: self.lower + self.size,
-            lower: self.lower,
-        }
-    }
-    pub fn revcomp(&self) -> Interval {
-        Interval {
-            upper: self.lower_rev + self.size,
-            lower: self.lower_rev,
+    /// Construct a new instance like `new`, but return an error instead of building an
+    /// index that panics or gives wrong results later, if the BWT is empty or contains
+    /// symbols that are not covered by the less array or the occ array, e.g. because they
+    /// have been calculated with an alphabet that lacks them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// use bio::data_structures::bwt::{less, Occ};
+    /// use bio::data_structures::fmindex::{FMIndex, IndexBuildError};
+    ///
+    /// let alphabet = Alphabet::new(b"$ACGT");
+    /// let bwt = b"ATTATTCAGGACCC$CTTTCAA".to_vec();
+    /// let (less, occ) = (less(&bwt, &alphabet), Occ::new(&bwt, 3, &alphabet));
+    /// assert!(FMIndex::try_new(&bwt, &less, &occ).is_ok());
+    ///
+    /// let other = b"ATTATTCAGGXCCC$CTTTCAA".to_vec();
+    /// assert_eq!(
+    ///     FMIndex::try_new(&other, &less, &occ).err(),
+    ///     Some(IndexBuildError::InvalidSymbol(b'X', 10))
+    /// );
+    /// ```
+    pub fn try_new(bwt: DBWT, less: DLess, occ: DOcc) -> Result<Self, IndexBuildError> {
+        {
+            let (text, less, occ): (&BWT, &Less, &Occ) =
+                (bwt.borrow(), less.borrow(), occ.borrow());
+            if text.is_empty() {
+                return Err(IndexBuildError::EmptyText);
+            }
+            // the counts of sampled occ arrays are indexed by symbol
+            let counted = occ.checkpoint(0).map_or(256, |counts| counts.len());
+            let is_covered = |c: u8| {
+                let folded = if occ.is_case_insensitive() {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                };
+                (c as usize) < less.len() && (folded as usize) < counted
+            };
+            if let Some(pos) = text.iter().position(|&c| !is_covered(c)) {
+                return Err(IndexBuildError::InvalidSymbol(text[pos], pos));
+            }
         }
-    }
 
-    fn swapped(&self) -> BiInterval {
-        BiInterval {
-            lower: self.lower_rev,
-            lower_rev: self.lower,
-            size: self.size,
-            match_size: self.match_size,
-        }
+        Ok(Self::new(bwt, less, occ))
     }
 }
 
-/// The FMD-Index for linear time search of supermaximal exact matches on forward and reverse
-/// strand of DNA texts (Li, 2012).
+/// A sample of every s-th suffix array entry, from which all other entries can be
+/// recovered by walking the LF-mapping of an FM-Index until a sampled row is reached.
+///
+/// Rows with the sentinel in the BWT are stored in addition, so that the walk never
+/// crosses a sequence boundary. This is necessary for texts with multiple `$` separators:
+/// the suffix array orders the sentinels by their text position, whereas the LF-mapping
+/// orders them by the suffixes following them, so that an LF step from a row whose BWT
+/// symbol is `$` could land on the sentinel of a different sequence.
+///
+/// Alternatively, every s-th text position can be sampled (see `from_sparse_suffix_array`),
+/// in which case all sampled rows are stored like the rows with the sentinel in the BWT.
 #[derive(Serialize, Deserialize)]
-pub struct FMDIndex<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> {
-    fmindex: FMIndex<DBWT, DLess, DOcc>,
+pub struct SASample {
+    /// the entries of every s-th row, empty if sampled by text position
+    sample: Vec<usize>,
+    s: usize,
+    /// rows whose text position is stored in addition to (or instead of) the sample
+    extra_rows: HashMap<usize, usize>,
 }
 
-impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndexable
-    for FMDIndex<DBWT, DLess, DOcc>
-{
-    fn occ(&self, r: usize, a: u8) -> usize {
-        self.fmindex.occ(r, a)
-    }
+impl SASample {
+    /// Sample the given suffix array.
+    ///
+    /// # Arguments
+    ///
+    /// * `sa` - the suffix array
+    /// * `bwt` - the corresponding BWT
+    /// * `s` - the sampling rate: every s-th entry will be kept
+    pub fn new<SA: SuffixArray>(sa: &SA, bwt: &BWT, s: usize) -> Self {
+        assert!(s > 0, "Expecting a sampling rate of at least 1.");
+        // the suffix at text position 0 is preceded by the final sentinel
+        let sentinel = (0..sa.len())
+            .find(|&r| sa.get(r) == Some(0))
+            .map(|r| bwt[r])
+            .expect("Expecting non-empty suffix array.");
+        let sample = (0..sa.len())
+            .step_by(s)
+            .map(|r| sa.get(r).unwrap())
+            .collect();
+        let extra_rows = bwt
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c == sentinel)
+            .map(|(r, _)| (r, sa.get(r).unwrap()))
+            .collect();
 
-    fn less(&self, a: u8) -> usize {
-        self.fmindex.less(a)
+        SASample {
+            sample,
+            s,
+            extra_rows,
+        }
     }
 
-    /// Provide a reference to the underlying BWT.
-    fn bwt(&self) -> &BWT {
-        self.fmindex.bwt()
+    /// Sample the suffix array of the given FM-Index without the full suffix array, by
+    /// walking the LF-mapping backwards through each `$`-terminated sequence of the text.
+    /// Since the suffix array orders the sentinels by decreasing text position, the walk for
+    /// the j-th last sequence starts at row j, and a single pass over the text suffices.
+    /// Complexity: O(n * k) time with the occ sampling rate k, O(n / s) space.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmindex` - the FM-Index providing the LF-mapping
+    /// * `s` - the sampling rate: every s-th entry will be kept
+    pub fn from_fmindex<I: FMIndexable>(fmindex: &I, s: usize) -> Self {
+        assert!(s > 0, "Expecting a sampling rate of at least 1.");
+        let bwt = fmindex.bwt();
+        let n = bwt.len();
+        let sentinel = fmindex_sentinel(fmindex);
+        let mut sample = vec![0; n.div_ceil(s)];
+        let mut extra_rows = HashMap::new();
+
+        // text position of the sentinel ending the current sequence
+        let mut end = n;
+        let mut r: usize = 0;
+        while end > 0 {
+            let mut pos = end - 1;
+            let mut row = r;
+            loop {
+                if row.is_multiple_of(s) {
+                    sample[row / s] = pos;
+                }
+                if bwt[row] == sentinel {
+                    break;
+                }
+                row = fmindex.lf(row);
+                pos -= 1;
+            }
+            // row is the first suffix of the sequence, preceded by the previous sentinel
+            extra_rows.insert(row, pos);
+            end = pos;
+            r += 1;
+        }
+
+        SASample {
+            sample,
+            s,
+            extra_rows,
+        }
     }
-}
 
-impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> From<FMIndex<DBWT, DLess, DOcc>>
-    for FMDIndex<DBWT, DLess, DOcc>
-{
-    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics).
-    /// This expects a BWT that was created from a text over the DNA alphabet with N
-    /// (`alphabets::dna::n_alphabet()`) consisting of the
-    /// concatenation with its reverse complement, separated by the sentinel symbol `$`.
-    /// I.e., let T be the original text and R be its reverse complement.
-    /// Then, the expected text is T$R$. Further, multiple concatenated texts are allowed, e.g.
-    /// T1$R1$T2$R2$T3$R3$.
+    /// Sample every s-th text position, given the sparse suffix array of the text (see
+    /// `suffix_array::sparse_suffix_array`), without the full suffix array or an LF walk.
+    /// The rows of the sampled positions and of the positions following a sentinel are
+    /// calculated from the text, such that an LF walk from any row reaches a stored row
+    /// within s steps (see `suffix_array::sparse_ranks`). Complexity: O(r * s * n log^2(n / s))
+    /// time for r distinct residues modulo s of the positions following a sentinel,
+    /// O(n / s + b) space for b sequences.
     ///
-    fn from(fmindex: FMIndex<DBWT, DLess, DOcc>) -> FMDIndex<DBWT, DLess, DOcc> {
-        let mut alphabet = dna::n_alphabet();
-        alphabet.insert(b'$');
-        assert!(
-            alphabet.is_word(fmindex.bwt()),
-            "Expecting BWT over the DNA alphabet (including N) with the sentinel $."
+    /// # Arguments
+    ///
+    /// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+    /// * `sparse` - the sparse suffix array of the text
+    /// * `s` - the sampling rate the sparse suffix array was built with
+    pub fn from_sparse_suffix_array(text: &[u8], sparse: &[usize], s: usize) -> Self {
+        assert!(s > 0, "Expecting a sampling rate of at least 1.");
+        assert_eq!(
+            sparse.len(),
+            text.len().div_ceil(s),
+            "Expecting a sparse suffix array with every s-th text position."
         );
+        let sentinel = text[text.len() - 1];
+        let positions: Vec<usize> = sparse
+            .iter()
+            .cloned()
+            .chain((1..text.len()).filter(|&p| text[p - 1] == sentinel && !p.is_multiple_of(s)))
+            .collect();
+        let rows = suffix_array::sparse_ranks(text, s, &positions);
+        let extra_rows = rows.into_iter().zip(positions).collect();
 
-        FMDIndex { fmindex }
+        SASample {
+            sample: Vec::new(),
+            s,
+            extra_rows,
+        }
     }
-}
 
-impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMDIndex<DBWT, DLess, DOcc> {
-    /// Find supermaximal exact matches of given pattern that overlap position i in the pattern.
-    /// Complexity O(m) with pattern of length m.
+    /// The sampling rate.
+    pub fn sampling_rate(&self) -> usize {
+        self.s
+    }
+
+    /// Write the sample in the raw little-endian binary format of
+    /// `CompactSuffixArray::write_to`: the sampling rate, the sampled entries and the
+    /// additionally stored rows and their text positions, each preceded by a header.
     ///
     /// # Example
     ///
     /// ```
-    /// use bio::alphabets::dna;
-    /// use bio::data_structures::fmindex::{FMIndex, FMDIndex};
+    /// use bio::data_structures::bwt::bwt;
+    /// use bio::data_structures::fmindex::SASample;
     /// use bio::data_structures::suffix_array::suffix_array;
-    /// use bio::data_structures::bwt::{bwt, less, Occ};
     ///
-    /// let text = b"ATTC$GAAT$";
-    /// let alphabet = dna::n_alphabet();
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
     /// let sa = suffix_array(text);
-    /// let bwt = bwt(text, &sa);
-    /// let less = less(&bwt, &alphabet);
-    /// let occ = Occ::new(&bwt, 3, &alphabet);
-    /// let fm = FMIndex::new(&bwt, &less, &occ);
-    /// let fmdindex = FMDIndex::from(fm);
-    ///
-    /// let pattern = b"ATT";
-    /// let intervals = fmdindex.smems(pattern, 2);
-    ///
-    /// let forward_positions = intervals[0].forward().occ(&sa);
+    /// let sample = SASample::new(&sa, &bwt(text, &sa), 4);
     ///
-    /// let revcomp_positions = intervals[0].revcomp().occ(&sa);
-    ///
-    /// assert_eq!(forward_positions, [0]);
-    /// assert_eq!(revcomp_positions, [6]);
+    /// let mut buffer = Vec::new();
+    /// sample.write_to(&mut buffer).unwrap();
+    /// let read = SASample::read_from(&buffer[..]).unwrap();
+    /// assert_eq!(read.sampling_rate(), 4);
     /// ```
-    pub fn smems(&self, pattern: &[u8], i: usize) -> Vec<BiInterval> {
-        let curr = &mut Vec::new();
-        let prev = &mut Vec::new();
-        let mut matches = Vec::new();
-
-        let mut interval = self.init_interval_with(pattern[i]);
-
-        for &a in pattern[i + 1..].iter() {
-            // forward extend interval
-            let forward_interval = self.forward_ext(&interval, a);
+    pub fn write_to<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        let mut extra_rows: Vec<(usize, usize)> =
+            self.extra_rows.iter().map(|(&r, &p)| (r, p)).collect();
+        extra_rows.sort_unstable();
+        suffix_array::write_raw(&mut writer, 8, 1, Some(self.s as u64))?;
+        write_positions(&mut writer, self.sample.iter().cloned())?;
+        write_positions(&mut writer, extra_rows.iter().map(|&(r, _)| r))?;
+        write_positions(&mut writer, extra_rows.iter().map(|&(_, p)| p))?;
+        io::Write::flush(&mut writer)
+    }
 
-            // if size changed, add last interval to list
-            if interval.size != forward_interval.size {
-                curr.push(interval);
-            }
-            // if new interval size is zero, stop, as no further forward extension is possible
-            if forward_interval.size == 0 {
-                break;
+    /// Read a sample written by `write_to`. A truncated input yields an error of kind
+    /// `UnexpectedEof`, and invalid contents one of kind `InvalidData`.
+    pub fn read_from<R: io::Read>(reader: R) -> io::Result<Self> {
+        let mut reader = io::BufReader::new(reader);
+        let s = match read_positions(&mut reader)?[..] {
+            [s] if s > 0 => s,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "expecting a sampling rate of at least 1",
+                ))
             }
-            interval = forward_interval;
+        };
+        let sample = read_positions(&mut reader)?;
+        let rows = read_positions(&mut reader)?;
+        let positions = read_positions(&mut reader)?;
+        if rows.len() != positions.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expecting a text position for each stored row",
+            ));
         }
-        // add the last non-zero interval
-        curr.push(interval);
-        // reverse intervals such that longest comes first
-        curr.reverse();
-
-        swap(curr, prev);
-        let mut j = pattern.len() as isize;
-
-        for k in (-1..i as isize).rev() {
-            let a = if k == -1 { b'$' } else { pattern[k as usize] };
-            curr.clear();
-            // size of the last confirmed interval
-            let mut last_size = -1;
 
-            for interval in prev.iter() {
-                // backward extend interval
-                let forward_interval = self.backward_ext(interval, a);
+        Ok(SASample {
+            sample,
+            s,
+            extra_rows: rows.into_iter().zip(positions).collect(),
+        })
+    }
 
-                if (forward_interval.size == 0 || k == -1) &&
-                        // interval could not be extended further
-                        // if no interval has been extended this iteration,
-                        // interval is maximal and can be added to the matches
-                        curr.is_empty() && k < j
-                {
-                    j = k;
-                    matches.push(*interval);
-                }
-                // add _interval to curr (will be further extended next iteration)
-                if forward_interval.size != 0 && forward_interval.size as isize != last_size {
-                    last_size = forward_interval.size as isize;
-                    curr.push(forward_interval);
-                }
+    /// Get the text position of the suffix at row `r`, using the given FM-Index for the
+    /// LF-mapping. Complexity: O(s * k) with the occ sampling rate k.
+    pub fn get<I: FMIndexable>(&self, fmindex: &I, r: usize) -> usize {
+        let mut r = r;
+        let mut offset = 0;
+        loop {
+            if !self.sample.is_empty() && r.is_multiple_of(self.s) {
+                return self.sample[r / self.s] + offset;
             }
-            if curr.is_empty() {
-                break;
+            if let Some(&pos) = self.extra_rows.get(&r) {
+                return pos + offset;
             }
-            swap(curr, prev);
+            r = fmindex.lf(r);
+            offset += 1;
         }
-
-        matches
     }
+}
 
-    /// Initialize interval with given start character.
-    pub fn init_interval_with(&self, a: u8) -> BiInterval {
-        let comp_a = dna::complement(a);
-        let lower = self.fmindex.less(a);
+/// Write the given positions with `suffix_array::write_raw`, using 32 bit if possible.
+fn write_positions<W: io::Write, I: ExactSizeIterator<Item = usize> + Clone>(
+    writer: &mut W,
+    positions: I,
+) -> io::Result<()> {
+    let width = if positions.clone().all(|p| p <= u32::MAX as usize) {
+        4
+    } else {
+        8
+    };
+    suffix_array::write_raw(writer, width, positions.len(), positions.map(|p| p as u64))
+}
 
-        BiInterval {
-            lower,
-            lower_rev: self.fmindex.less(comp_a),
-            size: self.fmindex.less(a + 1) - lower,
-            match_size: 1,
+/// Read positions written by `write_positions`.
+fn read_positions<R: io::Read>(reader: &mut R) -> io::Result<Vec<usize>> {
+    match suffix_array::read_raw_header(reader)? {
+        (width, len) if width == 4 || width == 8 => {
+            suffix_array::read_raw(reader, width, len, |p| p as usize)
         }
+        (width, _) => Err(suffix_array::invalid_width(width)),
     }
+}
 
-    /// Initialize interval for empty pattern. The interval points at the whole suffix array.
-    pub fn init_interval(&self) -> BiInterval {
-        BiInterval {
-            lower: 0,
-            lower_rev: 0,
-            size: self.fmindex.bwt.borrow().len(),
-            match_size: 0,
-        }
+/// Check the sampling rates and the symbols of the given BWT.
+fn validate_bwt(bwt: &BWT, k: u32, s: usize, alphabet: &Alphabet) -> Result<(), IndexBuildError> {
+    if k == 0 {
+        return Err(IndexBuildError::InvalidOccSampling);
     }
-
-    /// Backward extension of given interval with given character.
-    pub fn backward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
-        let mut s = 0;
-        let mut o = 0;
-        let mut l = interval.lower_rev;
+    if s == 0 {
+        return Err(IndexBuildError::InvalidSASampling);
+    }
+    if bwt.is_empty() {
+        return Err(IndexBuildError::EmptyText);
+    }
+    // like bwt_sentinel, consider the smallest symbol to be the sentinel
+    let sentinel = bwt_sentinel(bwt);
+    if alphabet.contains(sentinel) {
+        return Err(IndexBuildError::MissingSentinel);
+    }
+    if let Some(pos) = bwt
+        .iter()
+        .position(|&c| c != sentinel && !alphabet.contains(c))
+    {
+        return Err(IndexBuildError::InvalidSymbol(bwt[pos], pos));
+    }
+
+    Ok(())
+}
+
+/// Return the sentinel of a BWT, i.e. its lexicographically smallest symbol.
+fn bwt_sentinel(bwt: &BWT) -> u8 {
+    *bwt.iter().min().expect("Expecting non-empty BWT.")
+}
+
+/// Return the sentinel of an FM-Index, i.e. the symbol of its BWT that is preceded by no
+/// other symbol in the less array. In contrast to `bwt_sentinel`, this also holds for
+/// sentinels that are not the lexicographically smallest symbol.
+fn fmindex_sentinel<I: FMIndexable>(fmindex: &I) -> u8 {
+    let mut present = [false; 256];
+    for &c in fmindex.bwt() {
+        present[c as usize] = true;
+    }
+    (0..=255)
+        .find(|&a| present[a as usize] && fmindex.less(a) == 0)
+        .expect("Expecting non-empty BWT.")
+}
+
+/// An FM-Index that owns its BWT, less and occurrence arrays together with a sample of
+/// the suffix array, such that occurrences can be located without keeping the full
+/// suffix array in memory.
+/// Usually, it is constructed via `FMIndexBuilder`.
+#[derive(Serialize, Deserialize)]
+pub struct SampledFMIndex {
+    fmindex: FMIndex<BWT, Less, Occ>,
+    sa: SASample,
+}
+
+impl SampledFMIndex {
+    /// Construct a new instance from the given suffix array.
+    ///
+    /// # Arguments
+    ///
+    /// * `sa` - the suffix array of the text
+    /// * `bwt` - the BWT of the text
+    /// * `k` - the sampling rate of the occurrence array
+    /// * `s` - the sampling rate of the suffix array
+    /// * `alphabet` - the alphabet of the text
+    pub fn new<SA: SuffixArray>(sa: &SA, bwt: BWT, k: u32, s: usize, alphabet: &Alphabet) -> Self {
+        let less = less(&bwt, alphabet);
+        let occ = Occ::new(&bwt, k, alphabet);
+        let sa = SASample::new(sa, &bwt, s);
+
+        SampledFMIndex {
+            fmindex: FMIndex::new(bwt, less, occ),
+            sa,
+        }
+    }
+
+    /// Construct a new instance like `new`, but return an error instead of panicking or
+    /// building an unusable index if a sampling rate is zero, or the BWT is empty, lacks the
+    /// sentinel `$` or contains symbols that are not part of the alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::bwt;
+    /// use bio::data_structures::fmindex::{IndexBuildError, SampledFMIndex};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let alphabet = dna::n_alphabet();
+    ///
+    /// assert!(SampledFMIndex::try_new(&sa, bwt.clone(), 3, 4, &alphabet).is_ok());
+    /// assert_eq!(
+    ///     SampledFMIndex::try_new(&sa, bwt, 3, 0, &alphabet).err(),
+    ///     Some(IndexBuildError::InvalidSASampling)
+    /// );
+    /// ```
+    pub fn try_new<SA: SuffixArray>(
+        sa: &SA,
+        bwt: BWT,
+        k: u32,
+        s: usize,
+        alphabet: &Alphabet,
+    ) -> Result<Self, IndexBuildError> {
+        validate_bwt(&bwt, k, s, alphabet)?;
+        Ok(Self::new(sa, bwt, k, s, alphabet))
+    }
+
+    /// Construct a new instance like `from_bwt`, but return an error for invalid parameters
+    /// or BWTs (see `try_new`).
+    pub fn try_from_bwt(
+        bwt: BWT,
+        k: u32,
+        s: usize,
+        alphabet: &Alphabet,
+    ) -> Result<Self, IndexBuildError> {
+        validate_bwt(&bwt, k, s, alphabet)?;
+        Ok(Self::from_bwt(bwt, k, s, alphabet))
+    }
+
+    /// Construct a new instance from the BWT alone, deriving the suffix array sample by
+    /// walking the LF-mapping (see `SASample::from_fmindex`). In contrast to `new`, the full
+    /// suffix array need not be kept in memory, at the cost of an O(n * k) walk instead of
+    /// an O(n) copy.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT of the text
+    /// * `k` - the sampling rate of the occurrence array
+    /// * `s` - the sampling rate of the suffix array
+    /// * `alphabet` - the alphabet of the text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::bwt;
+    /// use bio::data_structures::fmindex::{FMIndexable, SampledFMIndex};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let bwt = bwt(text, &suffix_array(text));
+    /// let fm = SampledFMIndex::from_bwt(bwt, 3, 4, &dna::n_alphabet());
+    ///
+    /// let interval = fm.backward_search(b"TTA".iter());
+    /// let mut positions = fm.positions_from_interval(&interval);
+    /// positions.sort();
+    /// assert_eq!(positions, [3, 9, 12]);
+    /// ```
+    pub fn from_bwt(bwt: BWT, k: u32, s: usize, alphabet: &Alphabet) -> Self {
+        let less = less(&bwt, alphabet);
+        let occ = Occ::new(&bwt, k, alphabet);
+        let fmindex = FMIndex::new(bwt, less, occ);
+        let sa = SASample::from_fmindex(&fmindex, s);
+
+        SampledFMIndex { fmindex, sa }
+    }
+
+    /// Construct a new instance from the BWT and the sparse suffix array of the text, which
+    /// samples every s-th text position instead of every s-th row (see
+    /// `SASample::from_sparse_suffix_array`). Neither the full suffix array nor an LF walk
+    /// over the whole text is needed, and locating behaves like with an index built by `new`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - the text
+    /// * `sparse` - the sparse suffix array of the text
+    /// * `bwt` - the BWT of the text
+    /// * `k` - the sampling rate of the occurrence array
+    /// * `s` - the sampling rate of the sparse suffix array
+    /// * `alphabet` - the alphabet of the text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::bwt_from_text;
+    /// use bio::data_structures::fmindex::{FMIndexable, SampledFMIndex};
+    /// use bio::data_structures::suffix_array::sparse_suffix_array;
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let sparse = sparse_suffix_array(text, 4);
+    /// let fm = SampledFMIndex::from_sparse_suffix_array(
+    ///     text,
+    ///     &sparse,
+    ///     bwt_from_text(text),
+    ///     3,
+    ///     4,
+    ///     &dna::n_alphabet(),
+    /// );
+    ///
+    /// let interval = fm.backward_search(b"TTA".iter());
+    /// let mut positions = fm.positions_from_interval(&interval);
+    /// positions.sort();
+    /// assert_eq!(positions, [3, 9, 12]);
+    /// ```
+    pub fn from_sparse_suffix_array(
+        text: &[u8],
+        sparse: &[usize],
+        bwt: BWT,
+        k: u32,
+        s: usize,
+        alphabet: &Alphabet,
+    ) -> Self {
+        let less = less(&bwt, alphabet);
+        let occ = Occ::new(&bwt, k, alphabet);
+        let sa = SASample::from_sparse_suffix_array(text, sparse, s);
+
+        SampledFMIndex {
+            fmindex: FMIndex::new(bwt, less, occ),
+            sa,
+        }
+    }
+
+    /// Construct a new instance that treats upper and lowercase variants of a symbol as the
+    /// same symbol, e.g. for soft-masked genomes. The stored BWT retains the original case.
+    ///
+    /// # Arguments
+    ///
+    /// * `sa` - the suffix array of the uppercased text
+    /// * `bwt` - the BWT of the original text, derived from the suffix array of the
+    ///   uppercased text
+    /// * `k` - the sampling rate of the occurrence array
+    /// * `s` - the sampling rate of the suffix array
+    /// * `alphabet` - the alphabet of the text
+    pub fn new_case_insensitive<SA: SuffixArray>(
+        sa: &SA,
+        bwt: BWT,
+        k: u32,
+        s: usize,
+        alphabet: &Alphabet,
+    ) -> Self {
+        let less = less_case_insensitive(&bwt, alphabet);
+        let occ = Occ::new_case_insensitive(&bwt, k, alphabet);
+        let sa = SASample::new(sa, &bwt, s);
+
+        SampledFMIndex {
+            fmindex: FMIndex::new(bwt, less, occ),
+            sa,
+        }
+    }
+
+    /// Extend the index by the text of another index, e.g. for a new batch of sequences,
+    /// by merging both BWTs (see `bwt::merge`) and rebuilding the less and occurrence
+    /// arrays and the suffix array sample. The result is an index of the concatenation of
+    /// both texts, i.e. positions in the text of `other` are shifted by the text length of
+    /// this index.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the index of the text to append
+    /// * `k` - the sampling rate of the occurrence array
+    /// * `s` - the sampling rate of the suffix array
+    /// * `alphabet` - the alphabet of both texts
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let build = |text: &[u8]| {
+    ///     FMIndexBuilder::new()
+    ///         .text(text)
+    ///         .alphabet(&alphabet)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let fm = build(b"GCCTTAACAT$").merge_with(&build(b"TATTACGCCTA$"), 3, 4, &alphabet);
+    ///
+    /// let interval = fm.backward_search(b"TTA".iter());
+    /// let mut positions = fm.positions_from_interval(&interval);
+    /// positions.sort();
+    /// assert_eq!(positions, [3, 13]);
+    /// ```
+    pub fn merge_with(
+        &self,
+        other: &SampledFMIndex,
+        k: u32,
+        s: usize,
+        alphabet: &Alphabet,
+    ) -> Self {
+        let case_insensitive = self.fmindex.occ.borrow().is_case_insensitive();
+        assert_eq!(
+            case_insensitive,
+            other.fmindex.occ.borrow().is_case_insensitive(),
+            "Expecting both indexes to be either case-sensitive or case-insensitive."
+        );
+        let bwt = merge_by(self.bwt(), other.bwt(), alphabet, case_insensitive);
+        let (less, occ) = if case_insensitive {
+            (
+                less_case_insensitive(&bwt, alphabet),
+                Occ::new_case_insensitive(&bwt, k, alphabet),
+            )
+        } else {
+            (less(&bwt, alphabet), Occ::new(&bwt, k, alphabet))
+        };
+        let fmindex = FMIndex::new(bwt, less, occ);
+        let sa = SASample::from_fmindex(&fmindex, s);
+
+        SampledFMIndex { fmindex, sa }
+    }
+
+    /// Provide a reference to the suffix array sample.
+    pub fn sa_sample(&self) -> &SASample {
+        &self.sa
+    }
+
+    /// Get the text position of the suffix at row `r` of the suffix array.
+    pub fn sa_pos_to_text_pos(&self, r: usize) -> usize {
+        self.sa.get(self, r)
+    }
+
+    /// Get the text positions of all suffixes in the given interval.
+    pub fn positions_from_interval(&self, interval: &Interval) -> Vec<usize> {
+        (interval.lower..interval.upper)
+            .map(|r| self.sa_pos_to_text_pos(r))
+            .collect()
+    }
+}
+
+impl FMIndexable for SampledFMIndex {
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.fmindex.occ(r, a)
+    }
+
+    fn less(&self, a: u8) -> usize {
+        self.fmindex.less(a)
+    }
+
+    /// Provide a reference to the underlying BWT.
+    fn bwt(&self) -> &BWT {
+        self.fmindex.bwt()
+    }
+}
+
+quick_error! {
+    #[derive(Debug, PartialEq)]
+    pub enum IndexBuildError {
+        MissingText {
+            description("no text given to build the index from")
+        }
+        EmptyText {
+            description("cannot build an index over an empty text")
+        }
+        InvalidOccSampling {
+            description("occ sampling rate must be at least 1")
+        }
+        InvalidSASampling {
+            description("suffix array sampling rate must be at least 1")
+        }
+        MissingSentinel {
+            description("text is not terminated by the sentinel")
+            display("expecting text to end with the sentinel")
+        }
+        InvalidSentinel(sentinel: u8) {
+            description("sentinel is a letter, which is not allowed for case-insensitive indexes")
+            display("sentinel '{}' cannot be used for a case-insensitive index", char::from(*sentinel))
+        }
+        InvalidSymbol(symbol: u8, pos: usize) {
+            description("text contains a symbol that is not part of the alphabet")
+            display("symbol '{}' at position {} is not part of the alphabet", char::from(*symbol), pos)
+        }
+        InvalidFMDLayout(seq: usize) {
+            description("text is not of the form T1$R1$T2$R2$ with Ri being the reverse complement of Ti")
+            display("sequence {} of the text is not the reverse complement of the preceding sequence", seq)
+        }
+    }
+}
+
+/// Builder for `SampledFMIndex` and `SampledFMDIndex`, validating all parameters.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::fmindex::FMIndexBuilder;
+/// use bio::alphabets::dna;
+///
+/// let alphabet = dna::n_alphabet();
+/// let fmd = FMIndexBuilder::new()
+///     .text(b"ATTC$")
+///     .alphabet(&alphabet)
+///     .fmd(true)
+///     .build_fmd()
+///     .unwrap();
+///
+/// let intervals = fmd.smems(b"ATT", 2);
+/// assert_eq!(fmd.positions_from_interval(&intervals[0].forward()), [0]);
+/// assert_eq!(fmd.positions_from_interval(&intervals[0].revcomp()), [6]);
+/// ```
+pub struct FMIndexBuilder<'a> {
+    text: Option<&'a [u8]>,
+    alphabet: Option<&'a Alphabet>,
+    k: u32,
+    s: usize,
+    fmd: bool,
+    case_insensitive: bool,
+    occ_backend: OccBackend,
+    sentinel: u8,
+}
+
+/// Data structure backing the occurrence array of an index built by `FMIndexBuilder`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OccBackend {
+    /// Counts sampled at every k-th position of the BWT (see `Occ::new`).
+    #[default]
+    Sampled,
+    /// A wavelet tree (see `Occ::new_wavelet_tree`), which needs less memory for larger
+    /// alphabets like proteins.
+    WaveletTree,
+    /// A 2-bit packed BWT with popcount-based rank queries (see `Occ::new_packed_dna`), which
+    /// is advisable for DNA texts.
+    PackedDna,
+    /// A run-length encoded BWT (see `Occ::new_run_length`), which is advisable for highly
+    /// repetitive texts.
+    RunLength,
+}
+
+impl<'a> Default for FMIndexBuilder<'a> {
+    fn default() -> Self {
+        FMIndexBuilder {
+            text: None,
+            alphabet: None,
+            k: 32,
+            s: 32,
+            fmd: false,
+            case_insensitive: false,
+            occ_backend: OccBackend::Sampled,
+            sentinel: b'$',
+        }
+    }
+}
+
+impl<'a> FMIndexBuilder<'a> {
+    /// Create a new builder with occ and suffix array sampling rates of 32.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The text to index. It has to be terminated by the sentinel (`$` by default), and may
+    /// contain multiple sentinel-separated sequences.
+    pub fn text(mut self, text: &'a [u8]) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    /// The alphabet of the text (without the sentinel). If not given, the alphabet is
+    /// inferred from the text.
+    pub fn alphabet(mut self, alphabet: &'a Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+
+    /// Sampling rate `k` of the occurrence array: every k-th entry will be stored.
+    pub fn occ_sampling(mut self, k: u32) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Sampling rate `s` of the suffix array: every s-th entry will be stored.
+    pub fn sa_sampling(mut self, s: usize) -> Self {
+        self.s = s;
+        self
+    }
+
+    /// If true, the reverse complement of each sequence in the text is appended after
+    /// it, yielding the layout T1$R1$T2$R2$ expected by the FMD-Index. Text positions of
+    /// the index refer to this layout.
+    pub fn fmd(mut self, fmd: bool) -> Self {
+        self.fmd = fmd;
+        self
+    }
+
+    /// If true, upper and lowercase variants of a symbol (e.g. soft-masked bases) are
+    /// treated as the same symbol, such that the search becomes case-insensitive.
+    /// The BWT of the index retains the original case.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// The data structure backing the occurrence array (sampled counts by default). The occ
+    /// sampling rate is ignored for wavelet trees and packed DNA, and is the sampling rate
+    /// of runs for run-length encoded BWTs.
+    pub fn occ_backend(mut self, occ_backend: OccBackend) -> Self {
+        self.occ_backend = occ_backend;
+        self
+    }
+
+    /// The sentinel terminating the text (`$` by default). It is ordered before all other
+    /// symbols regardless of its value, such that texts over arbitrary bytes (e.g. containing
+    /// `0` or symbols below `$`) can be indexed with a sentinel that does not occur in them
+    /// otherwise. FMD-Indexes always use `$`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, FMIndexable};
+    ///
+    /// let text = [3, 0, 1, 0, 1, 2, 0, 1, 0];
+    /// let fm = FMIndexBuilder::new()
+    ///     .text(&text)
+    ///     .alphabet(&Alphabet::new(&[1, 2, 3]))
+    ///     .sentinel(0)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let interval = fm.backward_search([1].iter());
+    /// let mut positions = fm.positions_from_interval(&interval);
+    /// positions.sort();
+    /// assert_eq!(positions, [2, 4, 7]);
+    /// ```
+    pub fn sentinel(mut self, sentinel: u8) -> Self {
+        self.sentinel = sentinel;
+        self
+    }
+
+    /// Build a `SampledFMIndex`.
+    pub fn build(&self) -> Result<SampledFMIndex, IndexBuildError> {
+        let text = self.validated_text()?;
+        if self.fmd {
+            return Ok(self.index(&self.fmd_text(text)?));
+        }
+
+        Ok(self.index(text))
+    }
+
+    /// Build a `SampledFMDIndex`. The text has to be over the DNA alphabet with N, and of the
+    /// form T1$R1$T2$R2$ with Ri being the reverse complement of Ti, unless the reverse
+    /// complements are appended by the builder (see `fmd`).
+    pub fn build_fmd(&self) -> Result<SampledFMDIndex, IndexBuildError> {
+        let text = self.validated_text()?;
+        let SampledFMIndex { fmindex, sa } = if self.fmd {
+            self.index(&self.fmd_text(text)?)
+        } else {
+            if let Some((pos, symbol)) = FMD_SYMBOLS.find_invalid(text) {
+                return Err(IndexBuildError::InvalidSymbol(symbol, pos));
+            }
+            let seqs: Vec<&[u8]> = text[..text.len() - 1].split(|&c| c == b'$').collect();
+            for (i, pair) in seqs.chunks(2).enumerate() {
+                let is_revcomp = match *pair {
+                    [seq, rev] if self.case_insensitive => {
+                        seq.len() == rev.len()
+                            && dna::revcomp_iter(seq)
+                                .zip(rev)
+                                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+                    }
+                    [seq, rev] => dna::revcomp_iter(seq).eq(rev.iter().cloned()),
+                    _ => false,
+                };
+                if !is_revcomp {
+                    return Err(IndexBuildError::InvalidFMDLayout(2 * i + 1));
+                }
+            }
+            self.index(text)
+        };
+
+        Ok(SampledFMDIndex {
+            fmdindex: FMDIndex { fmindex },
+            sa,
+        })
+    }
+
+    /// Append the reverse complement after each sequence of the (validated) text.
+    fn fmd_text(&self, text: &[u8]) -> Result<Vec<u8>, IndexBuildError> {
+        if let Some((pos, symbol)) = FMD_SYMBOLS.find_invalid(text) {
+            return Err(IndexBuildError::InvalidSymbol(symbol, pos));
+        }
+        let mut doubled = Vec::with_capacity(text.len() * 2);
+        for seq in text[..text.len() - 1].split(|&c| c == b'$') {
+            doubled.extend_from_slice(seq);
+            doubled.push(b'$');
+            doubled.extend(dna::revcomp_iter(seq));
+            doubled.push(b'$');
+        }
+
+        Ok(doubled)
+    }
+
+    /// Construct the index over the given (validated) text.
+    fn index(&self, text: &[u8]) -> SampledFMIndex {
+        // the occ array also has to count the sentinel, which may be larger than all symbols
+        // of the alphabet
+        let mut alphabet = match self.alphabet {
+            Some(alphabet) => alphabet.clone(),
+            None => Alphabet::new(text),
+        };
+        alphabet.insert(self.sentinel);
+        let alphabet = &alphabet;
+        // the suffix array sample is derived from the BWT, such that the full suffix array
+        // never has to be kept in memory
+        let (bwt, less) = if self.case_insensitive {
+            let bwt = bwt_sorted_by_with_sentinel(text, &text.to_ascii_uppercase());
+            let less = less_case_insensitive_with_sentinel(&bwt, alphabet, self.sentinel);
+            (bwt, less)
+        } else {
+            let bwt = bwt_sorted_by_with_sentinel(text, text);
+            let less = less_with_sentinel(&bwt, alphabet, self.sentinel);
+            (bwt, less)
+        };
+        let occ = match self.occ_backend {
+            OccBackend::WaveletTree => Occ::build_wavelet_tree(&bwt, self.case_insensitive),
+            OccBackend::PackedDna => Occ::build_packed_dna(&bwt, self.case_insensitive),
+            OccBackend::RunLength => {
+                Occ::build_run_length(&bwt, self.k as usize, self.case_insensitive)
+            }
+            OccBackend::Sampled if self.case_insensitive => {
+                Occ::new_case_insensitive(&bwt, self.k, alphabet)
+            }
+            OccBackend::Sampled => Occ::new(&bwt, self.k, alphabet),
+        };
+        let fmindex = FMIndex::new(bwt, less, occ);
+        let sa = SASample::from_fmindex(&fmindex, self.s);
+
+        SampledFMIndex { fmindex, sa }
+    }
+
+    /// Check parameters and return the text to index.
+    fn validated_text(&self) -> Result<&'a [u8], IndexBuildError> {
+        let text = self.text.ok_or(IndexBuildError::MissingText)?;
+        if self.k == 0 {
+            return Err(IndexBuildError::InvalidOccSampling);
+        }
+        if self.s == 0 {
+            return Err(IndexBuildError::InvalidSASampling);
+        }
+        if text.is_empty() {
+            return Err(IndexBuildError::EmptyText);
+        }
+        if text[text.len() - 1] != self.sentinel {
+            return Err(IndexBuildError::MissingSentinel);
+        }
+        if self.case_insensitive && self.sentinel.is_ascii_alphabetic() {
+            return Err(IndexBuildError::InvalidSentinel(self.sentinel));
+        }
+        if let Some(alphabet) = self.alphabet {
+            if let Some(pos) = text
+                .iter()
+                .position(|&c| c != self.sentinel && !alphabet.contains(c))
+            {
+                return Err(IndexBuildError::InvalidSymbol(text[pos], pos));
+            }
+        }
+
+        Ok(text)
+    }
+}
+
+/// A bi-interval on suffix array of the forward and reverse strand of a DNA text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BiInterval {
+    lower: usize,
+    lower_rev: usize,
+    size: usize,
+    match_size: usize,
+}
+
+impl BiInterval {
+    pub fn forward(&self) -> Interval {
+        Interval {
+            upper: self.lower + self.size,
+            lower: self.lower,
+        }
+    }
+    pub fn revcomp(&self) -> Interval {
+        Interval {
+            upper: self.lower_rev + self.size,
+            lower: self.lower_rev,
+        }
+    }
+
+    /// Return the occurrences of the forward interval as hits in the documents (T1$R1$,
+    /// T2$R2$, ...) of an FMD-Index text. Occurrences in the reverse complement copies are
+    /// reported with respect to the coordinates of the forward copies. Occurrences covering
+    /// a sentinel are skipped.
+    pub fn occ_docs<SA: SuffixArray>(&self, sa: &SA, docs: &DocArray) -> Vec<DocHit> {
+        self.forward()
+            .occ(sa)
+            .into_iter()
+            .filter_map(|pos| docs.doc_hit(pos, self.match_size))
+            .collect()
+    }
+
+    /// Count the occurrences of the forward interval per strand of the original text, given
+    /// an FMD-Index text T$R$ with the reverse complement R of the original text T of length
+    /// `orig_len` (excluding the sentinel). Occurrences in R are occurrences of the reverse
+    /// complement of the pattern in T, and thus counted for the reverse strand.
+    /// Returns the pair of forward and reverse counts.
+    pub fn strand_counts<SA: SuffixArray>(&self, sa: &SA, orig_len: usize) -> (usize, usize) {
+        let forward = (self.lower..self.lower + self.size)
+            .filter(|&r| sa.get(r).unwrap() <= orig_len)
+            .count();
+
+        (forward, self.size - forward)
+    }
+
+    /// Length of the pattern matched by this bi-interval.
+    pub fn match_size(&self) -> usize {
+        self.match_size
+    }
+
+    fn swapped(&self) -> BiInterval {
+        BiInterval {
+            lower: self.lower_rev,
+            lower_rev: self.lower,
+            size: self.size,
+            match_size: self.match_size,
+        }
+    }
+}
+
+/// A document array over a text consisting of multiple sentinel-terminated sequences, mapping
+/// text positions back to the sequence they belong to (see also
+/// `suffix_array::generalized_suffix_array`). For FMD-Index texts of the form T1$R1$T2$R2$,
+/// each pair of sequences forms a document.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocArray {
+    ends: Vec<usize>,
+    /// the sequences in the order of their sentinels in the suffix array
+    sentinel_seqs: Vec<usize>,
+}
+
+/// An occurrence of a pattern in a document of an FMD-Index text, given as the offset
+/// in the forward copy of the document and the strand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DocHit {
+    pub doc: usize,
+    pub pos: usize,
+    pub strand: ReqStrand,
+}
+
+impl DocArray {
+    /// Create a new document array, storing the positions of the sentinels in the text. The
+    /// sentinel is the last symbol of the text, i.e. `$` for FMD-Index texts.
+    pub fn new(text: &[u8]) -> Self {
+        let ends = match text.last() {
+            Some(&sentinel) => text
+                .iter()
+                .enumerate()
+                .filter(|&(_, &c)| c == sentinel)
+                .map(|(i, _)| i)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        DocArray {
+            ends,
+            sentinel_seqs: sentinel_map(text),
+        }
+    }
+
+    /// Number of sentinel-terminated sequences in the text.
+    pub fn num_seqs(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Return the index of the sequence containing the given text position and the offset
+    /// inside of that sequence. The sentinel of a sequence belongs to it, with the offset
+    /// being the length of the sequence.
+    /// Complexity: O(log d) with d being the number of sequences.
+    pub fn seq_of(&self, pos: usize) -> (usize, usize) {
+        let seq = match self.ends.binary_search(&pos) {
+            Ok(i) | Err(i) => i,
+        };
+        assert!(seq < self.ends.len(), "Position out of range of text.");
+
+        (seq, pos - self.seq_start(seq))
+    }
+
+    /// Return the sorted distinct indices of the sequences containing the suffixes in the
+    /// interval [lower, upper) of the given suffix array, i.e., the sequences containing a
+    /// pattern if the interval is the one of its occurrences.
+    /// Complexity: O(k log d) for an interval of size k.
+    pub fn seqs_of_interval<SA: SuffixArray>(
+        &self,
+        pos: &SA,
+        lower: usize,
+        upper: usize,
+    ) -> Vec<usize> {
+        let mut seqs: Vec<usize> = (lower..upper)
+            .map(|r| self.seq_of(pos.get(r).unwrap()).0)
+            .collect();
+        seqs.sort_unstable();
+        seqs.dedup();
+
+        seqs
+    }
+
+    /// Return the text position of the sentinel at the given row of the suffix array, i.e.,
+    /// for the rows 0 to `num_seqs() - 1`, without a suffix array lookup. The sequence
+    /// terminated by it is given by `bwt::sentinel_map`.
+    pub fn sentinel_pos(&self, row: usize) -> usize {
+        assert!(row < self.ends.len(), "Expecting a row of a sentinel.");
+        self.ends[self.sentinel_seqs[row]]
+    }
+
+    /// Start position of the given sequence in the text.
+    pub fn seq_start(&self, seq: usize) -> usize {
+        if seq == 0 {
+            0
+        } else {
+            self.ends[seq - 1] + 1
+        }
+    }
+
+    /// Length of the given sequence (without sentinel).
+    pub fn seq_len(&self, seq: usize) -> usize {
+        self.ends[seq] - self.seq_start(seq)
+    }
+
+    /// Map a match of length `match_size` at the given position of an FMD-Index text
+    /// (T1$R1$T2$R2$) to the document and the offset on its forward copy.
+    /// Matches on a reverse complement copy are reported with `ReqStrand::Reverse`.
+    /// Returns `None` if the match covers the sentinel of its sequence.
+    pub fn doc_hit(&self, pos: usize, match_size: usize) -> Option<DocHit> {
+        let (seq, offset) = self.seq_of(pos);
+        let rest = self.seq_len(seq).checked_sub(offset + match_size)?;
+        Some(if seq % 2 == 0 {
+            DocHit {
+                doc: seq / 2,
+                pos: offset,
+                strand: ReqStrand::Forward,
+            }
+        } else {
+            DocHit {
+                doc: seq / 2,
+                pos: rest,
+                strand: ReqStrand::Reverse,
+            }
+        })
+    }
+}
+
+/// Reusable buffers for `FMDIndex::smems_with`.
+#[derive(Default, Debug, Clone)]
+pub struct SmemScratch {
+    curr: Vec<BiInterval>,
+    prev: Vec<BiInterval>,
+}
+
+/// The FMD-Index for linear time search of supermaximal exact matches on forward and reverse
+/// strand of DNA texts (Li, 2012).
+#[derive(Serialize, Deserialize)]
+pub struct FMDIndex<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> {
+    fmindex: FMIndex<DBWT, DLess, DOcc>,
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndexable
+    for FMDIndex<DBWT, DLess, DOcc>
+{
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.fmindex.occ(r, a)
+    }
+
+    fn less(&self, a: u8) -> usize {
+        self.fmindex.less(a)
+    }
+
+    /// Provide a reference to the underlying BWT.
+    fn bwt(&self) -> &BWT {
+        self.fmindex.bwt()
+    }
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> From<FMIndex<DBWT, DLess, DOcc>>
+    for FMDIndex<DBWT, DLess, DOcc>
+{
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics).
+    /// This expects a BWT that was created from a text over the DNA alphabet with N
+    /// (`alphabets::dna::n_alphabet()`) consisting of the
+    /// concatenation with its reverse complement, separated by the sentinel symbol `$`.
+    /// I.e., let T be the original text and R be its reverse complement.
+    /// Then, the expected text is T$R$. Further, multiple concatenated texts are allowed, e.g.
+    /// T1$R1$T2$R2$T3$R3$.
+    ///
+    fn from(fmindex: FMIndex<DBWT, DLess, DOcc>) -> FMDIndex<DBWT, DLess, DOcc> {
+        assert!(
+            FMD_SYMBOLS.is_word(fmindex.bwt()),
+            "Expecting BWT over the DNA alphabet (including N) with the sentinel $."
+        );
+
+        FMDIndex { fmindex }
+    }
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMDIndex<DBWT, DLess, DOcc> {
+    /// Construct a new instance of the FMD index like `from`, but return an error instead of
+    /// panicking if the BWT contains a symbol other than the DNA alphabet with N and the
+    /// sentinel `$`. Note that `std::convert::TryFrom` is already implemented via `From`,
+    /// which never fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// use bio::data_structures::bwt::{less, Occ};
+    /// use bio::data_structures::fmindex::{FMDIndex, FMIndex, IndexBuildError};
+    ///
+    /// let alphabet = Alphabet::new(b"ACGTX");
+    /// let bwt = b"TX$A$".to_vec();
+    /// let (less, occ) = (less(&bwt, &alphabet), Occ::new(&bwt, 3, &alphabet));
+    /// let fmindex = FMIndex::new(&bwt, &less, &occ);
+    /// assert_eq!(
+    ///     FMDIndex::try_from(fmindex).err(),
+    ///     Some(IndexBuildError::InvalidSymbol(b'X', 1))
+    /// );
+    /// ```
+    pub fn try_from(fmindex: FMIndex<DBWT, DLess, DOcc>) -> Result<Self, IndexBuildError> {
+        if let Some((pos, symbol)) = FMD_SYMBOLS.find_invalid(fmindex.bwt()) {
+            return Err(IndexBuildError::InvalidSymbol(symbol, pos));
+        }
+
+        Ok(FMDIndex { fmindex })
+    }
+
+    /// Find supermaximal exact matches of given pattern that overlap position i in the pattern.
+    /// Complexity O(m) with pattern of length m.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::FMIndexBuilder;
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let fmdindex = FMIndexBuilder::new()
+    ///     .text(b"ATTC$GAAT$")
+    ///     .alphabet(&alphabet)
+    ///     .occ_sampling(3)
+    ///     .build_fmd()
+    ///     .unwrap();
+    ///
+    /// let pattern = b"ATT";
+    /// let intervals = fmdindex.smems(pattern, 2);
+    ///
+    /// let forward_positions = fmdindex.positions_from_interval(&intervals[0].forward());
+    ///
+    /// let revcomp_positions = fmdindex.positions_from_interval(&intervals[0].revcomp());
+    ///
+    /// assert_eq!(forward_positions, [0]);
+    /// assert_eq!(revcomp_positions, [6]);
+    /// ```
+    pub fn smems(&self, pattern: &[u8], i: usize) -> Vec<BiInterval> {
+        let mut scratch = SmemScratch::default();
+        let mut matches = Vec::new();
+        self.smems_with(pattern, i, &mut scratch, &mut matches);
+
+        matches
+    }
+
+    /// Find supermaximal exact matches of given pattern that overlap position i in the pattern,
+    /// like `smems`, but reusing the buffers of the given scratch space and writing the matches
+    /// into `matches` (which is cleared first). This avoids any allocation after warm-up when
+    /// seeding many patterns. Returns the end of the longest match starting at position i,
+    /// such that continuing the search from there yields all SMEMs of the pattern (see
+    /// `search_fasta`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndexBuilder, SmemScratch};
+    ///
+    /// let alphabet = dna::n_alphabet();
+    /// let fmdindex = FMIndexBuilder::new()
+    ///     .text(b"ATTC$GAAT$")
+    ///     .alphabet(&alphabet)
+    ///     .build_fmd()
+    ///     .unwrap();
+    ///
+    /// let mut scratch = SmemScratch::default();
+    /// let mut matches = Vec::new();
+    /// for pattern in [&b"ATT"[..], b"GAATTC"].iter() {
+    ///     fmdindex.smems_with(pattern, 2, &mut scratch, &mut matches);
+    ///     assert_eq!(matches, fmdindex.smems(pattern, 2));
+    /// }
+    /// ```
+    pub fn smems_with(
+        &self,
+        pattern: &[u8],
+        i: usize,
+        scratch: &mut SmemScratch,
+        matches: &mut Vec<BiInterval>,
+    ) -> usize {
+        let curr = &mut scratch.curr;
+        let prev = &mut scratch.prev;
+        curr.clear();
+        prev.clear();
+        matches.clear();
+
+        let mut interval = self.init_interval_with(pattern[i]);
+
+        for &a in pattern[i + 1..].iter() {
+            // forward extend interval
+            let forward_interval = self.forward_ext(&interval, a);
+
+            // if size changed, add last interval to list
+            if interval.size != forward_interval.size {
+                curr.push(interval);
+            }
+            // if new interval size is zero, stop, as no further forward extension is possible
+            if forward_interval.size == 0 {
+                break;
+            }
+            interval = forward_interval;
+        }
+        // add the last non-zero interval
+        curr.push(interval);
+        let end = i + interval.match_size;
+        // reverse intervals such that longest comes first
+        curr.reverse();
+
+        swap(curr, prev);
+        let mut j = pattern.len() as isize;
+
+        for k in (-1..i as isize).rev() {
+            let a = if k == -1 { b'$' } else { pattern[k as usize] };
+            curr.clear();
+            // size of the last confirmed interval
+            let mut last_size = -1;
+
+            for interval in prev.iter() {
+                // backward extend interval
+                let forward_interval = self.backward_ext(interval, a);
+
+                if (forward_interval.size == 0 || k == -1) &&
+                        // interval could not be extended further
+                        // if no interval has been extended this iteration,
+                        // interval is maximal and can be added to the matches
+                        curr.is_empty() && k < j
+                {
+                    j = k;
+                    matches.push(*interval);
+                }
+                // add _interval to curr (will be further extended next iteration)
+                if forward_interval.size != 0 && forward_interval.size as isize != last_size {
+                    last_size = forward_interval.size as isize;
+                    curr.push(forward_interval);
+                }
+            }
+            if curr.is_empty() {
+                break;
+            }
+            swap(curr, prev);
+        }
+
+        end
+    }
+
+    /// Initialize interval with given start character.
+    pub fn init_interval_with(&self, a: u8) -> BiInterval {
+        let comp_a = dna::complement(a);
+        let lower = self.fmindex.less(a);
+
+        BiInterval {
+            lower,
+            lower_rev: self.fmindex.less(comp_a),
+            size: self.fmindex.next_symbol_bound(a) - lower,
+            match_size: 1,
+        }
+    }
+
+    /// Initialize interval for empty pattern. The interval points at the whole suffix array.
+    pub fn init_interval(&self) -> BiInterval {
+        BiInterval {
+            lower: 0,
+            lower_rev: 0,
+            size: self.fmindex.bwt.borrow().len(),
+            match_size: 0,
+        }
+    }
+
+    /// Backward extension of given interval with given character.
+    pub fn backward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
+        // with case folding, lowercase symbols share the counts of their uppercase variant
+        let a = if self.fmindex.occ.borrow().is_case_insensitive() {
+            a.to_ascii_uppercase()
+        } else {
+            a
+        };
+        // count all symbols at both ends of the interval at once
+        let occ = self.fmindex.occ.borrow();
+        let bwt = self.fmindex.bwt.borrow();
         // Interval [l(c(aP)), u(c(aP))] is a subinterval of [l(c(P)), u(c(P))] for each a,
         // starting with the lexicographically smallest ($),
         // then c(T) = A, c(G) = C, c(C) = G, N, c(A) = T, ...
         // Hence, we calculate lower revcomp bounds by iterating over
         // symbols and updating from previous one.
-        for &b in b"$TGCNAtgcna".iter() {
+        let symbols = b"$TGCNAtgcna";
+        let (lower_counts, upper_counts) =
+            occ.get_all_bounds(bwt, interval.lower, interval.lower + interval.size, symbols);
+        let mut s = 0;
+        let mut o = 0;
+        let mut l = interval.lower_rev;
+        for &b in symbols.iter() {
             l += s;
-            o = if interval.lower == 0 {
-                0
-            } else {
-                self.fmindex.occ(interval.lower - 1, b)
-            };
+            o = lower_counts[b as usize];
             // calculate size
-            s = self.fmindex.occ(interval.lower + interval.size - 1, b) - o;
+            s = upper_counts[b as usize] - o;
             if b == a {
                 break;
             }
         }
-        // calculate lower bound
-        let k = self.fmindex.less(a) + o;
+        // calculate lower bound
+        let k = self.fmindex.less(a) + o;
+
+        BiInterval {
+            lower: k,
+            lower_rev: l,
+            size: s,
+            match_size: interval.match_size + 1,
+        }
+    }
+
+    pub fn forward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
+        let comp_a = dna::complement(a);
+
+        self.backward_ext(&interval.swapped(), comp_a).swapped()
+    }
+}
+
+/// An FMD-Index that owns its BWT, less and occurrence arrays together with a sample of
+/// the suffix array. Usually, it is constructed via `FMIndexBuilder::build_fmd`.
+/// It dereferences to the underlying `FMDIndex`.
+#[derive(Serialize, Deserialize)]
+pub struct SampledFMDIndex {
+    fmdindex: FMDIndex<BWT, Less, Occ>,
+    sa: SASample,
+}
+
+impl SampledFMDIndex {
+    /// Provide a reference to the suffix array sample.
+    pub fn sa_sample(&self) -> &SASample {
+        &self.sa
+    }
+
+    /// Get the text position of the suffix at row `r` of the suffix array.
+    pub fn sa_pos_to_text_pos(&self, r: usize) -> usize {
+        self.sa.get(&self.fmdindex, r)
+    }
+
+    /// Get the text positions of all suffixes in the given interval.
+    pub fn positions_from_interval(&self, interval: &Interval) -> Vec<usize> {
+        (interval.lower..interval.upper)
+            .map(|r| self.sa_pos_to_text_pos(r))
+            .collect()
+    }
+}
+
+impl Deref for SampledFMDIndex {
+    type Target = FMDIndex<BWT, Less, Occ>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.fmdindex
+    }
+}
+
+/// How `search_fasta` treats records containing symbols that are not part of the index
+/// alphabet (`ACGTN`, and their lowercase variants for case-insensitive indexes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidSymbolPolicy {
+    /// Silently skip the record.
+    Skip,
+    /// Yield a `SearchError::InvalidSymbol` for the record.
+    Error,
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum SearchError {
+        Fasta(err: fasta::Error) {
+            from()
+            description("error reading FASTA record")
+            display("error reading FASTA record: {}", err)
+            cause(err)
+        }
+        InvalidSymbol(id: String, symbol: u8, pos: usize) {
+            description("record contains a symbol that is not part of the index alphabet")
+            display("record {} contains invalid symbol '{}' at position {}", id, char::from(*symbol), pos)
+        }
+    }
+}
+
+/// Search all records of a FASTA file against an FMD-Index. For each record, the id and
+/// the supermaximal exact matches (SMEMs) of length at least `min_len` (and at least 1) are
+/// yielded, ordered by their end in the record. Since the search continues after the longest
+/// match from the previous start, each SMEM is found once and the search takes O(m) steps for
+/// a record of length m. Records with symbols outside of the index alphabet are handled
+/// according to `policy`.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::fmindex::{search_fasta, FMIndexBuilder, InvalidSymbolPolicy};
+/// use bio::io::fasta;
+///
+/// let alphabet = dna::n_alphabet();
+/// let fmdindex = FMIndexBuilder::new()
+///     .text(b"GCCTTAACATTATTACGCCTA$TAGGCGTAATAATGTTAAGGC$")
+///     .alphabet(&alphabet)
+///     .build_fmd()
+///     .unwrap();
+///
+/// let reader = fasta::Reader::new(&b">r1\nACATTAT\n>r2\nTAXT\n"[..]);
+/// let results = search_fasta(&fmdindex, reader, 5, InvalidSymbolPolicy::Skip)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(results.len(), 1);
+/// let (id, seeds) = &results[0];
+/// assert_eq!(id, "r1");
+/// assert_eq!(fmdindex.positions_from_interval(&seeds[0].forward()), [6]);
+/// ```
+pub fn search_fasta<'a, R: io::Read + 'a>(
+    index: &'a SampledFMDIndex,
+    reader: fasta::Reader<R>,
+    min_len: usize,
+    policy: InvalidSymbolPolicy,
+) -> impl Iterator<Item = Result<(String, Vec<BiInterval>), SearchError>> + 'a {
+    let alphabet = dna::n_alphabet();
+    let case_insensitive = index.fmindex.occ.is_case_insensitive();
+    let min_len = min_len.max(1);
+    let mut scratch = SmemScratch::default();
+    let mut matches = Vec::new();
+
+    reader.records().filter_map(move |record| {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => return Some(Err(SearchError::Fasta(e))),
+        };
+        let seq = record.seq();
+
+        let invalid = seq.iter().position(|&c| {
+            let c = if case_insensitive {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            };
+            !alphabet.contains(c)
+        });
+        if let Some(pos) = invalid {
+            return match policy {
+                InvalidSymbolPolicy::Skip => None,
+                InvalidSymbolPolicy::Error => Some(Err(SearchError::InvalidSymbol(
+                    record.id().to_owned(),
+                    seq[pos],
+                    pos,
+                ))),
+            };
+        }
+
+        let mut seeds = Vec::new();
+        let mut i = 0;
+        while i < seq.len() {
+            i = index.smems_with(seq, i, &mut scratch, &mut matches);
+            seeds.extend(
+                matches
+                    .iter()
+                    .filter(|smem| smem.size > 0 && smem.match_size >= min_len),
+            );
+        }
+
+        Some(Ok((record.id().to_owned(), seeds)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::{dna, protein, RankTransform};
+    use crate::data_structures::bwt::{bwt, less, Occ};
+    use crate::data_structures::suffix_array::{
+        sparse_suffix_array, suffix_array, verify_suffix_array, CompactSuffixArray,
+    };
+    use rand::{Rng, SeedableRng, StdRng};
+
+    const ISSUE39_READS: &[u8] = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG$CCGC\
+                   CCACTTCGACCTCCCAAAGTGCTGGGATTACAGGCATAAGCCACCACGCC$CGAAGTGG\
+                   GCGGATCACTTGAGGTCAGGAGTTGGAGACTAGCCTGGCCAACACGATGAAACCCCGTC\
+                   TCTAATA$TATTAGAGACGGGGTTTCATCGTGTTGGCCAGGCTAGTCTCCAACTCCTGA\
+                   CCTCAAGTGATCCGCCCACTTCG$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTA\
+                   TTCATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTG$CAAAAAA\
+                   TGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAATGAATAATTTTACCAAAAT\
+                   AAGCAAACATTTTTCGAGCT$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTATTC\
+                   ATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTGAAATTACATAT\
+                   $ATATGTAATTTCAAAAAATGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAA\
+                   TGAATAATTTTACCAAAATAAGCAAACATTTTTCGAGCT$TAAAATTTCCTCTGACAGT\
+                   GTAAAAGAGATCTTCATACAAAAATCAGAATTTATATAGTCTCTTTCCAAAAGACCATA\
+                   AAACCAATCAGTTAATAGTTGAT$ATCAACTATTAACTGATTGGTTTTATGGTCTTTTG\
+                   GAAAGAGACTATATAAATTCTGATTTTTGTATGAAGATCTCTTTTACACTGTCAGAGGA\
+                   AATTTTA$CACCTATCTACCCTGAATCTAAGTGCTAACAGGAAAGGATGCCAGATTGCA\
+                   TGCCTGCTGATAAAGCCACAGTTTGGACTGTCACTCAATCACCATCGTTC$GAACGATG\
+                   GTGATTGAGTGACAGTCCAAACTGTGGCTTTATCAGCAGGCATGCAATCTGGCATCCTT\
+                   TCCTGTTAGCACTTAGATTCAGGGTAGATAGGTG$CATCGTTCCTCCTGTGACTCAGTA\
+                   TAACAAGATTGGGAGAATACTCTACAGTTCCTGATTCCCCCACAG$CTGTGGGGGAATC\
+                   AGGAACTGTAGAGTATTCTCCCAATCTTGTTATACTGAGTCACAGGAGGAACGATG$TG\
+                   TAAATTCTGAGAAAAATTTGCAGGTCTTTCTTCAGGAGCATGTAATCTCTTGCTCTCTT\
+                   TGTTATCTATCTATAGTACTGTAGGTTATCTGGAGTTGCT$AGCAACTCCAGATAACCT\
+                   ACAGTACTATAGATAGATAACAAAGAGAGCAAGAGATTACATGCTCCTGAAGAAAGACC\
+                   TGCAAATTTTTCTCAGAATTTACA$CACTTCTCCTTGTCTTTACAGACTGGTTTTGCAC\
+                   TGGGAAATCCTTTCACCAGTCAGCCCAGTTAGAGATTCTG$CAGAATCTCTAACTGGGC\
+                   TGACTGGTGAAAGGATTTCCCAGTGCAAAACCAGTCTGTAAAGACAAGGAGAAGTG$AA\
+                   TGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGATAGAT\
+                   CAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATT$AATTTTGTTAAGACTCTCTTTG\
+                   TGACCTAACATTTGATCTATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAA\
+                   TTTATATACCTCCATT$TTAATTTTGTTAAGACTCTCTTTGTGACCTAACATTTGATCT\
+                   ATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAATTTATATACCTCCATTTT\
+                   $AAAATGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGA\
+                   TAGATCAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATTAA$TTCTTCTTTGACTCA\
+                   TTGGTTGTTCAATAGTATGTTGTTTAATTTCCATATATTTGTAAATGTTTCCGTTTTCC\
+                   TTCTACTATTGAATTTTTGCTTCATC$GATGAAGCAAAAATTCAATAGTAGAAGGAAAA\
+                   CGGAAACATTTACAAATATATGGAAATTAAACAACATACTATTGAACAACCAATGAGTC\
+                   AAAGAAGAA$AGGAAAACGGAAACATTTACAAATATATGGAAATTAAACAACATACTAT\
+                   TGAACAACCAATGAGTCAAAGAAGAAATCAAAAAGAATATTAGAAAAC$GTTTTCTAAT\
+                   ATTCTTTTTGATTTCTTCTTTGACTCATTGGTTGTTCAATAGTATGTTGTTTAATTTCC\
+                   ATATATTTGTAAATGTTTCCGTTTTCCT$TTAGAAAACAAGCTGACAAAAAAATAAAAA\
+                   AACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAAATTTAT\
+                   AGCAATAAATGC$GCATTTATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCA\
+                   TTTCTAAGTTTTGCTATGTTGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTAA$TTT\
+                   ATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCATTTCTAAGTTTTGCTATGT\
+                   TGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTA$TAGAAAACAAGCTGACAAAAAAA\
+                   TAAAAAAACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAA\
+                   ATTTATAGCAATAAA$TCTTTCTTCTTTTTTAAGGTAGGCATTTATTGCTATAAATTTC\
+                   CCTCTTTAGTACTGCCTTTG$CAAAGGCAGTACTAAAGAGGGAAATTTATAGCAATAAA\
+                   TGCCTACCTTAAAAAAGAAGAAAGA$";
+
+    #[test]
+    fn test_fmindex() {
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        verify_suffix_array(text, &sa).unwrap();
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let pattern = b"TTA";
+        let sai = fm.backward_search(pattern.iter());
+
+        let positions = sai.occ(&sa);
+
+        assert_eq!(positions, [3, 12, 9]);
+    }
+
+    #[test]
+    fn test_fmindex_protein() {
+        let text = b"MKVLAAGIVGLLLAWESSQ*MKVLUOXBZAWESKVLAG*$";
+        let alphabet = protein::iupac_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        for pattern in [&b"KVL"[..], b"AWES", b"G*", b"UOX", b"MKVLA", b"W"] {
+            let mut positions = fm.backward_search(pattern.iter()).occ(&sa);
+            positions.sort_unstable();
+            let expected: Vec<usize> = (0..=text.len() - pattern.len())
+                .filter(|&p| &text[p..p + pattern.len()] == pattern)
+                .collect();
+            assert_eq!(positions, expected);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        for s in 1..5 {
+            let fm = FMIndexBuilder::new()
+                .text(text)
+                .alphabet(&alphabet)
+                .occ_sampling(3)
+                .sa_sampling(s)
+                .build()
+                .unwrap();
+            for (r, &pos) in sa.iter().enumerate() {
+                assert_eq!(fm.sa_pos_to_text_pos(r), pos);
+            }
+            let interval = fm.backward_search(b"TTA".iter());
+            assert_eq!(fm.positions_from_interval(&interval), [3, 12, 9]);
+        }
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let alphabet = dna::n_alphabet();
+        let builder = || FMIndexBuilder::new().alphabet(&alphabet);
+        assert_eq!(
+            FMIndexBuilder::new().build().err(),
+            Some(IndexBuildError::MissingText)
+        );
+        assert_eq!(
+            builder().text(b"").build().err(),
+            Some(IndexBuildError::EmptyText)
+        );
+        assert_eq!(
+            builder().text(b"ACGT$").occ_sampling(0).build().err(),
+            Some(IndexBuildError::InvalidOccSampling)
+        );
+        assert_eq!(
+            builder().text(b"ACGT$").sa_sampling(0).build().err(),
+            Some(IndexBuildError::InvalidSASampling)
+        );
+        assert_eq!(
+            builder().text(b"ACGT").build().err(),
+            Some(IndexBuildError::MissingSentinel)
+        );
+        assert_eq!(
+            builder().text(b"ACXT$").build().err(),
+            Some(IndexBuildError::InvalidSymbol(b'X', 2))
+        );
+        let protein = crate::alphabets::protein::alphabet();
+        assert_eq!(
+            FMIndexBuilder::new()
+                .text(b"ACLT$")
+                .alphabet(&protein)
+                .build_fmd()
+                .err(),
+            Some(IndexBuildError::InvalidSymbol(b'L', 2))
+        );
+        for &(text, seq) in [
+            (&b"ACGT$"[..], 1),
+            (b"AACG$CGTA$", 1),
+            (b"AC$GT$AG$CT$T$", 5),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                builder().text(text).build_fmd().err(),
+                Some(IndexBuildError::InvalidFMDLayout(seq))
+            );
+        }
+        assert!(builder().text(b"AACG$CGTT$").build_fmd().is_ok());
+        assert_eq!(
+            builder().text(b"AACG$cgtt$").build_fmd().err(),
+            Some(IndexBuildError::InvalidFMDLayout(1))
+        );
+        assert!(builder()
+            .text(b"AACG$cgtt$")
+            .case_insensitive(true)
+            .build_fmd()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_try_new_errors() {
+        let alphabet = dna::n_alphabet();
+        let text = b"ACGT$";
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        for &from_bwt in [false, true].iter() {
+            let f = |bwt: &[u8], k, s| {
+                if from_bwt {
+                    SampledFMIndex::try_from_bwt(bwt.to_vec(), k, s, &alphabet).err()
+                } else {
+                    SampledFMIndex::try_new(&sa, bwt.to_vec(), k, s, &alphabet).err()
+                }
+            };
+            assert_eq!(f(&bwt, 3, 2), None);
+            assert_eq!(f(&bwt, 0, 2), Some(IndexBuildError::InvalidOccSampling));
+            assert_eq!(f(&bwt, 3, 0), Some(IndexBuildError::InvalidSASampling));
+            assert_eq!(f(b"", 3, 2), Some(IndexBuildError::EmptyText));
+            assert_eq!(f(b"TACG", 3, 2), Some(IndexBuildError::MissingSentinel));
+            assert_eq!(
+                f(b"T$XG", 3, 2),
+                Some(IndexBuildError::InvalidSymbol(b'X', 2))
+            );
+            // the smallest symbol is the sentinel, even if it is not '$'
+            assert_eq!(f(b"T#CG", 3, 2), None);
+            assert_eq!(
+                f(b"T#$G", 3, 2),
+                Some(IndexBuildError::InvalidSymbol(b'$', 2))
+            );
+        }
+    }
 
-        BiInterval {
-            lower: k,
-            lower_rev: l,
-            size: s,
-            match_size: interval.match_size + 1,
+    #[test]
+    fn test_fmindex_try_new_errors() {
+        let alphabet = dna::n_alphabet();
+        let text = b"ACGTN$";
+        let bwt = bwt(text, &suffix_array(text));
+        let (less, occ) = (less(&bwt, &alphabet), Occ::new(&bwt, 3, &alphabet));
+        assert!(FMIndex::try_new(&bwt, &less, &occ).is_ok());
+        let empty: BWT = Vec::new();
+        assert_eq!(
+            FMIndex::try_new(&empty, &less, &occ).err(),
+            Some(IndexBuildError::EmptyText)
+        );
+        // symbols beyond the less array, and beyond the counts of the occ array
+        let unknown = b"NT$GAz".to_vec();
+        assert_eq!(
+            FMIndex::try_new(&unknown, &less, &occ).err(),
+            Some(IndexBuildError::InvalidSymbol(b'z', 5))
+        );
+        let wide = vec![0; 256];
+        let occ_acg = Occ::new(b"GCA$CA", 3, &Alphabet::new(b"$ACG"));
+        let pos = bwt.iter().position(|&c| c > b'G').unwrap();
+        assert_eq!(
+            FMIndex::try_new(&bwt, &wide, &occ_acg).err(),
+            Some(IndexBuildError::InvalidSymbol(bwt[pos], pos))
+        );
+
+        let fmindex = FMIndex::new(&bwt, &less, &occ);
+        assert!(FMDIndex::try_from(fmindex).is_ok());
+        let protein = b"TL$A$".to_vec();
+        let fmindex = FMIndex::new(&protein, &less, &occ);
+        assert_eq!(
+            FMDIndex::try_from(fmindex).err(),
+            Some(IndexBuildError::InvalidSymbol(b'L', 1))
+        );
+    }
+
+    #[test]
+    fn test_builder_fmd() {
+        let orig_text = b"GCCTTAACAT";
+        let revcomp_text = dna::revcomp(orig_text);
+        let text_builder: Vec<&[u8]> = vec![orig_text, b"$", &revcomp_text[..], b"$"];
+        let text = text_builder.concat();
+        let sa = suffix_array(&text);
+
+        let alphabet = dna::n_alphabet();
+        let fmd = FMIndexBuilder::new()
+            .text(&text)
+            .alphabet(&alphabet)
+            .occ_sampling(3)
+            .sa_sampling(4)
+            .build_fmd()
+            .unwrap();
+        assert_eq!(fmd.bwt(), &bwt(&text, &sa));
+
+        let intervals = fmd.smems(b"CTTAA", 1);
+        assert_eq!(fmd.positions_from_interval(&intervals[0].forward()), [2]);
+        assert_eq!(fmd.positions_from_interval(&intervals[0].revcomp()), [14]);
+
+        // the reverse complement is appended by the builder
+        let builder = || {
+            FMIndexBuilder::new()
+                .text(b"GCCTTAACAT$")
+                .alphabet(&alphabet)
+                .fmd(true)
+        };
+        let appended = builder().build_fmd().unwrap();
+        assert_eq!(appended.bwt(), fmd.bwt());
+        let intervals = appended.smems(b"CTTAA", 1);
+        assert_eq!(
+            appended.positions_from_interval(&intervals[0].revcomp()),
+            [14]
+        );
+        assert_eq!(builder().build().unwrap().bwt(), fmd.bwt());
+        assert_eq!(
+            builder().text(b"GCXT$").build_fmd().err(),
+            Some(IndexBuildError::InvalidSymbol(b'X', 2))
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let text = b"GCCTTaacatTATTACGccta$";
+        let alphabet = dna::n_alphabet();
+        let fm = FMIndexBuilder::new()
+            .text(text)
+            .alphabet(&alphabet)
+            .occ_sampling(3)
+            .sa_sampling(3)
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        // the BWT keeps the soft-masking
+        assert!(fm.bwt().contains(&b'a'));
+        assert!(fm.bwt().contains(&b'T'));
+
+        // patterns spanning the masked/unmasked boundaries
+        let cases: [(&[u8], usize); 4] =
+            [(b"TTAAC", 3), (b"ttaac", 3), (b"CATTA", 7), (b"cgCCT", 15)];
+        for &(pattern, pos) in cases.iter() {
+            let interval = fm.backward_search(pattern.iter());
+            assert_eq!(fm.positions_from_interval(&interval), [pos]);
+        }
+        let mut positions = fm.positions_from_interval(&fm.backward_search(b"tta".iter()));
+        positions.sort();
+        assert_eq!(positions, [3, 9, 12]);
+
+        let fmd = FMIndexBuilder::new()
+            .text(b"GCCTTaacat$ATGTTAAGGC$")
+            .alphabet(&alphabet)
+            .occ_sampling(3)
+            .case_insensitive(true)
+            .build_fmd()
+            .unwrap();
+        for pattern in [&b"CTTAA"[..], b"cttaa"].iter() {
+            let intervals = fmd.smems(pattern, 1);
+            assert_eq!(fmd.positions_from_interval(&intervals[0].forward()), [2]);
+            assert_eq!(fmd.positions_from_interval(&intervals[0].revcomp()), [14]);
         }
     }
 
-    pub fn forward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
-        let comp_a = dna::complement(a);
+    #[test]
+    fn test_backward_search_ambig() {
+        let text = b"ACATGGACGTTACTTT$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
 
-        self.backward_ext(&interval.swapped(), comp_a).swapped()
+        let positions = |intervals: Vec<Interval>| {
+            let mut positions: Vec<usize> = intervals
+                .iter()
+                .flat_map(|interval| interval.occ(&sa))
+                .collect();
+            positions.sort();
+            positions
+        };
+        assert_eq!(positions(fm.backward_search_ambig(b"ACRT")), [0, 6]);
+        assert_eq!(positions(fm.backward_search_ambig(b"ACYT")), [11]);
+        assert_eq!(positions(fm.backward_search_ambig(b"ACNT")), [0, 6, 11]);
+        assert!(fm.backward_search_ambig(b"GGGG").is_empty());
+
+        // NNN matches every 3-mer of the text and the intervals are merged
+        let intervals = fm.backward_search_ambig(b"NNN");
+        assert!(intervals.windows(2).all(|w| w[0].upper < w[1].lower));
+        assert_eq!(
+            positions(intervals),
+            (0..text.len() - 3).collect::<Vec<_>>()
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::alphabets::dna;
-    use crate::data_structures::bwt::{bwt, less, Occ};
-    use crate::data_structures::suffix_array::suffix_array;
+    #[test]
+    fn test_backward_search_ambig_empty() {
+        let alphabet = dna::n_alphabet();
+        let bwt: BWT = Vec::new();
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+        assert!(fm.backward_search_ambig(b"ACNT").is_empty());
+        assert!(fm.backward_search_ambig(b"").is_empty());
+    }
 
     #[test]
-    fn test_fmindex() {
+    fn test_contains() {
         let text = b"GCCTTAACATTATTACGCCTA$";
         let alphabet = dna::n_alphabet();
         let sa = suffix_array(text);
@@ -424,12 +2338,203 @@ mod tests {
         let occ = Occ::new(&bwt, 3, &alphabet);
         let fm = FMIndex::new(&bwt, &less, &occ);
 
-        let pattern = b"TTA";
-        let sai = fm.backward_search(pattern.iter());
+        for pattern in [&b"TTA"[..], b"A", b"GCCTTAACATTATTACGCCTA", b"CC", b""].iter() {
+            let interval = fm.backward_search(pattern.iter());
+            assert!(fm.contains(pattern));
+            assert_eq!(fm.count_occurrences(pattern), interval.occ(&sa).len());
+        }
+        // absent patterns, including a symbol smaller than any text symbol
+        for pattern in [&b"GGG"[..], b"TTTA", b"N", b"ACGTN", b"\x00"].iter() {
+            assert!(!fm.contains(pattern));
+            assert_eq!(fm.count_occurrences(pattern), 0);
+            assert!(fm.backward_search(pattern.iter()).occ(&sa).is_empty());
+        }
+    }
 
-        let positions = sai.occ(&sa);
+    #[test]
+    fn test_doc_array() {
+        let docs = DocArray::new(b"ACGT$TGCA$GG$CC$");
+        assert_eq!(docs.num_seqs(), 4);
+        assert_eq!(docs.seq_of(0), (0, 0));
+        assert_eq!(docs.seq_of(4), (0, 4));
+        assert_eq!(docs.seq_of(5), (1, 0));
+        assert_eq!(docs.seq_of(14), (3, 1));
+        assert_eq!(docs.seq_len(2), 2);
+        assert_eq!(
+            docs.doc_hit(1, 2).unwrap(),
+            DocHit {
+                doc: 0,
+                pos: 1,
+                strand: ReqStrand::Forward,
+            }
+        );
+        // "GC" at offset 1 of TGCA is "GC" at offset 1 of ACGT on the reverse strand
+        assert_eq!(
+            docs.doc_hit(6, 2).unwrap(),
+            DocHit {
+                doc: 0,
+                pos: 1,
+                strand: ReqStrand::Reverse,
+            }
+        );
+        assert_eq!(
+            docs.doc_hit(13, 2).unwrap(),
+            DocHit {
+                doc: 1,
+                pos: 0,
+                strand: ReqStrand::Reverse,
+            }
+        );
+        // hits touching the separator of either strand
+        assert_eq!(docs.doc_hit(3, 2), None);
+        assert_eq!(docs.doc_hit(4, 1), None);
+        assert_eq!(docs.doc_hit(8, 2), None);
+        assert_eq!(docs.doc_hit(9, 1), None);
+        assert!(docs.doc_hit(8, 1).is_some());
+    }
 
-        assert_eq!(positions, [3, 12, 9]);
+    #[test]
+    fn test_sentinel_pos() {
+        let text = b"ACGTACGTTT$GA$TTAGCA$";
+        let sa = suffix_array(text);
+        let map = sentinel_map(text);
+        let docs = DocArray::new(text);
+        for (row, &seq) in map.iter().enumerate() {
+            assert_eq!(docs.sentinel_pos(row), sa[row]);
+            assert_eq!(
+                docs.seq_of(docs.sentinel_pos(row)),
+                (seq, docs.seq_len(seq))
+            );
+        }
+        assert_eq!(docs.sentinel_pos(1), 13);
+
+        let text = b"AC#G#TTAG#";
+        let sa = suffix_array(text);
+        let docs = DocArray::new(text);
+        assert_eq!(docs.num_seqs(), 3);
+        for (row, &pos) in sa.iter().take(3).enumerate() {
+            assert_eq!(docs.sentinel_pos(row), pos);
+        }
+    }
+
+    #[test]
+    fn test_occ_docs() {
+        let reads = ISSUE39_READS;
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(reads);
+        verify_suffix_array(reads, &sa).unwrap();
+        let bwt = bwt(reads, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fmdindex = FMDIndex::from(FMIndex::new(&bwt, &less, &occ));
+        let docs = DocArray::new(reads);
+        assert_eq!(docs.num_seqs() % 2, 0);
+
+        let search = |pattern: &[u8]| {
+            let mut interval = fmdindex.init_interval();
+            for &a in pattern.iter().rev() {
+                interval = fmdindex.backward_ext(&interval, a);
+            }
+            interval.occ_docs(&sa, &docs)
+        };
+
+        let read = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG";
+        let hit = |doc, pos, strand| vec![DocHit { doc, pos, strand }];
+        assert_eq!(search(read), hit(0, 0, ReqStrand::Forward));
+        assert_eq!(
+            search(&dna::revcomp(&read[..])),
+            hit(0, 0, ReqStrand::Reverse)
+        );
+        assert_eq!(search(&read[10..30]), hit(0, 10, ReqStrand::Forward));
+        assert_eq!(
+            search(&dna::revcomp(&read[10..30])),
+            hit(0, 10, ReqStrand::Reverse)
+        );
+
+        // second document, given by its reverse complement copy
+        let read = b"CGAAGTGGGCGGATCACTTGAGGTCAGGAGTTGGAGACTAGCCTGGCCAACACGATGAAACCCCGTCTCTAATA";
+        assert_eq!(search(&read[5..25]), hit(1, 5, ReqStrand::Forward));
+        assert_eq!(
+            search(&dna::revcomp(&read[5..25])),
+            hit(1, 5, ReqStrand::Reverse)
+        );
+
+        // the match covers the separator after the first read
+        assert!(search(b"GCGG$").is_empty());
+    }
+
+    #[test]
+    fn test_smems_with_scratch() {
+        let reads = ISSUE39_READS;
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(reads);
+        let bwt = bwt(reads, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fmdindex = FMDIndex::from(FMIndex::new(&bwt, &less, &occ));
+
+        let mut scratch = SmemScratch::default();
+        let mut matches = vec![fmdindex.init_interval()];
+        let patterns: [&[u8]; 4] = [
+            b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG",
+            b"AC",
+            b"TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTTGATCA",
+            b"CCCAGCACTTTGGG",
+        ];
+        for _ in 0..2 {
+            for pattern in patterns.iter() {
+                for i in 0..pattern.len() {
+                    fmdindex.smems_with(pattern, i, &mut scratch, &mut matches);
+                    assert_eq!(matches, fmdindex.smems(pattern, i));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sampled_multiple_sentinels() {
+        // LF-mapping the sentinel rows naively would attribute positions to the wrong sequence
+        let text = b"TTTTACGATTC$AACGAT$CCTTACG$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        verify_suffix_array(text, &sa).unwrap();
+        for s in 1..8 {
+            let fm = FMIndexBuilder::new()
+                .text(text)
+                .alphabet(&alphabet)
+                .occ_sampling(2)
+                .sa_sampling(s)
+                .build()
+                .unwrap();
+            for (r, &pos) in sa.iter().enumerate() {
+                assert_eq!(fm.sa_pos_to_text_pos(r), pos);
+            }
+            for pattern in [&b"ACG"[..], b"AT", b"TTAC", b"T", b"C$"].iter() {
+                let interval = fm.backward_search(pattern.iter());
+                assert_eq!(fm.positions_from_interval(&interval), interval.occ(&sa));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_suffix_array() {
+        let text = b"TTTTACGATTC$AACGAT$CCTTACG$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let compact = [
+            CompactSuffixArray::new(text),
+            CompactSuffixArray::U64(sa.clone()),
+        ];
+        for csa in compact.iter() {
+            let text_bwt = bwt(text, csa);
+            assert_eq!(text_bwt, bwt(text, &sa));
+            let fm = SampledFMIndex::new(csa, text_bwt, 2, 3, &alphabet);
+            for pattern in [&b"ACG"[..], b"AT", b"TTAC", b"C$"].iter() {
+                let interval = fm.backward_search(pattern.iter());
+                assert_eq!(interval.occ(csa), interval.occ(&sa));
+                assert_eq!(fm.positions_from_interval(&interval), interval.occ(&sa));
+            }
+        }
     }
 
     #[test]
@@ -441,6 +2546,7 @@ mod tests {
 
         let alphabet = dna::n_alphabet();
         let sa = suffix_array(&text);
+        verify_suffix_array(&text, &sa).unwrap();
         let bwt = bwt(&text, &sa);
         let less = less(&bwt, &alphabet);
         let occ = Occ::new(&bwt, 3, &alphabet);
@@ -491,58 +2597,11 @@ mod tests {
 
     #[test]
     fn test_issue39() {
-        let reads = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG$CCGC\
-                       CCACTTCGACCTCCCAAAGTGCTGGGATTACAGGCATAAGCCACCACGCC$CGAAGTGG\
-                       GCGGATCACTTGAGGTCAGGAGTTGGAGACTAGCCTGGCCAACACGATGAAACCCCGTC\
-                       TCTAATA$TATTAGAGACGGGGTTTCATCGTGTTGGCCAGGCTAGTCTCCAACTCCTGA\
-                       CCTCAAGTGATCCGCCCACTTCG$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTA\
-                       TTCATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTG$CAAAAAA\
-                       TGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAATGAATAATTTTACCAAAAT\
-                       AAGCAAACATTTTTCGAGCT$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTATTC\
-                       ATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTGAAATTACATAT\
-                       $ATATGTAATTTCAAAAAATGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAA\
-                       TGAATAATTTTACCAAAATAAGCAAACATTTTTCGAGCT$TAAAATTTCCTCTGACAGT\
-                       GTAAAAGAGATCTTCATACAAAAATCAGAATTTATATAGTCTCTTTCCAAAAGACCATA\
-                       AAACCAATCAGTTAATAGTTGAT$ATCAACTATTAACTGATTGGTTTTATGGTCTTTTG\
-                       GAAAGAGACTATATAAATTCTGATTTTTGTATGAAGATCTCTTTTACACTGTCAGAGGA\
-                       AATTTTA$CACCTATCTACCCTGAATCTAAGTGCTAACAGGAAAGGATGCCAGATTGCA\
-                       TGCCTGCTGATAAAGCCACAGTTTGGACTGTCACTCAATCACCATCGTTC$GAACGATG\
-                       GTGATTGAGTGACAGTCCAAACTGTGGCTTTATCAGCAGGCATGCAATCTGGCATCCTT\
-                       TCCTGTTAGCACTTAGATTCAGGGTAGATAGGTG$CATCGTTCCTCCTGTGACTCAGTA\
-                       TAACAAGATTGGGAGAATACTCTACAGTTCCTGATTCCCCCACAG$CTGTGGGGGAATC\
-                       AGGAACTGTAGAGTATTCTCCCAATCTTGTTATACTGAGTCACAGGAGGAACGATG$TG\
-                       TAAATTCTGAGAAAAATTTGCAGGTCTTTCTTCAGGAGCATGTAATCTCTTGCTCTCTT\
-                       TGTTATCTATCTATAGTACTGTAGGTTATCTGGAGTTGCT$AGCAACTCCAGATAACCT\
-                       ACAGTACTATAGATAGATAACAAAGAGAGCAAGAGATTACATGCTCCTGAAGAAAGACC\
-                       TGCAAATTTTTCTCAGAATTTACA$CACTTCTCCTTGTCTTTACAGACTGGTTTTGCAC\
-                       TGGGAAATCCTTTCACCAGTCAGCCCAGTTAGAGATTCTG$CAGAATCTCTAACTGGGC\
-                       TGACTGGTGAAAGGATTTCCCAGTGCAAAACCAGTCTGTAAAGACAAGGAGAAGTG$AA\
-                       TGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGATAGAT\
-                       CAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATT$AATTTTGTTAAGACTCTCTTTG\
-                       TGACCTAACATTTGATCTATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAA\
-                       TTTATATACCTCCATT$TTAATTTTGTTAAGACTCTCTTTGTGACCTAACATTTGATCT\
-                       ATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAATTTATATACCTCCATTTT\
-                       $AAAATGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGA\
-                       TAGATCAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATTAA$TTCTTCTTTGACTCA\
-                       TTGGTTGTTCAATAGTATGTTGTTTAATTTCCATATATTTGTAAATGTTTCCGTTTTCC\
-                       TTCTACTATTGAATTTTTGCTTCATC$GATGAAGCAAAAATTCAATAGTAGAAGGAAAA\
-                       CGGAAACATTTACAAATATATGGAAATTAAACAACATACTATTGAACAACCAATGAGTC\
-                       AAAGAAGAA$AGGAAAACGGAAACATTTACAAATATATGGAAATTAAACAACATACTAT\
-                       TGAACAACCAATGAGTCAAAGAAGAAATCAAAAAGAATATTAGAAAAC$GTTTTCTAAT\
-                       ATTCTTTTTGATTTCTTCTTTGACTCATTGGTTGTTCAATAGTATGTTGTTTAATTTCC\
-                       ATATATTTGTAAATGTTTCCGTTTTCCT$TTAGAAAACAAGCTGACAAAAAAATAAAAA\
-                       AACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAAATTTAT\
-                       AGCAATAAATGC$GCATTTATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCA\
-                       TTTCTAAGTTTTGCTATGTTGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTAA$TTT\
-                       ATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCATTTCTAAGTTTTGCTATGT\
-                       TGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTA$TAGAAAACAAGCTGACAAAAAAA\
-                       TAAAAAAACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAA\
-                       ATTTATAGCAATAAA$TCTTTCTTCTTTTTTAAGGTAGGCATTTATTGCTATAAATTTC\
-                       CCTCTTTAGTACTGCCTTTG$CAAAGGCAGTACTAAAGAGGGAAATTTATAGCAATAAA\
-                       TGCCTACCTTAAAAAAGAAGAAAGA$";
+        let reads = ISSUE39_READS;
 
         let alphabet = dna::n_alphabet();
         let sa = suffix_array(reads);
+        verify_suffix_array(reads, &sa).unwrap();
         let bwt = bwt(reads, &sa);
         let less = less(&bwt, &alphabet);
         let occ = Occ::new(&bwt, 3, &alphabet);
@@ -564,4 +2623,598 @@ mod tests {
             assert_eq!(matches, vec![read_pos]);
         }
     }
+
+    #[test]
+    fn test_sampled_from_bwt() {
+        let texts: [&[u8]; 4] = [
+            b"GCCTTAACATTATTACGCCTA$",
+            b"ACGT$A$$TTGCA$",
+            b"$",
+            ISSUE39_READS,
+        ];
+        let alphabet = dna::n_alphabet();
+        for text in texts.iter() {
+            let sa = suffix_array(text);
+            let bwt = bwt(text, &sa);
+            for &s in [1, 2, 3, 7, 32].iter() {
+                let from_sa = SampledFMIndex::new(&sa, bwt.clone(), 3, s, &alphabet);
+                let from_bwt = SampledFMIndex::from_bwt(bwt.clone(), 3, s, &alphabet);
+                let all = Interval {
+                    lower: 0,
+                    upper: text.len(),
+                };
+                assert_eq!(
+                    from_bwt.positions_from_interval(&all),
+                    from_sa.positions_from_interval(&all)
+                );
+                assert_eq!(from_bwt.positions_from_interval(&all), sa);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sampled_from_sparse_suffix_array() {
+        let texts: [&[u8]; 4] = [
+            b"GCCTTAACATTATTACGCCTA$",
+            b"ACGT$A$$TTGCA$",
+            b"$",
+            ISSUE39_READS,
+        ];
+        let alphabet = dna::n_alphabet();
+        for text in texts.iter() {
+            let sa = suffix_array(text);
+            let bwt = bwt(text, &sa);
+            for &s in [1, 2, 3, 7, 32].iter() {
+                let from_sa = SampledFMIndex::new(&sa, bwt.clone(), 3, s, &alphabet);
+                let sparse = sparse_suffix_array(text, s);
+                let from_sparse = SampledFMIndex::from_sparse_suffix_array(
+                    text,
+                    &sparse,
+                    bwt.clone(),
+                    3,
+                    s,
+                    &alphabet,
+                );
+                let all = Interval {
+                    lower: 0,
+                    upper: text.len(),
+                };
+                assert_eq!(
+                    from_sparse.positions_from_interval(&all),
+                    from_sa.positions_from_interval(&all)
+                );
+                for pattern in [&b"TTA"[..], b"A", b"GC$", b"AAAT"].iter() {
+                    let interval = from_sparse.backward_search(pattern.iter());
+                    assert_eq!(
+                        from_sparse.positions_from_interval(&interval),
+                        from_sa.positions_from_interval(&interval)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_sentinel() {
+        let mut rng = StdRng::from_seed(&[61]);
+        for &sentinel in [0u8, 0xff].iter() {
+            let mut text = Vec::new();
+            for _ in 0..5 {
+                for _ in 0..rng.gen_range(1, 200) {
+                    let a = loop {
+                        let a = *rng.choose(&[0, 1, 2, b'$', 0xfe, 0xff]).unwrap();
+                        if a != sentinel {
+                            break a;
+                        }
+                    };
+                    text.push(a);
+                }
+                text.push(sentinel);
+            }
+            let fm = FMIndexBuilder::new()
+                .text(&text)
+                .sa_sampling(3)
+                .sentinel(sentinel)
+                .build()
+                .unwrap();
+            for _ in 0..50 {
+                let len = rng.gen_range(1, 5);
+                let start = rng.gen_range(0, text.len() - len);
+                let pattern = &text[start..start + len];
+                if pattern.contains(&sentinel) {
+                    continue;
+                }
+                let interval = fm.backward_search(pattern.iter());
+                let mut positions = fm.positions_from_interval(&interval);
+                positions.sort_unstable();
+                let expected: Vec<usize> = (0..=text.len() - len)
+                    .filter(|&p| &text[p..p + len] == pattern)
+                    .collect();
+                assert_eq!(positions, expected);
+            }
+        }
+        assert_eq!(
+            FMIndexBuilder::new()
+                .text(b"ACGT$")
+                .sentinel(0)
+                .build()
+                .err(),
+            Some(IndexBuildError::MissingSentinel)
+        );
+        assert_eq!(
+            FMIndexBuilder::new()
+                .text(b"ACGTX")
+                .sentinel(b'X')
+                .case_insensitive(true)
+                .build()
+                .err(),
+            Some(IndexBuildError::InvalidSentinel(b'X'))
+        );
+    }
+
+    #[test]
+    fn test_sa_sample_write_read() {
+        let text = ISSUE39_READS;
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let alphabet = dna::n_alphabet();
+        let fm = SampledFMIndex::new(&sa, bwt.clone(), 3, 5, &alphabet);
+        let sparse = sparse_suffix_array(text, 5);
+        for sample in [
+            SASample::new(&sa, &bwt, 5),
+            SASample::from_sparse_suffix_array(text, &sparse, 5),
+        ]
+        .iter()
+        {
+            let mut buffer = Vec::new();
+            sample.write_to(&mut buffer).unwrap();
+            let read = SASample::read_from(&buffer[..]).unwrap();
+            assert_eq!(read.sampling_rate(), 5);
+            for (r, &pos) in sa.iter().enumerate() {
+                assert_eq!(read.get(&fm, r), pos);
+            }
+
+            assert_eq!(
+                SASample::read_from(&buffer[..buffer.len() - 3])
+                    .err()
+                    .map(|err| err.kind()),
+                Some(io::ErrorKind::UnexpectedEof)
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_with() {
+        let alphabet = dna::n_alphabet();
+        let batches: [&[u8]; 3] = [b"GCCTTAACATTATTACGCCTA$", b"ACGT$A$$TTGCA$", ISSUE39_READS];
+        for &case_insensitive in &[false, true] {
+            let build = |text: &[u8]| {
+                FMIndexBuilder::new()
+                    .text(text)
+                    .alphabet(&alphabet)
+                    .case_insensitive(case_insensitive)
+                    .build()
+                    .unwrap()
+            };
+            let mut merged = build(batches[0]);
+            let mut text = batches[0].to_vec();
+            for batch in &batches[1..] {
+                merged = merged.merge_with(&build(batch), 3, 5, &alphabet);
+                text.extend_from_slice(batch);
+            }
+            let expected = build(&text);
+            assert_eq!(merged.bwt(), expected.bwt());
+            let all = Interval {
+                lower: 0,
+                upper: text.len(),
+            };
+            assert_eq!(
+                merged.positions_from_interval(&all),
+                expected.positions_from_interval(&all)
+            );
+        }
+    }
+
+    #[test]
+    fn test_strand_counts() {
+        let text = b"GCCTTAACAT$ATGTTAAGGC$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fmdindex = FMDIndex::from(FMIndex::new(&bwt, &less, &occ));
+
+        // AA occurs once in the text, its reverse complement TT once as well
+        // CTTAA occurs once in the text, its reverse complement TTAAG never
+        for &(pattern, counts) in [(&b"AA"[..], (1, 1)), (b"CTTAA", (1, 0))].iter() {
+            let interval = pattern
+                .iter()
+                .fold(fmdindex.init_interval(), |interval, &a| {
+                    fmdindex.forward_ext(&interval, a)
+                });
+            assert_eq!(interval.strand_counts(&sa, 10), counts);
+        }
+    }
+
+    #[test]
+    fn test_lf_psi() {
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        // walk backwards from the sentinel row, row 0 being the last suffix
+        let mut reconstructed = vec![b'$'];
+        let mut r = 0;
+        for _ in 1..text.len() {
+            reconstructed.push(bwt[r]);
+            r = fm.lf(r);
+        }
+        reconstructed.reverse();
+        assert_eq!(reconstructed, &text[..]);
+
+        for r in 0..text.len() {
+            assert_eq!(sa[fm.lf(r)], (sa[r] + text.len() - 1) % text.len());
+            assert_eq!(sa[fm.psi(r)], (sa[r] + 1) % text.len());
+        }
+
+        // rows starting with the largest possible symbol
+        let text = [3, 255, 1, 255, 255, 2, 0];
+        let alphabet = Alphabet::new(&text[..6]);
+        let sa = suffix_array(&text);
+        let text_bwt = crate::data_structures::bwt::bwt(&text, &sa);
+        let text_less = crate::data_structures::bwt::less(&text_bwt, &alphabet);
+        let text_occ = Occ::new(&text_bwt, 3, &alphabet);
+        let fm = FMIndex::new(&text_bwt, &text_less, &text_occ);
+        for r in 0..text.len() {
+            assert_eq!(sa[fm.psi(r)], (sa[r] + 1) % text.len());
+        }
+    }
+
+    #[test]
+    fn test_psi_case_insensitive_and_sentinel() {
+        let soft_masked = b"GCCttaACATTATtacgCCTA$acgtNNac$";
+        let bytes = [3, 0, 1, 0, 7, 2, 0, 1, 0, 9, 7, 2, 1, 9];
+        let dna = dna::n_alphabet();
+        let byte_alphabet = Alphabet::new([0, 1, 2, 3, 7]);
+        let no_zero = Alphabet::new([1, 2, 3, 7]);
+        // '!' is smaller than the sentinel '#'
+        let below = Alphabet::new(b"!ACGT");
+        let cases: [(&[u8], &Alphabet, bool, u8); 5] = [
+            (soft_masked, &dna, true, b'$'),
+            (soft_masked, &dna, false, b'$'),
+            (&bytes, &byte_alphabet, false, 9),
+            (&[3, 1, 7, 0, 1, 2, 1, 0], &no_zero, false, 0),
+            (b"AC!GT#A!!CG#", &below, false, b'#'),
+        ];
+        for &(text, alphabet, case_insensitive, sentinel) in cases.iter() {
+            let fm = FMIndexBuilder::new()
+                .text(text)
+                .alphabet(alphabet)
+                .case_insensitive(case_insensitive)
+                .sentinel(sentinel)
+                .occ_sampling(3)
+                .build()
+                .unwrap();
+            let n = text.len();
+            for r in 0..n {
+                assert_eq!(fm.psi(fm.lf(r)), r);
+                assert_eq!(fm.lf(fm.psi(r)), r);
+                // rows of sentinels are continued by the suffix of another sequence
+                let pos = fm.sa_pos_to_text_pos(r);
+                if text[pos] != sentinel {
+                    assert_eq!(fm.sa_pos_to_text_pos(fm.psi(r)), pos + 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_symbol_bound() {
+        // rank transformed text
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let ranks = RankTransform::new(&Alphabet::new(b"$ACGT"));
+        let text = ranks.transform(text);
+        let alphabet = Alphabet::new([0, 1, 2, 3, 4]);
+        let sa = suffix_array(&text);
+        let text_bwt = bwt(&text, &sa);
+        let less = less(&text_bwt, &alphabet);
+        let occ = Occ::new(&text_bwt, 3, &alphabet);
+        let fm = FMIndex::new(&text_bwt, &less, &occ);
+        for a in 0..5 {
+            let count = text.iter().filter(|&&c| c == a).count();
+            assert_eq!(fm.next_symbol_bound(a) - fm.less(a), count);
+        }
+        assert_eq!(fm.next_symbol_bound(4), text.len());
+
+        // case-insensitive text with a symbol between the upper and lowercase letters, where
+        // less(b'z' + 1) is not the bound of b'z'
+        let text = b"ZzA_zCZ_Gz$";
+        let upper = text.to_ascii_uppercase();
+        let alphabet = Alphabet::new(b"$ACGZ_acgz");
+        let text_bwt = bwt(text, &suffix_array(&upper));
+        let less = less_case_insensitive(&text_bwt, &alphabet);
+        let occ = Occ::new_case_insensitive(&text_bwt, 3, &alphabet);
+        let fm = FMIndex::new(&text_bwt, &less, &occ);
+        for &a in b"$ACGZ_acgz" {
+            let count = upper
+                .iter()
+                .filter(|&&c| c == a.to_ascii_uppercase())
+                .count();
+            assert_eq!(fm.next_symbol_bound(a) - fm.less(a), count);
+        }
+        assert_ne!(fm.less(b'z' + 1), fm.next_symbol_bound(b'z'));
+        assert_eq!(fm.count_occurrences(b"Z"), 5);
+
+        // case-insensitive FMD-index
+        let text = b"ACGgtTAcgt$ACGTaCcgT$";
+        let upper = text.to_ascii_uppercase();
+        let text_bwt = bwt(text, &suffix_array(&upper));
+        let alphabet = Alphabet::new(b"$ACGTNacgtn");
+        let less = less_case_insensitive(&text_bwt, &alphabet);
+        let occ = Occ::new_case_insensitive(&text_bwt, 3, &alphabet);
+        let fmdindex = FMDIndex::from(FMIndex::new(&text_bwt, &less, &occ));
+        for &a in b"ACGTacgt" {
+            let count = upper
+                .iter()
+                .filter(|&&c| c == a.to_ascii_uppercase())
+                .count();
+            assert_eq!(fmdindex.init_interval_with(a).size, count);
+        }
+    }
+
+    #[test]
+    fn test_wavelet_tree_backing() {
+        let protein = crate::alphabets::protein::alphabet();
+        let dna = dna::n_alphabet();
+        type Case<'a> = (&'a [u8], &'a Alphabet, &'a [&'a [u8]]);
+        let cases: [Case<'_>; 2] = [
+            (
+                ISSUE39_READS,
+                &dna,
+                &[
+                    b"GGCGTGG",
+                    b"AAAT",
+                    b"CCCC",
+                    b"TTTTTTTTTT",
+                    b"A",
+                    b"GCGGATCA",
+                ],
+            ),
+            (
+                b"MKVLAAGIVGLLLAQPAMAHRWCYQEFSTND$MAHRWCYQLLLAQPKV$",
+                &protein,
+                &[b"LLLAQP", b"MAHRWCYQ", b"A", b"WW", b"QEFSTND", b"KV"],
+            ),
+        ];
+        for &(text, alphabet, patterns) in cases.iter() {
+            let builder = FMIndexBuilder::new().text(text).alphabet(alphabet);
+            let sampled = builder.build().unwrap();
+            let wavelet = builder
+                .occ_backend(OccBackend::WaveletTree)
+                .build()
+                .unwrap();
+            for pattern in patterns {
+                let interval = sampled.backward_search(pattern.iter());
+                assert_eq!(wavelet.backward_search(pattern.iter()), interval);
+                assert_eq!(
+                    wavelet.positions_from_interval(&interval),
+                    sampled.positions_from_interval(&interval)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed_dna_backing() {
+        let alphabet = dna::n_alphabet();
+        let soft_masked = ISSUE39_READS.to_ascii_lowercase();
+        for &(text, case_insensitive) in &[(ISSUE39_READS, false), (&soft_masked[..], true)] {
+            let builder = FMIndexBuilder::new()
+                .text(text)
+                .alphabet(&alphabet)
+                .case_insensitive(case_insensitive);
+            let sampled = builder.build().unwrap();
+            let builder = builder.occ_backend(OccBackend::PackedDna);
+            let packed = builder.build().unwrap();
+            for pattern in &[
+                &b"GGCGTGG"[..],
+                b"AAAT",
+                b"CCCC",
+                b"TTTTTTTTTT",
+                b"A",
+                b"ggcgga",
+            ] {
+                let interval = sampled.backward_search(pattern.iter());
+                assert_eq!(packed.backward_search(pattern.iter()), interval);
+                assert_eq!(
+                    packed.positions_from_interval(&interval),
+                    sampled.positions_from_interval(&interval)
+                );
+            }
+            let fmd = builder
+                .occ_backend(OccBackend::Sampled)
+                .build_fmd()
+                .unwrap();
+            assert!(fmd.contains(b"GGCGTGG"));
+        }
+    }
+
+    #[test]
+    fn test_run_length_backing() {
+        let alphabet = dna::n_alphabet();
+        let mut repetitive = b"GATTACAGGCCTTAACATTATTACG".repeat(40);
+        repetitive[333] = b'T';
+        repetitive[777] = b'N';
+        repetitive.push(b'$');
+        let soft_masked = ISSUE39_READS.to_ascii_lowercase();
+        for &(text, case_insensitive) in &[
+            (ISSUE39_READS, false),
+            (&repetitive[..], false),
+            (&soft_masked[..], true),
+        ] {
+            let builder = FMIndexBuilder::new()
+                .text(text)
+                .alphabet(&alphabet)
+                .case_insensitive(case_insensitive)
+                .occ_sampling(4);
+            let sampled = builder.build().unwrap();
+            let run_length = builder.occ_backend(OccBackend::RunLength).build().unwrap();
+            for pattern in &[
+                &b"GGCGTGG"[..],
+                b"GATTACA",
+                b"TACGGATT",
+                b"CCCC",
+                b"A",
+                b"ggcgga",
+                b"ACNGG",
+            ] {
+                let interval = sampled.backward_search(pattern.iter());
+                assert_eq!(run_length.backward_search(pattern.iter()), interval);
+                assert_eq!(
+                    run_length.positions_from_interval(&interval),
+                    sampled.positions_from_interval(&interval)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_backward_ext_backends() {
+        let mut rng = StdRng::from_seed(&[62]);
+        let alphabet = dna::n_alphabet();
+        for &case_insensitive in &[false, true] {
+            let symbols: &[u8] = if case_insensitive {
+                b"ACGTNacgt"
+            } else {
+                b"ACGTN"
+            };
+            let mut text = vec![];
+            for _ in 0..3 {
+                // mostly unambiguous bases, and a few N or soft-masked ones
+                let mut seq: Vec<u8> = (0..rng.gen_range(1, 300))
+                    .map(|_| *rng.choose(&symbols[..4]).unwrap())
+                    .collect();
+                for _ in 0..rng.gen_range(0, 3) {
+                    let i = rng.gen_range(0, seq.len());
+                    seq[i] = *rng.choose(symbols).unwrap();
+                }
+                text.extend_from_slice(&seq);
+                text.push(b'$');
+                text.extend(dna::revcomp(&seq));
+                text.push(b'$');
+            }
+            let builder = || {
+                FMIndexBuilder::new()
+                    .text(&text)
+                    .alphabet(&alphabet)
+                    .case_insensitive(case_insensitive)
+                    .occ_sampling(5)
+            };
+            let sampled = builder().build_fmd().unwrap();
+            let backends: Vec<_> = [
+                OccBackend::WaveletTree,
+                OccBackend::PackedDna,
+                OccBackend::RunLength,
+            ]
+            .iter()
+            .map(|&backend| builder().occ_backend(backend).build_fmd().unwrap())
+            .collect();
+            for _ in 0..200 {
+                let mut interval = sampled.init_interval();
+                let mut intervals = vec![interval; backends.len()];
+                for _ in 0..rng.gen_range(1, 12) {
+                    let a = *rng.choose(symbols).unwrap();
+                    let forward: bool = rng.gen();
+                    let extend = |fmd: &SampledFMDIndex, interval: &BiInterval| {
+                        if forward {
+                            fmd.forward_ext(interval, a)
+                        } else {
+                            fmd.backward_ext(interval, a)
+                        }
+                    };
+                    interval = extend(&sampled, &interval);
+                    for (fmd, other) in backends.iter().zip(intervals.iter_mut()) {
+                        *other = extend(fmd, other);
+                        assert_eq!(*other, interval);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_fasta() {
+        let alphabet = dna::n_alphabet();
+        let fmdindex = FMIndexBuilder::new()
+            .text(ISSUE39_READS)
+            .alphabet(&alphabet)
+            .occ_sampling(3)
+            .build_fmd()
+            .unwrap();
+
+        let fasta = b">first\nGGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG\n\
+                      >invalid\nGGCGTGGXGGCTTATG\n\
+                      >last\nCGAAGTGGGCGGATCACTTGAGGTCAGGAGTTGGAGACTAGCCTGGCCAACACGATGAAACCCCGTCTCTAATA\n";
+        let search = |policy| {
+            search_fasta(&fmdindex, fasta::Reader::new(&fasta[..]), 20, policy).collect::<Vec<_>>()
+        };
+
+        let results = search(InvalidSymbolPolicy::Skip)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        let expected = [("first", 54, 0), ("last", 74, 110)];
+        for ((id, seeds), &(expected_id, len, pos)) in results.iter().zip(expected.iter()) {
+            assert_eq!(id, expected_id);
+            // each read occurs exactly once, so the whole read is its only SMEM
+            assert_eq!(seeds.len(), 1);
+            assert_eq!(seeds[0].match_size(), len);
+            assert_eq!(fmdindex.positions_from_interval(&seeds[0].forward()), [pos]);
+        }
+
+        let results = search(InvalidSymbolPolicy::Error);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        match results[1] {
+            Err(SearchError::InvalidSymbol(ref id, b'X', 7)) => assert_eq!(id, "invalid"),
+            ref e => panic!("unexpected result {:?}", e),
+        }
+        assert!(results[2].is_ok());
+
+        // a chimeric read with an N absent from the index, compared to collecting and
+        // deduplicating the SMEMs overlapping every position
+        let chimera = b"GGCGTGGTGGCTTATGCCTGTNAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGGATCACTTGAGGTCAGGAG";
+        let fasta = [&b">chimera\n"[..], chimera, b"\n"].concat();
+        for &min_len in [0, 1, 10].iter() {
+            let results = search_fasta(
+                &fmdindex,
+                fasta::Reader::new(&fasta[..]),
+                min_len,
+                InvalidSymbolPolicy::Error,
+            )
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+            let seeds = &results[0].1;
+            let mut expected = Vec::new();
+            for i in 0..chimera.len() {
+                for smem in fmdindex.smems(chimera, i) {
+                    if smem.size > 0
+                        && smem.match_size >= min_len.max(1)
+                        && !expected.contains(&smem)
+                    {
+                        expected.push(smem);
+                    }
+                }
+            }
+            assert!(seeds.iter().all(|smem| smem.size > 0));
+            assert_eq!(seeds.len(), expected.len());
+            assert!(expected.iter().all(|smem| seeds.contains(smem)));
+        }
+    }
 }
@@ -8,7 +8,11 @@
 //! supplied.
 //!
 //! Upon inserting an interval may be associated with a data value. The intervals are stored in
-//! an augmented AVL-tree which allows for efficient inserting and querying.
+//! an augmented AVL-tree which allows for efficient inserting and querying. Empty intervals
+//! can not be inserted, since they would never be found.
+//!
+//! Besides overlap queries, the tree can find the intervals containing a position with
+//! `find_point`, and the closest interval not containing a position with `nearest`.
 //!
 //! # Example
 //! ```
@@ -27,10 +31,11 @@
 //! ```
 //!
 
-use crate::utils::Interval;
+use crate::utils::{Interval, IntervalError};
 use std::cmp;
 use std::iter::FromIterator;
 use std::mem;
+use std::ops::{Range, Sub};
 
 /// An interval tree for storing intervals with data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +74,8 @@ impl<'a, N: Ord + Clone + 'a, D: 'a> Entry<'a, N, D> {
 pub struct IntervalTreeIterator<'a, N: Ord + Clone, D> {
     nodes: Vec<&'a Node<N, D>>,
     interval: Interval<N>,
+    /// Whether the query is the position at the start of `interval` instead of the interval.
+    point: bool,
 }
 
 impl<'a, N: Ord + Clone + 'a, D: 'a> Iterator for IntervalTreeIterator<'a, N, D> {
@@ -89,13 +96,23 @@ impl<'a, N: Ord + Clone + 'a, D: 'a> Iterator for IntervalTreeIterator<'a, N, D>
 
                 // don't traverse right if the query interval is completely before the current
                 // interval
-                if self.interval.end > candidate.interval.start {
+                let before = if self.point {
+                    self.interval.start < candidate.interval.start
+                } else {
+                    self.interval.end <= candidate.interval.start
+                };
+                if !before {
                     if let Some(ref right) = candidate.right {
                         self.nodes.push(right);
                     }
 
                     // overlap is only possible if both tests pass
-                    if intersect(&self.interval, &candidate.interval) {
+                    let overlap = if self.point {
+                        self.interval.start < candidate.interval.end
+                    } else {
+                        intersect(&self.interval, &candidate.interval)
+                    };
+                    if overlap {
                         return Some(Entry {
                             data: &candidate.value,
                             interval: &candidate.interval,
@@ -176,7 +193,8 @@ impl<N: Clone + Ord, D> IntervalTree<N, D> {
         Default::default()
     }
 
-    /// Inserts an `Interval` into the tree and associates it with `data`
+    /// Inserts an `Interval` into the tree and associates it with `data`. Empty intervals
+    /// are stored, but never found by `find` or `find_point`, see `try_insert` to reject them.
     pub fn insert<I: Into<Interval<N>>>(&mut self, interval: I, data: D) {
         let interval = interval.into();
         match self.root {
@@ -185,20 +203,103 @@ impl<N: Clone + Ord, D> IntervalTree<N, D> {
         };
     }
 
+    /// Inserts a range into the tree and associates it with `data`. Returns an error if the
+    /// range is empty or has end < start.
+    pub fn try_insert(&mut self, range: Range<N>, data: D) -> Result<(), IntervalError> {
+        let interval = Interval::new(range)?;
+        if interval.start == interval.end {
+            return Err(IntervalError::EmptyRange);
+        }
+        self.insert(interval, data);
+        Ok(())
+    }
+
     /// Uses the provided `Interval` to find overlapping intervals in the tree and returns an
     /// `IntervalTreeIterator`
     pub fn find<I: Into<Interval<N>>>(&self, interval: I) -> IntervalTreeIterator<'_, N, D> {
-        let interval = interval.into();
-        match self.root {
-            Some(ref n) => IntervalTreeIterator {
-                nodes: vec![n],
-                interval,
-            },
-            None => {
-                let nodes = vec![];
-                IntervalTreeIterator { nodes, interval }
+        self.iter(interval.into(), false)
+    }
+
+    /// Find the intervals that contain the given position.
+    pub fn find_point(&self, pos: N) -> IntervalTreeIterator<'_, N, D> {
+        self.iter(Interval::from(pos.clone()..pos), true)
+    }
+
+    fn iter(&self, interval: Interval<N>, point: bool) -> IntervalTreeIterator<'_, N, D> {
+        let nodes = match self.root {
+            Some(ref n) => vec![n],
+            None => vec![],
+        };
+        IntervalTreeIterator {
+            nodes,
+            interval,
+            point,
+        }
+    }
+
+    /// Find the interval closest to the given position among the intervals that do not
+    /// contain it. The distance to an interval before the position is measured from its last
+    /// contained position. If intervals before and after the position are equally close, the
+    /// one before is returned.
+    pub fn nearest(&self, pos: N) -> Option<Entry<'_, N, D>>
+    where
+        N: Sub<Output = N>,
+    {
+        let root = self.root.as_ref()?;
+        // the interval with the largest end not after the position
+        let mut before: Option<&Node<N, D>> = None;
+        let mut nodes = vec![root];
+        while let Some(node) = nodes.pop() {
+            if before.is_some_and(|best| node.max <= best.interval.end) {
+                // no interval in this subtree ends later
+                continue;
+            }
+            if node.interval.end <= pos
+                && before.is_none_or(|best| node.interval.end > best.interval.end)
+            {
+                before = Some(node);
+            }
+            if let Some(ref left) = node.left {
+                nodes.push(left);
+            }
+            // intervals in the right subtree start after this one, so they end after pos if
+            // this one starts at or after it
+            if node.interval.start < pos {
+                if let Some(ref right) = node.right {
+                    nodes.push(right);
+                }
+            }
+        }
+        // the interval with the smallest start after the position
+        let mut after: Option<&Node<N, D>> = None;
+        let mut node = Some(root);
+        while let Some(n) = node {
+            if n.interval.start > pos {
+                if after.is_none_or(|best| n.interval.start < best.interval.start) {
+                    after = Some(n);
+                }
+                node = n.left.as_deref();
+            } else {
+                node = n.right.as_deref();
             }
         }
+
+        let nearest = match (before, after) {
+            (Some(before), Some(after)) => {
+                // pos - (before.end - 1) <= after.start - pos
+                if pos.clone() - before.interval.end.clone() < after.interval.start.clone() - pos {
+                    before
+                } else {
+                    after
+                }
+            }
+            (Some(node), None) | (None, Some(node)) => node,
+            (None, None) => return None,
+        };
+        Some(Entry {
+            data: &nearest.value,
+            interval: &nearest.interval,
+        })
     }
 
     /// Uses the provided `Interval` to find overlapping intervals in the tree and returns an
@@ -379,9 +480,12 @@ fn intersect<N: Ord + Clone>(range_1: &Interval<N>, range_2: &Interval<N>) -> bo
 #[cfg(test)]
 mod tests {
     use super::{Entry, IntervalTree, Node};
-    use crate::utils::Interval;
+    use crate::io::bed;
+    use crate::utils::{Interval, IntervalError};
+    use rand::{Rng, SeedableRng, StdRng};
     use std::cmp;
     use std::cmp::{max, min};
+    use std::collections::HashMap;
     use std::ops::Range;
 
     fn validate(node: &Node<i64, String>) {
@@ -598,6 +702,148 @@ mod tests {
         assert_not_found(&tree, 55..55);
     }
 
+    #[test]
+    fn try_insert() {
+        let mut tree: IntervalTree<i64, String> = IntervalTree::new();
+        assert_eq!(
+            tree.try_insert(10..10, "10:10".to_string()),
+            Err(IntervalError::EmptyRange)
+        );
+        let (start, end) = (10, 5);
+        assert_eq!(
+            tree.try_insert(start..end, "10:5".to_string()),
+            Err(IntervalError::InvalidRange)
+        );
+        assert!(tree.root.is_none());
+
+        assert!(tree.try_insert(10..11, "10:11".to_string()).is_ok());
+        assert!(tree.try_insert(50..60, "50:60".to_string()).is_ok());
+        assert_intersections(&tree, 5..55, vec![10..11, 50..60]);
+    }
+
+    #[test]
+    fn find_point() {
+        let tree: IntervalTree<i64, usize> = vec![(10..20, 0), (15..30, 1), (40..41, 2)]
+            .into_iter()
+            .collect();
+        let points = |pos| -> Vec<usize> {
+            let mut found: Vec<usize> = tree.find_point(pos).map(|e| *e.data()).collect();
+            found.sort();
+            found
+        };
+        assert_eq!(points(9), []);
+        assert_eq!(points(10), [0]);
+        assert_eq!(points(15), [0, 1]);
+        assert_eq!(points(20), [1]);
+        assert_eq!(points(30), []);
+        assert_eq!(points(40), [2]);
+        assert_eq!(points(41), []);
+        assert_eq!(IntervalTree::<i64, ()>::new().find_point(1).count(), 0);
+    }
+
+    #[test]
+    fn nearest() {
+        let tree: IntervalTree<i64, usize> = vec![(10..20, 0), (30..40, 1), (15..25, 2)]
+            .into_iter()
+            .collect();
+        let nearest = |pos| tree.nearest(pos).map(|e| *e.data());
+        assert_eq!(nearest(0), Some(0));
+        // contained in interval 0, interval 2 starts 3 positions after
+        assert_eq!(nearest(12), Some(2));
+        assert_eq!(nearest(22), Some(0));
+        assert_eq!(nearest(26), Some(2));
+        // equally close to 24 (interval 2) and 30 (interval 1)
+        assert_eq!(nearest(27), Some(2));
+        assert_eq!(nearest(28), Some(1));
+        assert_eq!(nearest(100), Some(1));
+        assert_eq!(IntervalTree::<i64, ()>::new().nearest(1), None);
+        let tree: IntervalTree<i64, ()> = vec![(0..10, ())].into_iter().collect();
+        assert!(tree.nearest(5).is_none());
+    }
+
+    #[test]
+    fn random_intervals() {
+        let mut rng = StdRng::from_seed(&[42]);
+        let intervals: Vec<Range<i64>> = (0..3000)
+            .map(|_| {
+                let start = rng.gen_range(0, 10000);
+                start..start + rng.gen_range(1, 200)
+            })
+            .collect();
+        let mut tree = IntervalTree::new();
+        for (i, interval) in intervals.iter().enumerate() {
+            tree.insert(interval, i);
+        }
+        let sorted = |entries: Vec<Entry<'_, i64, usize>>| -> Vec<usize> {
+            let mut found: Vec<usize> = entries.iter().map(|e| *e.data()).collect();
+            found.sort();
+            found
+        };
+        for _ in 0..500 {
+            let start = rng.gen_range(-100, 10300);
+            let query = start..start + rng.gen_range(1, 300);
+            let expected: Vec<usize> = (0..intervals.len())
+                .filter(|&i| intervals[i].start < query.end && query.start < intervals[i].end)
+                .collect();
+            assert_eq!(sorted(tree.find(&query).collect()), expected);
+
+            let pos = query.start;
+            let expected: Vec<usize> = (0..intervals.len())
+                .filter(|&i| intervals[i].start <= pos && pos < intervals[i].end)
+                .collect();
+            assert_eq!(sorted(tree.find_point(pos).collect()), expected);
+
+            let distance = |r: &Range<i64>| {
+                if r.end <= pos {
+                    pos - r.end + 1
+                } else {
+                    r.start - pos
+                }
+            };
+            let expected = intervals
+                .iter()
+                .filter(|r| r.end <= pos || r.start > pos)
+                .map(distance)
+                .min();
+            let nearest = tree.nearest(pos).map(|e| distance(e.interval()));
+            assert_eq!(nearest, expected);
+        }
+    }
+
+    #[test]
+    fn bed_per_contig() {
+        let bed = b"chr1\t100\t200\tgeneA
+chr2\t100\t200\tgeneB
+chr1\t150\t400\tgeneC
+chr1\t1000\t2000\tgeneD
+";
+        let mut trees: HashMap<String, IntervalTree<u64, String>> = HashMap::new();
+        for record in bed::Reader::new(&bed[..]).records() {
+            let record = record.unwrap();
+            trees
+                .entry(record.chrom().to_owned())
+                .or_default()
+                .insert(
+                    record.start()..record.end(),
+                    record.name().unwrap().to_owned(),
+                );
+        }
+        let names = |chrom: &str, range: Range<u64>| -> Vec<String> {
+            let mut names: Vec<String> = trees
+                .get(chrom)
+                .map(|tree| tree.find(range).map(|e| e.data().clone()).collect())
+                .unwrap_or_default();
+            names.sort();
+            names
+        };
+        assert_eq!(names("chr1", 160..190), ["geneA", "geneC"]);
+        assert_eq!(names("chr1", 200..300), ["geneC"]);
+        assert_eq!(names("chr2", 0..1000), ["geneB"]);
+        assert!(names("chr3", 0..1000).is_empty());
+        let nearest = trees["chr1"].nearest(600).unwrap();
+        assert_eq!(nearest.data(), "geneC");
+    }
+
     #[test]
     fn from_iterator() {
         let tree: IntervalTree<i64, ()> = vec![(10..100, ()), (10..20, ()), (1..8, ())]
@@ -14,5 +14,7 @@ pub mod interpolation_table;
 pub mod interval_tree;
 pub mod qgram_index;
 pub mod rank_select;
+pub mod repeats;
 pub mod smallints;
 pub mod suffix_array;
+pub mod wavelet_tree;
@@ -31,7 +31,7 @@ use bv::BitVec;
 use bv::Bits;
 
 /// A rank/select data structure.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RankSelect {
     n: usize,
     bits: BitVec<u8>,
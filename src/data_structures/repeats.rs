@@ -0,0 +1,255 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Repeat analysis based on the suffix and lcp array of a text
+//! (Abouelhoda, Kurtz and Ohlebusch (2002). "The Enhanced Suffix Array and Its Applications
+//! to Genome Analysis"). Repeats never span a sentinel, since the lcp array stops at them.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::repeats::{longest_repeated_substring, supermaximal_repeats, Repeat};
+//! use bio::data_structures::suffix_array::{lcp, suffix_array};
+//!
+//! let text = b"GCTGCTACTA$";
+//! let sa = suffix_array(text);
+//! let lcp = lcp(text, &sa);
+//!
+//! assert_eq!(longest_repeated_substring(text, &sa, &lcp), (7, 3));
+//! assert_eq!(
+//!     supermaximal_repeats(text, &sa, &lcp, 2),
+//!     [
+//!         Repeat { len: 3, positions: vec![0, 3] },
+//!         Repeat { len: 3, positions: vec![4, 7] },
+//!     ]
+//! );
+//! ```
+
+use crate::data_structures::suffix_array::{LcpArray, SuffixArray};
+
+/// A repeated substring of a text, given by its length and the start positions of all of
+/// its (possibly overlapping) occurrences in increasing order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repeat {
+    pub len: usize,
+    pub positions: Vec<usize>,
+}
+
+/// Find a longest substring occurring at least twice in the text. Complexity: O(n)
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `pos` - the suffix array of the text
+/// * `lcp` - the lcp array of the text
+///
+/// # Returns
+///
+/// The start and length of the repeat, choosing the occurrence whose suffix is the smallest
+/// if there are several. If no symbol occurs twice, the length is 0.
+pub fn longest_repeated_substring<SA: SuffixArray>(
+    text: &[u8],
+    pos: &SA,
+    lcp: &LcpArray,
+) -> (usize, usize) {
+    assert_eq!(text.len(), pos.len());
+    let mut longest = (0, 0);
+    for r in 1..text.len() {
+        let l = lcp.get(r).unwrap().max(0) as usize;
+        if l > longest.1 {
+            longest = (pos.get(r - 1).unwrap(), l);
+        }
+    }
+
+    longest
+}
+
+/// Find all supermaximal repeats of at least the given length, i.e. repeats that are not a
+/// substring of any other repeat. They are the lcp-intervals without child intervals whose
+/// suffixes are preceded by pairwise distinct symbols, which are found by a bottom-up scan
+/// over the lcp-intervals with a stack. The text start and sentinels count as distinct
+/// preceding symbols. Complexity: O(n)
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `pos` - the suffix array of the text
+/// * `lcp` - the lcp array of the text
+/// * `min_len` - the minimum length of the reported repeats
+///
+/// # Returns
+///
+/// The repeats ordered by the position of their first occurrence.
+pub fn supermaximal_repeats<SA: SuffixArray>(
+    text: &[u8],
+    pos: &SA,
+    lcp: &LcpArray,
+    min_len: usize,
+) -> Vec<Repeat> {
+    assert_eq!(text.len(), pos.len());
+    let n = text.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let sentinel = text[n - 1];
+    let min_len = min_len.max(1);
+
+    let mut repeats = Vec::new();
+    let mut report = |lb: usize, rb: usize, len: usize| {
+        let mut seen = [false; 256];
+        let mut positions = Vec::with_capacity(rb - lb + 1);
+        for r in lb..=rb {
+            let p = pos.get(r).unwrap();
+            if p > 0 && text[p - 1] != sentinel {
+                let a = text[p - 1] as usize;
+                if seen[a] {
+                    return;
+                }
+                seen[a] = true;
+            }
+            positions.push(p);
+        }
+        positions.sort_unstable();
+        repeats.push(Repeat { len, positions });
+    };
+
+    // open lcp-intervals as (lcp value, left bound, whether it has a child interval)
+    let mut stack = vec![(0, 0, false)];
+    for r in 1..=n {
+        let l = lcp.get(r).unwrap().max(0) as usize;
+        let mut lb = r - 1;
+        let mut has_child = false;
+        while l < stack.last().unwrap().0 {
+            let (len, left, child) = stack.pop().unwrap();
+            if !child && len >= min_len {
+                report(left, r - 1, len);
+            }
+            lb = left;
+            // the closed interval is a child of the enclosing one, which is either the
+            // next interval on the stack or the one starting at lb that is opened below
+            match stack.last_mut() {
+                Some(top) if l <= top.0 => top.2 = true,
+                _ => has_child = true,
+            }
+        }
+        if l > stack.last().unwrap().0 {
+            stack.push((l, lb, has_child));
+        }
+    }
+
+    repeats.sort_unstable_by(|a, b| a.positions.cmp(&b.positions));
+    repeats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::suffix_array::{lcp, suffix_array};
+    use rand::{Rng, SeedableRng, StdRng};
+
+    fn occurrences(text: &[u8], w: &[u8]) -> Vec<usize> {
+        (0..=text.len() - w.len())
+            .filter(|&p| &text[p..p + w.len()] == w)
+            .collect()
+    }
+
+    /// Check all substrings without the sentinel for being a repeat that cannot be
+    /// extended by a symbol to either side while staying a repeat.
+    fn naive_supermaximal_repeats(text: &[u8], min_len: usize) -> Vec<Repeat> {
+        let sentinel = text[text.len() - 1];
+        let is_repeat =
+            |w: &[u8]| w.iter().all(|&a| a != sentinel) && occurrences(text, w).len() > 1;
+        let mut repeats = Vec::new();
+        for p in 0..text.len() {
+            for q in p + min_len.max(1)..text.len() {
+                let w = &text[p..q];
+                if !is_repeat(w) || occurrences(text, w)[0] != p {
+                    continue;
+                }
+                let extensible = b"ACGT"
+                    .iter()
+                    .any(|&a| is_repeat(&[&[a], w].concat()) || is_repeat(&[w, &[a]].concat()));
+                if !extensible {
+                    repeats.push(Repeat {
+                        len: w.len(),
+                        positions: occurrences(text, w),
+                    });
+                }
+            }
+        }
+        repeats.sort_unstable_by(|a, b| a.positions.cmp(&b.positions));
+        repeats
+    }
+
+    fn random_text(rng: &mut StdRng) -> Vec<u8> {
+        let mut text = Vec::new();
+        for _ in 0..rng.gen_range(1, 3) {
+            for _ in 0..rng.gen_range(0, 30) {
+                text.push(*rng.choose(b"ACGT").unwrap());
+            }
+            text.push(b'$');
+        }
+        text
+    }
+
+    #[test]
+    fn test_longest_repeated_substring() {
+        let mut rng = StdRng::from_seed(&[43]);
+        for _ in 0..200 {
+            let text = random_text(&mut rng);
+            let sa = suffix_array(&text);
+            let lcp_array = lcp(&text, &sa);
+            let (start, len) = longest_repeated_substring(&text, &sa, &lcp_array);
+
+            let longest = naive_supermaximal_repeats(&text, 1)
+                .iter()
+                .map(|repeat| repeat.len)
+                .max()
+                .unwrap_or(0);
+            assert_eq!(len, longest);
+            if len > 0 {
+                assert!(occurrences(&text, &text[start..start + len]).len() > 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_supermaximal_repeats() {
+        let mut rng = StdRng::from_seed(&[47]);
+        for _ in 0..200 {
+            let text = random_text(&mut rng);
+            let sa = suffix_array(&text);
+            let lcp_array = lcp(&text, &sa);
+            for min_len in 0..4 {
+                assert_eq!(
+                    supermaximal_repeats(&text, &sa, &lcp_array, min_len),
+                    naive_supermaximal_repeats(&text, min_len)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_supermaximal_repeats_edge_cases() {
+        let text = b"ACGT$";
+        let sa = suffix_array(text);
+        let lcp_array = lcp(text, &sa);
+        assert_eq!(longest_repeated_substring(text, &sa, &lcp_array), (0, 0));
+        assert!(supermaximal_repeats(text, &sa, &lcp_array, 1).is_empty());
+
+        // overlapping occurrences
+        let text = b"AAAAA$";
+        let sa = suffix_array(text);
+        let lcp_array = lcp(text, &sa);
+        assert_eq!(
+            supermaximal_repeats(text, &sa, &lcp_array, 1),
+            [Repeat {
+                len: 4,
+                positions: vec![0, 1]
+            }]
+        );
+        assert!(supermaximal_repeats(text, &sa, &lcp_array, 5).is_empty());
+    }
+}
@@ -8,10 +8,14 @@
 //! "Algorithmen auf Sequenzen", Kopczynski, Marschall, Martin and Rahmann, 2008 - 2015.
 
 use std;
-use std::cmp;
+use std::cmp::{self, Ordering};
 use std::fmt::Debug;
-use std::iter;
-use std::ops::Deref;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(feature = "parallel")]
+use std::mem;
+use std::ops::ControlFlow;
+#[cfg(feature = "parallel")]
+use std::thread;
 
 use num_integer::Integer;
 use num_traits::{cast, NumCast, Unsigned};
@@ -20,11 +24,20 @@ use bv::{BitVec, Bits, BitsMut};
 use vec_map::VecMap;
 
 use crate::alphabets::{Alphabet, RankTransform};
-use crate::data_structures::smallints::SmallInts;
+use crate::data_structures::fmindex::DocArray;
+use crate::data_structures::smallints::{self, SmallInts};
 
-pub type LCPArray = SmallInts<i8, isize>;
+/// Former name of `LcpArray`.
+pub type LCPArray = LcpArray;
 pub type RawSuffixArray = Vec<usize>;
 
+/// Minimal length of a text to construct its suffix array in parallel.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_TEXT_LEN: usize = 1 << 20;
+
+/// Number of positions processed by `suffix_array_with_progress` between two progress reports.
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
 /// A trait exposing general functionality of suffix arrays.
 pub trait SuffixArray {
     fn get(&self, index: usize) -> Option<usize>;
@@ -89,6 +102,20 @@ pub trait SuffixArray {
 //     s: usize, // Rate of sampling
 // }
 
+impl<SA: SuffixArray> SuffixArray for &SA {
+    fn get(&self, index: usize) -> Option<usize> {
+        (**self).get(index)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+
 impl SuffixArray for RawSuffixArray {
     fn get(&self, index: usize) -> Option<usize> {
         // Explicitly written out because Vec::get(index) generates a recursion warning
@@ -124,6 +151,245 @@ impl SuffixArray for RawSuffixArray {
     // }
 }
 
+/// A suffix array storing positions with 32 bit if the text is not longer than `u32::MAX`,
+/// and with 64 bit otherwise. This halves the memory of the suffix array for most genomes.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array, CompactSuffixArray, SuffixArray};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let sa = CompactSuffixArray::new(text);
+/// assert!(sa.is_u32());
+/// assert_eq!(sa.get(2), Some(5));
+/// assert_eq!(sa.to_vec(), suffix_array(text));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompactSuffixArray {
+    U32(Vec<u32>),
+    U64(Vec<usize>),
+}
+
+impl CompactSuffixArray {
+    /// Construct the suffix array of the given text (see `suffix_array`), choosing the
+    /// width of the positions by the text length.
+    pub fn new(text: &[u8]) -> Self {
+        match Self::new_u32(text) {
+            Ok(sa) => sa,
+            Err(_) => CompactSuffixArray::U64(construct_suffix_array(text)),
+        }
+    }
+
+    /// Construct the suffix array of the given text with 32 bit positions, returning an error
+    /// if the text is longer than `u32::MAX`.
+    pub fn new_u32(text: &[u8]) -> Result<Self, SuffixArrayError> {
+        check_u32_len(text.len())?;
+        Ok(CompactSuffixArray::U32(construct_suffix_array(text)))
+    }
+
+    /// Whether the positions are stored with 32 bit.
+    pub fn is_u32(&self) -> bool {
+        match *self {
+            CompactSuffixArray::U32(_) => true,
+            CompactSuffixArray::U64(_) => false,
+        }
+    }
+
+    /// Calculate the inverse suffix array (see `inverse_suffix_array`), storing the ranks with
+    /// the same width as the positions.
+    pub fn inverse(&self) -> Self {
+        match *self {
+            CompactSuffixArray::U32(ref sa) => CompactSuffixArray::U32(invert(sa)),
+            CompactSuffixArray::U64(ref sa) => CompactSuffixArray::U64(invert(sa)),
+        }
+    }
+
+    /// Return all positions.
+    pub fn to_vec(&self) -> RawSuffixArray {
+        match *self {
+            CompactSuffixArray::U32(ref sa) => sa.iter().map(|&p| p as usize).collect(),
+            CompactSuffixArray::U64(ref sa) => sa.clone(),
+        }
+    }
+
+    /// Write the suffix array in a raw little-endian binary format, which avoids the overhead
+    /// of serde for large arrays: a header with the width of the positions in bytes (`u8`)
+    /// and their number (`u64`), followed by the positions. Complexity: O(n)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::suffix_array::CompactSuffixArray;
+    ///
+    /// let sa = CompactSuffixArray::new(b"GCCTTAACATTATTACGCCTA$");
+    /// let mut buffer = Vec::new();
+    /// sa.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer.len(), 9 + 4 * 22);
+    /// assert_eq!(CompactSuffixArray::read_from(&buffer[..]).unwrap(), sa);
+    /// ```
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        match *self {
+            CompactSuffixArray::U32(ref sa) => {
+                write_raw(&mut writer, 4, sa.len(), sa.iter().map(|&p| p as u64))?
+            }
+            CompactSuffixArray::U64(ref sa) => {
+                write_raw(&mut writer, 8, sa.len(), sa.iter().map(|&p| p as u64))?
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read a suffix array written by `write_to`, keeping the width of the positions.
+    /// A truncated input yields an error of kind `UnexpectedEof`, and an invalid width one
+    /// of kind `InvalidData`.
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        match read_raw_header(&mut reader)? {
+            (4, len) => Ok(CompactSuffixArray::U32(read_raw(
+                &mut reader,
+                4,
+                len,
+                |p| p as u32,
+            )?)),
+            (8, len) => Ok(CompactSuffixArray::U64(read_raw(
+                &mut reader,
+                8,
+                len,
+                |p| p as usize,
+            )?)),
+            (width, _) => Err(invalid_width(width)),
+        }
+    }
+}
+
+/// Write a header with the given width in bytes and the number `len` of values, followed by
+/// the lowest `width` bytes of each value in little-endian order.
+pub(crate) fn write_raw<W: Write, I: IntoIterator<Item = u64>>(
+    writer: &mut W,
+    width: usize,
+    len: usize,
+    values: I,
+) -> io::Result<()> {
+    writer.write_all(&[width as u8])?;
+    writer.write_all(&(len as u64).to_le_bytes())?;
+    for value in values {
+        writer.write_all(&value.to_le_bytes()[..width])?;
+    }
+
+    Ok(())
+}
+
+/// Read the header written by `write_raw`, returning the width and number of the values.
+pub(crate) fn read_raw_header<R: Read>(reader: &mut R) -> io::Result<(usize, usize)> {
+    let mut width = [0; 1];
+    reader.read_exact(&mut width)?;
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+
+    Ok((width[0] as usize, u64::from_le_bytes(len) as usize))
+}
+
+/// Read `len` values of the given width written by `write_raw`, converting each of them with
+/// the given function.
+pub(crate) fn read_raw<R: Read, T, F: Fn(u64) -> T>(
+    reader: &mut R,
+    width: usize,
+    len: usize,
+    convert: F,
+) -> io::Result<Vec<T>> {
+    // do not trust the length of a possibly truncated input for the allocation
+    let mut values = Vec::with_capacity(len.min(1 << 20));
+    let mut buffer = [0; 8];
+    for _ in 0..len {
+        reader.read_exact(&mut buffer[..width])?;
+        values.push(convert(u64::from_le_bytes(buffer)));
+    }
+
+    Ok(values)
+}
+
+/// The error for a raw binary input with an unsupported width of the values.
+pub(crate) fn invalid_width(width: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unsupported width of {} bytes", width),
+    )
+}
+
+/// Invert the given permutation of 0..n.
+fn invert<I: NumCast + Copy>(pos: &[I]) -> Vec<I> {
+    let mut isa = vec![cast(0).unwrap(); pos.len()];
+    for (r, &p) in pos.iter().enumerate() {
+        isa[p.to_usize().unwrap()] = cast(r).unwrap();
+    }
+
+    isa
+}
+
+impl SuffixArray for CompactSuffixArray {
+    fn get(&self, index: usize) -> Option<usize> {
+        match *self {
+            CompactSuffixArray::U32(ref sa) => sa.get(index).map(|&p| p as usize),
+            CompactSuffixArray::U64(ref sa) => sa.as_slice().get(index).cloned(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            CompactSuffixArray::U32(ref sa) => sa.len(),
+            CompactSuffixArray::U64(ref sa) => sa.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+quick_error! {
+    #[derive(Debug, PartialEq)]
+    pub enum SuffixArrayError {
+        TextTooLong(len: usize) {
+            description("text is too long for a suffix array with 32 bit positions")
+            display("text of length {} is too long for a suffix array with 32 bit positions", len)
+        }
+        MissingSentinel {
+            description("text is not terminated by the sentinel")
+            display("expecting text to end with the sentinel")
+        }
+        SymbolBelowSentinel(symbol: u8, pos: usize) {
+            description("text contains a symbol that is lexicographically smaller than the sentinel")
+            display("symbol {} at position {} is lexicographically smaller than the sentinel '$'", symbol, pos)
+        }
+        ConsecutiveSentinels(pos: usize) {
+            description("text contains a sentinel that is directly followed by another sentinel")
+            display("sentinel at position {} is directly followed by another sentinel", pos)
+        }
+        InvalidLength(len: usize, text_len: usize) {
+            description("suffix array length differs from text length")
+            display("suffix array of length {} for a text of length {}", len, text_len)
+        }
+        NotAPermutation(rank: usize) {
+            description("suffix array is not a permutation of the text positions")
+            display("entry at rank {} is out of range or occurs twice", rank)
+        }
+        Unsorted(rank: usize) {
+            description("suffixes are not in lexicographical order")
+            display("suffix at rank {} is not larger than its predecessor", rank)
+        }
+    }
+}
+
+/// Check that a text of the given length can be indexed with 32 bit positions.
+fn check_u32_len(len: usize) -> Result<(), SuffixArrayError> {
+    if len > u32::MAX as usize {
+        Err(SuffixArrayError::TextTooLong(len))
+    } else {
+        Ok(())
+    }
+}
+
 // impl<DBWT: DerefBWT, DLess: DerefLess, DOcc: DerefOcc> SuffixArray for SampledSuffixArray<DBWT, DLess, DOcc> {
 //     fn get(&self, index: usize) -> Option<usize> {
 //         if index < self.len() {
@@ -205,30 +471,969 @@ impl SuffixArray for RawSuffixArray {
 /// ]);
 /// ```
 pub fn suffix_array(text: &[u8]) -> RawSuffixArray {
-    let n = text.len();
+    construct_suffix_array(text)
+}
+
+/// Construct the suffix array of the given text like `suffix_array`, after checking that the
+/// text is terminated by the sentinel `$`, that no symbol is lexicographically smaller than
+/// the sentinel, and that sentinels inside of the text only separate non-empty sequences.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{try_suffix_array, SuffixArrayError};
+/// assert_eq!(try_suffix_array(b"ACGT$GA$").unwrap(), [7, 4, 6, 0, 1, 5, 2, 3]);
+/// assert_eq!(try_suffix_array(b"ACGT"), Err(SuffixArrayError::MissingSentinel));
+/// assert_eq!(
+///     try_suffix_array(b"ACGT$$"),
+///     Err(SuffixArrayError::ConsecutiveSentinels(4))
+/// );
+/// ```
+pub fn try_suffix_array(text: &[u8]) -> Result<RawSuffixArray, SuffixArrayError> {
+    check_sentinel_positions(text, b'$')?;
+    if let Some(pos) = text.iter().position(|&a| a < b'$') {
+        return Err(SuffixArrayError::SymbolBelowSentinel(text[pos], pos));
+    }
+
+    Ok(suffix_array(text))
+}
+
+/// Construct the suffix array of a text over arbitrary bytes, which is terminated by the given
+/// sentinel. Like `$` for `suffix_array`, the sentinel is ordered before all other symbols,
+/// but it need not be the lexicographically smallest byte, such that e.g. texts containing
+/// `0` or symbols below `$` can be indexed. Occurrences of the sentinel inside of the text
+/// separate non-empty sequences.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by the sentinel
+/// * `sentinel` - the sentinel symbol
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array_with_sentinel, SuffixArrayError};
+/// let text = [2, 0, 1, 0xff, 0, 0, 0xff];
+/// assert_eq!(suffix_array_with_sentinel(&text, 0xff).unwrap(), [6, 3, 5, 4, 1, 2, 0]);
+/// assert_eq!(
+///     suffix_array_with_sentinel(b"ACGT$", 0),
+///     Err(SuffixArrayError::MissingSentinel)
+/// );
+/// ```
+pub fn suffix_array_with_sentinel(
+    text: &[u8],
+    sentinel: u8,
+) -> Result<RawSuffixArray, SuffixArrayError> {
+    check_sentinel_positions(text, sentinel)?;
+
+    Ok(construct_suffix_array_with_sentinel(text))
+}
+
+/// Check that the text is terminated by the given sentinel, and that sentinels inside of the
+/// text only separate non-empty sequences.
+pub(crate) fn check_sentinel_positions(text: &[u8], sentinel: u8) -> Result<(), SuffixArrayError> {
+    if text.last() != Some(&sentinel) {
+        return Err(SuffixArrayError::MissingSentinel);
+    }
+    if let Some(pos) = text
+        .windows(2)
+        .position(|w| w[0] == sentinel && w[1] == sentinel)
+    {
+        return Err(SuffixArrayError::ConsecutiveSentinels(pos));
+    }
+
+    Ok(())
+}
+
+/// A phase of the suffix array construction, as reported by `suffix_array_with_progress`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaPhase {
+    /// Transforming the text and classifying its positions.
+    Preparing,
+    /// Sorting the LMS substrings by inducing them from the unsorted LMS positions.
+    SortingLmsSubstrings,
+    /// Sorting the LMS suffixes, recursively if LMS substrings occur multiple times.
+    SortingLmsSuffixes,
+    /// Inducing the order of all suffixes from the sorted LMS suffixes.
+    InducingSuffixes,
+}
+
+/// The progress of the suffix array construction, given by the current phase and the
+/// fraction of the whole construction that is complete.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SaProgress {
+    pub phase: SaPhase,
+    pub fraction: f64,
+}
+
+/// Construct the suffix array of the given text like `suffix_array`, reporting the progress
+/// to the given callback, which can cancel the construction by returning
+/// `ControlFlow::Break`. The callback is invoked at the start of each phase and after every
+/// `2^16` positions processed by the induced sorting passes, and finally with a fraction
+/// of 1.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `progress` - the callback receiving the progress
+///
+/// # Returns
+///
+/// The suffix array, or `None` if the construction was cancelled.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array, suffix_array_with_progress};
+/// use std::ops::ControlFlow;
+///
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let mut fractions = Vec::new();
+/// let pos = suffix_array_with_progress(text, |progress| {
+///     fractions.push(progress.fraction);
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(pos, Some(suffix_array(text)));
+/// assert_eq!(fractions.last(), Some(&1.0));
+///
+/// assert_eq!(suffix_array_with_progress(text, |_| ControlFlow::Break(())), None);
+/// ```
+pub fn suffix_array_with_progress<F>(text: &[u8], mut progress: F) -> Option<RawSuffixArray>
+where
+    F: FnMut(SaProgress) -> ControlFlow<()>,
+{
+    check_sentinel(text);
+    let mut progress = Progress::new(&mut progress);
+    progress.enter(SaPhase::Preparing, 0.0, 0.0);
+    if !progress.tick(0.0) {
+        return None;
+    }
     let alphabet = Alphabet::new(text);
     let sentinel_count = sentinel_count(text);
-    let mut sais = SAIS::new(n);
 
     match alphabet.len() + sentinel_count {
-        a if a <= std::u8::MAX as usize => {
-            sais.construct(&transform_text::<u8>(text, &alphabet, sentinel_count))
-        }
-        a if a <= std::u16::MAX as usize => {
-            sais.construct(&transform_text::<u16>(text, &alphabet, sentinel_count))
+        a if a <= u8::MAX as usize => sort_suffixes_with_progress(
+            &transform_text::<u8>(text, &alphabet, sentinel_count),
+            progress,
+        ),
+        a if a <= u16::MAX as usize => sort_suffixes_with_progress(
+            &transform_text::<u16>(text, &alphabet, sentinel_count),
+            progress,
+        ),
+        a if a <= u32::MAX as usize => sort_suffixes_with_progress(
+            &transform_text::<u32>(text, &alphabet, sentinel_count),
+            progress,
+        ),
+        _ => sort_suffixes_with_progress(
+            &transform_text::<u64>(text, &alphabet, sentinel_count),
+            progress,
+        ),
+    }
+}
+
+/// Sort the suffixes of a transformed text with SAIS, reporting to the given progress.
+fn sort_suffixes_with_progress<T>(text: &[T], progress: Progress<'_>) -> Option<Vec<usize>>
+where
+    T: Integer + Unsigned + NumCast + Copy + Debug,
+{
+    let mut sais = SAIS::new(text.len());
+    sais.progress = Some(progress);
+    sais.construct(text);
+
+    let mut progress = sais.progress.take().unwrap();
+    progress.enter(SaPhase::InducingSuffixes, 1.0, 1.0);
+    if progress.cancelled || !progress.tick(1.0) {
+        return None;
+    }
+    Some(sais.pos)
+}
+
+/// Construct the suffix array of the given text (see `suffix_array`), storing positions
+/// with the integer type `I`, which has to be able to represent the text length.
+/// With the `parallel` feature, texts of at least `MIN_PARALLEL_TEXT_LEN` are sorted with
+/// one thread per available core.
+pub(crate) fn construct_suffix_array<
+    I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync,
+>(
+    text: &[u8],
+) -> Vec<I> {
+    check_sentinel(text);
+    construct_suffix_array_with_sentinel(text)
+}
+
+/// Construct the suffix array of the given text like `construct_suffix_array`, considering
+/// the last character of the text as the sentinel, which is ordered before all other symbols
+/// regardless of its value.
+pub(crate) fn construct_suffix_array_with_sentinel<
+    I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync,
+>(
+    text: &[u8],
+) -> Vec<I> {
+    #[cfg(feature = "parallel")]
+    {
+        let threads = thread::available_parallelism().map_or(1, |t| t.get());
+        if threads > 1 && text.len() >= MIN_PARALLEL_TEXT_LEN {
+            return construct_suffix_array_with(text, threads);
         }
-        a if a <= std::u32::MAX as usize => {
-            sais.construct(&transform_text::<u32>(text, &alphabet, sentinel_count))
+    }
+    construct_suffix_array_with(text, 1)
+}
+
+/// Construct the suffix array of the given text with the given number of threads, using
+/// SAIS if it is one and prefix doubling otherwise.
+fn construct_suffix_array_with<I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync>(
+    text: &[u8],
+    threads: usize,
+) -> Vec<I> {
+    let alphabet = Alphabet::new(text);
+    let sentinel_count = sentinel_count(text);
+
+    match alphabet.len() + sentinel_count {
+        a if a <= std::u8::MAX as usize => sort_suffixes(
+            &transform_text::<u8>(text, &alphabet, sentinel_count),
+            threads,
+        ),
+        a if a <= std::u16::MAX as usize => sort_suffixes(
+            &transform_text::<u16>(text, &alphabet, sentinel_count),
+            threads,
+        ),
+        a if a <= std::u32::MAX as usize => sort_suffixes(
+            &transform_text::<u32>(text, &alphabet, sentinel_count),
+            threads,
+        ),
+        _ => sort_suffixes(
+            &transform_text::<u64>(text, &alphabet, sentinel_count),
+            threads,
+        ),
+    }
+}
+
+/// Sort the suffixes of a transformed text (see `transform_text`).
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+fn sort_suffixes<I, T>(text: &[T], threads: usize) -> Vec<I>
+where
+    I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync,
+    T: Integer + Unsigned + NumCast + Copy + Debug + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if threads > 1 {
+            return prefix_doubling(text, threads);
         }
-        _ => sais.construct(&transform_text::<u64>(text, &alphabet, sentinel_count)),
     }
+    let mut sais = SAIS::new(text.len());
+    sais.construct(text);
 
     sais.pos
 }
 
-/// Construct lcp array for given text and suffix array of length n.
+/// Construct the suffix array of the given text (see `suffix_array`) with the given number
+/// of threads. The result is identical to the one of `suffix_array`, which is used for a
+/// single thread.
+///
+/// With multiple threads, the suffixes are sorted by prefix doubling (Manber and Myers, 1993):
+/// in each round, the groups of suffixes sharing their first h symbols are sorted by the rank
+/// of the suffix starting h positions later, in parallel over disjoint groups.
+/// Complexity: O(n log^2 n / t) with t threads.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array, suffix_array_parallel};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// assert_eq!(suffix_array_parallel(text, 4), suffix_array(text));
+/// ```
+#[cfg(feature = "parallel")]
+pub fn suffix_array_parallel(text: &[u8], threads: usize) -> RawSuffixArray {
+    assert!(threads > 0, "Expecting at least one thread.");
+    check_sentinel(text);
+    construct_suffix_array_with(text, threads)
+}
+
+/// Construct a sparse suffix array, i.e. the text positions 0, s, 2s, ... in the
+/// lexicographic order of their suffixes, without building the full suffix array.
+/// The positions are sorted by prefix doubling over the blocks of length s starting at them
+/// (see `sort_sampled_suffixes`), with sentinels ordered as in `suffix_array`. The result can
+/// be used to sample an FM-Index by text position (see
+/// `SampledFMIndex::from_sparse_suffix_array`).
+/// Complexity: O(n log(n / s) + n / s log^2(n / s)) time, O(n / s) space.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `s` - the sampling rate: every s-th text position will be kept
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{sparse_suffix_array, suffix_array};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let sparse = sparse_suffix_array(text, 4);
+/// assert_eq!(sparse, [20, 8, 16, 0, 4, 12]);
+///
+/// let full: Vec<usize> = suffix_array(text).into_iter().filter(|p| p % 4 == 0).collect();
+/// assert_eq!(sparse, full);
+/// ```
+pub fn sparse_suffix_array(text: &[u8], s: usize) -> Vec<usize> {
+    assert!(s > 0, "Expecting a sampling rate of at least 1.");
+    let sentinel = sentinel(text);
+    let positions: Vec<usize> = (0..text.len()).step_by(s).collect();
+
+    sort_sampled_suffixes(text, sentinel, s, &positions)
+        .into_iter()
+        .map(|i| positions[i])
+        .collect()
+}
+
+/// Calculate the rows (i.e. ranks among all suffixes) of the given positions. For each
+/// residue c modulo s of the given positions and each residue d, the suffixes at positions
+/// c, c + s, ... and d, d + s, ... are sorted jointly, which yields the number of smaller
+/// suffixes at positions d, d + s, ... for each of the former.
+/// Complexity: O(r * s * (n + n / s log^2(n / s))) time for r distinct residues,
+/// O(n / s + m) space for m positions.
+pub(crate) fn sparse_ranks(text: &[u8], s: usize, positions: &[usize]) -> Vec<usize> {
+    let sentinel = sentinel(text);
+    let n = text.len();
+    let mut residues: Vec<usize> = positions.iter().map(|&p| p % s).collect();
+    residues.sort_unstable();
+    residues.dedup();
+
+    let mut ranks = vec![0; positions.len()];
+    for c in residues {
+        let chain: Vec<usize> = (c..n).step_by(s).collect();
+        let mut chain_ranks = vec![0; chain.len()];
+        for d in 0..s.min(n) {
+            let mut joint = chain.clone();
+            if d != c {
+                joint.extend((d..n).step_by(s));
+            }
+            // the number of suffixes at positions d, d + s, ... seen so far
+            let mut smaller = 0;
+            for i in sort_sampled_suffixes(text, sentinel, s, &joint) {
+                if i < chain.len() {
+                    chain_ranks[i] += smaller;
+                }
+                if i >= chain.len() || d == c {
+                    smaller += 1;
+                }
+            }
+        }
+        for (rank, &p) in ranks.iter_mut().zip(positions) {
+            if p % s == c {
+                *rank = chain_ranks[p / s];
+            }
+        }
+    }
+
+    ranks
+}
+
+/// Sort the suffixes at the given positions by prefix doubling (Manber and Myers, 1993) over
+/// the blocks of length s starting at them, i.e. over the text of blocks at positions
+/// p, p + s, p + 2s, .... The positions have to consist of such chains, each listed in
+/// increasing order up to its last position in the text. Equal blocks share their rank,
+/// while blocks containing a sentinel are distinct, such that all suffixes are ordered after
+/// at most log(m) rounds. Returns the indices of the positions in the order of their suffixes.
+/// Complexity: O(m log m * s + m log^2 m) time for m positions, O(m) space.
+fn sort_sampled_suffixes(text: &[u8], sentinel: u8, s: usize, positions: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let m = positions.len();
+    let cmp = |i: usize, j: usize| cmp_blocks(text, sentinel, s, positions[i], positions[j]);
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_unstable_by(|&i, &j| cmp(i, j));
+
+    // the rank of a suffix is the start of its group of suffixes sharing the first h blocks
+    let mut rank = vec![0; m];
+    let mut groups = m.min(1);
+    for k in 1..m {
+        rank[order[k]] = if cmp(order[k - 1], order[k]) == Ordering::Equal {
+            rank[order[k - 1]]
+        } else {
+            groups += 1;
+            k
+        };
+    }
+
+    let mut h = 1;
+    while groups < m {
+        // suffixes sharing their first h blocks contain no sentinel in them, and are thus
+        // followed by further blocks
+        let key = |i: usize| (rank[i], (positions[i] + h * s < n).then(|| rank[i + h]));
+        order.sort_unstable_by_key(|&i| key(i));
+        let mut next = vec![0; m];
+        groups = m.min(1);
+        for k in 1..m {
+            next[order[k]] = if key(order[k - 1]) == key(order[k]) {
+                next[order[k - 1]]
+            } else {
+                groups += 1;
+                k
+            };
+        }
+        rank = next;
+        h *= 2;
+    }
+
+    order
+}
+
+/// Compare the blocks of length s at positions p and q, each ended by its first sentinel
+/// or the end of the text. Blocks sharing a sentinel are ordered like their suffixes (see
+/// `cmp_suffixes`), such that only equal blocks without a sentinel compare as equal.
+fn cmp_blocks(text: &[u8], sentinel: u8, s: usize, p: usize, q: usize) -> Ordering {
+    let block = |p: usize| &text[p..text.len().min(p + s)];
+    for (&a, &b) in block(p).iter().zip(block(q)) {
+        if a != b {
+            return a.cmp(&b);
+        }
+        if a == sentinel {
+            return q.cmp(&p);
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Compare the suffixes at positions p and q like `suffix_array`, i.e. the suffix with the
+/// later sentinel is the smaller one.
+pub(crate) fn cmp_suffixes(text: &[u8], sentinel: u8, p: usize, q: usize) -> Ordering {
+    for (&a, &b) in text[p..].iter().zip(&text[q..]) {
+        if a != b {
+            return a.cmp(&b);
+        }
+        if a == sentinel {
+            return q.cmp(&p);
+        }
+    }
+
+    q.cmp(&p)
+}
+
+/// Sort the suffixes of a transformed text by prefix doubling with the given number of threads.
+/// Since the sentinels are transformed into distinct symbols, all suffixes are distinct and
+/// there is exactly one order, such that the result equals the one of SAIS.
+#[cfg(feature = "parallel")]
+fn prefix_doubling<I, T>(text: &[T], threads: usize) -> Vec<I>
+where
+    I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync,
+    T: Integer + Unsigned + NumCast + Copy + Debug + Sync,
+{
+    let n = text.len();
+    let to_usize = |a: T| -> usize { cast(a).unwrap() };
+
+    // bucket the suffixes by their first k symbols, with k chosen such that there are at most
+    // n buckets, the rank of a suffix being the start of its group in the sorted suffixes
+    let sigma = text.iter().map(|&a| to_usize(a)).max().map_or(0, |a| a + 1);
+    let (mut k, mut buckets) = (1, sigma);
+    while let Some(b) = buckets.checked_mul(sigma) {
+        if b > n || k == n {
+            break;
+        }
+        k += 1;
+        buckets = b;
+    }
+    // symbols beyond the end of the text are padded with the last sentinel, which does not
+    // affect the order since it is unique
+    let high = buckets / sigma;
+    let mut keys = Vec::with_capacity(n);
+    let mut key = text
+        .iter()
+        .take(k)
+        .fold(0, |key, &a| key * sigma + to_usize(a));
+    for p in 0..n {
+        keys.push(key);
+        let next = text.get(p + k).map_or(0, |&a| to_usize(a));
+        key = (key - to_usize(text[p]) * high) * sigma + next;
+    }
+
+    let mut starts = vec![0; buckets + 1];
+    for &key in &keys {
+        starts[key + 1] += 1;
+    }
+    for b in 1..=buckets {
+        starts[b] += starts[b - 1];
+    }
+    let mut rank: Vec<I> = keys.iter().map(|&key| cast(starts[key]).unwrap()).collect();
+    let mut groups: Vec<(usize, usize)> = starts
+        .windows(2)
+        .filter(|w| w[1] - w[0] > 1)
+        .map(|w| (w[0], w[1]))
+        .collect();
+    let mut pos: Vec<I> = vec![I::zero(); n];
+    for (p, &key) in keys.iter().enumerate() {
+        pos[starts[key]] = cast(p).unwrap();
+        starts[key] += 1;
+    }
+    drop(keys);
+
+    let mut h = k;
+    while !groups.is_empty() {
+        // split the unsorted groups into runs of similar size, one per thread
+        let total: usize = groups.iter().map(|&(start, end)| end - start).sum();
+        let target = total / threads + 1;
+        let mut runs = Vec::new();
+        let (mut first, mut size) = (0, 0);
+        for (i, &(start, end)) in groups.iter().enumerate() {
+            size += end - start;
+            if size >= target || i + 1 == groups.len() {
+                runs.push(&groups[first..=i]);
+                first = i + 1;
+                size = 0;
+            }
+        }
+
+        let results: Vec<Refinement> = thread::scope(|scope| {
+            let rank = &rank;
+            let mut rest: &mut [I] = &mut pos;
+            let mut offset = 0;
+            let mut handles = Vec::with_capacity(runs.len());
+            for run in runs {
+                let (start, end) = (run[0].0, run[run.len() - 1].1);
+                let (_, tail) = mem::take(&mut rest).split_at_mut(start - offset);
+                let (chunk, tail) = tail.split_at_mut(end - start);
+                rest = tail;
+                offset = end;
+                handles.push(scope.spawn(move || refine_groups(chunk, start, run, rank, h)));
+            }
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        groups.clear();
+        for refinement in results {
+            for (p, r) in refinement.updates {
+                rank[p] = cast(r).unwrap();
+            }
+            groups.extend(refinement.groups);
+        }
+        h *= 2;
+    }
+
+    pos
+}
+
+/// Result of refining groups of suffixes in one round of prefix doubling.
+#[cfg(feature = "parallel")]
+struct Refinement {
+    /// Text positions of suffixes with a new rank, along with that rank.
+    updates: Vec<(usize, usize)>,
+    /// Groups of suffixes still sharing their first 2h symbols.
+    groups: Vec<(usize, usize)>,
+}
+
+/// Sort the given groups of suffixes sharing their first h symbols by the rank of the suffix
+/// h positions later. The chunk of the suffix array starts at the given offset.
+#[cfg(feature = "parallel")]
+fn refine_groups<I: Integer + Unsigned + NumCast + Copy + Debug + Send + Sync>(
+    chunk: &mut [I],
+    offset: usize,
+    groups: &[(usize, usize)],
+    rank: &[I],
+    h: usize,
+) -> Refinement {
+    let to_usize = |a: I| -> usize { cast(a).unwrap() };
+    // suffixes shorter than h are already in a group of their own, such that None never
+    // occurs in a group of more than one suffix
+    let key = |p: I| rank.get(to_usize(p) + h).cloned();
+
+    let (mut updates, mut refined) = (Vec::new(), Vec::new());
+    for &(start, end) in groups {
+        let group = &mut chunk[start - offset..end - offset];
+        group.sort_unstable_by_key(|&p| key(p));
+        let mut sub_start = 0;
+        for i in 1..=group.len() {
+            if i == group.len() || key(group[i]) != key(group[sub_start]) {
+                if i - sub_start > 1 {
+                    refined.push((start + sub_start, start + i));
+                }
+                if sub_start > 0 {
+                    for &p in &group[sub_start..i] {
+                        updates.push((to_usize(p), start + sub_start));
+                    }
+                }
+                sub_start = i;
+            }
+        }
+    }
+
+    Refinement {
+        updates,
+        groups: refined,
+    }
+}
+
+/// Search the given pattern by two binary searches on the suffix array (Manber and Myers,
+/// 1993), without any further index. Comparisons skip the prefix that the pattern shares
+/// with both bounds of the search range. The pattern should not contain the sentinel.
+/// Complexity: O(m log n) for a pattern of length m.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `pos` - the suffix array of the text
+/// * `pattern` - the pattern to search
+///
+/// # Returns
+///
+/// The interval of the ranks of the suffixes starting with the pattern, with the upper bound
+/// being exclusive like `fmindex::Interval`.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{search, search_positions, suffix_array};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let pos = suffix_array(text);
+/// assert_eq!(search(text, &pos, b"TTA"), (19, 22));
+/// assert_eq!(search_positions(text, &pos, b"TTA"), [3, 9, 12]);
+/// assert!(search_positions(text, &pos, b"GGG").is_empty());
+/// ```
+pub fn search<SA: SuffixArray>(text: &[u8], pos: &SA, pattern: &[u8]) -> (usize, usize) {
+    assert_eq!(text.len(), pos.len());
+    (
+        search_bound(text, pos, pattern, false),
+        search_bound(text, pos, pattern, true),
+    )
+}
+
+/// Search the given pattern like `search`, but replace the second binary search by a scan
+/// over the lcp array from the first suffix starting with the pattern.
+/// Complexity: O(m log n + k) for a pattern of length m with k occurrences.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (being lexicographically smallest)
+/// * `pos` - the suffix array of the text
+/// * `lcp` - the lcp array of the text
+/// * `pattern` - the pattern to search
+pub fn search_with_lcp<SA: SuffixArray>(
+    text: &[u8],
+    pos: &SA,
+    lcp: &LcpArray,
+    pattern: &[u8],
+) -> (usize, usize) {
+    assert_eq!(text.len(), pos.len());
+    let n = pos.len();
+    let lower = search_bound(text, pos, pattern, false);
+    if lower == n || !text[pos.get(lower).unwrap()..].starts_with(pattern) {
+        return (lower, lower);
+    }
+    let mut upper = lower + 1;
+    while upper < n && lcp.get(upper).unwrap() >= pattern.len() as isize {
+        upper += 1;
+    }
+
+    (lower, upper)
+}
+
+/// Search the given pattern like `search`, and return the text positions of all occurrences
+/// in increasing order.
+pub fn search_positions<SA: SuffixArray>(text: &[u8], pos: &SA, pattern: &[u8]) -> Vec<usize> {
+    let (lower, upper) = search(text, pos, pattern);
+    let mut positions: Vec<usize> = (lower..upper).map(|r| pos.get(r).unwrap()).collect();
+    positions.sort_unstable();
+
+    positions
+}
+
+/// Return the first rank whose suffix is not smaller than the pattern, or with `after`, the
+/// first one whose suffix is larger and does not start with the pattern.
+fn search_bound<SA: SuffixArray>(text: &[u8], pos: &SA, pattern: &[u8], after: bool) -> usize {
+    let (mut lower, mut upper) = (0, pos.len());
+    // the lengths of the common prefix of the pattern with the suffixes bounding the range
+    let (mut lower_lcp, mut upper_lcp) = (0, 0);
+    while lower < upper {
+        let mid = lower + (upper - lower) / 2;
+        let p = pos.get(mid).unwrap();
+        let mut l = cmp::min(lower_lcp, upper_lcp);
+        while l < pattern.len() && p + l < text.len() && text[p + l] == pattern[l] {
+            l += 1;
+        }
+        let before = if l == pattern.len() {
+            after
+        } else {
+            // a suffix being a prefix of the pattern is smaller
+            p + l == text.len() || text[p + l] < pattern[l]
+        };
+        if before {
+            lower = mid + 1;
+            lower_lcp = l;
+        } else {
+            upper = mid;
+            upper_lcp = l;
+        }
+    }
+
+    lower
+}
+
+/// Construct the suffix array of the concatenation of the given sequences, each of them
+/// terminated by a sentinel `$`. Since the sentinels are ranked as distinct symbols, no comparison
+/// of suffixes crosses the boundary of a sequence. Along with the text and its suffix array,
+/// a `DocArray` is returned, mapping text positions back to the sequences.
+/// Complexity: O(n).
+///
+/// # Arguments
+///
+/// * `seqs` - the sequences, containing only symbols lexicographically larger than `$`
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::generalized_suffix_array;
+/// let (text, pos, docs) = generalized_suffix_array(&[b"ACGT", b"CGA"]);
+/// assert_eq!(text, b"ACGT$CGA$");
+/// assert_eq!(docs.seq_of(6), (1, 1));
+/// // the suffixes starting with CG, at ranks 5 and 6
+/// assert_eq!(docs.seqs_of_interval(&pos, 5, 7), [0, 1]);
+/// ```
+pub fn generalized_suffix_array(seqs: &[&[u8]]) -> (Vec<u8>, RawSuffixArray, DocArray) {
+    let mut text = Vec::with_capacity(seqs.iter().map(|seq| seq.len() + 1).sum());
+    for seq in seqs {
+        assert!(
+            seq.iter().all(|&a| a > b'$'),
+            "Expecting only symbols lexicographically larger than the sentinel $."
+        );
+        text.extend_from_slice(seq);
+        text.push(b'$');
+    }
+    let pos = if text.is_empty() {
+        Vec::new()
+    } else {
+        suffix_array(&text)
+    };
+
+    let docs = DocArray::new(&text);
+
+    (text, pos, docs)
+}
+
+/// Find the longest common substrings of two sequences by scanning the lcp array of their
+/// generalized suffix array (see `generalized_suffix_array`) for adjacent suffixes of
+/// different sequences. All occurrences of all longest common substrings are reported as
+/// triples (start in a, start in b, length), sorted by their starts. If the sequences do not
+/// share any symbol, the result is empty.
+/// Complexity: O(n + k) for k reported occurrences.
+///
+/// # Arguments
+///
+/// * `a` - the first sequence, containing only symbols lexicographically larger than `$`
+/// * `b` - the second sequence, containing only symbols lexicographically larger than `$`
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::longest_common_substring;
+/// let hits = longest_common_substring(b"GCCTTAACAT", b"ACATTAGG");
+/// assert_eq!(hits, [(6, 0, 4)]);
+/// ```
+pub fn longest_common_substring(a: &[u8], b: &[u8]) -> Vec<(usize, usize, usize)> {
+    let (text, pos, docs) = generalized_suffix_array(&[a, b]);
+    let lcp = lcp(&text, &pos).decompress();
+    let n = text.len();
+    let seq = |r: usize| docs.seq_of(pos[r]).0;
+
+    // the longest common prefix of suffixes of different sequences
+    let len = (1..n)
+        .filter(|&r| seq(r - 1) != seq(r))
+        .map(|r| lcp[r])
+        .max()
+        .unwrap_or(0);
+    if len <= 0 {
+        return Vec::new();
+    }
+
+    // all suffixes in an interval of ranks with lcp values of at least len share a prefix of
+    // that length, and the last lcp value of -1 ends the last interval
+    let mut hits = Vec::new();
+    let mut lower = 0;
+    for (upper, &l) in lcp.iter().enumerate().skip(1) {
+        if l >= len {
+            continue;
+        }
+        let (starts_a, starts_b): (Vec<usize>, Vec<usize>) =
+            (lower..upper).map(|r| pos[r]).partition(|&p| p < a.len());
+        for &i in &starts_a {
+            for &j in &starts_b {
+                hits.push((i, j - a.len() - 1, len as usize));
+            }
+        }
+        lower = upper;
+    }
+    hits.sort_unstable();
+
+    hits
+}
+
+/// A maximal unique match of two sequences, given by its start in either sequence and its
+/// length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mum {
+    pub pos_a: usize,
+    pub pos_b: usize,
+    pub len: usize,
+}
+
+/// Find the maximal unique matches (MUMs) of two sequences, i.e. the substrings occurring
+/// exactly once in each sequence that cannot be extended to either side. In the lcp array of
+/// the generalized suffix array (see `generalized_suffix_array`), these are the lcp-intervals
+/// of exactly two suffixes from different sequences that are preceded by different symbols
+/// (or start a sequence). Matches on the reverse strand can be found by passing the reverse
+/// complement of one sequence.
+/// Complexity: O(n)
+///
+/// # Arguments
+///
+/// * `a` - the first sequence, containing only symbols lexicographically larger than `$`
+/// * `b` - the second sequence, containing only symbols lexicographically larger than `$`
+/// * `min_len` - the minimum length of the reported matches
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{mums, Mum};
+/// // a single SNP splits the match
+/// let found = mums(b"GATTACAGATC", b"GATTGCAGATC", 3);
+/// assert_eq!(
+///     found,
+///     [
+///         Mum { pos_a: 0, pos_b: 0, len: 4 },
+///         Mum { pos_a: 5, pos_b: 5, len: 6 },
+///     ]
+/// );
+/// ```
+pub fn mums(a: &[u8], b: &[u8], min_len: usize) -> Vec<Mum> {
+    let (text, pos, docs) = generalized_suffix_array(&[a, b]);
+    let lcp = lcp(&text, &pos).decompress();
+    let min_len = min_len.max(1) as isize;
+
+    let mut found = Vec::new();
+    for r in 1..text.len() {
+        // the common prefix of the suffixes at ranks r - 1 and r occurs nowhere else if both
+        // neighbouring lcp values are smaller
+        let len = lcp[r];
+        if len < min_len || lcp[r - 1] >= len || lcp[r + 1] >= len {
+            continue;
+        }
+        let (seq, offset) = docs.seq_of(pos[r - 1]);
+        let (other_seq, other_offset) = docs.seq_of(pos[r]);
+        if seq == other_seq {
+            continue;
+        }
+        let (pos_a, pos_b) = if seq == 0 {
+            (offset, other_offset)
+        } else {
+            (other_offset, offset)
+        };
+        // left-maximality
+        if pos_a > 0 && pos_b > 0 && a[pos_a - 1] == b[pos_b - 1] {
+            continue;
+        }
+        found.push(Mum {
+            pos_a,
+            pos_b,
+            len: len as usize,
+        });
+    }
+    found.sort_unstable();
+
+    found
+}
+
+/// Check that the given suffix array is the one of the text, as constructed by
+/// `suffix_array`. This is meant for testing custom constructions, and returns the first
+/// violation if the suffix array is not a permutation of the text positions or the suffixes
+/// are not in lexicographical order, with sentinels ordered by decreasing text position.
+/// Adjacent suffixes are compared by their first symbol and the ranks of their remaining
+/// suffixes (Burkhardt and Kärkkäinen, 2003).
+/// Complexity: O(n).
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array, verify_suffix_array, SuffixArrayError};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let mut pos = suffix_array(text);
+/// assert!(verify_suffix_array(text, &pos).is_ok());
+/// pos.swap(3, 4);
+/// assert_eq!(verify_suffix_array(text, &pos), Err(SuffixArrayError::Unsorted(4)));
+/// ```
+pub fn verify_suffix_array<SA: SuffixArray>(text: &[u8], pos: &SA) -> Result<(), SuffixArrayError> {
+    let n = text.len();
+    if pos.len() != n {
+        return Err(SuffixArrayError::InvalidLength(pos.len(), n));
+    }
+    let mut isa = vec![n; n];
+    for r in 0..n {
+        match pos.get(r) {
+            Some(p) if p < n && isa[p] == n => isa[p] = r,
+            _ => return Err(SuffixArrayError::NotAPermutation(r)),
+        }
+    }
+    if n == 0 {
+        return Ok(());
+    }
+
+    let sentinel = sentinel(text);
+    for r in 1..n {
+        let (p, q) = (pos.get(r - 1).unwrap(), pos.get(r).unwrap());
+        let sorted = match text[p].cmp(&text[q]) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            // sentinels are ordered by decreasing position
+            Ordering::Equal if text[p] == sentinel => p > q,
+            // neither suffix ends here, since the text ends with the sentinel
+            Ordering::Equal => isa[p + 1] < isa[q + 1],
+        };
+        if !sorted {
+            return Err(SuffixArrayError::Unsorted(r));
+        }
+    }
+
+    Ok(())
+}
+
+/// Calculate the inverse of a suffix array (also known as rank array), i.e. the rank of the
+/// suffix starting at each text position, such that `isa[sa[r]] == r` and `sa[isa[p]] == p`.
+/// Complexity: O(n).
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{inverse_suffix_array, lcp, suffix_array};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let pos = suffix_array(text);
+/// let isa = inverse_suffix_array(&pos);
+/// assert_eq!(isa[8], 6);
+/// assert_eq!(pos[isa[8]], 8);
+///
+/// // the suffix ATTATT... at position 8 shares the prefix ATTA with its lexicographical
+/// // predecessor ATTACG... at position 11
+/// let lcp = lcp(text, &pos);
+/// assert_eq!(pos[isa[8] - 1], 11);
+/// assert_eq!(lcp.get(isa[8]), Some(4));
+/// ```
+pub fn inverse_suffix_array<SA: SuffixArray>(pos: &SA) -> Vec<usize> {
+    let mut isa = vec![0; pos.len()];
+    for r in 0..pos.len() {
+        isa[pos.get(r).unwrap()] = r;
+    }
+
+    isa
+}
+
+/// Construct lcp array for given text and suffix array of length n, using the algorithm of
+/// Kasai et al. (2001).
 /// Complexity: O(n).
 ///
+/// Entry r of the returned array holds the length of the longest common prefix of the suffixes
+/// at ranks r - 1 and r. Entries 0 and n have no such pair of suffixes and are set to -1.
+/// Sentinels are treated as pairwise distinct symbols, such that a common prefix never
+/// extends over a sentinel; in particular, the entries 1 through m, with m being the number of
+/// sentinels, are 0.
+///
 /// # Arguments
 ///
 /// * `text` - the text ended by sentinel symbol (being lexicographically smallest)
@@ -259,30 +1464,199 @@ pub fn suffix_array(text: &[u8]) -> RawSuffixArray {
 ///     ]
 /// )
 /// ```
-pub fn lcp<SA: Deref<Target = RawSuffixArray>>(text: &[u8], pos: SA) -> LCPArray {
+pub fn lcp<SA: SuffixArray>(text: &[u8], pos: SA) -> LcpArray {
     assert_eq!(text.len(), pos.len());
     let n = text.len();
+    let mut lcp = SmallInts::from_elem(-1, n + 1);
+    if n == 0 {
+        return LcpArray { values: lcp };
+    }
+    let sentinel = sentinel(text);
 
     // provide the lexicographical rank for each suffix
-    let mut rank: Vec<usize> = iter::repeat(0).take(n).collect();
-    for (r, p) in pos.iter().enumerate() {
-        rank[*p] = r;
-    }
+    let rank = inverse_suffix_array(&pos);
 
-    let mut lcp = SmallInts::from_elem(-1, n + 1);
     let mut l = 0usize;
     for (p, &r) in rank.iter().enumerate().take(n - 1) {
         // since the sentinel has rank 0 and is excluded above,
         // we will never have a negative index below
-        let pred = pos[r - 1];
-        while pred + l < n && p + l < n && text[p + l] == text[pred + l] {
+        let pred = pos.get(r - 1).unwrap();
+        while pred + l < n && p + l < n && text[p + l] == text[pred + l] && text[p + l] != sentinel
+        {
             l += 1;
         }
         lcp.set(r, l as isize);
         l = if l > 0 { l - 1 } else { 0 };
     }
 
-    lcp
+    LcpArray { values: lcp }
+}
+
+/// Construct the permuted lcp array (PLCP) for the given text and suffix array, using the
+/// phi array of Kärkkäinen, Manzini and Puglisi (2009). In contrast to `lcp`, no inverse
+/// suffix array is needed: the phi array, which maps each suffix to its predecessor in the
+/// suffix array, is built in the output vector and overwritten in text order, such that only
+/// text, suffix array and output are kept in memory.
+/// Complexity: O(n).
+///
+/// Entry i of the returned vector holds the length of the longest common prefix of the suffix
+/// at text position i and its predecessor in the suffix array, or 0 for the smallest suffix.
+/// As for `lcp`, common prefixes never extend over a sentinel.
+///
+/// # Arguments
+///
+/// * `text` - the text ended by sentinel symbol (being lexicographically smallest)
+/// * `pos` - the suffix array for the text
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{lcp, plcp, plcp_to_lcp, suffix_array};
+/// let text = b"GCTGCTA$";
+/// let pos = suffix_array(text);
+///
+/// let plcp = plcp(text, &pos);
+/// assert_eq!(plcp, [3, 2, 1, 0, 0, 0, 0, 0]);
+/// assert_eq!(plcp_to_lcp(&plcp, &pos).decompress(), lcp(text, &pos).decompress());
+/// ```
+pub fn plcp<SA: SuffixArray>(text: &[u8], pos: &SA) -> Vec<usize> {
+    assert_eq!(text.len(), pos.len());
+    let n = text.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let sentinel = sentinel(text);
+
+    // phi[p] is the position of the suffix preceding the one at p, with n marking none
+    let mut plcp = vec![0; n];
+    let mut pred = n;
+    for r in 0..n {
+        let p = pos.get(r).unwrap();
+        plcp[p] = pred;
+        pred = p;
+    }
+
+    let mut l = 0;
+    for p in 0..n {
+        let pred = plcp[p];
+        if pred == n {
+            l = 0;
+            plcp[p] = 0;
+            continue;
+        }
+        while p + l < n && pred + l < n && text[p + l] == text[pred + l] && text[p + l] != sentinel
+        {
+            l += 1;
+        }
+        plcp[p] = l;
+        l = l.saturating_sub(1);
+    }
+
+    plcp
+}
+
+/// Convert a permuted lcp array (see `plcp`) into the lcp array in suffix array order, as
+/// constructed by `lcp`. Complexity: O(n).
+///
+/// # Arguments
+///
+/// * `plcp` - the permuted lcp array
+/// * `pos` - the suffix array for the text
+pub fn plcp_to_lcp<SA: SuffixArray>(plcp: &[usize], pos: &SA) -> LcpArray {
+    assert_eq!(plcp.len(), pos.len());
+    let n = pos.len();
+    let mut lcp = SmallInts::from_elem(-1, n + 1);
+    for r in 1..n {
+        lcp.set(r, plcp[pos.get(r).unwrap()] as isize);
+    }
+
+    LcpArray { values: lcp }
+}
+
+/// An lcp array as constructed by `lcp`, storing small values with 8 bit. Entry r is the
+/// length of the longest common prefix of the suffixes at ranks r - 1 and r, or -1 for the
+/// entries 0 and n that have no such pair of suffixes.
+#[derive(Serialize, Deserialize)]
+pub struct LcpArray {
+    values: SmallInts<i8, isize>,
+}
+
+impl LcpArray {
+    /// Get the r-th entry, or `None` if r is larger than the length of the text.
+    /// Complexity: O(1) for values below 127, and O(log m) for the m larger values.
+    pub fn get(&self, r: usize) -> Option<isize> {
+        self.values.get(r)
+    }
+
+    /// Number of entries, which is the length of the text plus one.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterate over the entries.
+    pub fn iter(&self) -> smallints::Iter<'_, i8, isize> {
+        self.values.iter()
+    }
+
+    /// Decompress into a vector of all entries.
+    pub fn decompress(&self) -> Vec<isize> {
+        self.values.decompress()
+    }
+
+    /// Write the lcp array in a raw little-endian binary format like
+    /// `CompactSuffixArray::write_to`, storing each entry with as many bytes as the largest
+    /// one needs. Complexity: O(n)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::suffix_array::{lcp, suffix_array, LcpArray};
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let lcp = lcp(text, suffix_array(text));
+    /// let mut buffer = Vec::new();
+    /// lcp.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer.len(), 9 + 23);
+    /// assert_eq!(LcpArray::read_from(&buffer[..]).unwrap().decompress(), lcp.decompress());
+    /// ```
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let max = self.iter().max().unwrap_or(0);
+        let width = [1, 2, 4]
+            .iter()
+            .cloned()
+            .find(|&width| max < 1 << (8 * width - 1))
+            .unwrap_or(8);
+        let mut writer = BufWriter::new(writer);
+        // the two's complement of -1 is truncated to the width, and sign-extended when read
+        let values = self.iter().map(|l| l as u64);
+        write_raw(&mut writer, width, self.len(), values)?;
+        writer.flush()
+    }
+
+    /// Read an lcp array written by `write_to`. A truncated input yields an error of kind
+    /// `UnexpectedEof`, and an invalid width one of kind `InvalidData`.
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let (width, len) = read_raw_header(&mut reader)?;
+        if ![1, 2, 4, 8].contains(&width) {
+            return Err(invalid_width(width));
+        }
+        let shift = 64 - 8 * width;
+        let mut values = SmallInts::from_elem(-1, len);
+        let mut buffer = [0; 8];
+        for r in 0..len {
+            reader.read_exact(&mut buffer[..width])?;
+            let l = ((u64::from_le_bytes(buffer) << shift) as i64) >> shift;
+            values.set(r, l as isize);
+        }
+
+        Ok(LcpArray { values })
+    }
 }
 
 /// Calculate all locally shortest unique substrings from a given suffix and lcp array
@@ -328,7 +1702,216 @@ pub fn shortest_unique_substrings<SA: SuffixArray>(pos: &SA, lcp: &LCPArray) ->
             sus[p] = Some(len);
         }
     }
-    sus
+    sus
+}
+
+/// The child table of an enhanced suffix array (Abouelhoda, Kurtz and Ohlebusch (2004).
+/// "Replacing suffix trees with enhanced suffix arrays"). Together with the suffix array, it
+/// represents the tree of lcp-intervals, which corresponds to the internal nodes of the suffix
+/// tree, and allows to traverse it top-down in constant time per child.
+///
+/// Intervals are given as half-open ranges `(lower, upper)` of suffix array ranks.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{lcp, suffix_array, ChildTable};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let pos = suffix_array(text);
+/// let child_table = ChildTable::new(&lcp(text, &pos));
+///
+/// // the suffixes starting with $, A, C, G and T
+/// let children: Vec<_> = child_table.interval_children(child_table.root()).collect();
+/// assert_eq!(children, [(0, 1), (1, 7), (7, 13), (13, 15), (15, 22)]);
+///
+/// let interval = child_table.find(text, &pos, b"TTA").unwrap();
+/// let mut positions: Vec<_> = (interval.0..interval.1).map(|r| pos[r]).collect();
+/// positions.sort();
+/// assert_eq!(positions, [3, 9, 12]);
+/// assert_eq!(child_table.find(text, &pos, b"GGG"), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildTable {
+    // For each entry, 0 denotes an undefined value. This is unambiguous since up values are
+    // always preceded by the start of the text, and down and next l-index values point to
+    // larger entries.
+    up: Vec<usize>,
+    down: Vec<usize>,
+    next: Vec<usize>,
+}
+
+impl ChildTable {
+    /// Construct the child table from the lcp array (see `lcp`) of a text of length n.
+    /// Complexity: O(n).
+    pub fn new(lcp: &LCPArray) -> Self {
+        let lcp = lcp.decompress();
+        let (mut up, mut down, mut next) =
+            (vec![0; lcp.len()], vec![0; lcp.len()], vec![0; lcp.len()]);
+
+        // up and down values
+        let mut last = None;
+        let mut stack = vec![0];
+        for i in 1..lcp.len() {
+            while lcp[i] < lcp[*stack.last().unwrap()] {
+                let popped = stack.pop().unwrap();
+                let top = *stack.last().unwrap();
+                if lcp[i] <= lcp[top] && lcp[top] != lcp[popped] {
+                    down[top] = popped;
+                }
+                last = Some(popped);
+            }
+            if let Some(popped) = last.take() {
+                up[i] = popped;
+            }
+            stack.push(i);
+        }
+
+        // next l-index values
+        let mut stack = vec![0];
+        for i in 1..lcp.len() {
+            while lcp[i] < lcp[*stack.last().unwrap()] {
+                stack.pop();
+            }
+            let top = *stack.last().unwrap();
+            if lcp[i] == lcp[top] {
+                next[top] = i;
+                stack.pop();
+            }
+            stack.push(i);
+        }
+
+        ChildTable { up, down, next }
+    }
+
+    /// The interval of all suffixes, i.e. the root of the lcp-interval tree.
+    pub fn root(&self) -> (usize, usize) {
+        (0, self.up.len() - 1)
+    }
+
+    /// Iterate over the child intervals of the given lcp-interval, in lexicographical order.
+    /// The children partition the interval. Singleton intervals are leaves without children.
+    /// The interval is expected to be the root or a child interval obtained from this method.
+    pub fn interval_children(&self, interval: (usize, usize)) -> IntervalChildren<'_> {
+        let (lower, upper) = interval;
+        assert!(
+            upper < self.up.len(),
+            "Expecting interval within the suffix array."
+        );
+        let first = if upper - lower < 2 {
+            None
+        } else if lower < self.up[upper] && self.up[upper] < upper {
+            Some(self.up[upper])
+        } else {
+            Some(self.down[lower])
+        };
+
+        IntervalChildren {
+            child_table: self,
+            lower,
+            upper,
+            l_index: first,
+        }
+    }
+
+    /// Find the interval of suffixes starting with the given pattern by descending the
+    /// lcp-interval tree. Returns `None` if the pattern does not occur.
+    /// The sentinel only matches as the last symbol of the pattern.
+    /// Complexity: O(m * A) for pattern length m and alphabet size A.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - the text this child table was built for
+    /// * `pos` - the suffix array of the text
+    /// * `pattern` - the pattern to search
+    pub fn find<SA: SuffixArray>(
+        &self,
+        text: &[u8],
+        pos: &SA,
+        pattern: &[u8],
+    ) -> Option<(usize, usize)> {
+        assert_eq!(text.len() + 1, self.up.len());
+        let sentinel = sentinel(text);
+        let m = pattern.len();
+        let (mut lower, mut upper) = self.root();
+        let mut depth = 0;
+        while depth < m {
+            if upper - lower == 1 {
+                let p = pos.get(lower).unwrap();
+                let matches = (depth..m).all(|i| {
+                    p + i < text.len()
+                        && text[p + i] == pattern[i]
+                        && (text[p + i] != sentinel || i + 1 == m)
+                });
+                return if matches { Some((lower, upper)) } else { None };
+            }
+
+            // extend the common prefix of the first and the last suffix of the interval,
+            // which is exactly the lcp value of the interval
+            let (first, last) = (pos.get(lower).unwrap(), pos.get(upper - 1).unwrap());
+            while depth < m
+                && text[first + depth] == text[last + depth]
+                && text[first + depth] != sentinel
+            {
+                if text[first + depth] != pattern[depth] {
+                    return None;
+                }
+                depth += 1;
+            }
+            if depth == m {
+                break;
+            }
+
+            // select the children continuing with the next pattern symbol
+            let a = pattern[depth];
+            let mut selected: Option<(usize, usize)> = None;
+            for (l, u) in self.interval_children((lower, upper)) {
+                if text[pos.get(l).unwrap() + depth] == a {
+                    // only sentinels can start multiple children
+                    selected = Some((selected.map_or(l, |s| s.0), u));
+                } else if selected.is_some() {
+                    break;
+                }
+            }
+            let (l, u) = selected?;
+            if a == sentinel {
+                return if depth + 1 == m { Some((l, u)) } else { None };
+            }
+            lower = l;
+            upper = u;
+            depth += 1;
+        }
+
+        Some((lower, upper))
+    }
+}
+
+/// Iterator over the child intervals of an lcp-interval, see `ChildTable::interval_children`.
+pub struct IntervalChildren<'a> {
+    child_table: &'a ChildTable,
+    lower: usize,
+    upper: usize,
+    l_index: Option<usize>,
+}
+
+impl<'a> Iterator for IntervalChildren<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let l_index = self.l_index?;
+        let child = (self.lower, l_index);
+        if l_index == self.upper {
+            self.l_index = None;
+        } else {
+            let next = self.child_table.next[l_index];
+            self.l_index = Some(if next == 0 || next >= self.upper {
+                self.upper
+            } else {
+                next
+            });
+            self.lower = l_index;
+        }
+        Some(child)
+    }
 }
 
 /// Return last character of the text (expected to be the sentinel).
@@ -336,15 +1919,19 @@ fn sentinel(text: &[u8]) -> u8 {
     text[text.len() - 1]
 }
 
-/// Count the sentinels occurring in the text given that the last character is the sentinel.
-fn sentinel_count(text: &[u8]) -> usize {
+/// Check that the last character of the text is its lexicographically smallest symbol.
+pub(crate) fn check_sentinel(text: &[u8]) {
     let sentinel = sentinel(text);
     assert!(
         text.iter().all(|&a| a >= sentinel),
         "Expecting extra sentinel symbol being lexicographically smallest at the end of the \
          text."
     );
+}
 
+/// Count the sentinels occurring in the text given that the last character is the sentinel.
+fn sentinel_count(text: &[u8]) -> usize {
+    let sentinel = sentinel(text);
     text.iter()
         .fold(0, |count, &a| count + (a == sentinel) as usize)
 }
@@ -357,7 +1944,7 @@ fn transform_text<T: Integer + Unsigned + NumCast + Copy + Debug>(
 ) -> Vec<T> {
     let sentinel = sentinel(text);
     let transform = RankTransform::new(alphabet);
-    let offset = sentinel_count - 1;
+    let sentinel_rank = *transform.ranks.get(sentinel as usize).unwrap() as usize;
 
     let mut transformed: Vec<T> = Vec::with_capacity(text.len());
     let mut s = sentinel_count;
@@ -366,37 +1953,127 @@ fn transform_text<T: Integer + Unsigned + NumCast + Copy + Debug>(
             s -= 1;
             transformed.push(cast(s).unwrap());
         } else {
-            transformed
-                .push(cast(*(transform.ranks.get(a as usize)).unwrap() as usize + offset).unwrap());
+            // the sentinels take the smallest ranks, even if symbols are smaller than them
+            let rank = *(transform.ranks.get(a as usize)).unwrap() as usize;
+            let rank = if rank > sentinel_rank { rank - 1 } else { rank };
+            transformed.push(cast(rank + sentinel_count).unwrap());
         }
     }
 
     transformed
 }
 
+/// Reports the progress of `SAIS` to a callback. Each recursion level of SAIS covers a range of
+/// the overall fraction, split into the sorting of the LMS substrings, the recursion, and the
+/// final induction.
+struct Progress<'a> {
+    callback: &'a mut dyn FnMut(SaProgress) -> ControlFlow<()>,
+    /// the fractions covered by the current recursion level
+    level: (f64, f64),
+    /// the phase and fractions covered by the current step
+    step: (SaPhase, f64, f64),
+    depth: usize,
+    cancelled: bool,
+}
+
+impl<'a> Progress<'a> {
+    fn new(callback: &'a mut dyn FnMut(SaProgress) -> ControlFlow<()>) -> Self {
+        Progress {
+            callback,
+            level: (0.0, 1.0),
+            step: (SaPhase::Preparing, 0.0, 0.0),
+            depth: 0,
+            cancelled: false,
+        }
+    }
+
+    /// Begin a step covering the given fractions of the current recursion level.
+    fn enter(&mut self, phase: SaPhase, start: f64, end: f64) {
+        let (lo, hi) = self.level;
+        // below the top level, everything is part of sorting the LMS suffixes
+        let phase = if self.depth > 0 {
+            SaPhase::SortingLmsSuffixes
+        } else {
+            phase
+        };
+        self.step = (phase, lo + start * (hi - lo), lo + end * (hi - lo));
+    }
+
+    /// Report that the given fraction of the current step is done. Return whether the
+    /// construction shall continue.
+    fn tick(&mut self, done: f64) -> bool {
+        let (phase, start, end) = self.step;
+        let fraction = start + done * (end - start);
+        if (self.callback)(SaProgress { phase, fraction }).is_break() {
+            self.cancelled = true;
+        }
+        !self.cancelled
+    }
+
+    /// Descend into a recursion level covering the current step.
+    fn descend(&mut self) -> (f64, f64) {
+        let (_, start, end) = self.step;
+        self.depth += 1;
+        std::mem::replace(&mut self.level, (start, end))
+    }
+
+    /// Return to the given recursion level.
+    fn ascend(&mut self, level: (f64, f64)) {
+        self.depth -= 1;
+        self.level = level;
+    }
+}
+
 /// SAIS implementation (see function `suffix_array` for description).
-struct SAIS {
-    pos: Vec<usize>,
-    lms_pos: Vec<usize>,
-    reduced_text_pos: Vec<usize>,
+/// Positions are stored with the integer type `I`, such that texts shorter than
+/// `u32::MAX` can be sorted with half the memory.
+struct SAIS<'a, I> {
+    pos: Vec<I>,
+    lms_pos: Vec<I>,
+    reduced_text_pos: Vec<I>,
     bucket_sizes: VecMap<usize>,
     bucket_start: Vec<usize>,
     bucket_end: Vec<usize>,
+    progress: Option<Progress<'a>>,
 }
 
-impl SAIS {
+impl<'a, I: Integer + Unsigned + NumCast + Copy + Debug> SAIS<'a, I> {
     /// Create a new instance.
     fn new(n: usize) -> Self {
         SAIS {
             pos: Vec::with_capacity(n),
             lms_pos: Vec::with_capacity(n),
-            reduced_text_pos: vec![0; n],
+            reduced_text_pos: vec![I::zero(); n],
             bucket_sizes: VecMap::new(),
             bucket_start: Vec::with_capacity(n),
             bucket_end: Vec::with_capacity(n),
+            progress: None,
+        }
+    }
+
+    /// Begin a step of the construction, covering the given fractions of the current
+    /// recursion level.
+    fn enter(&mut self, phase: SaPhase, start: f64, end: f64) {
+        if let Some(ref mut progress) = self.progress {
+            progress.enter(phase, start, end);
+        }
+    }
+
+    /// Report the fraction done of the current step. Return whether to continue.
+    fn tick(&mut self, done: f64) -> bool {
+        match self.progress {
+            Some(ref mut progress) => progress.tick(done),
+            None => true,
         }
     }
 
+    /// Whether the construction has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.progress
+            .as_ref()
+            .is_some_and(|progress| progress.cancelled)
+    }
+
     /// Init buckets.
     fn init_bucket_start<T: Integer + Unsigned + NumCast + Copy>(&mut self, text: &[T]) {
         self.bucket_sizes.clear();
@@ -467,15 +2144,23 @@ impl SAIS {
             // sort LMS suffixes by recursively building SA on reduced text
             let mut reduced_text: Vec<S> = vec![cast(0).unwrap(); lms_substring_count];
             let mut label = 0;
-            reduced_text[self.reduced_text_pos[self.pos[0]]] = cast(label).unwrap();
+            let first: usize = cast(self.pos[0]).unwrap();
+            reduced_text[cast::<I, usize>(self.reduced_text_pos[first]).unwrap()] =
+                cast(label).unwrap();
             let mut prev = None;
-            for &p in &self.pos {
+            self.enter(SaPhase::SortingLmsSuffixes, 0.4, 0.6);
+            for r in 0..self.pos.len() {
+                if r % PROGRESS_INTERVAL == 0 && !self.tick(0.0) {
+                    return;
+                }
+                let p: usize = cast(self.pos[r]).unwrap();
                 if pos_types.is_lms_pos(p) {
                     // choose same label if substrings are equal
                     if prev.is_some() && !self.lms_substring_eq(text, pos_types, prev.unwrap(), p) {
                         label += 1;
                     }
-                    reduced_text[self.reduced_text_pos[p]] = cast(label).unwrap();
+                    reduced_text[cast::<I, usize>(self.reduced_text_pos[p]).unwrap()] =
+                        cast(label).unwrap();
                     prev = Some(p);
                 }
             }
@@ -486,18 +2171,25 @@ impl SAIS {
                 // backup lms_pos
                 let lms_pos = self.lms_pos.clone();
                 // recurse SA construction for reduced text
+                let level = self.progress.as_mut().map(|progress| progress.descend());
                 self.construct(&reduced_text);
+                if let Some(level) = level {
+                    self.progress.as_mut().unwrap().ascend(level);
+                }
+                if self.is_cancelled() {
+                    return;
+                }
                 // obtain sorted lms suffixes
                 self.lms_pos.clear();
                 for &p in &self.pos {
-                    self.lms_pos.push(lms_pos[p]);
+                    self.lms_pos.push(lms_pos[cast::<I, usize>(p).unwrap()]);
                 }
             } else {
                 // otherwise, lms_pos is updated with the sorted suffixes from pos
                 // obtain sorted lms suffixes
                 self.lms_pos.clear();
                 for &p in &self.pos {
-                    if pos_types.is_lms_pos(p) {
+                    if pos_types.is_lms_pos(cast(p).unwrap()) {
                         self.lms_pos.push(p);
                     }
                 }
@@ -509,6 +2201,10 @@ impl SAIS {
     fn construct<T: Integer + Unsigned + NumCast + Copy + Debug>(&mut self, text: &[T]) {
         let pos_types = PosTypes::new(text);
         self.calc_lms_pos(text, &pos_types);
+        if self.is_cancelled() {
+            return;
+        }
+        self.enter(SaPhase::InducingSuffixes, 0.6, 1.0);
         self.calc_pos(text, &pos_types);
     }
 
@@ -525,14 +2221,18 @@ impl SAIS {
         let mut i = 0;
         for r in 0..n {
             if pos_types.is_lms_pos(r) {
-                self.lms_pos.push(r);
-                self.reduced_text_pos[r] = i;
+                self.lms_pos.push(cast(r).unwrap());
+                self.reduced_text_pos[r] = cast(i).unwrap();
                 i += 1;
             }
         }
 
         // sort LMS substrings by applying step 2 with unsorted LMS positions
+        self.enter(SaPhase::SortingLmsSubstrings, 0.0, 0.4);
         self.calc_pos(text, pos_types);
+        if self.is_cancelled() {
+            return;
+        }
 
         let lms_substring_count = self.lms_pos.len();
 
@@ -554,6 +2254,7 @@ impl SAIS {
         pos_types: &PosTypes,
     ) {
         let n = text.len();
+        let undefined: I = cast(n).unwrap();
         self.pos.clear();
 
         self.init_bucket_start(text);
@@ -561,12 +2262,12 @@ impl SAIS {
 
         // init all positions as unknown (n-1 is max position)
         for _ in text.iter() {
-            self.pos.push(n);
+            self.pos.push(undefined);
         }
 
         // insert LMS positions to the end of their buckets
         for &p in self.lms_pos.iter().rev() {
-            let c: usize = cast(text[p]).unwrap();
+            let c: usize = cast(text[cast::<I, usize>(p).unwrap()]).unwrap();
             self.pos[self.bucket_end[c]] = p;
             // subtract without overflow: last -1 will cause overflow, but it does not matter
             self.bucket_end[c] = self.bucket_end[c].wrapping_sub(1);
@@ -577,7 +2278,10 @@ impl SAIS {
 
         // insert L-positions into buckets
         for r in 0..n {
-            let p = self.pos[r];
+            if r % PROGRESS_INTERVAL == 0 && !self.tick(r as f64 / (2 * n) as f64) {
+                return;
+            }
+            let p: usize = cast(self.pos[r]).unwrap();
             // ignore undefined positions and the zero since it has no predecessor
             if p == n || p == 0 {
                 continue;
@@ -585,21 +2289,24 @@ impl SAIS {
             let pred = p - 1;
             if pos_types.is_l_pos(pred) {
                 let c: usize = cast(text[pred]).unwrap();
-                self.pos[self.bucket_start[c]] = pred;
+                self.pos[self.bucket_start[c]] = cast(pred).unwrap();
                 self.bucket_start[c] += 1;
             }
         }
 
         // insert S-positions into buckets
         for r in (0..n).rev() {
-            let p = self.pos[r];
+            if r % PROGRESS_INTERVAL == 0 && !self.tick((2 * n - r) as f64 / (2 * n) as f64) {
+                return;
+            }
+            let p: usize = cast(self.pos[r]).unwrap();
             if p == 0 {
                 continue;
             }
             let pred = p - 1;
             if pos_types.is_s_pos(pred) {
                 let c: usize = cast(text[pred]).unwrap();
-                self.pos[self.bucket_end[c]] = pred;
+                self.pos[self.bucket_end[c]] = cast(pred).unwrap();
                 // subtract without overflow: last -1 will cause overflow, but it won't be used
                 self.bucket_end[c] = self.bucket_end[c].wrapping_sub(1);
             }
@@ -665,10 +2372,412 @@ mod tests {
     use super::*;
     use super::{transform_text, PosTypes, SAIS};
     use crate::alphabets::Alphabet;
+    use crate::data_structures::bwt::{bwt, less, Occ};
+    use crate::data_structures::fmindex::{FMIndex, FMIndexable};
     use bv::{BitVec, BitsPush};
+    use rand::{Rng, SeedableRng, StdRng};
     //use data_structures::bwt::{bwt, less, Occ};
+    use std::io;
+    use std::ops::ControlFlow;
     use std::str;
 
+    #[test]
+    fn test_compact_suffix_array() {
+        let text = b"GCCTTAACATTATTACGCCTA$ACGT$";
+        let sa = suffix_array(text);
+        let compact = CompactSuffixArray::new(text);
+        assert!(compact.is_u32());
+        assert_eq!(compact, CompactSuffixArray::new_u32(text).unwrap());
+        assert_eq!(compact.len(), sa.len());
+        for i in 0..sa.len() {
+            assert_eq!(compact.get(i), sa.get(i));
+        }
+        assert_eq!(compact.get(sa.len()), None);
+        assert_eq!(compact.to_vec(), sa);
+        assert_eq!(CompactSuffixArray::U64(sa.clone()).to_vec(), sa);
+
+        assert!(check_u32_len(u32::MAX as usize).is_ok());
+        assert_eq!(
+            check_u32_len(u32::MAX as usize + 1),
+            Err(SuffixArrayError::TextTooLong(u32::MAX as usize + 1))
+        );
+    }
+
+    #[test]
+    fn test_try_suffix_array() {
+        let text = b"GCCTTAACATTATTACGCCTA$ACGT$T$";
+        assert_eq!(try_suffix_array(text), Ok(suffix_array(text)));
+        assert_eq!(try_suffix_array(b"$"), Ok(vec![0]));
+        assert_eq!(
+            try_suffix_array(b""),
+            Err(SuffixArrayError::MissingSentinel)
+        );
+        assert_eq!(
+            try_suffix_array(b"ACGT$A"),
+            Err(SuffixArrayError::MissingSentinel)
+        );
+        assert_eq!(
+            try_suffix_array(b"ACG\nT$"),
+            Err(SuffixArrayError::SymbolBelowSentinel(b'\n', 3))
+        );
+        assert_eq!(
+            try_suffix_array(b"ACGT$$A$"),
+            Err(SuffixArrayError::ConsecutiveSentinels(4))
+        );
+        assert_eq!(
+            try_suffix_array(b"ACGT$$"),
+            Err(SuffixArrayError::ConsecutiveSentinels(4))
+        );
+    }
+
+    #[test]
+    fn test_suffix_array_with_sentinel() {
+        let mut rng = StdRng::from_seed(&[59]);
+        for &sentinel in [0u8, b'$', 0x80, 0xff].iter() {
+            for _ in 0..20 {
+                let mut text = Vec::new();
+                for _ in 0..rng.gen_range(1, 4) {
+                    for _ in 0..rng.gen_range(1, 50) {
+                        let a = loop {
+                            let a = *rng.choose(&[0, 1, b'$', 0x80, 0xfe, 0xff]).unwrap();
+                            if a != sentinel {
+                                break a;
+                            }
+                        };
+                        text.push(a);
+                    }
+                    text.push(sentinel);
+                }
+                // the naive order, with sentinels first and ordered by decreasing position
+                let key = |p: usize| -> Vec<(bool, usize)> {
+                    (p..text.len())
+                        .map(|q| match text[q] {
+                            a if a == sentinel => (false, usize::MAX - q),
+                            a => (true, a as usize),
+                        })
+                        .collect()
+                };
+                let mut expected: Vec<usize> = (0..text.len()).collect();
+                expected.sort_by_key(|&p| key(p));
+                assert_eq!(suffix_array_with_sentinel(&text, sentinel), Ok(expected));
+            }
+        }
+        assert_eq!(
+            suffix_array_with_sentinel(&[1, 0, 0], 0),
+            Err(SuffixArrayError::ConsecutiveSentinels(1))
+        );
+    }
+
+    #[test]
+    fn test_write_read_raw() {
+        let mut rng = StdRng::from_seed(&[67]);
+        let mut text: Vec<u8> = (0..300).map(|_| *rng.choose(b"ACGT").unwrap()).collect();
+        let repeat = text.clone();
+        text.extend(repeat);
+        text.push(b'$');
+
+        let sa = CompactSuffixArray::new(&text);
+        let long = CompactSuffixArray::U64(sa.to_vec());
+        for sa in [sa, long].iter() {
+            let mut buffer = Vec::new();
+            sa.write_to(&mut buffer).unwrap();
+            assert_eq!(&CompactSuffixArray::read_from(&buffer[..]).unwrap(), sa);
+
+            let err = CompactSuffixArray::read_from(&buffer[..buffer.len() - 1]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            let err = CompactSuffixArray::read_from(&buffer[..5]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            buffer[0] = 3;
+            let err = CompactSuffixArray::read_from(&buffer[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        // the repeat yields entries that need more than 8 bit
+        let lcp_array = lcp(&text, suffix_array(&text));
+        let mut buffer = Vec::new();
+        lcp_array.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 9 + 2 * lcp_array.len());
+        let read = LcpArray::read_from(&buffer[..]).unwrap();
+        assert_eq!(read.decompress(), lcp_array.decompress());
+        assert_eq!(
+            LcpArray::read_from(&buffer[..buffer.len() - 1])
+                .err()
+                .map(|err| err.kind()),
+            Some(io::ErrorKind::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let mut rng = StdRng::from_seed(&[71]);
+        let alphabet = Alphabet::new(b"ACGT$");
+        let texts: Vec<Vec<u8>> = vec![
+            b"GCCTTAACATTATTACGCCTA$".to_vec(),
+            b"ACGT$A$TTGCA$".to_vec(),
+            b"$".to_vec(),
+            (0..500)
+                .map(|_| *rng.choose(b"AC").unwrap())
+                .chain(Some(b'$'))
+                .collect(),
+        ];
+        for text in &texts {
+            let pos = suffix_array(text);
+            let lcp_array = lcp(text, &pos);
+            let bwt = bwt(text, &pos);
+            let less = less(&bwt, &alphabet);
+            let occ = Occ::new(&bwt, 3, &alphabet);
+            let fm = FMIndex::new(&bwt, &less, &occ);
+
+            // the empty pattern, an absent one, and one longer than the text
+            let mut patterns: Vec<Vec<u8>> =
+                vec![b"".to_vec(), b"GGG".to_vec(), vec![b'A'; text.len() + 1]];
+            for _ in 0..50 {
+                let len = rng.gen_range(1, 12);
+                patterns.push((0..len).map(|_| *rng.choose(b"ACGT").unwrap()).collect());
+            }
+            for pattern in patterns.iter().filter(|pattern| !pattern.contains(&b'$')) {
+                let interval = fm.backward_search(pattern.iter());
+                let (lower, upper) = search(text, &pos, pattern);
+                assert_eq!(upper - lower, interval.upper.saturating_sub(interval.lower));
+                if lower < upper {
+                    assert_eq!((lower, upper), (interval.lower, interval.upper));
+                }
+                assert_eq!(
+                    search_with_lcp(text, &pos, &lcp_array, pattern),
+                    (lower, upper)
+                );
+
+                let expected: Vec<usize> = (0..text.len())
+                    .filter(|&p| text[p..].starts_with(pattern))
+                    .collect();
+                assert_eq!(search_positions(text, &pos, pattern), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plcp() {
+        let mut rng = StdRng::from_seed(&[73]);
+        for _ in 0..50 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 4) {
+                for _ in 0..rng.gen_range(0, 200) {
+                    text.push(*rng.choose(b"ACG").unwrap());
+                }
+                text.push(b'$');
+            }
+            let pos = suffix_array(&text);
+            let plcp = plcp(&text, &pos);
+            let kasai = lcp(&text, &pos);
+            for r in 1..text.len() {
+                assert_eq!(plcp[pos[r]] as isize, kasai.get(r).unwrap());
+            }
+            assert_eq!(plcp_to_lcp(&plcp, &pos).decompress(), kasai.decompress());
+        }
+
+        // a single repeated symbol yields the largest possible lcp values
+        let mut text = vec![b'A'; 1000];
+        text.push(b'$');
+        let pos = suffix_array(&text);
+        let plcp = plcp(&text, &pos);
+        assert_eq!(plcp[..1000], (0..1000).rev().collect::<Vec<_>>()[..]);
+        assert_eq!(plcp[1000], 0);
+        assert_eq!(
+            plcp_to_lcp(&plcp, &pos).decompress(),
+            lcp(&text, &pos).decompress()
+        );
+    }
+
+    #[test]
+    fn test_inverse_suffix_array() {
+        let mut rng = StdRng::from_seed(&[31]);
+        for n in 0..50 {
+            let mut perm: Vec<usize> = (0..n).collect();
+            rng.shuffle(&mut perm);
+            let isa = inverse_suffix_array(&perm);
+            assert!((0..n).all(|r| isa[perm[r]] == r && perm[isa[r]] == r));
+
+            let mut text: Vec<u8> = (0..n).map(|_| *rng.choose(b"ACGT").unwrap()).collect();
+            text.push(b'$');
+            let sa = CompactSuffixArray::new(&text);
+            let isa = inverse_suffix_array(&sa);
+            assert!((0..=n).all(|r| isa[sa.get(r).unwrap()] == r));
+            assert_eq!(sa.inverse().to_vec(), isa);
+            assert!(sa.inverse().is_u32());
+            assert_eq!(
+                CompactSuffixArray::U64(sa.to_vec()).inverse(),
+                CompactSuffixArray::U64(isa)
+            );
+        }
+    }
+
+    #[test]
+    fn test_suffix_array_with_progress() {
+        let mut rng = StdRng::from_seed(&[53]);
+        let mut text: Vec<u8> = (0..1 << 18)
+            .map(|_| *rng.choose(b"ACGT").unwrap())
+            .collect();
+        text.push(b'$');
+
+        let mut reports: Vec<SaProgress> = Vec::new();
+        let pos = suffix_array_with_progress(&text, |progress| {
+            reports.push(progress);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(pos, Some(suffix_array(&text)));
+        assert!(reports.len() > 2 * (text.len() / PROGRESS_INTERVAL));
+        assert!(reports.windows(2).all(|w| w[0].fraction <= w[1].fraction));
+        assert_eq!(reports[0].phase, SaPhase::Preparing);
+        assert_eq!(reports.last().unwrap().fraction, 1.0);
+        for phase in [
+            SaPhase::SortingLmsSubstrings,
+            SaPhase::SortingLmsSuffixes,
+            SaPhase::InducingSuffixes,
+        ]
+        .iter()
+        {
+            assert!(reports.iter().any(|progress| progress.phase == *phase));
+        }
+
+        // cancel after the first invocation, and while inducing the suffixes
+        let mut calls = 0;
+        let pos = suffix_array_with_progress(&text, |_| {
+            calls += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!((pos, calls), (None, 1));
+
+        let mut calls_after_cancel = 0;
+        let mut cancelled = false;
+        let pos = suffix_array_with_progress(&text, |progress| {
+            if cancelled {
+                calls_after_cancel += 1;
+            }
+            if progress.phase == SaPhase::InducingSuffixes {
+                cancelled = true;
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        });
+        assert_eq!((pos, calls_after_cancel), (None, 0));
+    }
+
+    #[test]
+    fn test_sparse_suffix_array() {
+        let mut rng = StdRng::from_seed(&[41]);
+        for _ in 0..50 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 4) {
+                for _ in 0..rng.gen_range(0, 100) {
+                    text.push(*rng.choose(b"AC").unwrap());
+                }
+                text.push(b'$');
+            }
+            let sa = suffix_array(&text);
+            for s in 1..6 {
+                let sparse = sparse_suffix_array(&text, s);
+                let expected: Vec<usize> = sa.iter().cloned().filter(|p| p % s == 0).collect();
+                assert_eq!(sparse, expected);
+
+                let ranks: Vec<usize> =
+                    (0..sa.len()).filter(|&r| sa[r].is_multiple_of(s)).collect();
+                assert_eq!(sparse_ranks(&text, s, &sparse), ranks);
+
+                // rows of arbitrary positions, i.e. the inverse suffix array
+                let positions: Vec<usize> = (0..text.len()).rev().collect();
+                let mut rows = vec![0; text.len()];
+                for (r, &p) in sa.iter().enumerate() {
+                    rows[p] = r;
+                }
+                let expected: Vec<usize> = positions.iter().map(|&p| rows[p]).collect();
+                assert_eq!(sparse_ranks(&text, s, &positions), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse_suffix_array_homopolymer() {
+        let mut text = vec![b'A'; 20000];
+        text.push(b'$');
+        text.extend_from_slice(b"AAAA$");
+        let sa = suffix_array(&text);
+        for &s in &[1, 4, 7] {
+            let sparse = sparse_suffix_array(&text, s);
+            let expected: Vec<usize> = sa.iter().cloned().filter(|p| p % s == 0).collect();
+            assert_eq!(sparse, expected);
+        }
+        let sparse = sparse_suffix_array(&text, 32);
+        let ranks: Vec<usize> = (0..sa.len())
+            .filter(|&r| sa[r].is_multiple_of(32))
+            .collect();
+        assert_eq!(sparse_ranks(&text, 32, &sparse), ranks);
+    }
+
+    #[test]
+    fn test_verify_suffix_array() {
+        let mut rng = StdRng::from_seed(&[37]);
+        for _ in 0..50 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 4) {
+                for _ in 0..rng.gen_range(0, 200) {
+                    text.push(*rng.choose(b"ACGT").unwrap());
+                }
+                text.push(b'$');
+            }
+            let pos = suffix_array(&text);
+            assert_eq!(verify_suffix_array(&text, &pos), Ok(()));
+            assert_eq!(
+                verify_suffix_array(&text, &CompactSuffixArray::new(&text)),
+                Ok(())
+            );
+
+            if text.len() > 1 {
+                let r = rng.gen_range(1, text.len());
+                let mut swapped = pos.clone();
+                swapped.swap(r - 1, r);
+                // the swap also affects the ranks used for comparing earlier suffixes
+                match verify_suffix_array(&text, &swapped) {
+                    Err(SuffixArrayError::Unsorted(rank)) => assert!(rank <= r),
+                    result => panic!("unexpected result {:?}", result),
+                }
+            }
+        }
+
+        let text = b"ACGT$GA$";
+        let pos = suffix_array(text);
+        assert_eq!(
+            verify_suffix_array(text, &pos[1..].to_vec()),
+            Err(SuffixArrayError::InvalidLength(7, 8))
+        );
+        let mut duplicate = pos.clone();
+        duplicate[5] = duplicate[2];
+        assert_eq!(
+            verify_suffix_array(text, &duplicate),
+            Err(SuffixArrayError::NotAPermutation(5))
+        );
+        let mut out_of_range = pos.clone();
+        out_of_range[0] = 8;
+        assert_eq!(
+            verify_suffix_array(text, &out_of_range),
+            Err(SuffixArrayError::NotAPermutation(0))
+        );
+        // sentinels in increasing order of their positions
+        let mut sentinels = pos.clone();
+        sentinels.swap(0, 1);
+        assert_eq!(
+            verify_suffix_array(text, &sentinels),
+            Err(SuffixArrayError::Unsorted(1))
+        );
+        assert_eq!(verify_suffix_array(b"", &Vec::new()), Ok(()));
+
+        // a multi-megabyte text is checked quickly
+        let mut text: Vec<u8> = (0..2_000_000)
+            .map(|_| *rng.choose(b"ACGT").unwrap())
+            .collect();
+        text.push(b'$');
+        assert_eq!(verify_suffix_array(&text, &suffix_array(&text)), Ok(()));
+    }
+
     #[test]
     fn test_pos_types() {
         let orig_text = b"GCCTTAACATTATTACGCCTA$";
@@ -693,7 +2802,7 @@ mod tests {
         let text: Vec<u8> = transform_text(orig_text, &alphabet, 1);
         let n = text.len();
 
-        let mut sais = SAIS::new(n);
+        let mut sais = SAIS::<usize>::new(n);
         sais.init_bucket_start(&text);
         assert_eq!(sais.bucket_start, vec![0, 1, 7, 13, 15]);
         sais.init_bucket_end(&text);
@@ -707,7 +2816,7 @@ mod tests {
         let text: Vec<u8> = transform_text(orig_text, &alphabet, 1);
         let n = text.len();
 
-        let mut sais = SAIS::new(n);
+        let mut sais = SAIS::<usize>::new(n);
         let pos_types = PosTypes::new(&text);
         sais.lms_pos = vec![21, 5, 14, 8, 11, 17, 1];
         sais.calc_pos(&text, &pos_types);
@@ -724,7 +2833,7 @@ mod tests {
         let text: Vec<u8> = transform_text(orig_text, &alphabet, 1);
         let n = text.len();
 
-        let mut sais = SAIS::new(n);
+        let mut sais = SAIS::<usize>::new(n);
         let pos_types = PosTypes::new(&text);
         sais.calc_lms_pos(&text, &pos_types);
     }
@@ -759,6 +2868,377 @@ mod tests {
         ) + "$"
     }
 
+    fn naive_lcp(text: &[u8], pos: &RawSuffixArray) -> Vec<isize> {
+        let sentinel = *text.last().unwrap();
+        let mut lcp = vec![-1];
+        for r in 1..pos.len() {
+            let l = text[pos[r - 1]..]
+                .iter()
+                .zip(&text[pos[r]..])
+                .take_while(|&(&a, &b)| a == b && a != sentinel)
+                .count();
+            lcp.push(l as isize);
+        }
+        lcp.push(-1);
+        lcp
+    }
+
+    #[test]
+    fn test_lcp() {
+        let text = b"GCCTTAACAT$";
+        let pos = suffix_array(text);
+        let fixture = lcp(text, &pos);
+        assert_eq!(fixture.len(), text.len() + 1);
+        assert_eq!(fixture.decompress(), [-1, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, -1]);
+        assert_eq!(fixture.decompress(), naive_lcp(text, &pos));
+        assert_eq!(fixture.iter().collect::<Vec<_>>(), fixture.decompress());
+        assert_eq!(fixture.get(text.len() + 1), None);
+        // the suffix array may also be passed by value
+        assert_eq!(lcp(text, pos.clone()).decompress(), fixture.decompress());
+
+        let mut rng = StdRng::from_seed(&[42]);
+        for _ in 0..100 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 5) {
+                for _ in 0..rng.gen_range(0, 60) {
+                    text.push(*rng.choose(b"ACGT").unwrap());
+                }
+                text.push(b'$');
+            }
+            let pos = suffix_array(&text);
+            let sentinels = text.iter().filter(|&&a| a == b'$').count();
+            let lcp = lcp(&text, CompactSuffixArray::new(&text));
+            assert_eq!(lcp.decompress(), naive_lcp(&text, &pos));
+            assert!((1..=sentinels).all(|r| lcp.get(r) == Some(0)));
+        }
+    }
+
+    /// Child intervals by splitting at the minimal lcp values of the interval.
+    fn naive_children(lcp: &[isize], lower: usize, upper: usize) -> Vec<(usize, usize)> {
+        if upper - lower < 2 {
+            return Vec::new();
+        }
+        let l = *lcp[lower + 1..upper].iter().min().unwrap();
+        let mut children = Vec::new();
+        let mut start = lower;
+        for (k, &v) in lcp.iter().enumerate().take(upper).skip(lower + 1) {
+            if v == l {
+                children.push((start, k));
+                start = k;
+            }
+        }
+        children.push((start, upper));
+        children
+    }
+
+    #[test]
+    fn test_child_table() {
+        let mut rng = StdRng::from_seed(&[7]);
+        let mut texts = vec![
+            b"GCCTTAACAT$".to_vec(),
+            b"AAAAAAAA$".to_vec(),
+            b"$".to_vec(),
+        ];
+        for _ in 0..50 {
+            let mut text = Vec::new();
+            for _ in 0..rng.gen_range(1, 4) {
+                for _ in 0..rng.gen_range(0, 80) {
+                    text.push(*rng.choose(b"ACGT").unwrap());
+                }
+                text.push(b'$');
+            }
+            texts.push(text);
+        }
+        for text in &texts {
+            let pos = suffix_array(text);
+            let lcp_array = lcp(text, &pos);
+            let child_table = ChildTable::new(&lcp_array);
+            let lcp_values = lcp_array.decompress();
+
+            // the children of the root partition the suffix array
+            let root = child_table.root();
+            assert_eq!(root, (0, text.len()));
+            let children: Vec<_> = child_table.interval_children(root).collect();
+            if text.len() > 1 {
+                assert_eq!(children.first().unwrap().0, 0);
+                assert_eq!(children.last().unwrap().1, text.len());
+                assert!(children.windows(2).all(|w| w[0].1 == w[1].0));
+            } else {
+                assert!(children.is_empty());
+            }
+
+            let mut intervals = vec![root];
+            while let Some((lower, upper)) = intervals.pop() {
+                let children: Vec<_> = child_table.interval_children((lower, upper)).collect();
+                assert_eq!(children, naive_children(&lcp_values, lower, upper));
+                intervals.extend(children);
+            }
+        }
+    }
+
+    #[test]
+    fn test_child_table_find() {
+        let texts: [&[u8]; 3] = [
+            b"GCCTTAACATTATTACGCCTA$",
+            b"ACGTACGTTTACG$AACGAT$CCTTACG$",
+            b"AAAAAAAA$",
+        ];
+        let alphabet = Alphabet::new(b"$ACGT");
+        for &text in texts.iter() {
+            let pos = suffix_array(text);
+            let child_table = ChildTable::new(&lcp(text, &pos));
+            let bwt = bwt(text, &pos);
+            let less = less(&bwt, &alphabet);
+            let occ = Occ::new(&bwt, 3, &alphabet);
+            let fm = FMIndex::new(&bwt, &less, &occ);
+            for pattern in [
+                &b"A"[..],
+                b"ACG",
+                b"TTA",
+                b"CCT",
+                b"AAAA",
+                b"ACGTACGTTTACG",
+                b"G$",
+                b"$",
+                b"GGG",
+                b"TACGA",
+                b"",
+            ]
+            .iter()
+            {
+                let interval = fm.backward_search(pattern.iter());
+                let mut expected = interval.occ(&pos);
+                expected.sort();
+                let mut found = child_table
+                    .find(text, &pos, pattern)
+                    .map_or(Vec::new(), |(lower, upper)| pos[lower..upper].to_vec());
+                found.sort();
+                assert_eq!(found, expected, "pattern {:?}", str::from_utf8(pattern));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generalized_suffix_array() {
+        let seqs: [&[u8]; 3] = [b"ACGTACG", b"TACGG", b"GTAC"];
+        let (text, pos, docs) = generalized_suffix_array(&seqs);
+        assert_eq!(text, b"ACGTACG$TACGG$GTAC$");
+        assert_eq!(pos, suffix_array(&text));
+        assert_eq!(docs.num_seqs(), 3);
+
+        // boundaries
+        assert_eq!(docs.seq_of(0), (0, 0));
+        assert_eq!(docs.seq_of(6), (0, 6));
+        assert_eq!(docs.seq_of(7), (0, 7));
+        assert_eq!(docs.seq_of(8), (1, 0));
+        assert_eq!(docs.seq_of(13), (1, 5));
+        assert_eq!(docs.seq_of(14), (2, 0));
+        assert_eq!(docs.seq_of(18), (2, 4));
+        for (p, &a) in text.iter().enumerate() {
+            let (seq, offset) = docs.seq_of(p);
+            assert_eq!(seqs[seq].get(offset).cloned().unwrap_or(b'$'), a);
+        }
+
+        let child_table = ChildTable::new(&lcp(&text, &pos));
+        for &(pattern, expected) in [
+            (&b"TAC"[..], &[0, 1, 2][..]),
+            (b"ACGG", &[1]),
+            (b"GTA", &[0, 2]),
+            // would match G$T across the boundary of the first two sequences
+            (b"GT", &[0, 2]),
+            (b"CGGT", &[]),
+        ]
+        .iter()
+        {
+            let found = child_table
+                .find(&text, &pos, pattern)
+                .map_or(Vec::new(), |(lower, upper)| {
+                    docs.seqs_of_interval(&pos, lower, upper)
+                });
+            assert_eq!(found, expected);
+        }
+
+        let (text, pos, docs) = generalized_suffix_array(&[]);
+        assert!(text.is_empty() && pos.is_empty());
+        assert_eq!(docs.num_seqs(), 0);
+    }
+
+    /// All longest common substrings by comparing all pairs of starts.
+    fn naive_longest_common_substring(a: &[u8], b: &[u8]) -> Vec<(usize, usize, usize)> {
+        let mut hits = Vec::new();
+        let mut best = 1;
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                let len = a[i..]
+                    .iter()
+                    .zip(&b[j..])
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                if len > best {
+                    best = len;
+                    hits.clear();
+                }
+                if len == best {
+                    hits.push((i, j, len));
+                }
+            }
+        }
+
+        hits
+    }
+
+    #[test]
+    fn test_longest_common_substring() {
+        // only single symbols in common
+        assert_eq!(
+            longest_common_substring(b"ACAC", b"GGAT"),
+            [(0, 2, 1), (2, 2, 1)]
+        );
+        assert!(longest_common_substring(b"AAAA", b"CGT").is_empty());
+        assert!(longest_common_substring(b"", b"CGT").is_empty());
+        // at the start and end of the sequences
+        assert_eq!(
+            longest_common_substring(b"GATTACA", b"TTTCCGAT"),
+            [(0, 5, 3)]
+        );
+        assert_eq!(longest_common_substring(b"CCGTGA", b"TGACC"), [(3, 0, 3)]);
+        assert_eq!(longest_common_substring(b"ACGT", b"ACGT"), [(0, 0, 4)]);
+        // multiple co-optimal hits, also of the same substring
+        assert_eq!(
+            longest_common_substring(b"ACGTTTGCA", b"GCATTACG"),
+            [(0, 5, 3), (6, 0, 3)]
+        );
+        assert_eq!(
+            longest_common_substring(b"AAA", b"AA"),
+            [(0, 0, 2), (1, 0, 2)]
+        );
+
+        let mut rng = StdRng::from_seed(&[13]);
+        for _ in 0..200 {
+            let a: Vec<u8> = (0..rng.gen_range(0, 40))
+                .map(|_| *rng.choose(b"ACG").unwrap())
+                .collect();
+            let b: Vec<u8> = (0..rng.gen_range(0, 40))
+                .map(|_| *rng.choose(b"ACGT").unwrap())
+                .collect();
+            assert_eq!(
+                longest_common_substring(&a, &b),
+                naive_longest_common_substring(&a, &b)
+            );
+        }
+    }
+
+    /// Check all substrings of a for occurring exactly once in either sequence without the
+    /// occurrences being extensible to either side.
+    fn naive_mums(a: &[u8], b: &[u8], min_len: usize) -> Vec<Mum> {
+        let occurrences = |text: &[u8], w: &[u8]| -> Vec<usize> {
+            (0..(text.len() + 1).saturating_sub(w.len()))
+                .filter(|&p| &text[p..p + w.len()] == w)
+                .collect()
+        };
+        let mut found = Vec::new();
+        for i in 0..a.len() {
+            for j in i + min_len.max(1)..=a.len() {
+                let w = &a[i..j];
+                let (occ_a, occ_b) = (occurrences(a, w), occurrences(b, w));
+                if occ_a.len() != 1 || occ_b.len() != 1 {
+                    continue;
+                }
+                let (pos_a, pos_b, len) = (i, occ_b[0], w.len());
+                let left = pos_a > 0 && pos_b > 0 && a[pos_a - 1] == b[pos_b - 1];
+                let right = pos_a + len < a.len()
+                    && pos_b + len < b.len()
+                    && a[pos_a + len] == b[pos_b + len];
+                if !left && !right {
+                    found.push(Mum { pos_a, pos_b, len });
+                }
+            }
+        }
+        found.sort_unstable();
+
+        found
+    }
+
+    #[test]
+    fn test_mums() {
+        // a single SNP splits the match into two
+        let a = b"ACGGTCATGCTTAGCCATAGCGTAACG";
+        let mut b = a.to_vec();
+        b[12] = b'C';
+        assert_eq!(
+            mums(a, &b, 5),
+            [
+                Mum {
+                    pos_a: 0,
+                    pos_b: 0,
+                    len: 12
+                },
+                Mum {
+                    pos_a: 13,
+                    pos_b: 13,
+                    len: 14
+                },
+            ]
+        );
+        assert_eq!(
+            mums(a, a, 1),
+            [Mum {
+                pos_a: 0,
+                pos_b: 0,
+                len: a.len()
+            }]
+        );
+        // repeated substrings are not unique
+        assert!(mums(b"ACGACG", b"ACG", 1).is_empty());
+        assert!(mums(b"", b"ACG", 1).is_empty());
+
+        let mut rng = StdRng::from_seed(&[17]);
+        for _ in 0..200 {
+            let a: Vec<u8> = (0..rng.gen_range(0, 30))
+                .map(|_| *rng.choose(b"ACGT").unwrap())
+                .collect();
+            let b: Vec<u8> = (0..rng.gen_range(0, 30))
+                .map(|_| *rng.choose(b"ACGT").unwrap())
+                .collect();
+            for min_len in 0..4 {
+                assert_eq!(mums(&a, &b, min_len), naive_mums(&a, &b, min_len));
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_suffix_array_parallel() {
+        let mut rng = StdRng::from_seed(&[11]);
+        for threads in 2..6 {
+            for _ in 0..20 {
+                let mut text = Vec::new();
+                for _ in 0..rng.gen_range(1, 6) {
+                    for _ in 0..rng.gen_range(0, 500) {
+                        text.push(*rng.choose(b"ACGTN").unwrap());
+                    }
+                    text.push(b'$');
+                }
+                assert_eq!(suffix_array_parallel(&text, threads), suffix_array(&text));
+            }
+        }
+        for text in [&b"AAAAAAAAAAAAAAAA$"[..], b"$", b"ACACACACAC$ACACAC$"].iter() {
+            assert_eq!(suffix_array_parallel(text, 4), suffix_array(text));
+        }
+
+        // large enough to be sorted in parallel by suffix_array itself, with repeats
+        let mut text: Vec<u8> = (0..MIN_PARALLEL_TEXT_LEN)
+            .map(|_| *rng.choose(b"ACGT").unwrap())
+            .collect();
+        let repeat = text[1000..200_000].to_vec();
+        text.extend_from_slice(&repeat);
+        text.push(b'$');
+        let mut sais = SAIS::new(text.len());
+        let alphabet = Alphabet::new(&text);
+        sais.construct(&transform_text::<u8>(&text, &alphabet, 1));
+        assert_eq!(suffix_array(&text), sais.pos);
+    }
+
     #[test]
     fn test_sorts_lexically() {
         let test_cases =             [(&b"A$C$G$T$"[..], "simple"),
@@ -0,0 +1,203 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A balanced wavelet tree (Grossi, Gupta and Vitter, 2003) over a sequence of symbols,
+//! answering rank and access queries in O(log A) with A being the number of distinct symbols.
+//!
+//! Used as backing of the occurrence array (see `bwt::Occ::new_wavelet_tree`), a wavelet tree
+//! needs O(n log A) bits independently of any sampling rate, whereas the sampled `Occ` needs
+//! O(n / k * A) words, growing linearly in the alphabet size. In turn, a rank query has to
+//! descend log A levels of the tree instead of counting at most k symbols of the BWT, such
+//! that sampled occurrences are usually faster for small alphabets like DNA, while the
+//! wavelet tree pays off for larger ones like proteins.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::wavelet_tree::WaveletTree;
+//!
+//! let wt = WaveletTree::new(b"ARNDARNDCA");
+//! assert_eq!(wt.len(), 10);
+//! assert_eq!(wt.access(4), b'A');
+//! assert_eq!(wt.rank(b'A', 4), 2);
+//! assert_eq!(wt.rank(b'A', 9), 3);
+//! assert_eq!(wt.rank(b'Q', 9), 0);
+//! ```
+
+use bv::{BitVec, BitsMut};
+
+use crate::data_structures::rank_select::RankSelect;
+
+/// Superblock size of the rank structures, in multiples of 32 bits.
+const RANK_SAMPLING: usize = 4;
+
+/// A balanced wavelet tree over the dense ranks of the symbols of a sequence.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WaveletTree {
+    symbols: Vec<u8>,
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+/// An inner node of the wavelet tree, covering a range of symbol ranks. A set bit denotes
+/// that the symbol at that position belongs to the upper half of the range.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Node {
+    bits: RankSelect,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl WaveletTree {
+    /// Construct the wavelet tree of the given sequence.
+    /// Complexity: O(n log A).
+    pub fn new(seq: &[u8]) -> Self {
+        let mut present = [false; 256];
+        for &c in seq {
+            present[c as usize] = true;
+        }
+        let symbols: Vec<u8> = (0..=255u8).filter(|&c| present[c as usize]).collect();
+        let mut ranks = [0u8; 256];
+        for (rank, &c) in symbols.iter().enumerate() {
+            ranks[c as usize] = rank as u8;
+        }
+        let ranked: Vec<u8> = seq.iter().map(|&c| ranks[c as usize]).collect();
+
+        let mut nodes = Vec::new();
+        if symbols.len() > 1 {
+            build(&ranked, 0, symbols.len(), &mut nodes);
+        }
+
+        WaveletTree {
+            symbols,
+            nodes,
+            len: seq.len(),
+        }
+    }
+
+    /// Length of the sequence.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the symbol at position i of the sequence.
+    pub fn access(&self, i: usize) -> u8 {
+        assert!(i < self.len, "Expecting position within the sequence.");
+        let (mut lower, mut upper) = (0, self.symbols.len());
+        let mut node = if self.nodes.is_empty() { None } else { Some(0) };
+        let mut i = i as u64;
+        while let Some(index) = node {
+            let inner = &self.nodes[index];
+            let mid = (lower + upper) / 2;
+            if inner.bits.get(i) {
+                i = inner.bits.rank_1(i).unwrap() - 1;
+                lower = mid;
+                node = inner.right;
+            } else {
+                i = inner.bits.rank_0(i).unwrap() - 1;
+                upper = mid;
+                node = inner.left;
+            }
+        }
+
+        self.symbols[lower]
+    }
+
+    /// Get the occurrences of symbol a in seq[..r+1], like `bwt::Occ::get`.
+    pub fn rank(&self, a: u8, r: usize) -> usize {
+        assert!(r < self.len, "Expecting position within the sequence.");
+        let rank = match self.symbols.binary_search(&a) {
+            Ok(rank) => rank,
+            Err(_) => return 0,
+        };
+        let (mut lower, mut upper) = (0, self.symbols.len());
+        let mut node = if self.nodes.is_empty() { None } else { Some(0) };
+        // number of occurrences of the current range of symbols in the prefix
+        let mut count = r as u64 + 1;
+        while let Some(index) = node {
+            let inner = &self.nodes[index];
+            let mid = (lower + upper) / 2;
+            if rank >= mid {
+                count = inner.bits.rank_1(count - 1).unwrap();
+                lower = mid;
+                node = inner.right;
+            } else {
+                count = inner.bits.rank_0(count - 1).unwrap();
+                upper = mid;
+                node = inner.left;
+            }
+            if count == 0 {
+                return 0;
+            }
+        }
+
+        count as usize
+    }
+}
+
+/// Recursively build the node covering the symbol ranks [lower, upper) and return its index.
+fn build(seq: &[u8], lower: usize, upper: usize, nodes: &mut Vec<Node>) -> Option<usize> {
+    if upper - lower < 2 {
+        return None;
+    }
+    let mid = (lower + upper) / 2;
+    let mut bits: BitVec<u8> = BitVec::new_fill(false, seq.len() as u64);
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for (i, &c) in seq.iter().enumerate() {
+        if c as usize >= mid {
+            bits.set_bit(i as u64, true);
+            right.push(c);
+        } else {
+            left.push(c);
+        }
+    }
+
+    let index = nodes.len();
+    nodes.push(Node {
+        bits: RankSelect::new(bits, RANK_SAMPLING),
+        left: None,
+        right: None,
+    });
+    nodes[index].left = build(&left, lower, mid, nodes);
+    nodes[index].right = build(&right, mid, upper, nodes);
+
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_access() {
+        let seqs: [&[u8]; 4] = [
+            b"GCCTTAACATTATTACGCCTA$",
+            b"MKVLAAGIVGLLLAQPAMAHRWCYQEFSTND$",
+            b"AAAA",
+            b"",
+        ];
+        for seq in seqs.iter() {
+            let wt = WaveletTree::new(seq);
+            assert_eq!(wt.len(), seq.len());
+            for (i, &c) in seq.iter().enumerate() {
+                assert_eq!(wt.access(i), c);
+            }
+            for a in 0..=255u8 {
+                let mut count = 0;
+                for (r, &c) in seq.iter().enumerate() {
+                    if c == a {
+                        count += 1;
+                    }
+                    assert_eq!(wt.rank(a, r), count);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1645 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! BED format reading and writing. Records may have 3 to 12 columns, which may vary between
+//! the lines of a file. The BED12 columns (thick part, color and blocks) are validated when
+//! reading. Records can be sorted, merged, and summarized as per-base coverage for
+//! [`bedgraph`](../bedgraph/index.html) output.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::bed;
+//! let example = b"1\t5\t5000\tname1\t0.5";
+//! let mut reader = bed::Reader::new(&example[..]);
+//! let mut writer = bed::Writer::new(vec![]);
+//! for record in reader.records() {
+//!     let rec = record.ok().expect("Error reading record.");
+//!     println!("{}", rec.chrom());
+//!     writer.write(&rec).ok().expect("Error writing record.");
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::AsRef;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::iter;
+use std::marker::Copy;
+use std::mem;
+use std::ops::Deref;
+use std::path::Path;
+
+use csv;
+use itertools::Itertools;
+
+use crate::io::CoordinateError;
+use crate::utils::{Strand, StrandError};
+
+use bio_types::annot;
+use bio_types::annot::loc::Loc;
+use bio_types::strand;
+
+mod sorted;
+pub use self::sorted::{Query, SortedReader};
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Csv(err: csv::Error) {
+            from()
+            description("error reading BED input")
+            display("error reading BED input: {}", err)
+            cause(err)
+        }
+        /// Malformed input at the given 1-based line. The context is a truncated copy of the
+        /// offending line.
+        Parse { line: u64, msg: &'static str, context: String } {
+            description("malformed BED input")
+            display("line {}: {} {:?}", line, msg, context)
+        }
+        /// Start, end or a block at the given 1-based line is not a non-negative integer, or
+        /// the block ends beyond the largest 64 bit integer.
+        InvalidCoordinate { line: u64, field: &'static str, value: String } {
+            description("invalid coordinate in BED input")
+            display("line {}: expected non-negative integer {}, found {:?}", line, field, value)
+        }
+        /// Invalid strand column at the given 1-based line.
+        InvalidStrand { line: u64, err: StrandError } {
+            description("invalid strand in BED input")
+            display("line {}: {}", line, err)
+            cause(err)
+        }
+        /// The record at the given 1-based line is not sorted by chromosome and start.
+        Unsorted { line: u64 } {
+            description("unsorted BED input")
+            display("line {}: records are not sorted by chromosome and start", line)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Csv(err) => err.into(),
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// A BED reader. Comment lines starting with `#`, and `track` and `browser` lines of the
+/// UCSC genome browser are skipped, and collected as header lines.
+#[derive(Debug)]
+pub struct Reader<R: io::Read> {
+    inner: csv::Reader<R>,
+    header_lines: Vec<String>,
+}
+
+impl Reader<fs::File> {
+    /// Read from a given file path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::new)
+    }
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Read from a given reader.
+    pub fn new(reader: R) -> Self {
+        Reader {
+            inner: csv::ReaderBuilder::new()
+                .delimiter(b'\t')
+                .has_headers(false)
+                .flexible(true)
+                .quoting(false)
+                .from_reader(reader),
+            header_lines: Vec::new(),
+        }
+    }
+
+    /// Iterate over all records.
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records {
+            inner: self.inner.records(),
+            header_lines: &mut self.header_lines,
+        }
+    }
+
+    /// The comment, track and browser lines that have been skipped so far, without line
+    /// terminator. Lines between records are included once the records have been read.
+    pub fn header_lines(&self) -> &[String] {
+        &self.header_lines
+    }
+}
+
+/// An iterator over the records of a BED file.
+pub struct Records<'a, R: io::Read> {
+    inner: csv::StringRecordsIter<'a, R>,
+    header_lines: &'a mut Vec<String>,
+}
+
+impl<'a, R: io::Read> Iterator for Records<'a, R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        let res = loop {
+            match self.inner.next()? {
+                Ok(ref fields) if is_header(fields) => {
+                    self.header_lines.push(fields.iter().join("\t"))
+                }
+                res => break res,
+            }
+        };
+
+        Some(
+            res.map_err(Error::from)
+                .and_then(|fields| parse_record(&fields)),
+        )
+    }
+}
+
+/// Whether the given fields are a header line rather than a record.
+fn is_header(fields: &csv::StringRecord) -> bool {
+    fields.get(0).is_some_and(super::is_header_line)
+}
+
+/// Parse and validate a record from the given fields.
+fn parse_record(fields: &csv::StringRecord) -> Result<Record, Error> {
+    let line = fields.position().map_or(0, |pos| pos.line());
+    let parse_error = |msg| Error::Parse {
+        line,
+        msg,
+        context: super::error_context(&fields.iter().join("\t")),
+    };
+    if fields.len() < 3 {
+        return Err(parse_error(
+            "Expected at least 3 columns (chrom, start and end).",
+        ));
+    }
+    let coordinate = |field, value: &str| {
+        value.parse().map_err(|_| Error::InvalidCoordinate {
+            line,
+            field,
+            value: value.to_owned(),
+        })
+    };
+    let start = coordinate("start", &fields[1])?;
+    let end = coordinate("end", &fields[2])?;
+    let record = Record {
+        chrom: fields[0].to_owned(),
+        start,
+        end,
+        aux: fields
+            .iter()
+            .skip(3)
+            .map(|field| field.to_owned())
+            .collect(),
+    };
+    if let Some(strand) = record.aux(5) {
+        strand
+            .parse::<Strand>()
+            .map_err(|err| Error::InvalidStrand { line, err })?;
+    }
+    if let (Some(sizes), Some(starts)) = (record.block_sizes(), record.block_starts()) {
+        let overflows = starts
+            .iter()
+            .zip(&sizes)
+            .any(|(&start, &size)| record.block_end(start, size).is_none());
+        if overflows {
+            return Err(Error::InvalidCoordinate {
+                line,
+                field: "blockSizes",
+                value: fields[10].to_owned(),
+            });
+        }
+    }
+    record.validate().map_err(parse_error)?;
+
+    Ok(record)
+}
+
+/// A BED writer.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    inner: csv::Writer<W>,
+}
+
+impl Writer<fs::File> {
+    /// Write to a given file path.
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::create(path).map(Writer::new)
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Write to a given writer.
+    pub fn new(writer: W) -> Self {
+        Writer {
+            inner: csv::WriterBuilder::new()
+                .delimiter(b'\t')
+                .flexible(true)
+                .quote_style(csv::QuoteStyle::Never)
+                .from_writer(writer),
+        }
+    }
+
+    /// Write the given header lines, e.g. the ones collected by
+    /// [`Reader::header_lines`](struct.Reader.html#method.header_lines).
+    pub fn write_header_lines<I, S>(&mut self, lines: I) -> csv::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for line in lines {
+            self.inner.write_record([line.as_ref()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a given BED record, with as many columns as the record has.
+    pub fn write(&mut self, record: &Record) -> csv::Result<()> {
+        if record.aux.is_empty() {
+            self.inner
+                .serialize(&(&record.chrom, record.start, record.end))
+        } else {
+            self.inner
+                .serialize(&(&record.chrom, record.start, record.end, &record.aux))
+        }
+    }
+}
+
+/// A BED record as defined by BEDtools
+/// (http://bedtools.readthedocs.org/en/latest/content/general-usage.html)
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct Record {
+    chrom: String,
+    start: u64,
+    end: u64,
+    aux: Vec<String>,
+}
+
+impl Record {
+    /// Create a new BED record.
+    pub fn new() -> Self {
+        Record {
+            chrom: "".to_owned(),
+            start: 0,
+            end: 0,
+            aux: vec![],
+        }
+    }
+
+    /// Chromosome of the feature.
+    pub fn chrom(&self) -> &str {
+        &self.chrom
+    }
+
+    /// Start position of feature (0-based).
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// End position of feature (0-based, not included).
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Name of the feature.
+    pub fn name(&self) -> Option<&str> {
+        self.aux(3)
+    }
+
+    /// Score of the feature.
+    pub fn score(&self) -> Option<&str> {
+        self.aux(4)
+    }
+
+    /// Strand of the feature, or `None` if it is not `+` or `-`.
+    #[deprecated(since = "0.28.3", note = "use `feature_strand` instead")]
+    pub fn strand(&self) -> Option<strand::Strand> {
+        match self.aux(5) {
+            Some("+") => Some(strand::Strand::Forward),
+            Some("-") => Some(strand::Strand::Reverse),
+            _ => None,
+        }
+    }
+
+    /// Strand of the feature, or `None` if the record has no strand column. Both `.` and `?`
+    /// are an unknown strand.
+    pub fn feature_strand(&self) -> Option<Strand> {
+        self.aux(5)
+            .map(|strand| strand.parse().unwrap_or(Strand::Unknown))
+    }
+
+    /// Start and end in the 1-based, inclusive coordinates of GFF. Following the GFF3
+    /// specification, an empty feature, e.g. an insertion site, is converted to a start equal
+    /// to the end, i.e. it lies right of the base at the end. It is converted back to a
+    /// feature of 1 bp.
+    ///
+    /// ```
+    /// use bio::io::bed;
+    ///
+    /// let mut record = bed::Record::new();
+    /// record.set_start(0);
+    /// record.set_end(1);
+    /// assert_eq!(record.to_gff_coords(), Ok((1, 1)));
+    /// ```
+    pub fn to_gff_coords(&self) -> Result<(u64, u64), CoordinateError> {
+        if self.start > self.end {
+            return Err(CoordinateError::StartAfterEnd {
+                start: self.start,
+                end: self.end,
+            });
+        }
+        if self.start == self.end {
+            return match self.start {
+                0 => Err(CoordinateError::EmptyAtOrigin),
+                pos => Ok((pos, pos)),
+            };
+        }
+
+        Ok((self.start + 1, self.end))
+    }
+
+    /// Start of the thickly drawn part of the feature, e.g. the start codon (0-based).
+    pub fn thick_start(&self) -> Option<u64> {
+        self.aux(6).and_then(|field| field.parse().ok())
+    }
+
+    /// End of the thickly drawn part of the feature, e.g. the stop codon (0-based, not
+    /// included).
+    pub fn thick_end(&self) -> Option<u64> {
+        self.aux(7).and_then(|field| field.parse().ok())
+    }
+
+    /// Display color of the feature (e.g. `255,0,0`, or `0`).
+    pub fn item_rgb(&self) -> Option<&str> {
+        self.aux(8)
+    }
+
+    /// Number of blocks (e.g. exons) of the feature.
+    pub fn block_count(&self) -> Option<u64> {
+        self.aux(9).and_then(|field| field.parse().ok())
+    }
+
+    /// Sizes of the blocks of the feature.
+    pub fn block_sizes(&self) -> Option<Vec<u64>> {
+        self.aux(10).and_then(parse_list)
+    }
+
+    /// Starts of the blocks of the feature, relative to the feature start.
+    pub fn block_starts(&self) -> Option<Vec<u64>> {
+        self.aux(11).and_then(parse_list)
+    }
+
+    /// Set the BED12 columns from the given thick part and blocks, given by their starts
+    /// relative to the feature start and their sizes. Missing name, score and strand are
+    /// set to `.`, `0` and `.`, and the color is set to `0`.
+    pub fn set_blocks(&mut self, thick_start: u64, thick_end: u64, blocks: &[(u64, u64)]) {
+        let defaults = [".", "0", "."];
+        if self.aux.len() < defaults.len() {
+            let missing = &defaults[self.aux.len()..];
+            self.aux
+                .extend(missing.iter().map(|field| (*field).to_owned()));
+        }
+        let item_rgb = self.item_rgb().unwrap_or("0").to_owned();
+        self.aux.truncate(3);
+        self.aux.push(thick_start.to_string());
+        self.aux.push(thick_end.to_string());
+        self.aux.push(item_rgb);
+        self.aux.push(blocks.len().to_string());
+        self.aux.push(
+            blocks
+                .iter()
+                .map(|&(_, size)| format!("{},", size))
+                .collect(),
+        );
+        self.aux.push(
+            blocks
+                .iter()
+                .map(|&(start, _)| format!("{},", start))
+                .collect(),
+        );
+    }
+
+    /// Check the consistency of the BED12 columns that are present: the thick part has to
+    /// be within the feature, there have to be as many block sizes and starts as blocks, the
+    /// first block has to start at 0, and blocks have to be sorted, disjoint and within the
+    /// feature.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.aux.len() > 3 {
+            let thick_start = self.thick_start().ok_or("Expected integer thickStart.")?;
+            if self.aux.len() > 4 {
+                let thick_end = self.thick_end().ok_or("Expected integer thickEnd.")?;
+                if thick_start > thick_end || thick_start < self.start || thick_end > self.end {
+                    return Err("Expected thickStart and thickEnd within the feature.");
+                }
+            }
+        }
+        if self.aux.len() > 6 {
+            if self.aux.len() < 9 {
+                return Err("Expected blockCount, blockSizes and blockStarts together.");
+            }
+            let count = self.block_count().ok_or("Expected integer blockCount.")?;
+            let sizes = self
+                .block_sizes()
+                .ok_or("Expected comma separated integer blockSizes.")?;
+            let starts = self
+                .block_starts()
+                .ok_or("Expected comma separated integer blockStarts.")?;
+            if sizes.len() as u64 != count || starts.len() as u64 != count {
+                return Err("Expected as many blockSizes and blockStarts as blockCount.");
+            }
+            if starts.first().is_some_and(|&start| start != 0) {
+                return Err("Expected first block to start at 0.");
+            }
+            let mut last_end = 0;
+            for (&start, &size) in starts.iter().zip(&sizes) {
+                let end = match self.block_end(start, size) {
+                    Some(end) if start >= last_end && end <= self.end => end,
+                    _ => return Err("Expected sorted, disjoint blocks within the feature."),
+                };
+                last_end = end - self.start;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The absolute end of the block with the given relative start and size, or `None` if it
+    /// overflows.
+    fn block_end(&self, start: u64, size: u64) -> Option<u64> {
+        self.start.checked_add(start)?.checked_add(size)
+    }
+
+    /// Access auxilliary fields after the strand field by index
+    /// (counting first field (chromosome) as 0).
+    pub fn aux(&self, i: usize) -> Option<&str> {
+        let j = i - 3;
+        if j < self.aux.len() {
+            Some(&self.aux[j])
+        } else {
+            None
+        }
+    }
+
+    /// Set chromosome.
+    pub fn set_chrom(&mut self, chrom: &str) {
+        self.chrom = chrom.to_owned();
+    }
+
+    /// Set start of feature.
+    pub fn set_start(&mut self, start: u64) {
+        self.start = start;
+    }
+
+    /// Set end of feature.
+    pub fn set_end(&mut self, end: u64) {
+        self.end = end;
+    }
+
+    /// Set name.
+    pub fn set_name(&mut self, name: &str) {
+        if self.aux.is_empty() {
+            self.aux.push(name.to_owned());
+        } else {
+            self.aux[0] = name.to_owned();
+        }
+    }
+
+    /// Set score.
+    pub fn set_score(&mut self, score: &str) {
+        if self.aux.is_empty() {
+            self.aux.push("".to_owned());
+        }
+        if self.aux.len() < 2 {
+            self.aux.push(score.to_owned());
+        } else {
+            self.aux[1] = score.to_owned();
+        }
+    }
+
+    /// Set strand of the feature. Missing name and score are set to `.` and `0`. An unknown
+    /// strand is written as `.`.
+    pub fn set_strand(&mut self, strand: Strand) {
+        let defaults = [".", "0"];
+        if self.aux.len() < defaults.len() {
+            let missing = &defaults[self.aux.len()..];
+            self.aux
+                .extend(missing.iter().map(|field| (*field).to_owned()));
+        }
+        if self.aux.len() < 3 {
+            self.aux.push(strand.to_string());
+        } else {
+            self.aux[2] = strand.to_string();
+        }
+    }
+
+    /// Add auxilliary field. This has to happen after name and score have been set.
+    pub fn push_aux(&mut self, field: &str) {
+        self.aux.push(field.to_owned());
+    }
+}
+
+/// Parse a comma separated list of integers, allowing a trailing comma.
+fn parse_list(field: &str) -> Option<Vec<u64>> {
+    let field = field.strip_suffix(',').unwrap_or(field);
+    if field.is_empty() {
+        return Some(Vec::new());
+    }
+    field.split(',').map(|value| value.parse().ok()).collect()
+}
+
+impl<'a> From<&'a Record> for annot::contig::Contig<String, strand::Strand> {
+    /// Returns a `Contig` annotation for the BED record.
+    ///
+    /// ```
+    /// # extern crate bio;
+    /// # extern crate bio_types;
+    /// use bio::io::bed;
+    /// use bio_types::strand::Strand;
+    /// use bio_types::annot::contig::Contig;
+    /// # use std::error::Error;
+    /// # fn try_main() -> Result<(), Box<Error>> {
+    /// let example = b"chr1\t5\t5000\tname1\t0.5";
+    /// let mut reader = bed::Reader::new(&example[..]);
+    /// let rec = reader.records().next().ok_or("No record available!")??;
+    /// let loc = Contig::from(&rec);
+    /// assert_eq!(loc.to_string(), "chr1:5-5000");
+    /// # Ok(())
+    /// # }
+    /// # fn main() { try_main().unwrap(); }
+    /// ```
+    fn from(rec: &Record) -> Self {
+        annot::contig::Contig::new(
+            rec.chrom.to_string(),
+            rec.start as isize,
+            (rec.end - rec.start) as usize,
+            rec.feature_strand()
+                .map_or(strand::Strand::Unknown, Into::into),
+        )
+    }
+}
+
+/// Generate a BED format `Record` for an annotation position.
+///
+/// This record will have length 1, and when created it will have an
+/// empty name.
+impl<R, S> From<annot::pos::Pos<R, S>> for Record
+where
+    R: Deref<Target = str>,
+    S: Into<strand::Strand> + Copy,
+{
+    fn from(pos: annot::pos::Pos<R, S>) -> Self {
+        let mut bed = Record::new();
+        bed.set_chrom(pos.refid());
+        bed.set_start(pos.pos() as u64);
+        bed.set_end((pos.pos() + 1) as u64);
+        bed.set_name("");
+        bed.set_score("0");
+        bed.push_aux(pos.strand().into().strand_symbol());
+        bed
+    }
+}
+
+/// Generate a BED format `Record` for the location.
+///
+/// As created, it will have an empty name.
+impl<R, S> From<annot::contig::Contig<R, S>> for Record
+where
+    R: Deref<Target = str>,
+    S: Into<strand::Strand> + Copy,
+{
+    fn from(contig: annot::contig::Contig<R, S>) -> Self {
+        let mut bed = Record::new();
+        bed.set_chrom(contig.refid());
+        bed.set_start(contig.start() as u64);
+        bed.set_end((contig.start() + contig.length() as isize) as u64);
+        bed.set_name("");
+        bed.set_score("0");
+        bed.push_aux(contig.strand().into().strand_symbol());
+        bed
+    }
+}
+
+/// Generate a BED format `Record` for the position.
+///
+/// Splicing information will be represented with the 12-column
+/// BED format, using columns 10 through 12 (blockCount,
+/// blockSizes, and blockStarts) for exons.
+///
+/// As created, it will have an empty name and default to using
+/// the overall start & end (columns 1 and 2) for the start and
+/// end of the "thick" region (columns 7 and 8).
+/// ```
+/// # extern crate bio;
+/// # extern crate bio_types;
+/// use bio_types::strand::ReqStrand;
+/// use bio_types::annot::AnnotError;
+/// use bio_types::annot::spliced::{Spliced,SplicingError};
+/// use bio::io::bed;
+/// # fn try_main() -> Result<(), Box<SplicingError>> {
+/// let tad3 = Spliced::with_lengths_starts("chrXII".to_owned(), 765265,
+///                                         &vec![808,52,109], &vec![0,864,984],
+///                                         ReqStrand::Reverse)?;
+/// assert_eq!(tad3.to_string(), "chrXII:765265-766073;766129-766181;766249-766358(-)");
+/// let tad3_exons = tad3.exon_contigs();
+/// assert_eq!(tad3_exons.len(), 3);
+/// assert_eq!(tad3_exons[0].to_string(), "chrXII:766249-766358(-)");
+/// assert_eq!(tad3_exons[1].to_string(), "chrXII:766129-766181(-)");
+/// assert_eq!(tad3_exons[2].to_string(), "chrXII:765265-766073(-)");
+/// let mut buf = Vec::new();
+/// {
+///     let mut writer = bed::Writer::new(&mut buf);
+///     let mut tad3_bed = bed::Record::from(tad3);
+///     tad3_bed.set_name("YLR316C");
+///     writer.write(&tad3_bed).ok().unwrap();
+/// }
+/// assert_eq!("chrXII\t765265\t766358\tYLR316C\t0\t-\t765265\t766358\t0\t3\t808,52,109,\t0,864,984,\n",
+///            String::from_utf8(buf).unwrap_or_else(|_| "???".to_owned()).as_str());
+/// # Ok(())
+/// # }
+/// # fn main() { try_main().unwrap(); }
+/// ```
+impl<R, S> From<annot::spliced::Spliced<R, S>> for Record
+where
+    R: Deref<Target = str>,
+    S: Into<strand::Strand> + Copy,
+{
+    fn from(spliced: annot::spliced::Spliced<R, S>) -> Self {
+        let mut bed = Record::new();
+        bed.set_chrom(spliced.refid());
+        bed.set_start(spliced.start() as u64);
+        bed.set_end((spliced.start() + spliced.length() as isize) as u64);
+        bed.set_name("");
+        bed.set_score("0");
+        bed.push_aux(spliced.strand().into().strand_symbol());
+        bed.push_aux(spliced.start().to_string().as_str()); // thickStart = chromStart
+        bed.push_aux(
+            (spliced.start() + spliced.length() as isize)
+                .to_string()
+                .as_str(),
+        ); // thickEnd = chromEnd
+        bed.push_aux("0"); // RGB color = black
+
+        bed.push_aux(spliced.exon_count().to_string().as_str());
+
+        let mut block_sizes = String::new();
+        for block_size in spliced.exon_lengths() {
+            write!(block_sizes, "{},", block_size).unwrap();
+        }
+        bed.push_aux(&block_sizes);
+
+        let mut block_starts = String::new();
+        for block_start in spliced.exon_starts() {
+            write!(block_starts, "{},", block_start).unwrap();
+        }
+        bed.push_aux(&block_starts);
+        bed
+    }
+}
+
+/// How the names and scores of merged records are combined by [`merge`](fn.merge.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Write only chromosome, start and end (and the strand when merging per strand).
+    Discard,
+    /// Join the names and the scores of the merged records with commas.
+    Concatenate,
+    /// Join the names with commas and sum the scores. Scores that are not numbers are ignored.
+    SumScores,
+}
+
+/// Sort records by chromosome, start and end. Chromosomes are sorted in natural order, i.e.
+/// numbers in their names are compared by value, such that `chr2` comes before `chr10`, and
+/// before `chrM` and `chrX`.
+pub fn sort(records: &mut [Record]) {
+    records.sort_by(cmp_records);
+}
+
+/// Merge overlapping or nearby records of each chromosome into single records, which are
+/// returned sorted like by [`sort`](fn.sort.html). Unsorted input is sorted first.
+///
+/// # Arguments
+///
+/// * `records` - the records to merge
+/// * `min_gap` - the largest distance between two records that are still merged. With `0`,
+///   bookended records are merged, and a negative value requires an overlap of at least its
+///   absolute value.
+/// * `stranded` - only merge records on the same strand, and keep the strand in the result
+///   (an unknown strand, also when given as `?`, is written as `.`)
+/// * `policy` - how to combine names and scores
+pub fn merge(
+    records: &[Record],
+    min_gap: isize,
+    stranded: bool,
+    policy: MergePolicy,
+) -> Vec<Record> {
+    let mut sorted: Vec<&Record> = records.iter().collect();
+    if !records
+        .windows(2)
+        .all(|w| cmp_records(&w[0], &w[1]) != Ordering::Greater)
+    {
+        sorted.sort_by(|a, b| cmp_records(a, b));
+    }
+
+    let mut merged = Vec::new();
+    // the open clusters of the current chromosome, one per strand
+    let mut clusters: Vec<Cluster<'_>> = Vec::new();
+    for record in sorted {
+        let strand = if stranded {
+            record.feature_strand().unwrap_or(Strand::Unknown)
+        } else {
+            Strand::Unknown
+        };
+        if clusters
+            .first()
+            .is_some_and(|cluster| cluster.chrom != record.chrom)
+        {
+            merged.extend(
+                clusters
+                    .drain(..)
+                    .map(|cluster| cluster.finish(stranded, policy)),
+            );
+        }
+        match clusters.iter().position(|cluster| cluster.strand == strand) {
+            Some(i) if record.start as isize - clusters[i].end as isize <= min_gap => {
+                clusters[i].push(record)
+            }
+            Some(i) => {
+                let cluster = mem::replace(&mut clusters[i], Cluster::new(record, strand));
+                merged.push(cluster.finish(stranded, policy));
+            }
+            None => clusters.push(Cluster::new(record, strand)),
+        }
+    }
+    merged.extend(
+        clusters
+            .into_iter()
+            .map(|cluster| cluster.finish(stranded, policy)),
+    );
+    sort(&mut merged);
+
+    merged
+}
+
+/// A run of bases with the same, non-zero coverage, as computed by
+/// [`coverage`](fn.coverage.html) and written by
+/// [`bedgraph::Writer`](../bedgraph/struct.Writer.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageRecord {
+    chrom: String,
+    start: u64,
+    end: u64,
+    depth: u64,
+}
+
+impl CoverageRecord {
+    /// Chromosome of the run.
+    pub fn chrom(&self) -> &str {
+        &self.chrom
+    }
+
+    /// Start position of the run (0-based).
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// End position of the run (0-based, not included).
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Number of records covering each base of the run.
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+}
+
+/// Compute the per-base coverage of the given records, e.g. read alignments, as maximal runs
+/// of bases with the same depth. Bases that are not covered yield no run. Runs are returned
+/// sorted like by [`sort`](fn.sort.html), and the input may be in any order. Start and end
+/// of all records are kept in memory.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::bed;
+///
+/// let example = b"chr1\t0\t10\nchr1\t5\t20\n";
+/// let mut reader = bed::Reader::new(&example[..]);
+/// let records = reader.records().map(|r| r.unwrap());
+/// let runs: Vec<_> = bed::coverage(records)
+///     .map(|run| (run.start(), run.end(), run.depth()))
+///     .collect();
+/// assert_eq!(runs, [(0, 5, 1), (5, 10, 2), (10, 20, 1)]);
+/// ```
+pub fn coverage<I>(records: I) -> impl Iterator<Item = CoverageRecord>
+where
+    I: IntoIterator<Item = Record>,
+{
+    // depth changes at start and end of each record, per chromosome
+    let mut events: HashMap<String, Vec<(u64, i64)>> = HashMap::new();
+    for record in records {
+        if record.start < record.end {
+            let contig = events.entry(record.chrom).or_default();
+            contig.push((record.start, 1));
+            contig.push((record.end, -1));
+        }
+    }
+    let mut contigs: Vec<_> = events.into_iter().collect();
+    contigs.sort_unstable_by(|a, b| cmp_chrom(&a.0, &b.0));
+
+    contigs.into_iter().flat_map(|(chrom, mut events)| {
+        events.sort_unstable();
+        let mut runs = Vec::new();
+        let (mut depth, mut run_start) = (0, 0);
+        let mut i = 0;
+        while i < events.len() {
+            let pos = events[i].0;
+            let mut next_depth = depth;
+            while i < events.len() && events[i].0 == pos {
+                next_depth += events[i].1;
+                i += 1;
+            }
+            // e.g. bookended records do not change the depth
+            if next_depth != depth {
+                if depth > 0 {
+                    runs.push(CoverageRecord {
+                        chrom: chrom.clone(),
+                        start: run_start,
+                        end: pos,
+                        depth: depth as u64,
+                    });
+                }
+                depth = next_depth;
+                run_start = pos;
+            }
+        }
+        runs
+    })
+}
+
+/// Records of one strand that are merged into one.
+struct Cluster<'a> {
+    chrom: &'a str,
+    strand: Strand,
+    start: u64,
+    end: u64,
+    names: Vec<&'a str>,
+    scores: Vec<&'a str>,
+}
+
+impl<'a> Cluster<'a> {
+    fn new(record: &'a Record, strand: Strand) -> Self {
+        let mut cluster = Cluster {
+            chrom: &record.chrom,
+            strand,
+            start: record.start,
+            end: record.end,
+            names: Vec::new(),
+            scores: Vec::new(),
+        };
+        cluster.push(record);
+        cluster
+    }
+
+    fn push(&mut self, record: &'a Record) {
+        self.end = self.end.max(record.end);
+        self.names.extend(record.name());
+        self.scores.extend(record.score());
+    }
+
+    fn finish(self, stranded: bool, policy: MergePolicy) -> Record {
+        let mut record = Record::new();
+        record.set_chrom(self.chrom);
+        record.set_start(self.start);
+        record.set_end(self.end);
+        let join = |fields: &[&str]| {
+            if fields.is_empty() {
+                ".".to_owned()
+            } else {
+                fields.join(",")
+            }
+        };
+        match policy {
+            MergePolicy::Discard => (),
+            MergePolicy::Concatenate => {
+                record.set_name(&join(&self.names));
+                record.set_score(&join(&self.scores));
+            }
+            MergePolicy::SumScores => {
+                let sum = self
+                    .scores
+                    .iter()
+                    .filter_map(|score| score.parse::<f64>().ok())
+                    .fold(0.0, |sum, score| sum + score);
+                record.set_name(&join(&self.names));
+                record.set_score(&sum.to_string());
+            }
+        }
+        if stranded {
+            record.set_strand(self.strand);
+        }
+        record
+    }
+}
+
+/// Order records by chromosome in natural order, start and end.
+fn cmp_records(a: &Record, b: &Record) -> Ordering {
+    cmp_chrom(&a.chrom, &b.chrom)
+        .then(a.start.cmp(&b.start))
+        .then(a.end.cmp(&b.end))
+}
+
+/// Compare chromosome names in natural order. The names are split into runs of digits and
+/// other characters, numbers are compared by value and come before other characters, and
+/// names that only differ in leading zeros are ordered lexicographically.
+fn cmp_chrom(a: &str, b: &str) -> Ordering {
+    let chunks = |name| {
+        let mut rest: &str = name;
+        iter::from_fn(move || {
+            let first = rest.chars().next()?;
+            let len = rest
+                .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (chunk, tail) = rest.split_at(len);
+            rest = tail;
+            Some(chunk)
+        })
+    };
+    let mut chunks_a = chunks(a);
+    let mut chunks_b = chunks(b);
+    loop {
+        let ordering = match (chunks_a.next(), chunks_b.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let is_number = |chunk: &str| chunk.starts_with(|c: char| c.is_ascii_digit());
+                match (is_number(x), is_number(y)) {
+                    (true, true) => {
+                        let x = x.trim_start_matches('0');
+                        let y = y.trim_start_matches('0');
+                        x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+                    }
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => x.cmp(y),
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bio_types::annot::spliced::Spliced;
+    use bio_types::strand::ReqStrand;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    const BED_FILE: &'static [u8] = b"1\t5\t5000\tname1\tup
+2\t3\t5005\tname2\tup
+";
+    const BED_FILE_COMPACT: &[u8] = b"1\t5\t5000\n2\t3\t5005\n";
+    const BED_FILE_MIXED: &[u8] = b"chr1\t10\t100
+chr1\t20\t200\tfeature\t960\t+
+chr2\t1000\t5000\ttx1\t0\t-\t1200\t4900\t255,0,0\t3\t500,400,1000,\t0,1500,3000,
+chr2\t1000\t1100\ttx2\t0\t+\t1000\t1000\t0\t1\t100\t0
+";
+
+    #[test]
+    fn test_reader() {
+        let chroms = ["1", "2"];
+        let starts = [5, 3];
+        let ends = [5000, 5005];
+        let names = ["name1", "name2"];
+        let scores = ["up", "up"];
+
+        let mut reader = Reader::new(BED_FILE);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.ok().expect("Error reading record");
+            assert_eq!(record.chrom(), chroms[i]);
+            assert_eq!(record.start(), starts[i]);
+            assert_eq!(record.end(), ends[i]);
+            assert_eq!(record.name().expect("Error reading name"), names[i]);
+            assert_eq!(record.score().expect("Error reading score"), scores[i]);
+        }
+    }
+
+    #[test]
+    /// Test for 'compact' BED files which only have chrom, start, and stop fields.
+    fn test_reader_compact() {
+        let chroms = ["1", "2"];
+        let starts = [5, 3];
+        let ends = [5000, 5005];
+
+        let mut reader = Reader::new(BED_FILE_COMPACT);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.unwrap();
+            assert_eq!(record.chrom(), chroms[i]);
+            assert_eq!(record.start(), starts[i]);
+            assert_eq!(record.end(), ends[i]);
+            assert!(record.name().is_none());
+        }
+    }
+
+    #[test]
+    fn test_reader_mixed() {
+        let records: Vec<Record> = Reader::new(BED_FILE_MIXED)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].aux(3), None);
+        assert_eq!(records[0].thick_start(), None);
+        assert_eq!(records[1].name(), Some("feature"));
+        assert_eq!(records[1].feature_strand(), Some(Strand::Forward));
+        assert_eq!(records[1].block_count(), None);
+
+        let tx1 = &records[2];
+        assert_eq!(tx1.feature_strand(), Some(Strand::Reverse));
+        assert_eq!(
+            (tx1.thick_start(), tx1.thick_end()),
+            (Some(1200), Some(4900))
+        );
+        assert_eq!(tx1.item_rgb(), Some("255,0,0"));
+        assert_eq!(tx1.block_count(), Some(3));
+        assert_eq!(tx1.block_sizes(), Some(vec![500, 400, 1000]));
+        assert_eq!(tx1.block_starts(), Some(vec![0, 1500, 3000]));
+        // lists without trailing comma
+        assert_eq!(records[3].block_sizes(), Some(vec![100]));
+        assert_eq!(records[3].block_starts(), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_writer_mixed() {
+        let mut writer = Writer::new(vec![]);
+        for r in Reader::new(BED_FILE_MIXED).records() {
+            writer.write(&r.unwrap()).unwrap();
+        }
+        assert_eq!(writer.inner.into_inner().unwrap(), BED_FILE_MIXED);
+    }
+
+    #[test]
+    fn test_header_lines() {
+        let bed = b"browser position chr1:1-1000
+track name=reads description=\"aligned reads\" visibility=2
+#chrom\tstart\tend
+chr1\t10\t100
+# a comment between records
+chr1\t20\t200\tfeature
+";
+        let mut reader = Reader::new(&bed[..]);
+        let records: Vec<_> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(intervals(&records), [("chr1", 10, 100), ("chr1", 20, 200)]);
+        assert_eq!(
+            reader.header_lines(),
+            [
+                "browser position chr1:1-1000",
+                "track name=reads description=\"aligned reads\" visibility=2",
+                "#chrom\tstart\tend",
+                "# a comment between records",
+            ]
+        );
+
+        // the error of a malformed record refers to its line
+        let mut reader = Reader::new(&b"track name=x\n#comment\nchr1\tx\t10\n"[..]);
+        match reader.records().next() {
+            Some(Err(Error::InvalidCoordinate { line, .. })) => assert_eq!(line, 3),
+            r => panic!("expected invalid coordinate, got {:?}", r),
+        }
+        assert_eq!(reader.header_lines().len(), 2);
+
+        let mut writer = Writer::new(vec![]);
+        writer
+            .write_header_lines(&reader.header_lines()[..1])
+            .unwrap();
+        writer.write(&records[0]).unwrap();
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            b"track name=x\nchr1\t10\t100\n"
+        );
+    }
+
+    #[test]
+    fn test_set_blocks() {
+        let mut record = Record::new();
+        record.set_chrom("chr1");
+        record.set_start(100);
+        record.set_end(1000);
+        record.set_blocks(150, 900, &[(0, 200), (700, 200)]);
+        assert!(record.validate().is_ok());
+        assert_eq!(record.name(), Some("."));
+        assert_eq!(record.block_sizes(), Some(vec![200, 200]));
+        assert_eq!(record.block_starts(), Some(vec![0, 700]));
+        let mut writer = Writer::new(vec![]);
+        writer.write(&record).unwrap();
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            &b"chr1\t100\t1000\t.\t0\t.\t150\t900\t0\t2\t200,200,\t0,700,\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_strand() {
+        let bed = b"chr1\t10\t100\ta\t0\t+\nchr1\t10\t100\tb\t0\t-\n\
+chr1\t10\t100\tc\t0\t.\nchr1\t10\t100\td\t0\t?\nchr1\t10\t100\n";
+        let records: Vec<Record> = Reader::new(&bed[..])
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        let strands: Vec<_> = records.iter().map(|r| r.feature_strand()).collect();
+        assert_eq!(
+            strands,
+            [
+                Some(Strand::Forward),
+                Some(Strand::Reverse),
+                Some(Strand::Unknown),
+                Some(Strand::Unknown),
+                None
+            ]
+        );
+        #[allow(deprecated)]
+        let strands: Vec<_> = records.iter().map(|r| r.strand()).collect();
+        assert_eq!(
+            strands,
+            [
+                Some(strand::Strand::Forward),
+                Some(strand::Strand::Reverse),
+                None,
+                None,
+                None
+            ]
+        );
+        let mut writer = Writer::new(vec![]);
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        assert_eq!(writer.inner.into_inner().unwrap(), &bed[..]);
+
+        let mut record = records[4].clone();
+        record.set_strand(Strand::Reverse);
+        assert_eq!(record.aux, [".", "0", "-"]);
+        record.set_strand(Strand::Reverse.opposite());
+        assert_eq!(record.feature_strand(), Some(Strand::Forward));
+
+        let bed = b"chr1\t10\t100\ta\t0\t+\nchr1\t10\t100\tb\t0\tx\n";
+        let mut reader = Reader::new(&bed[..]);
+        let mut records = reader.records();
+        assert!(records.next().unwrap().is_ok());
+        match records.next().unwrap() {
+            Err(Error::InvalidStrand { line, err }) => {
+                assert_eq!(line, 2);
+                assert_eq!(err, StrandError::InvalidSymbol("x".to_owned()));
+            }
+            res => panic!("expected invalid strand, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_reader_malformed() {
+        let malformed: &[(&[u8], &str)] = &[
+            (b"chr1\t10\n", "Expected at least 3 columns"),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t50\t20\n",
+                "Expected thickStart and thickEnd within the feature.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t2\n",
+                "Expected blockCount, blockSizes and blockStarts together.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t2\t10,\t0,20,\n",
+                "Expected as many blockSizes and blockStarts as blockCount.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t1\t10,\t5,\n",
+                "Expected first block to start at 0.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t2\t10,20,\t0,80,\n",
+                "Expected sorted, disjoint blocks within the feature.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t2\t10,20,\t0,5,\n",
+                "Expected sorted, disjoint blocks within the feature.",
+            ),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t2\t10,a,\t0,5,\n",
+                "Expected comma separated integer blockSizes.",
+            ),
+        ];
+        for &(line, expected) in malformed {
+            let mut bed = b"chr1\t0\t10\n".to_vec();
+            bed.extend_from_slice(line);
+            let mut reader = Reader::new(&bed[..]);
+            let mut records = reader.records();
+            assert!(records.next().unwrap().is_ok());
+            match records.next().unwrap() {
+                Err(Error::Parse { line, msg, .. }) => {
+                    assert_eq!(line, 2);
+                    assert!(msg.starts_with(expected), "{}", msg);
+                }
+                res => panic!("expected parse error, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_invalid_coordinates() {
+        for &(line, field, value) in &[
+            (&b"chr1\tten\t100\n"[..], "start", "ten"),
+            (b"chr1\t-1\t100\n", "start", "-1"),
+            (b"chr1\t10\t1e3\n", "end", "1e3"),
+            (b"chr1\t10\t\n", "end", ""),
+            (
+                b"chr1\t10\t100\tx\t0\t+\t10\t100\t0\t1\t18446744073709551615,\t0,\n",
+                "blockSizes",
+                "18446744073709551615,",
+            ),
+        ] {
+            let mut bed = b"chr1\t0\t10\n".to_vec();
+            bed.extend_from_slice(line);
+            let mut reader = Reader::new(&bed[..]);
+            let mut records = reader.records();
+            assert!(records.next().unwrap().is_ok());
+            match records.next().unwrap() {
+                Err(err @ Error::InvalidCoordinate { .. }) => {
+                    assert_eq!(
+                        err.to_string(),
+                        format!(
+                            "line 2: expected non-negative integer {}, found {:?}",
+                            field, value
+                        )
+                    );
+                }
+                res => panic!("expected invalid coordinate, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_block_overflow() {
+        let mut record = Record::new();
+        record.set_chrom("chr1");
+        record.set_start(100);
+        record.set_end(1000);
+        record.set_blocks(100, 1000, &[(0, u64::MAX)]);
+        assert_eq!(
+            record.validate(),
+            Err("Expected sorted, disjoint blocks within the feature.")
+        );
+    }
+
+    #[test]
+    fn test_to_gff_coords() {
+        let record = |start, end| {
+            let mut record = Record::new();
+            record.set_chrom("chr1");
+            record.set_start(start);
+            record.set_end(end);
+            record
+        };
+        assert_eq!(record(0, 1).to_gff_coords(), Ok((1, 1)));
+        assert_eq!(record(0, 100).to_gff_coords(), Ok((1, 100)));
+        assert_eq!(record(99, 100).to_gff_coords(), Ok((100, 100)));
+        // an insertion site between the 0-based bases 9 and 10
+        assert_eq!(record(10, 10).to_gff_coords(), Ok((10, 10)));
+        assert_eq!(
+            record(0, 0).to_gff_coords(),
+            Err(CoordinateError::EmptyAtOrigin)
+        );
+        assert_eq!(
+            record(10, 5).to_gff_coords(),
+            Err(CoordinateError::StartAfterEnd { start: 10, end: 5 })
+        );
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut reader = Reader::new(BED_FILE);
+        let mut writer = Writer::new(vec![]);
+        for r in reader.records() {
+            writer
+                .write(&r.expect("Error reading record"))
+                .expect("Error writing record");
+        }
+        assert_eq!(writer.inner.into_inner().unwrap(), BED_FILE);
+    }
+
+    #[test]
+    fn spliced_to_bed() {
+        //chrV    166236  166885  YER007C-A       0       -       166236  166885  0       2       535,11, 0,638,
+        let tma20 = Spliced::with_lengths_starts(
+            "chrV".to_owned(),
+            166236,
+            &vec![535, 11],
+            &vec![0, 638],
+            ReqStrand::Reverse,
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        {
+            let mut writer = Writer::new(&mut buf);
+            let mut tma20_bed = Record::from(tma20);
+            tma20_bed.set_name("YER007C-A");
+            writer.write(&tma20_bed).ok().unwrap();
+        }
+        assert_eq!(
+            "chrV\t166236\t166885\tYER007C-A\t0\t-\t166236\t166885\t0\t2\t535,11,\t0,638,\n",
+            String::from_utf8(buf).unwrap().as_str()
+        );
+
+        //chrXVI  173151  174702  YPL198W 0       +       173151  174702  0       3       11,94,630,      0,420,921,
+        let rpl7b = Spliced::with_lengths_starts(
+            "chrXVI".to_owned(),
+            173151,
+            &vec![11, 94, 630],
+            &vec![0, 420, 921],
+            ReqStrand::Forward,
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        {
+            let mut writer = Writer::new(&mut buf);
+            let mut rpl7b_bed = Record::from(rpl7b);
+            rpl7b_bed.set_name("YPL198W");
+            writer.write(&rpl7b_bed).ok().unwrap();
+        }
+        assert_eq!(
+            "chrXVI\t173151\t174702\tYPL198W\t0\t+\t173151\t174702\t0\t3\t11,94,630,\t0,420,921,\n",
+            String::from_utf8(buf).unwrap().as_str()
+        );
+
+        //chrXII  765265  766358  YLR316C 0       -       765265  766358  0       3       808,52,109,     0,864,984,
+        let tad3 = Spliced::with_lengths_starts(
+            "chrXII".to_owned(),
+            765265,
+            &vec![808, 52, 109],
+            &vec![0, 864, 984],
+            ReqStrand::Reverse,
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        {
+            let mut writer = Writer::new(&mut buf);
+            let mut tad3_bed = Record::from(tad3);
+            tad3_bed.set_name("YLR316C");
+            writer.write(&tad3_bed).ok().unwrap();
+        }
+        assert_eq!("chrXII\t765265\t766358\tYLR316C\t0\t-\t765265\t766358\t0\t3\t808,52,109,\t0,864,984,\n",
+                   String::from_utf8(buf).unwrap().as_str());
+    }
+
+    fn read(bed: &[u8]) -> Vec<Record> {
+        Reader::new(bed).records().map(|r| r.unwrap()).collect()
+    }
+
+    fn intervals(records: &[Record]) -> Vec<(&str, u64, u64)> {
+        records
+            .iter()
+            .map(|r| (r.chrom(), r.start(), r.end()))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut records =
+            read(b"chrX\t5\t10\nchr10\t1\t2\nchr2\t8\t9\nchr2\t3\t9\nchrM\t0\t1\nchr2\t3\t4\n");
+        sort(&mut records);
+        assert_eq!(
+            intervals(&records),
+            [
+                ("chr2", 3, 4),
+                ("chr2", 3, 9),
+                ("chr2", 8, 9),
+                ("chr10", 1, 2),
+                ("chrM", 0, 1),
+                ("chrX", 5, 10),
+            ]
+        );
+
+        let mut names = [
+            "chr1_random",
+            "chr1",
+            "chr01",
+            "scaffold10",
+            "2",
+            "scaffold9",
+            "10",
+        ];
+        names.sort_by(|a, b| cmp_chrom(a, b));
+        assert_eq!(
+            names,
+            [
+                "2",
+                "10",
+                "chr01",
+                "chr1",
+                "chr1_random",
+                "scaffold9",
+                "scaffold10"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_bookended() {
+        let records = read(b"chr1\t0\t10\nchr1\t10\t20\nchr1\t21\t30\n");
+        let merged = merge(&records, 0, false, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 20), ("chr1", 21, 30)]);
+        assert!(merged.iter().all(|r| r.name().is_none()));
+        let merged = merge(&records, 1, false, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 30)]);
+    }
+
+    #[test]
+    fn test_merge_negative_gap() {
+        let records = read(b"chr1\t0\t10\nchr1\t8\t20\nchr1\t19\t30\nchr1\t30\t40\n");
+        // overlaps of 2, 1 and 0 bases
+        let merged = merge(&records, -1, false, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 30), ("chr1", 30, 40)]);
+        let merged = merge(&records, -2, false, MergePolicy::Discard);
+        assert_eq!(
+            intervals(&merged),
+            [("chr1", 0, 20), ("chr1", 19, 30), ("chr1", 30, 40)]
+        );
+        // an interval contained in the first one overlaps by its whole length
+        let records = read(b"chr1\t0\t100\nchr1\t10\t15\nchr1\t90\t120\n");
+        let merged = merge(&records, -10, false, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 120)]);
+        let merged = merge(&records, -11, false, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 100), ("chr1", 90, 120)]);
+    }
+
+    #[test]
+    fn test_merge_multiple_contigs() {
+        // unsorted, with equal coordinates on different chromosomes
+        let records = read(
+            b"chr10\t5\t10\nchr2\t0\t10\nchr10\t0\t6\nchr2\t5\t20\nchrX\t0\t10\nchr2\t20\t25\n",
+        );
+        let merged = merge(&records, 0, false, MergePolicy::Discard);
+        assert_eq!(
+            intervals(&merged),
+            [("chr2", 0, 25), ("chr10", 0, 10), ("chrX", 0, 10)]
+        );
+        assert!(merge(&[], 0, false, MergePolicy::Discard).is_empty());
+    }
+
+    #[test]
+    fn test_merge_stranded() {
+        let records = read(
+            b"chr1\t0\t10\ta\t1\t+\nchr1\t5\t15\tb\t2\t-\nchr1\t12\t20\tc\t3\t+\nchr1\t14\t30\td\t4\t-\n",
+        );
+        let merged = merge(&records, 2, true, MergePolicy::Discard);
+        assert_eq!(intervals(&merged), [("chr1", 0, 20), ("chr1", 5, 30)]);
+        assert_eq!(merged[0].feature_strand(), Some(Strand::Forward));
+        assert_eq!(merged[1].feature_strand(), Some(Strand::Reverse));
+        let merged = merge(&records, 1, true, MergePolicy::Concatenate);
+        assert_eq!(
+            intervals(&merged),
+            [("chr1", 0, 10), ("chr1", 5, 30), ("chr1", 12, 20)]
+        );
+        assert_eq!(merged[1].name(), Some("b,d"));
+        assert_eq!(merged[2].feature_strand(), Some(Strand::Forward));
+
+        let merged = merge(&records, 0, false, MergePolicy::Concatenate);
+        assert_eq!(intervals(&merged), [("chr1", 0, 30)]);
+        assert_eq!(merged[0].feature_strand(), None);
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let records =
+            read(b"chr1\t0\t10\ta\t1.5\nchr1\t5\t15\tb\tn/a\nchr1\t12\t20\tc\t3\nchr1\t30\t40\n");
+        let merged = merge(&records, 0, false, MergePolicy::Concatenate);
+        assert_eq!(merged[0].name(), Some("a,b,c"));
+        assert_eq!(merged[0].score(), Some("1.5,n/a,3"));
+        assert_eq!(
+            (merged[1].name(), merged[1].score()),
+            (Some("."), Some("."))
+        );
+        let merged = merge(&records, 0, false, MergePolicy::SumScores);
+        assert_eq!(merged[0].name(), Some("a,b,c"));
+        assert_eq!(merged[0].score(), Some("4.5"));
+        assert_eq!(
+            (merged[1].name(), merged[1].score()),
+            (Some("."), Some("0"))
+        );
+    }
+
+    /// Per-base coverage of each chromosome, computed naively.
+    fn brute_force_coverage(records: &[Record]) -> Vec<(String, Vec<u64>)> {
+        let mut chroms: Vec<&str> = records.iter().map(|r| r.chrom()).collect();
+        chroms.sort_by(|a, b| cmp_chrom(a, b));
+        chroms.dedup();
+        chroms
+            .into_iter()
+            .map(|chrom| {
+                let len = records.iter().map(|r| r.end()).max().unwrap_or(0) as usize;
+                let mut depths = vec![0; len];
+                for r in records.iter().filter(|r| r.chrom() == chrom) {
+                    for depth in &mut depths[r.start() as usize..r.end() as usize] {
+                        *depth += 1;
+                    }
+                }
+                (chrom.to_owned(), depths)
+            })
+            .filter(|(_, depths)| depths.iter().any(|&depth| depth > 0))
+            .collect()
+    }
+
+    /// Check the runs against the brute force coverage, and that they are maximal.
+    fn check_coverage(records: &[Record]) -> Vec<CoverageRecord> {
+        let runs: Vec<_> = coverage(records.to_vec()).collect();
+        let mut expected = brute_force_coverage(records).into_iter();
+        for chrom_runs in runs.chunk_by(|a, b| a.chrom() == b.chrom()) {
+            let (chrom, expected) = expected.next().unwrap();
+            assert_eq!(chrom_runs[0].chrom(), chrom);
+            let mut depths = vec![0; expected.len()];
+            for run in chrom_runs {
+                assert!(run.start() < run.end() && run.depth() > 0);
+                for depth in &mut depths[run.start() as usize..run.end() as usize] {
+                    *depth = run.depth();
+                }
+            }
+            assert_eq!(depths, expected);
+            for w in chrom_runs.windows(2) {
+                assert!(w[0].end() < w[1].start() || w[0].depth() != w[1].depth());
+                assert!(w[0].end() <= w[1].start());
+            }
+        }
+        assert!(expected.next().is_none());
+        runs
+    }
+
+    fn runs(runs: &[CoverageRecord]) -> Vec<(&str, u64, u64, u64)> {
+        runs.iter()
+            .map(|r| (r.chrom(), r.start(), r.end(), r.depth()))
+            .collect()
+    }
+
+    #[test]
+    fn test_coverage() {
+        // nested, identical and bookended records
+        let records = read(
+            b"chr1\t0\t100\nchr1\t10\t20\nchr1\t10\t20\nchr1\t20\t30\nchr1\t100\t110\nchr1\t120\t130\n",
+        );
+        assert_eq!(
+            runs(&check_coverage(&records)),
+            [
+                ("chr1", 0, 10, 1),
+                ("chr1", 10, 20, 3),
+                ("chr1", 20, 30, 2),
+                ("chr1", 30, 110, 1),
+                ("chr1", 120, 130, 1),
+            ]
+        );
+        let records = read(b"chr1\t5\t15\nchr1\t5\t15\nchr1\t15\t25\nchr1\t15\t25\n");
+        assert_eq!(runs(&check_coverage(&records)), [("chr1", 5, 25, 2)]);
+    }
+
+    #[test]
+    fn test_coverage_contigs() {
+        // empty records do not cover anything
+        let records = read(b"chr10\t5\t10\nchr2\t0\t1\nchr3\t4\t4\nchr10\t0\t6\n");
+        assert_eq!(
+            runs(&check_coverage(&records)),
+            [
+                ("chr2", 0, 1, 1),
+                ("chr10", 0, 5, 1),
+                ("chr10", 5, 6, 2),
+                ("chr10", 6, 10, 1),
+            ]
+        );
+        assert!(coverage(Vec::new()).next().is_none());
+    }
+
+    #[test]
+    fn test_coverage_random() {
+        let mut rng = StdRng::from_seed(&[17]);
+        for _ in 0..50 {
+            let records: Vec<_> = (0..rng.gen_range(1, 40))
+                .map(|_| {
+                    let mut record = Record::new();
+                    record.set_chrom(["chr1", "chr2", "chrX"][rng.gen_range(0, 3)]);
+                    let start = rng.gen_range(0, 100);
+                    record.set_start(start);
+                    record.set_end(start + rng.gen_range(0, 20));
+                    record
+                })
+                .collect();
+            check_coverage(&records);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Region queries on BED files that are sorted by chromosome and start, without loading
+//! the records into memory. A first pass over the file builds a linear index, holding for
+//! each chromosome and bin of positions the file offset of the first record that may overlap
+//! the bin. A query seeks to the bin of its start and reads records until they start behind
+//! its end.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use csv;
+
+use super::{is_header, parse_record, Error, Reader, Record};
+
+/// The bin size used when querying before an index has been built.
+const DEFAULT_BIN_SIZE: u64 = 16384;
+
+/// A reader for region queries on a BED file where the records of each chromosome are
+/// consecutive and sorted by start. The order of the chromosomes does not matter.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::bed;
+/// use std::io;
+///
+/// let example = b"chr1\t5\t50\nchr1\t20\t30\nchr1\t60\t70\nchr2\t0\t100\n";
+/// let mut reader = bed::SortedReader::new(io::Cursor::new(&example[..]));
+/// reader.build_linear_index(10).unwrap();
+/// let starts: Vec<_> = reader
+///     .query("chr1", 25, 65)
+///     .unwrap()
+///     .map(|record| record.unwrap().start())
+///     .collect();
+/// assert_eq!(starts, [5, 20, 60]);
+/// ```
+#[derive(Debug)]
+pub struct SortedReader<R: io::Read + io::Seek> {
+    inner: Reader<R>,
+    index: Option<LinearIndex>,
+}
+
+/// File positions of the first record overlapping each bin, per chromosome.
+#[derive(Debug)]
+struct LinearIndex {
+    bin_size: u64,
+    bins: HashMap<String, Vec<csv::Position>>,
+}
+
+impl SortedReader<fs::File> {
+    /// Read from a given file path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(SortedReader::new)
+    }
+}
+
+impl<R: io::Read + io::Seek> SortedReader<R> {
+    /// Read from a given seekable reader.
+    pub fn new(reader: R) -> Self {
+        SortedReader {
+            inner: Reader::new(reader),
+            index: None,
+        }
+    }
+
+    /// Read the whole file and build the index with bins of the given size. Smaller bins
+    /// take more memory, at most one file position per bin up to the largest end of each
+    /// chromosome, but skip more records that do not overlap a query. Fails if the records
+    /// are not sorted.
+    pub fn build_linear_index(&mut self, bin_size: u64) -> Result<(), Error> {
+        assert!(bin_size > 0, "bin size has to be at least 1");
+        let reader = &mut self.inner.inner;
+        reader.seek(csv::Position::new())?;
+
+        let mut bins: HashMap<String, Vec<Option<csv::Position>>> = HashMap::new();
+        let mut seen = HashSet::new();
+        // chromosome and start of the last record, and the number of filled bins of its
+        // chromosome
+        let mut last: Option<(String, u64)> = None;
+        let mut filled = 0;
+        let mut fields = csv::StringRecord::new();
+        while reader.read_record(&mut fields)? {
+            if is_header(&fields) {
+                continue;
+            }
+            let record = parse_record(&fields)?;
+            let line = fields.position().map_or(0, |pos| pos.line());
+            match last {
+                Some((ref chrom, start)) if *chrom == record.chrom => {
+                    if record.start < start {
+                        return Err(Error::Unsorted { line });
+                    }
+                }
+                _ => {
+                    if !seen.insert(record.chrom.clone()) {
+                        return Err(Error::Unsorted { line });
+                    }
+                    filled = 0;
+                }
+            }
+
+            // bins already filled by earlier records are consecutive up to `filled`, because
+            // records are sorted by start
+            let first_bin = ((record.start / bin_size) as usize).max(filled);
+            let end_bin = ((record.end.max(record.start + 1) - 1) / bin_size) as usize + 1;
+            if first_bin < end_bin {
+                let contig_bins = bins.entry(record.chrom.clone()).or_default();
+                contig_bins.resize(end_bin, None);
+                let position = fields.position().cloned();
+                for bin in &mut contig_bins[first_bin..] {
+                    *bin = position.clone();
+                }
+                filled = end_bin;
+            }
+            last = Some((record.chrom, record.start));
+        }
+
+        // bins that no record overlaps start at the next record
+        let bins = bins
+            .into_iter()
+            .map(|(chrom, contig_bins)| {
+                let mut next = None;
+                let mut filled_bins: Vec<_> = contig_bins
+                    .into_iter()
+                    .rev()
+                    .filter_map(|bin| {
+                        if bin.is_some() {
+                            next = bin;
+                        }
+                        next.clone()
+                    })
+                    .collect();
+                filled_bins.reverse();
+                (chrom, filled_bins)
+            })
+            .collect();
+        self.index = Some(LinearIndex { bin_size, bins });
+
+        Ok(())
+    }
+
+    /// Iterate over the records of the given chromosome that overlap the given range
+    /// (0-based, end not included), in the order of the file. The index is built with bins
+    /// of 16384 positions if it has not been built yet.
+    pub fn query(&mut self, contig: &str, start: u64, end: u64) -> Result<Query<'_, R>, Error> {
+        if self.index.is_none() {
+            self.build_linear_index(DEFAULT_BIN_SIZE)?;
+        }
+        let index = self.index.as_ref().unwrap();
+        let position = index
+            .bins
+            .get(contig)
+            .and_then(|bins| bins.get((start / index.bin_size) as usize));
+        let done = match position {
+            Some(position) if start < end => {
+                self.inner.inner.seek(position.clone())?;
+                false
+            }
+            _ => true,
+        };
+
+        Ok(Query {
+            inner: &mut self.inner.inner,
+            fields: csv::StringRecord::new(),
+            contig: contig.to_owned(),
+            start,
+            end,
+            done,
+        })
+    }
+}
+
+/// An iterator over the records overlapping a region, see
+/// [`SortedReader::query`](struct.SortedReader.html#method.query).
+pub struct Query<'a, R: io::Read> {
+    inner: &'a mut csv::Reader<R>,
+    fields: csv::StringRecord,
+    contig: String,
+    start: u64,
+    end: u64,
+    done: bool,
+}
+
+impl<'a, R: io::Read> Iterator for Query<'a, R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        while !self.done {
+            let record = match self.inner.read_record(&mut self.fields) {
+                Ok(true) if is_header(&self.fields) => continue,
+                Ok(true) => parse_record(&self.fields),
+                Ok(false) => {
+                    self.done = true;
+                    break;
+                }
+                Err(err) => Err(err.into()),
+            };
+            match record {
+                Ok(record) if record.chrom != self.contig || record.start >= self.end => {
+                    self.done = true;
+                }
+                Ok(record) if record.end <= self.start => (),
+                res => {
+                    self.done = res.is_err();
+                    return Some(res);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::bed::Writer;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    /// A sorted BED file with records of random lengths on three chromosomes.
+    fn sorted_bed(rng: &mut StdRng) -> Vec<u8> {
+        let mut writer = Writer::new(vec![]);
+        writer.write_header_lines(["track name=test"]).unwrap();
+        for &chrom in &["chr2", "chr1", "chr10"] {
+            let mut start = 0;
+            for i in 0..rng.gen_range(0, 100) {
+                start += rng.gen_range(0, 30);
+                let mut record = Record::new();
+                record.set_chrom(chrom);
+                record.set_start(start);
+                // mostly short records, some long and some empty
+                record.set_end(start + [0, 1, 5, 20, 50, 500][rng.gen_range(0, 6)]);
+                record.set_name(&format!("{}:{}", chrom, i));
+                writer.write(&record).unwrap();
+            }
+        }
+        writer.inner.into_inner().unwrap()
+    }
+
+    fn names(records: Vec<Record>) -> Vec<String> {
+        records
+            .into_iter()
+            .map(|r| r.name().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let mut rng = StdRng::from_seed(&[5]);
+        for _ in 0..10 {
+            let bed = sorted_bed(&mut rng);
+            let all: Vec<_> = Reader::new(&bed[..])
+                .records()
+                .map(|r| r.unwrap())
+                .collect();
+            for &bin_size in &[1, 7, 64, 1000, 100_000] {
+                let mut reader = SortedReader::new(io::Cursor::new(&bed));
+                reader.build_linear_index(bin_size).unwrap();
+                for _ in 0..50 {
+                    let contig = ["chr1", "chr2", "chr10", "chrX"][rng.gen_range(0, 4)];
+                    let start = rng.gen_range(0, 3000);
+                    let end = start + rng.gen_range(0, 600);
+                    let expected: Vec<_> = all
+                        .iter()
+                        .filter(|r| r.chrom() == contig && r.start() < end && r.end() > start)
+                        .cloned()
+                        .collect();
+                    let found: Vec<_> = reader
+                        .query(contig, start, end)
+                        .unwrap()
+                        .map(|r| r.unwrap())
+                        .collect();
+                    assert_eq!(names(found), names(expected));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_without_index() {
+        let bed = b"chr1\t0\t10\ta\nchr1\t5\t100\tb\n#comment\nchr1\t50\t60\tc\nchr2\t0\t10\td\n";
+        let mut reader = SortedReader::new(io::Cursor::new(&bed[..]));
+        let found: Vec<_> = reader
+            .query("chr1", 20, 55)
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(names(found), ["b", "c"]);
+        assert_eq!(reader.query("chr2", 10, 20).unwrap().count(), 0);
+        assert_eq!(reader.query("chr1", 5, 5).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_unsorted() {
+        for (bed, unsorted_line) in &[
+            (&b"chr1\t10\t20\nchr1\t5\t30\n"[..], 2),
+            (
+                &b"chr1\t10\t20\nchr2\t0\t10\n#comment\nchr1\t30\t40\n"[..],
+                4,
+            ),
+        ] {
+            let mut reader = SortedReader::new(io::Cursor::new(bed));
+            match reader.build_linear_index(10) {
+                Err(Error::Unsorted { line }) => assert_eq!(line, *unsorted_line),
+                res => panic!("expected unsorted error, got {:?}", res),
+            }
+            assert!(reader.query("chr1", 0, 100).is_err());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! BedGraph writing. Each line holds a run of bases with the same depth, as chromosome,
+//! 0-based start, end (not included) and depth, e.g. as computed by
+//! [`bed::coverage`](../bed/fn.coverage.html).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::{bed, bedgraph};
+//!
+//! let example = b"chr1\t0\t10\nchr1\t5\t20\n";
+//! let mut reader = bed::Reader::new(&example[..]);
+//! let records = reader.records().map(|r| r.unwrap());
+//! let mut buf = Vec::new();
+//! {
+//!     let mut writer = bedgraph::Writer::new(&mut buf);
+//!     for run in bed::coverage(records) {
+//!         writer.write(&run).unwrap();
+//!     }
+//! }
+//! assert_eq!(buf, b"chr1\t0\t5\t1\nchr1\t5\t10\t2\nchr1\t10\t20\t1\n");
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use csv;
+
+use crate::io::bed::CoverageRecord;
+
+/// A bedGraph writer.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    inner: csv::Writer<W>,
+}
+
+impl Writer<fs::File> {
+    /// Write to a given file path.
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::create(path).map(Writer::new)
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Write to a given writer.
+    pub fn new(writer: W) -> Self {
+        Writer {
+            inner: csv::WriterBuilder::new()
+                .delimiter(b'\t')
+                .quote_style(csv::QuoteStyle::Never)
+                .from_writer(writer),
+        }
+    }
+
+    /// Write a given run of coverage.
+    pub fn write(&mut self, record: &CoverageRecord) -> csv::Result<()> {
+        self.inner
+            .serialize((record.chrom(), record.start(), record.end(), record.depth()))
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::bed;
+
+    #[test]
+    fn test_writer() {
+        let bed = b"chrX\t10\t20\nchr2\t0\t30\nchr2\t10\t20\nchr2\t20\t25\n";
+        let mut reader = bed::Reader::new(&bed[..]);
+        let records = reader.records().map(|r| r.unwrap());
+        let mut writer = Writer::new(vec![]);
+        for run in bed::coverage(records) {
+            writer.write(&run).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(
+            String::from_utf8(writer.inner.into_inner().unwrap()).unwrap(),
+            "chr2\t0\t10\t1\nchr2\t10\t25\t2\nchr2\t25\t30\t1\nchrX\t10\t20\t1\n"
+        );
+    }
+}
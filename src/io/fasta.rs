@@ -16,21 +16,64 @@
 use std::cmp::min;
 use std::collections;
 use std::convert::AsRef;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::ops::Range;
 use std::path::Path;
+use std::str::FromStr;
 
 use csv;
 
+use crate::alphabets::{dna, Alphabet, Encoding, InvalidSymbol, MaskedIntervals};
+use crate::data_structures::bitenc::BitEnc;
+use crate::io::sample;
 use crate::utils::{Text, TextSlice};
 
 /// Maximum size of temporary buffer used for reading indexed FASTA files.
 const MAX_FASTA_BUFFER_SIZE: usize = 512;
 
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Io(err: io::Error) {
+            from()
+            description("error reading FASTA input")
+            display("error reading FASTA input: {}", err)
+            cause(err)
+        }
+        /// Malformed input at the given 1-based line, while parsing the record with the
+        /// given 0-based index. The context is a truncated copy of the offending line.
+        Parse { line: usize, record: usize, msg: &'static str, context: String } {
+            description("malformed FASTA input")
+            display("line {} (record {}): {} {:?}", line, record, msg, context)
+        }
+        InvalidSymbol(id: String, err: InvalidSymbol) {
+            description("record contains a symbol that is not part of the alphabet")
+            display("record {}: {}", id, err)
+            cause(err)
+        }
+        /// Records with the given 0-based indices have the same id.
+        DuplicateId(id: String, first: usize, second: usize) {
+            description("duplicate record id")
+            display("records {} and {} have the same id {:?}", first, second, id)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
 /// Trait for FASTA readers.
 pub trait FastaRead {
-    fn read(&mut self, record: &mut Record) -> io::Result<()>;
+    fn read(&mut self, record: &mut Record) -> Result<(), Error>;
 }
 
 /// A FASTA reader.
@@ -38,12 +81,42 @@ pub trait FastaRead {
 pub struct Reader<R: io::Read> {
     reader: io::BufReader<R>,
     line: String,
+    /// Number of lines read so far.
+    line_no: usize,
+    /// Index of the next record.
+    record_no: usize,
+    /// Number of bytes read so far.
+    offset: u64,
+    uppercase: bool,
+}
+
+/// The position of a record in a FASTA file, as returned by `Reader::position`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FilePos {
+    offset: u64,
+    line: usize,
+    record: usize,
+}
+
+impl FilePos {
+    /// Byte offset of the record header.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Index of the record.
+    pub fn record(&self) -> usize {
+        self.record
+    }
 }
 
 impl Reader<fs::File> {
-    /// Read FASTA from given file path.
+    /// Read FASTA from given file path. Fails for gzip compressed files.
     pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        fs::File::open(path).map(Reader::new)
+        let mut reader = Reader::new(fs::File::open(path)?);
+        super::check_uncompressed(&mut reader.reader)?;
+
+        Ok(reader)
     }
 }
 
@@ -65,9 +138,54 @@ impl<R: io::Read> Reader<R> {
         Reader {
             reader: io::BufReader::new(reader),
             line: String::new(),
+            line_no: 0,
+            record_no: 0,
+            offset: 0,
+            uppercase: false,
         }
     }
 
+    /// Convert sequences to uppercase while reading, keeping the soft-masked (lowercase)
+    /// intervals, which are then returned by `Record::masked_intervals`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Reader;
+    /// let mut records = Reader::new(&b">chr1\nACgt\nnnGT\n"[..]).uppercase(true).records();
+    /// let record = records.next().unwrap().unwrap();
+    /// assert_eq!(record.seq(), b"ACGTNNGT");
+    /// assert_eq!(record.masked_intervals(), [2..6]);
+    /// ```
+    pub fn uppercase(mut self, uppercase: bool) -> Self {
+        self.uppercase = uppercase;
+        self
+    }
+
+    /// Read the next record into the given one, reusing its buffers such that no
+    /// allocations happen once they are large enough. Returns `false` if there are no more
+    /// records, leaving the given one empty. The last record does not need to be terminated
+    /// by a newline, and a header without sequence lines yields a record with an empty
+    /// sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::{Reader, Record};
+    /// let mut reader = Reader::new(&b">id1\nACGT\nAC\n>id2\nGGG"[..]);
+    /// let mut record = Record::new();
+    /// let mut bases = 0;
+    /// while reader.read_next(&mut record).unwrap() {
+    ///     bases += record.seq().len();
+    /// }
+    /// assert_eq!(bases, 9);
+    /// ```
+    pub fn read_next(&mut self, record: &mut Record) -> Result<bool, Error> {
+        FastaRead::read(self, record)?;
+
+        Ok(!record.is_empty())
+    }
+
     /// Return an iterator over the records of this Fasta file.
     ///
     /// # Example
@@ -94,6 +212,316 @@ impl<R: io::Read> Reader<R> {
             error_has_occured: false,
         }
     }
+
+    /// Draw a uniform sample of `n` records in a single pass, see `io::sample::reservoir`.
+    pub fn sample(self, n: usize, seed: u64) -> Result<Vec<Record>, Error> {
+        sample::reservoir(self.records(), n, seed)
+    }
+
+    /// Sample each record with probability `p` in a single pass, see
+    /// `io::sample::fraction`.
+    pub fn sample_frac(self, p: f64, seed: u64) -> Result<Vec<Record>, Error> {
+        sample::fraction(self.records(), p, seed)
+    }
+
+    /// Concatenate the sequences of all remaining records into a single text, each one
+    /// terminated by a sentinel `$`, as needed for building a suffix array or an FM-Index
+    /// over multiple sequences. Records are read one at a time, such that only the text
+    /// itself is held in memory. The returned metadata gives the name, start and length of
+    /// each sequence in the text, with indices matching those of `DocArray::seq_of`. Fails if
+    /// a sequence contains a symbol that is not part of the given alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::io::fasta::Reader;
+    ///
+    /// let mut reader = Reader::new(&b">chr1\nACGT\nAC\n>chr2\nGGT\n"[..]);
+    /// let (text, seqs) = reader.concat_with_sentinel(&dna::alphabet()).unwrap();
+    /// assert_eq!(text, b"ACGTAC$GGT$");
+    /// assert_eq!((seqs[1].name.as_str(), seqs[1].offset, seqs[1].len), ("chr2", 7, 3));
+    /// ```
+    pub fn concat_with_sentinel(
+        &mut self,
+        alphabet: &Alphabet,
+    ) -> Result<(Vec<u8>, Vec<SeqMeta>), Error> {
+        self.concat(alphabet, false)
+    }
+
+    /// Concatenate the sequences of all remaining records together with their reverse
+    /// complements into a text of the form T1$R1$T2$R2$... as needed for building an
+    /// FMD-Index. The returned metadata gives the name, start and length of the forward copy
+    /// of each sequence, with indices matching the documents reported by
+    /// `BiInterval::occ_docs`. Otherwise, this behaves like `concat_with_sentinel`.
+    pub fn concat_with_revcomp(
+        &mut self,
+        alphabet: &Alphabet,
+    ) -> Result<(Vec<u8>, Vec<SeqMeta>), Error> {
+        self.concat(alphabet, true)
+    }
+
+    fn concat(
+        &mut self,
+        alphabet: &Alphabet,
+        revcomp: bool,
+    ) -> Result<(Vec<u8>, Vec<SeqMeta>), Error> {
+        let mut text = Vec::new();
+        let mut seqs = Vec::new();
+        let mut record = Record::new();
+        while self.read_next(&mut record)? {
+            let seq = record.seq();
+            if let Err(err) = alphabet.validate(seq) {
+                return Err(Error::InvalidSymbol(record.id.clone(), err));
+            }
+            let offset = text.len();
+            text.extend_from_slice(seq);
+            text.push(b'$');
+            if revcomp {
+                text.extend(seq.iter().rev().map(|&a| dna::complement(a)));
+                text.push(b'$');
+            }
+            seqs.push(SeqMeta {
+                name: record.id.clone(),
+                offset,
+                len: seq.len(),
+            });
+        }
+
+        Ok((text, seqs))
+    }
+
+    /// Read the next record, encoding its sequence with the given encoding while reading
+    /// it, such that the sequence is never held as text. The buffers of the given record
+    /// are reused. Returns `false` if there are no more records. Fails with
+    /// `Error::InvalidSymbol`, giving the position in the sequence, if a symbol cannot be
+    /// encoded (see `Encoding::fallback` to encode such symbols instead).
+    pub fn read_bitenc(
+        &mut self,
+        record: &mut BitRecord,
+        encoding: &Encoding,
+    ) -> Result<bool, Error> {
+        let seq = &mut record.seq;
+        if seq.width() != encoding.width() {
+            *seq = BitEnc::new(encoding.width());
+        }
+        seq.clear();
+        let mut pos = 0;
+        self.read_parts(
+            &mut record.header,
+            &mut record.id,
+            &mut record.desc,
+            |line| {
+                for (i, &a) in line.as_bytes().iter().enumerate() {
+                    match encoding.rank(a) {
+                        Some(r) => seq.push(r),
+                        None => {
+                            let mut err = InvalidSymbol::new(line.as_bytes(), i, a);
+                            err.pos = pos + i;
+                            return Err(err);
+                        }
+                    }
+                }
+                pos += line.len();
+                Ok(())
+            },
+        )
+    }
+
+    /// Return an iterator over the remaining records with bit-encoded sequences (see
+    /// `read_bitenc`). It stops after yielding the first error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::{dna, Encoding};
+    /// use bio::io::fasta::Reader;
+    ///
+    /// let encoding = Encoding::for_alphabet(&dna::alphabet()).fallback(b'A');
+    /// let mut reader = Reader::new(&b">chr1\nACGT\nNA\n"[..]);
+    /// let records: Vec<_> = reader.records_bitenc(&encoding).collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(records[0].id(), "chr1");
+    /// assert_eq!(encoding.decode(records[0].seq()), b"ACGTAA");
+    /// ```
+    pub fn records_bitenc<'a>(
+        &'a mut self,
+        encoding: &'a Encoding,
+    ) -> impl Iterator<Item = Result<BitRecord, Error>> + 'a {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let mut record = BitRecord::new(encoding.width());
+            match self.read_bitenc(&mut record, encoding) {
+                Ok(true) => Some(Ok(record)),
+                Ok(false) => {
+                    done = true;
+                    None
+                }
+                Err(err) => {
+                    done = true;
+                    Some(Err(err))
+                }
+            }
+        })
+    }
+
+    /// Read the header of the next record into the given buffers and pass each of its
+    /// sequence lines to `seq_line`, returning `false` at the end of the input. The given
+    /// description is reused if there is one.
+    fn read_parts<F>(
+        &mut self,
+        header: &mut String,
+        id: &mut String,
+        desc: &mut Option<String>,
+        mut seq_line: F,
+    ) -> Result<bool, Error>
+    where
+        F: FnMut(&str) -> Result<(), InvalidSymbol>,
+    {
+        header.clear();
+        id.clear();
+        let desc_buf = desc.take();
+        if self.line.is_empty() {
+            // skip blank lines before the first record
+            loop {
+                if self.read_line()? == 0 {
+                    return Ok(false);
+                }
+                if !self.line.trim_end().is_empty() {
+                    break;
+                }
+            }
+        }
+
+        if !self.line.starts_with('>') {
+            return Err(self.parse_error("Expected > at record start."));
+        }
+        let line = self.line[1..].trim_end_matches(&['\n', '\r'][..]);
+        let (record_id, rest) = match line.char_indices().find(|&(_, c)| c.is_whitespace()) {
+            Some((i, c)) => (&line[..i], line[i + c.len_utf8()..].trim_end()),
+            None => (line, ""),
+        };
+        if record_id.is_empty() {
+            return Err(self.parse_error("Expected id after >."));
+        }
+        header.push_str(line);
+        id.push_str(record_id);
+        if !rest.is_empty() {
+            let mut desc_buf = desc_buf.unwrap_or_default();
+            desc_buf.clear();
+            desc_buf.push_str(rest);
+            *desc = Some(desc_buf);
+        }
+        let mut blank_line = None;
+        while self.read_line()? > 0 {
+            let seq = self.line.trim_end();
+            if seq.is_empty() {
+                blank_line = blank_line.or(Some(self.line_no));
+                continue;
+            }
+            if seq.starts_with('>') {
+                break;
+            }
+            if let Some(line) = blank_line {
+                return Err(Error::Parse {
+                    line,
+                    record: self.record_no,
+                    msg: "Unexpected blank line.",
+                    context: String::new(),
+                });
+            }
+            if let Err(err) = seq_line(seq) {
+                return Err(Error::InvalidSymbol(id.clone(), err));
+            }
+        }
+        self.record_no += 1;
+
+        Ok(true)
+    }
+
+    /// Return the position of the next record, which can be used to continue reading from
+    /// there with `seek_to`.
+    pub fn position(&self) -> FilePos {
+        FilePos {
+            offset: self.offset - self.line.len() as u64,
+            line: self.line_no - if self.line.is_empty() { 0 } else { 1 },
+            record: self.record_no,
+        }
+    }
+
+    /// Read the next line into the line buffer.
+    fn read_line(&mut self) -> io::Result<usize> {
+        self.line.clear();
+        let n = super::read_line(&mut self.reader, &mut self.line_no, &mut self.line)?;
+        self.offset += n as u64;
+
+        Ok(n)
+    }
+
+    /// Create a parse error for the current line.
+    fn parse_error(&self, msg: &'static str) -> Error {
+        Error::Parse {
+            line: self.line_no,
+            record: self.record_no,
+            msg,
+            context: super::error_context(&self.line),
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek> Reader<R> {
+    /// Continue reading at the given position, which has been obtained from `position`
+    /// on a reader of the same file, e.g. to resume processing after a checkpoint. Records
+    /// are numbered as if the file was read from the start. Fails with
+    /// `io::ErrorKind::InvalidInput` if the position is not at the start of a line beginning
+    /// with `>`, e.g. because the file has changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::{Reader, Record};
+    /// use std::io;
+    ///
+    /// let fasta = b">id1\nACGT\n>id2\nGG\n>id3\nTT\n";
+    /// let mut reader = Reader::new(io::Cursor::new(&fasta[..]));
+    /// let mut record = Record::new();
+    /// reader.read_next(&mut record).unwrap();
+    /// let pos = reader.position();
+    ///
+    /// let mut reader = Reader::new(io::Cursor::new(&fasta[..]));
+    /// reader.seek_to(pos).unwrap();
+    /// reader.read_next(&mut record).unwrap();
+    /// assert_eq!(record.id(), "id2");
+    /// ```
+    pub fn seek_to(&mut self, pos: FilePos) -> Result<(), Error> {
+        let invalid = || {
+            Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Position is not at the start of a FASTA record.",
+            ))
+        };
+        // the position has to follow a line break
+        if pos.offset > 0 {
+            self.reader.seek(io::SeekFrom::Start(pos.offset - 1))?;
+            let mut prev = [0; 1];
+            self.reader.read_exact(&mut prev)?;
+            if prev[0] != b'\n' {
+                return Err(invalid());
+            }
+        }
+        self.reader.seek(io::SeekFrom::Start(pos.offset))?;
+        self.offset = pos.offset;
+        self.line_no = pos.line;
+        self.record_no = pos.record;
+        if self.read_line()? > 0 && !self.line.starts_with('>') {
+            self.line.clear();
+            return Err(invalid());
+        }
+
+        Ok(())
+    }
 }
 
 impl<R> FastaRead for Reader<R>
@@ -111,8 +539,10 @@ where
     ///
     /// # Errors
     ///
-    /// This function will return an error if the record is incomplete,
-    /// syntax is violated or any form of I/O error is encountered.
+    /// This function will return an error if the syntax is violated or any form of I/O
+    /// error is encountered. Parse errors report the line at which they occurred. Records
+    /// need a non-empty id. Lines may end with `\n` or `\r\n`, and blank lines between
+    /// records are skipped, while blank lines inside of a sequence are an error.
     ///
     /// # Example
     ///
@@ -136,31 +566,24 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    fn read(&mut self, record: &mut Record) -> io::Result<()> {
+    fn read(&mut self, record: &mut Record) -> Result<(), Error> {
+        // keep the buffer of the description for reuse
+        let desc = record.desc.take();
         record.clear();
-        if self.line.is_empty() {
-            r#try!(self.reader.read_line(&mut self.line));
-            if self.line.is_empty() {
-                return Ok(());
-            }
-        }
-
-        if !self.line.starts_with('>') {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Expected > at record start.",
-            ));
-        }
-        let mut header_fields = self.line[1..].trim_end().splitn(2, ' ');
-        record.id = header_fields.next().map(|s| s.to_owned()).unwrap();
-        record.desc = header_fields.next().map(|s| s.to_owned());
-        loop {
-            self.line.clear();
-            r#try!(self.reader.read_line(&mut self.line));
-            if self.line.is_empty() || self.line.starts_with('>') {
-                break;
-            }
-            record.seq.push_str(self.line.trim_end());
+        record.desc = desc;
+        let seq = &mut record.seq;
+        let found = self.read_parts(
+            &mut record.header,
+            &mut record.id,
+            &mut record.desc,
+            |line| {
+                seq.push_str(line);
+                Ok(())
+            },
+        )?;
+        if found && self.uppercase {
+            record.mask = Some(MaskedIntervals::new(record.seq.as_bytes()));
+            record.seq.make_ascii_uppercase();
         }
 
         Ok(())
@@ -192,6 +615,88 @@ impl Index {
         Ok(Index { inner, name_to_rid })
     }
 
+    /// Build the index of the given FASTA, like `samtools faidx`. All sequence lines of a
+    /// record except the last one have to be of the same length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Index;
+    /// let fasta = b">chr1 desc\nACGT\nAC\n>chr2\nGGGGG\n";
+    /// let mut fai = Vec::new();
+    /// Index::build(&fasta[..]).unwrap().write(&mut fai).unwrap();
+    /// assert_eq!(fai, b"chr1\t6\t11\t4\t5\nchr2\t5\t25\t5\t6\n");
+    /// ```
+    pub fn build<R: io::Read>(fasta: R) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut reader = io::BufReader::new(fasta);
+        let mut index = Index {
+            inner: vec![],
+            name_to_rid: collections::HashMap::new(),
+        };
+        let mut line = Vec::new();
+        let mut offset = 0;
+        // whether the record has seen a line shorter than the first one, which has to be last
+        let mut last_line = false;
+        loop {
+            line.clear();
+            let line_bytes = reader.read_until(b'\n', &mut line)? as u64;
+            offset += line_bytes;
+            if line_bytes == 0 {
+                break;
+            }
+            let line_bases = line
+                .iter()
+                .rposition(|&c| c != b'\n' && c != b'\r')
+                .map_or(0, |pos| pos as u64 + 1);
+            if line.starts_with(b">") {
+                let header = String::from_utf8_lossy(&line[1..line_bases as usize]);
+                let name = header.split_whitespace().next().unwrap_or("").to_owned();
+                index.name_to_rid.insert(name.clone(), index.inner.len());
+                index.inner.push(IndexRecord {
+                    name,
+                    len: 0,
+                    offset,
+                    line_bases: 0,
+                    line_bytes: 0,
+                });
+                last_line = false;
+                continue;
+            }
+            let record = match index.inner.last_mut() {
+                Some(record) => record,
+                None => return Err(invalid("Expected > at record start.")),
+            };
+            if line_bases == 0 {
+                last_line = true;
+                continue;
+            }
+            if record.line_bases == 0 {
+                record.line_bases = line_bases;
+                record.line_bytes = line_bytes;
+            } else if last_line || line_bases > record.line_bases {
+                return Err(invalid("Inconsistent line length in FASTA record."));
+            }
+            last_line |= line_bases < record.line_bases || line_bytes < record.line_bytes;
+            record.len += line_bases;
+        }
+
+        Ok(index)
+    }
+
+    /// Write the index in the .fai format.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        for record in &self.inner {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                record.name, record.len, record.offset, record.line_bases, record.line_bytes
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Open a FASTA index from a given file path.
     pub fn from_file<P: AsRef<Path>>(path: &P) -> csv::Result<Self> {
         fs::File::open(path)
@@ -221,6 +726,210 @@ impl Index {
     }
 }
 
+quick_error! {
+    #[derive(Debug)]
+    pub enum RegionError {
+        Syntax(region: String) {
+            description("invalid region syntax")
+            display("invalid region {:?}, expecting name[:start[-[end]]]", region)
+        }
+        ZeroStart {
+            description("region start is 0")
+            display("region start has to be at least 1, since coordinates are 1-based")
+        }
+        StartAfterEnd(start: u64, end: u64) {
+            description("region start after region end")
+            display("region start {} is after region end {}", start, end)
+        }
+        OutOfBounds(start: u64, len: u64) {
+            description("region start after sequence end")
+            display("region start {} is after the end of the sequence of length {}", start, len)
+        }
+        UnknownSequence(name: String) {
+            description("unknown sequence name")
+            display("sequence {:?} is not part of the index", name)
+        }
+        Io(err: io::Error) {
+            from()
+            description("error reading FASTA region")
+            display("error reading FASTA region: {}", err)
+            cause(err)
+        }
+    }
+}
+
+/// A region of a sequence in the notation used by SAMtools, i.e. with 1-based, inclusive
+/// coordinates. A missing start denotes the start of the sequence and a missing end the
+/// end of the sequence.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fasta::Region;
+///
+/// let region = Region::parse("chr1:1,000-2,000").unwrap();
+/// assert_eq!(region, Region::new("chr1", Some(1000), Some(2000)));
+/// assert_eq!(Region::parse("chr1:500-").unwrap().end, None);
+/// assert_eq!(Region::parse("chr1").unwrap().start, None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub name: String,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
+impl Region {
+    /// Create a new region with 1-based, inclusive coordinates.
+    pub fn new(name: &str, start: Option<u64>, end: Option<u64>) -> Self {
+        Region {
+            name: name.to_owned(),
+            start,
+            end,
+        }
+    }
+
+    /// Parse a region of the form `name`, `name:start`, `name:start-` or `name:start-end`,
+    /// where the coordinates are decimal numbers without leading zeros that may contain commas
+    /// as thousands separators. As for SAMtools, `name:start` extends to the end of the
+    /// sequence. If the part after the last `:` is not of this form, the whole region is
+    /// taken as the name, such that names may contain colons (e.g. `HLA-A*01:01`).
+    pub fn parse(region: &str) -> Result<Self, RegionError> {
+        let syntax_error = || RegionError::Syntax(region.to_owned());
+        let coordinate = |s: &str| -> Option<u64> {
+            let digits = s.replace(',', "");
+            if digits.is_empty()
+                || !digits.bytes().all(|c| c.is_ascii_digit())
+                || (digits.len() > 1 && digits.starts_with('0'))
+            {
+                return None;
+            }
+            digits.parse().ok()
+        };
+        let coordinates = |s: &str| -> Option<(u64, Option<u64>)> {
+            match s.find('-') {
+                None => Some((coordinate(s)?, None)),
+                Some(j) if j + 1 == s.len() => Some((coordinate(&s[..j])?, None)),
+                Some(j) => Some((coordinate(&s[..j])?, Some(coordinate(&s[j + 1..])?))),
+            }
+        };
+
+        let parsed = region
+            .rfind(':')
+            .and_then(|i| Some((i, coordinates(&region[i + 1..])?)));
+        let (name, start, end) = match parsed {
+            Some((i, (start, end))) => (&region[..i], Some(start), end),
+            None => (region, None, None),
+        };
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(syntax_error());
+        }
+
+        let region = Region::new(name, start, end);
+        region.check()?;
+
+        Ok(region)
+    }
+
+    /// Check that the start is at least 1 and not after the end.
+    fn check(&self) -> Result<(), RegionError> {
+        match (self.start, self.end) {
+            (Some(0), _) => Err(RegionError::ZeroStart),
+            (Some(start), Some(end)) if start > end => Err(RegionError::StartAfterEnd(start, end)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Return the 0-based, half-open interval of this region on a sequence of the given
+    /// length. The end is clipped to the length of the sequence.
+    fn interval(&self, len: u64) -> Result<(u64, u64), RegionError> {
+        self.check()?;
+        let start = match self.start {
+            Some(start) if start > len => return Err(RegionError::OutOfBounds(start, len)),
+            Some(start) => start - 1,
+            None => 0,
+        };
+        let end = self.end.map_or(len, |end| end.min(len));
+
+        Ok((start, end))
+    }
+}
+
+impl FromStr for Region {
+    type Err = RegionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Region::parse(s)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(start) = self.start {
+            write!(f, ":{}-", start)?;
+            if let Some(end) = self.end {
+                write!(f, "{}", end)?;
+            }
+        } else if let Some(end) = self.end {
+            write!(f, ":1-{}", end)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A FASTA record with a bit-encoded sequence, as read by `Reader::read_bitenc`.
+pub struct BitRecord {
+    header: String,
+    id: String,
+    desc: Option<String>,
+    seq: BitEnc,
+}
+
+impl BitRecord {
+    /// Create a new, empty record for an encoding of the given width.
+    pub fn new(width: usize) -> Self {
+        BitRecord {
+            header: String::new(),
+            id: String::new(),
+            desc: None,
+            seq: BitEnc::new(width),
+        }
+    }
+
+    /// Return the id of the record.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Return the description of the record if present.
+    pub fn desc(&self) -> Option<&str> {
+        self.desc.as_deref()
+    }
+
+    /// Return the complete header line of the record without the leading `>`.
+    pub fn full_header(&self) -> &str {
+        &self.header
+    }
+
+    /// Return the encoded sequence.
+    pub fn seq(&self) -> &BitEnc {
+        &self.seq
+    }
+}
+
+/// Name and position of a sequence in a text created by `Reader::concat_with_sentinel` or
+/// `Reader::concat_with_revcomp`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SeqMeta {
+    pub name: String,
+    /// Start of the sequence in the text.
+    pub offset: usize,
+    /// Length of the sequence (without sentinel).
+    pub len: usize,
+}
+
 /// A FASTA reader with an index as created by SAMtools (.fai).
 #[derive(Debug)]
 pub struct IndexedReader<R: io::Read + io::Seek> {
@@ -306,6 +1015,39 @@ impl<R: io::Read + io::Seek> IndexedReader<R> {
         Ok(())
     }
 
+    /// Read the given region into the given vector. In contrast to `fetch`, the region
+    /// uses 1-based, inclusive coordinates as SAMtools does, and its end is clipped to the
+    /// length of the sequence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::{Index, IndexedReader, Region};
+    /// use std::io;
+    ///
+    /// let fasta = b">chr1\nACGTACGT\nTTGG\n";
+    /// let index = Index::build(&fasta[..]).unwrap();
+    /// let mut reader = IndexedReader::with_index(io::Cursor::new(&fasta[..]), index);
+    /// let mut seq = Vec::new();
+    /// reader
+    ///     .fetch_region(&"chr1:7-10".parse().unwrap(), &mut seq)
+    ///     .unwrap();
+    /// assert_eq!(seq, b"GTTT");
+    /// ```
+    pub fn fetch_region(&mut self, region: &Region, seq: &mut Text) -> Result<(), RegionError> {
+        let idx = match self.index.name_to_rid.get(&region.name) {
+            Some(&rid) => self.idx_by_rid(rid)?,
+            None => return Err(RegionError::UnknownSequence(region.name.clone())),
+        };
+        let (start, stop) = region.interval(idx.len)?;
+        self.start = Some(start);
+        self.stop = Some(stop);
+        self.fetched_idx = Some(idx);
+        self.read(seq)?;
+
+        Ok(())
+    }
+
     /// Read the fetched sequence into the given vector.
     pub fn read(&mut self, seq: &mut Text) -> io::Result<()> {
         let idx = self.fetched_idx.clone();
@@ -421,6 +1163,11 @@ impl<R: io::Read + io::Seek> IndexedReader<R> {
     /// of the cursor on the line that the seek ended on is returned.
     fn seek_to(&mut self, idx: &IndexRecord, start: u64) -> io::Result<u64> {
         assert!(start <= idx.len);
+        if idx.line_bases == 0 {
+            // empty sequence
+            self.reader.seek(io::SeekFrom::Start(idx.offset))?;
+            return Ok(0);
+        }
 
         let line_offset = start % idx.line_bases;
         let line_start = start / idx.line_bases * idx.line_bytes;
@@ -560,6 +1307,7 @@ impl<'a, R: io::Read + io::Seek + 'a> Iterator for IndexedReaderIterator<'a, R>
 #[derive(Debug)]
 pub struct Writer<W: io::Write> {
     writer: io::BufWriter<W>,
+    width: usize,
 }
 
 impl Writer<fs::File> {
@@ -570,29 +1318,88 @@ impl Writer<fs::File> {
 }
 
 impl<W: io::Write> Writer<W> {
-    /// Create a new Fasta writer.
+    /// Create a new Fasta writer, writing each sequence on a single line.
     pub fn new(writer: W) -> Self {
         Writer {
             writer: io::BufWriter::new(writer),
+            width: 0,
         }
     }
 
-    /// Directly write a Fasta record.
+    /// Wrap sequences into lines of the given width (e.g. 60 or 80), with 0 meaning that
+    /// sequences are not wrapped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Writer;
+    /// let mut fasta = Vec::new();
+    /// {
+    ///     let mut writer = Writer::new(&mut fasta).width(4);
+    ///     writer.write("id", Some("a description"), b"ACGTACGTAC").unwrap();
+    /// }
+    /// assert_eq!(fasta, b">id a description\nACGT\nACGT\nAC\n");
+    /// ```
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Directly write a Fasta record. The header is written as returned by
+    /// `Record::full_header`, such that headers of records that have been read from a file
+    /// are reproduced exactly.
     pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
-        self.write(record.id(), record.desc(), record.seq())
+        Self::check_header(record.id(), record.desc())?;
+        self.writer.write_all(b">")?;
+        self.writer.write_all(record.full_header().as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.write_seq(record.seq())
     }
 
-    /// Write a Fasta record with given id, optional description and sequence.
+    /// Write a Fasta record with given id, optional description and sequence. Fails if the
+    /// id contains whitespace or the description contains a line break, since they could
+    /// not be read back. An empty sequence yields a record without sequence lines.
     pub fn write(&mut self, id: &str, desc: Option<&str>, seq: TextSlice<'_>) -> io::Result<()> {
-        r#try!(self.writer.write_all(b">"));
-        r#try!(self.writer.write_all(id.as_bytes()));
-        if desc.is_some() {
-            r#try!(self.writer.write_all(b" "));
-            r#try!(self.writer.write_all(desc.unwrap().as_bytes()));
+        Self::check_header(id, desc)?;
+        self.writer.write_all(b">")?;
+        self.writer.write_all(id.as_bytes())?;
+        if let Some(desc) = desc {
+            self.writer.write_all(b" ")?;
+            self.writer.write_all(desc.as_bytes())?;
+        }
+        self.writer.write_all(b"\n")?;
+        self.write_seq(seq)
+    }
+
+    /// Check that the given id and description can be read back.
+    fn check_header(id: &str, desc: Option<&str>) -> io::Result<()> {
+        if id.contains(char::is_whitespace) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Fasta record id contains whitespace.",
+            ));
+        }
+        if desc.is_some_and(|desc| desc.contains(&['\n', '\r'][..])) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Fasta record description contains a line break.",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Write the sequence lines of a record.
+    fn write_seq(&mut self, seq: TextSlice<'_>) -> io::Result<()> {
+        let width = if self.width == 0 {
+            seq.len().max(1)
+        } else {
+            self.width
+        };
+        for line in seq.chunks(width) {
+            self.writer.write_all(line)?;
+            self.writer.write_all(b"\n")?;
         }
-        r#try!(self.writer.write_all(b"\n"));
-        r#try!(self.writer.write_all(seq));
-        r#try!(self.writer.write_all(b"\n"));
 
         Ok(())
     }
@@ -606,31 +1413,41 @@ impl<W: io::Write> Writer<W> {
 /// A FASTA record.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Record {
+    #[serde(default)]
+    header: String,
     id: String,
     desc: Option<String>,
     seq: String,
+    /// Soft-masked intervals of a sequence that has been converted to uppercase.
+    #[serde(default)]
+    mask: Option<MaskedIntervals>,
 }
 
 impl Record {
     /// Create a new instance.
     pub fn new() -> Self {
         Record {
+            header: String::new(),
             id: String::new(),
             desc: None,
             seq: String::new(),
+            mask: None,
         }
     }
 
-    /// Create a new Fasta record from given attributes.
+    /// Create a new Fasta record from given attributes. The header consists of the id,
+    /// followed by a space and the description if present.
     pub fn with_attrs(id: &str, desc: Option<&str>, seq: TextSlice<'_>) -> Self {
-        let desc = match desc {
-            Some(desc) => Some(desc.to_owned()),
-            _ => None,
+        let header = match desc {
+            Some(desc) => format!("{} {}", id, desc),
+            None => id.to_owned(),
         };
         Record {
+            header,
             id: id.to_owned(),
-            desc,
+            desc: desc.map(|desc| desc.to_owned()),
             seq: String::from_utf8(seq.to_vec()).unwrap(),
+            mask: None,
         }
     }
 
@@ -644,6 +1461,9 @@ impl Record {
         if self.id().is_empty() {
             return Err("Expecting id for Fasta record.");
         }
+        if self.id.contains(char::is_whitespace) {
+            return Err("Whitespace found in id.");
+        }
         if !self.seq.is_ascii() {
             return Err("Non-ascii character found in sequence.");
         }
@@ -651,12 +1471,29 @@ impl Record {
         Ok(())
     }
 
-    /// Return the id of the record.
+    /// Check that the sequence of the record is a word over the given alphabet, reporting the
+    /// first invalid symbol otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::io::fasta::Record;
+    /// let record = Record::with_attrs("chr1", None, b"ACGTUACGT");
+    /// let err = record.validate(&dna::alphabet()).unwrap_err();
+    /// assert_eq!((err.symbol, err.pos), (b'U', 4));
+    /// ```
+    pub fn validate(&self, alphabet: &Alphabet) -> Result<(), InvalidSymbol> {
+        alphabet.validate(self.seq())
+    }
+
+    /// Return the id of the record, i.e. the header up to the first whitespace.
     pub fn id(&self) -> &str {
         self.id.as_ref()
     }
 
-    /// Return descriptions if present.
+    /// Return the description of the record if present, i.e. the header after the first
+    /// whitespace, without trailing whitespace.
     pub fn desc(&self) -> Option<&str> {
         match self.desc.as_ref() {
             Some(desc) => Some(&desc),
@@ -664,16 +1501,55 @@ impl Record {
         }
     }
 
+    /// Return the complete header line of the record without the leading `>`. For records
+    /// read from a file, this is the header exactly as found in the file, including the
+    /// whitespace separating the id and description and any trailing whitespace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Reader;
+    /// let mut records = Reader::new(&b">chr1\tAC:CM000663.2 gi:568336023 \nACGT\n"[..]).records();
+    /// let record = records.next().unwrap().unwrap();
+    /// assert_eq!(record.id(), "chr1");
+    /// assert_eq!(record.desc(), Some("AC:CM000663.2 gi:568336023"));
+    /// assert_eq!(record.full_header(), "chr1\tAC:CM000663.2 gi:568336023 ");
+    /// ```
+    pub fn full_header(&self) -> &str {
+        &self.header
+    }
+
     /// Return the sequence of the record.
     pub fn seq(&self) -> TextSlice<'_> {
         self.seq.as_bytes()
     }
 
+    /// Return the soft-masked intervals of the sequence, i.e. the maximal runs of lowercase
+    /// symbols as 0-based, half-open intervals ordered by position. If the sequence has been
+    /// converted to uppercase by `Reader::uppercase`, the intervals from before the
+    /// conversion are returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Record;
+    /// let record = Record::with_attrs("chr1", None, b"acGTTtaA");
+    /// assert_eq!(record.masked_intervals(), [0..2, 5..7]);
+    /// ```
+    pub fn masked_intervals(&self) -> Vec<Range<usize>> {
+        match self.mask {
+            Some(ref mask) => mask.intervals.clone(),
+            None => MaskedIntervals::new(self.seq()).intervals,
+        }
+    }
+
     /// Clear the record.
     fn clear(&mut self) {
+        self.header.clear();
         self.id.clear();
         self.desc = None;
         self.seq.clear();
+        self.mask = None;
     }
 }
 
@@ -684,9 +1560,9 @@ pub struct Records<R: io::Read> {
 }
 
 impl<R: io::Read> Iterator for Records<R> {
-    type Item = io::Result<Record>;
+    type Item = Result<Record, Error>;
 
-    fn next(&mut self) -> Option<io::Result<Record>> {
+    fn next(&mut self) -> Option<Result<Record, Error>> {
         if self.error_has_occured {
             None
         } else {
@@ -703,9 +1579,142 @@ impl<R: io::Read> Iterator for Records<R> {
     }
 }
 
+/// Records with distinct ids in file order, with lookup by id.
+#[derive(Default, Clone, Debug)]
+pub struct RecordMap {
+    records: Vec<Record>,
+    index: collections::HashMap<String, usize>,
+}
+
+impl RecordMap {
+    /// Add a record, returning the index of a previous record with the same id instead if
+    /// there is one.
+    fn insert(&mut self, record: Record) -> Result<(), usize> {
+        if let Some(&i) = self.index.get(record.id()) {
+            return Err(i);
+        }
+        self.index.insert(record.id.clone(), self.records.len());
+        self.records.push(record);
+
+        Ok(())
+    }
+
+    /// Return the record with the given id.
+    pub fn get(&self, id: &str) -> Option<&Record> {
+        self.index.get(id).map(|&i| &self.records[i])
+    }
+
+    /// Return the position of the record with the given id in file order.
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.index.get(id).cloned()
+    }
+
+    /// Iterate over the records in file order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Record> {
+        self.records.iter()
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Return the records in file order.
+    pub fn into_records(self) -> Vec<Record> {
+        self.records
+    }
+}
+
+/// A record that has been renamed by `read_deduplicated`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Renamed {
+    /// Index of the record in the file.
+    pub record: usize,
+    pub old_id: String,
+    pub new_id: String,
+}
+
+/// Read all records, failing with `Error::DuplicateId` if two records have the same id.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fasta::{read_unique, Error, Reader};
+///
+/// let records = read_unique(Reader::new(&b">chr1\nAC\n>chr2\nGT\n"[..])).unwrap();
+/// assert_eq!(records.get("chr2").unwrap().seq(), b"GT");
+///
+/// let reader = Reader::new(&b">chr1\nAC\n>chr2\nGT\n>chr1\nTT\n"[..]);
+/// match read_unique(reader) {
+///     Err(Error::DuplicateId(id, first, second)) => assert_eq!((id.as_str(), first, second), ("chr1", 0, 2)),
+///     _ => panic!("expected duplicate id"),
+/// }
+/// ```
+pub fn read_unique<R: io::Read>(reader: Reader<R>) -> Result<RecordMap, Error> {
+    let mut records = RecordMap::default();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        if let Err(first) = records.insert(record) {
+            let id = records.records[first].id.clone();
+            return Err(Error::DuplicateId(id, first, i));
+        }
+    }
+
+    Ok(records)
+}
+
+/// Read all records, renaming records whose id has been seen before by appending `.2`,
+/// `.3`, ... (choosing the first suffix giving an id that has not been seen before).
+/// Returns the records together with the renamings in file order.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fasta::{read_deduplicated, Reader};
+///
+/// let reader = Reader::new(&b">chr1\nAC\n>chr2\nGT\n>chr1 desc\nTT\n"[..]);
+/// let (records, renamed) = read_deduplicated(reader).unwrap();
+/// assert_eq!(records.get("chr1.2").unwrap().full_header(), "chr1.2 desc");
+/// assert_eq!((renamed[0].record, renamed[0].old_id.as_str()), (2, "chr1"));
+/// ```
+pub fn read_deduplicated<R: io::Read>(
+    reader: Reader<R>,
+) -> Result<(RecordMap, Vec<Renamed>), Error> {
+    let mut records = RecordMap::default();
+    let mut renamed = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let mut record = record?;
+        if records.index.contains_key(record.id()) {
+            let new_id = (2..)
+                .map(|n| format!("{}.{}", record.id, n))
+                .find(|id| !records.index.contains_key(id))
+                .unwrap();
+            record.header.replace_range(..record.id.len(), &new_id);
+            let old_id = std::mem::replace(&mut record.id, new_id.clone());
+            renamed.push(Renamed {
+                record: i,
+                old_id,
+                new_id,
+            });
+        }
+        records.insert(record).unwrap();
+    }
+
+    Ok((records, renamed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data_structures::bwt::{bwt, less, Occ};
+    use crate::data_structures::fmindex::{DocArray, FMDIndex, FMIndex};
+    use crate::data_structures::suffix_array::suffix_array;
+    use bio_types::strand::ReqStrand;
     use std::io;
 
     const FASTA_FILE: &'static [u8] = b">id desc
@@ -836,11 +1845,61 @@ ATTGTTGTTTTA
     fn test_reader_no_id() {
         let mut reader = Reader::new(&b">\nACGTA\n"[..]);
         let mut record = Record::new();
-        reader.read(&mut record).unwrap();
         assert!(
-            record.check().is_err(),
-            "check() should return Err if FASTA header is empty"
+            reader.read(&mut record).is_err(),
+            "read() should return Err if FASTA header is empty"
+        );
+    }
+
+    fn parse_error_location(err: Error) -> (usize, usize, String) {
+        match err {
+            Error::Parse {
+                line,
+                record,
+                context,
+                ..
+            } => (line, record, context),
+            err => panic!("expected parse error, found {}", err),
+        }
+    }
+
+    #[test]
+    fn test_reader_parse_errors() {
+        let fixtures: [(&[u8], _); 4] = [
+            (b"ACGT\n>id\nA\n", (1, 0, "ACGT")),
+            (b">id1\nACGT\n>id2\nAC\n\nGT\n", (5, 1, "")),
+            (b">id1\nACGT\n\n>id2\nAC\n\n>id3\n\nA\n", (8, 2, "")),
+            (b">id1\nA\n>id2\nC\n> desc\nG\n", (5, 2, "> desc")),
+        ];
+        for &(fasta, (line, record, context)) in &fixtures {
+            let mut records = Reader::new(fasta).records();
+            let err = records.find_map(|record| record.err()).unwrap();
+            assert_eq!(
+                parse_error_location(err),
+                (line, record, context.to_owned())
+            );
+            assert!(records.next().is_none());
+        }
+
+        let long_line = [b'A'; 100];
+        let mut record = Record::new();
+        let err = Reader::new(&long_line[..]).read(&mut record).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "line 1 (record 0): Expected > at record start. \"{}...\"",
+                "A".repeat(60)
+            )
         );
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+
+        // blank lines at the end of a record are fine
+        let mut reader = Reader::new(&b">id\nACGT\n\n>id2\nA\n\n\n"[..]);
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(record.seq(), b"ACGT");
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(record.seq(), b"A");
+        assert!(!reader.read_next(&mut record).unwrap());
     }
 
     #[test]
@@ -893,6 +1952,85 @@ ATTGTTGTTTTA
         assert_eq!(record.seq(), b"ATGCGGG");
     }
 
+    const FASTA_FILE_WIDTHS: &[u8] = b">chr1 first record
+ACGTACGTAC
+GTACGTACGT
+ACG
+>chr2
+TTGCA
+TTGC
+>chr3
+>chr4 desc
+CCCCCCCCCCCCCCC
+CC
+
+>chr5
+A
+";
+
+    #[test]
+    fn test_index_build() {
+        let mut fai = Vec::new();
+        Index::build(FASTA_FILE).unwrap().write(&mut fai).unwrap();
+        assert_eq!(fai, FAI_FILE);
+        let mut fai = Vec::new();
+        Index::build(FASTA_FILE_CRLF)
+            .unwrap()
+            .write(&mut fai)
+            .unwrap();
+        assert_eq!(fai, b"id\t52\t10\t12\t14\nid2\t40\t78\t12\t14\n");
+        let mut fai = Vec::new();
+        Index::build(FASTA_FILE_NO_TRAILING_LF)
+            .unwrap()
+            .write(&mut fai)
+            .unwrap();
+        assert_eq!(fai, b"id\t16\t9\t12\t13\n");
+
+        let index = Index::build(FASTA_FILE_WIDTHS).unwrap();
+        let mut fai = Vec::new();
+        index.write(&mut fai).unwrap();
+        let index = Index::new(&fai[..]).unwrap();
+        let lens: Vec<u64> = index.sequences().iter().map(|seq| seq.len).collect();
+        assert_eq!(lens, [23, 9, 0, 17, 1]);
+
+        // lines within a record differing in length
+        for fasta in &[
+            &b">id\nACGT\nAC\nACGT\n"[..],
+            b">id\nACGT\nACGTA\n",
+            b">id\nAC\n\nAC\n",
+        ] {
+            assert_eq!(
+                Index::build(*fasta).err().map(|err| err.kind()),
+                Some(io::ErrorKind::InvalidData)
+            );
+        }
+        assert!(Index::build(&b"ACGT\n>id\nACGT\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_indexed_reader_line_widths() {
+        let index = Index::build(FASTA_FILE_WIDTHS).unwrap();
+        let mut reader = IndexedReader::with_index(io::Cursor::new(FASTA_FILE_WIDTHS), index);
+        let records: Vec<Record> = Reader::new(FASTA_FILE_WIDTHS)
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        let mut seq = Vec::new();
+        for record in &records {
+            let len = record.seq().len() as u64;
+            for start in 0..=len {
+                for stop in start..=len {
+                    reader.fetch(record.id(), start, stop).unwrap();
+                    reader.read(&mut seq).unwrap();
+                    assert_eq!(seq, &record.seq()[start as usize..stop as usize]);
+                }
+            }
+            reader.fetch(record.id(), 0, len + 1).unwrap();
+            assert!(reader.read(&mut seq).is_err());
+        }
+        assert!(reader.fetch("chr6", 0, 1).is_err());
+    }
+
     #[test]
     fn test_index_sequences() {
         let reader = IndexedReader::new(io::Cursor::new(FASTA_FILE), FAI_FILE).unwrap();
@@ -915,6 +2053,92 @@ ATTGTTGTTTTA
         );
     }
 
+    #[test]
+    fn test_region_parse() {
+        let region = |name, start, end| Region::new(name, start, end);
+        for &(s, ref expected) in &[
+            ("chr1", region("chr1", None, None)),
+            ("chr1:500", region("chr1", Some(500), None)),
+            ("chr1:500-", region("chr1", Some(500), None)),
+            ("chr1:1,000-2,000", region("chr1", Some(1000), Some(2000))),
+            ("chr1:1000-1000", region("chr1", Some(1000), Some(1000))),
+            ("HLA-A*01:01:1-10", region("HLA-A*01:01", Some(1), Some(10))),
+            ("HLA-A*01:01", region("HLA-A*01:01", None, None)),
+            ("chr1:", region("chr1:", None, None)),
+            ("chr1:-5", region("chr1:-5", None, None)),
+            ("chr1:a-5", region("chr1:a-5", None, None)),
+            ("chr1:1-2-3", region("chr1:1-2-3", None, None)),
+            ("chr1:+1", region("chr1:+1", None, None)),
+        ] {
+            assert_eq!(&Region::parse(s).unwrap(), expected);
+            assert_eq!(&s.parse::<Region>().unwrap(), expected);
+        }
+        assert_eq!(region("chr1", Some(1000), None).to_string(), "chr1:1000-");
+        assert_eq!(region("chr1", None, Some(20)).to_string(), "chr1:1-20");
+        assert_eq!(
+            region("chr1", Some(1), Some(20))
+                .to_string()
+                .parse::<Region>()
+                .unwrap(),
+            region("chr1", Some(1), Some(20))
+        );
+
+        for s in &["", ":1-2", "chr 1", "chr 1:1-2"] {
+            match Region::parse(s) {
+                Err(RegionError::Syntax(region)) => assert_eq!(&region, s),
+                res => panic!("expected syntax error for {:?}, found {:?}", s, res),
+            }
+        }
+        assert!(matches!(
+            Region::parse("chr1:0-5"),
+            Err(RegionError::ZeroStart)
+        ));
+        assert!(matches!(
+            Region::parse("chr1:2,000-1,000"),
+            Err(RegionError::StartAfterEnd(2000, 1000))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_region() {
+        let mut reader = IndexedReader::new(io::Cursor::new(FASTA_FILE), FAI_FILE).unwrap();
+        let mut seq = Vec::new();
+        let mut fetch = |region: &str| {
+            reader
+                .fetch_region(&region.parse().unwrap(), &mut seq)
+                .map(|()| seq.clone())
+        };
+        assert_eq!(fetch("id:2-5").unwrap(), b"CCGT");
+        assert_eq!(fetch("id:12-13").unwrap(), b"AC");
+        assert_eq!(fetch("id:50").unwrap(), b"CCC");
+        assert_eq!(fetch("id:50-1,000").unwrap(), b"CCC");
+        assert_eq!(fetch("id:52-52").unwrap(), b"C");
+        assert_eq!(fetch("id2:37-").unwrap(), b"GGGG");
+        assert_eq!(
+            fetch("id2").unwrap(),
+            &b"ATTGTTGTTTTAATTGTTGTTTTAATTGTTGTTTTAGGGG"[..]
+        );
+        assert!(matches!(
+            fetch("id:53-60"),
+            Err(RegionError::OutOfBounds(53, 52))
+        ));
+        match fetch("id3:1-5") {
+            Err(RegionError::UnknownSequence(name)) => assert_eq!(name, "id3"),
+            res => panic!("expected unknown sequence, found {:?}", res),
+        }
+
+        let region = Region::new("id", Some(5), Some(2));
+        assert!(matches!(
+            reader.fetch_region(&region, &mut seq),
+            Err(RegionError::StartAfterEnd(5, 2))
+        ));
+        let region = Region::new("id", Some(0), None);
+        assert!(matches!(
+            reader.fetch_region(&region, &mut seq),
+            Err(RegionError::ZeroStart)
+        ));
+    }
+
     #[test]
     fn test_indexed_reader() {
         _test_indexed_reader(&FASTA_FILE, &FAI_FILE, _read_buffer);
@@ -1288,6 +2512,34 @@ ATTGTTGTTTTA
         assert!(reader.read_iter().is_err());
     }
 
+    #[test]
+    fn test_read_next_end() {
+        // last record without trailing newline and records without sequence
+        let mut reader = Reader::new(&b">id0 long description\nA\n>id1\n>id2 desc\nAC\nGT"[..]);
+        let mut record = Record::new();
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(record.desc(), Some("long description"));
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(
+            (record.id(), record.desc(), record.seq()),
+            ("id1", None, &b""[..])
+        );
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(
+            (record.id(), record.desc(), record.seq()),
+            ("id2", Some("desc"), &b"ACGT"[..])
+        );
+        assert!(!reader.read_next(&mut record).unwrap());
+
+        let mut reader = Reader::new(TRUNCATED_FASTA);
+        assert!(reader.read_next(&mut record).unwrap());
+        assert_eq!(record.seq(), b"ACCGTAGGCTGA");
+        assert!(!reader.read_next(&mut record).unwrap());
+
+        let mut reader = Reader::new(&b"ACGT\n"[..]);
+        assert!(reader.read_next(&mut record).is_err());
+    }
+
     #[test]
     fn test_writer() {
         let mut writer = Writer::new(Vec::new());
@@ -1296,4 +2548,439 @@ ATTGTTGTTTTA
         writer.flush().unwrap();
         assert_eq!(writer.writer.get_ref(), &WRITE_FASTA_FILE);
     }
+
+    #[test]
+    fn test_reader_line_endings() {
+        let unix = b">id1 desc\nACGT\nAC\n>id2\n>id3\nGGG\n";
+        let parse = |fasta: &[u8]| -> Vec<(String, Option<String>, Vec<u8>)> {
+            Reader::new(fasta)
+                .records()
+                .map(|record| {
+                    let record = record.unwrap();
+                    (
+                        record.id().to_owned(),
+                        record.desc().map(|desc| desc.to_owned()),
+                        record.seq().to_vec(),
+                    )
+                })
+                .collect()
+        };
+        let expected = parse(unix);
+        assert_eq!(expected.len(), 3);
+        for fasta in &[
+            &b">id1 desc\r\nACGT\r\nAC\r\n>id2\r\n>id3\r\nGGG\r\n"[..],
+            b">id1 desc\r\nACGT\nAC\r\n\r\n>id2\n\n>id3\r\nGGG",
+            b"\r\n\n>id1 desc\nACGT\nAC\n\n\n>id2\r\n>id3\nGGG\r\n\n\r\n",
+        ] {
+            assert_eq!(parse(fasta), expected);
+        }
+        let alphabet = crate::alphabets::dna::alphabet();
+        for record in Reader::new(&b">id\r\nACGT\r\nACGT\r\n"[..]).records() {
+            let record = record.unwrap();
+            assert_eq!(record.full_header(), "id");
+            assert!(record.validate(&alphabet).is_ok());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_masked_intervals() {
+        let fasta = b">start\nacGT\nACGT\n>end\nACGT\nACgt\n>lines\nACGTac\ngtacGT\nAc\n\
+>all\nacgt\nnnnn\nac\n>none\nACGT\n>empty\n";
+        let expected = [
+            vec![0..2],
+            vec![6..8],
+            vec![4..10, 13..14],
+            vec![0..10],
+            vec![],
+            vec![],
+        ];
+        let masked: Vec<_> = Reader::new(&fasta[..])
+            .records()
+            .map(|record| record.unwrap().masked_intervals())
+            .collect();
+        assert_eq!(masked, expected);
+
+        let original: Vec<Record> = Reader::new(&fasta[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        let mut reader = Reader::new(&fasta[..]).uppercase(true);
+        let mut record = Record::new();
+        for (original, expected) in original.iter().zip(&expected) {
+            assert!(reader.read_next(&mut record).unwrap());
+            assert_eq!(record.seq(), &original.seq().to_ascii_uppercase()[..]);
+            assert_eq!(&record.masked_intervals(), expected);
+            let mut seq = record.seq().to_vec();
+            MaskedIntervals {
+                intervals: record.masked_intervals(),
+            }
+            .apply(&mut seq);
+            assert_eq!(seq, original.seq());
+        }
+        assert!(!reader.read_next(&mut record).unwrap());
+    }
+
+    #[test]
+    fn test_records_bitenc() {
+        let fasta =
+            b">id1 desc\nACGTTG\nCA\n>id2\n>id3\r\nGGGGCCCCAAAATTTTGGGGCCCCAAAATTTTG\r\nA\n";
+        let encoding = Encoding::for_alphabet(&Alphabet::new(b"ACGT"));
+        let expected: Vec<Record> = Reader::new(&fasta[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        let mut reader = Reader::new(&fasta[..]);
+        let records: Vec<BitRecord> = reader
+            .records_bitenc(&encoding)
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), expected.len());
+        for (record, expected) in records.iter().zip(&expected) {
+            assert_eq!(record.id(), expected.id());
+            assert_eq!(record.desc(), expected.desc());
+            assert_eq!(record.full_header(), expected.full_header());
+            assert_eq!(record.seq().width(), 2);
+            assert_eq!(encoding.decode(record.seq()), expected.seq());
+        }
+
+        // the position of an invalid symbol is given relative to the sequence
+        let fasta = b">ok\nACGT\n>bad\nACGT\nACNGT\n>next\nA\n";
+        let mut reader = Reader::new(&fasta[..]);
+        let mut records = reader.records_bitenc(&encoding);
+        assert!(records.next().unwrap().is_ok());
+        match records.next().unwrap() {
+            Err(Error::InvalidSymbol(id, err)) => {
+                assert_eq!((id.as_str(), err.symbol, err.pos), ("bad", b'N', 6));
+                assert_eq!(err.context, "ACNGT");
+            }
+            _ => panic!("expected invalid symbol"),
+        }
+        assert!(records.next().is_none());
+
+        let encoding = Encoding::for_alphabet(&dna::n_alphabet()).fallback(b'N');
+        let mut reader = Reader::new(&b">r\nACRYN\nGT\n"[..]);
+        let mut record = BitRecord::new(1);
+        assert!(reader.read_bitenc(&mut record, &encoding).unwrap());
+        assert_eq!(encoding.decode(record.seq()), b"ACNNNGT");
+        assert!(!reader.read_bitenc(&mut record, &encoding).unwrap());
+    }
+
+    #[test]
+    fn test_seek_to() {
+        let records = |reader: &mut Reader<io::Cursor<&[u8]>>| -> Vec<(String, Vec<u8>)> {
+            let mut record = Record::new();
+            let mut records = Vec::new();
+            while reader.read_next(&mut record).unwrap() {
+                records.push((record.id().to_owned(), record.seq().to_vec()));
+            }
+            records
+        };
+        let expected = records(&mut Reader::new(io::Cursor::new(FASTA_FILE_WIDTHS)));
+        assert_eq!(expected.len(), 5);
+
+        let mut pos = Vec::new();
+        let mut reader = Reader::new(io::Cursor::new(FASTA_FILE_WIDTHS));
+        let mut record = Record::new();
+        pos.push(reader.position());
+        while reader.read_next(&mut record).unwrap() {
+            pos.push(reader.position());
+        }
+        assert_eq!(pos[0].offset(), 0);
+        assert_eq!(pos[5].offset(), FASTA_FILE_WIDTHS.len() as u64);
+        for (i, pos) in pos.iter().enumerate() {
+            assert_eq!(pos.record(), i);
+            if i < 5 {
+                assert_eq!(FASTA_FILE_WIDTHS[pos.offset() as usize], b'>');
+            }
+            let mut reader = Reader::new(io::Cursor::new(FASTA_FILE_WIDTHS));
+            reader.seek_to(*pos).unwrap();
+            assert_eq!(reader.position(), *pos);
+            assert_eq!(records(&mut reader), &expected[i..]);
+        }
+
+        // line numbers of errors after seeking refer to the whole file
+        let fasta: &[u8] = b">id1\nACGT\n>id2\nAC\n\nGT\n";
+        let mut reader = Reader::new(io::Cursor::new(fasta));
+        reader.read_next(&mut record).unwrap();
+        let pos = reader.position();
+        let mut reader = Reader::new(io::Cursor::new(fasta));
+        reader.seek_to(pos).unwrap();
+        match reader.read_next(&mut record) {
+            Err(Error::Parse { line, record, .. }) => assert_eq!((line, record), (5, 1)),
+            res => panic!("expected parse error, found {:?}", res),
+        }
+
+        // positions inside of records are rejected
+        for &offset in &[1, 5, 7] {
+            let mut reader = Reader::new(io::Cursor::new(fasta));
+            let pos = FilePos {
+                offset,
+                line: 0,
+                record: 0,
+            };
+            match reader.seek_to(pos) {
+                Err(Error::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+                res => panic!("expected error, found {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let fasta: &[u8] = b">chr1 AC:CM000663.2 gi:568336023
+ACGT
+>chr2\tAC:CM000664.2\tgi:568336022
+AC
+>chr3
+GT
+>chr4 trailing whitespace \t
+A
+>chr5\t
+>chr6  leading whitespace
+T
+";
+        let records: Vec<Record> = Reader::new(fasta)
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        let fields: Vec<_> = records
+            .iter()
+            .map(|record| (record.id(), record.desc()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("chr1", Some("AC:CM000663.2 gi:568336023")),
+                ("chr2", Some("AC:CM000664.2\tgi:568336022")),
+                ("chr3", None),
+                ("chr4", Some("trailing whitespace")),
+                ("chr5", None),
+                ("chr6", Some(" leading whitespace")),
+            ]
+        );
+        assert_eq!(records[3].full_header(), "chr4 trailing whitespace \t");
+
+        let mut writer = Writer::new(Vec::new());
+        for record in &records {
+            writer.write_record(record).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(writer.writer.get_ref(), &fasta);
+
+        let record = Record::with_attrs("id", Some("desc"), b"ACGT");
+        assert_eq!(record.full_header(), "id desc");
+        let record = Record::with_attrs("an id", None, b"ACGT");
+        assert!(record.check().is_err());
+        assert!(Writer::new(Vec::new()).write_record(&record).is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let fasta: String = (0..50).map(|i| format!(">seq{}\nACGT\n", i)).collect();
+        let ids = |records: Vec<Record>| -> Vec<String> {
+            records
+                .iter()
+                .map(|record| record.id().to_owned())
+                .collect()
+        };
+        let sample = ids(Reader::new(fasta.as_bytes()).sample(5, 3).unwrap());
+        assert_eq!(sample.len(), 5);
+        assert_eq!(
+            sample,
+            ids(Reader::new(fasta.as_bytes()).sample(5, 3).unwrap())
+        );
+        assert_eq!(
+            Reader::new(fasta.as_bytes()).sample(60, 3).unwrap().len(),
+            50
+        );
+        assert_eq!(
+            Reader::new(fasta.as_bytes())
+                .sample_frac(1.0, 3)
+                .unwrap()
+                .len(),
+            50
+        );
+        assert!(Reader::new(&b">seq0\nAC\n\nGT\n"[..]).sample(5, 3).is_err());
+    }
+
+    #[test]
+    fn test_concat() {
+        let fasta: &[u8] = b">chr1 first\nACGTT\nGA\n>chr2\n>chr3\nnnACG\n";
+        let naive = |revcomp: bool| {
+            let mut text = Vec::new();
+            for record in Reader::new(fasta).records() {
+                let record = record.unwrap();
+                text.extend_from_slice(record.seq());
+                text.push(b'$');
+                if revcomp {
+                    text.extend(dna::revcomp(record.seq()));
+                    text.push(b'$');
+                }
+            }
+            text
+        };
+        let alphabet = dna::n_alphabet().case_insensitive();
+
+        let (text, seqs) = Reader::new(fasta).concat_with_sentinel(&alphabet).unwrap();
+        assert_eq!(text, naive(false));
+        let docs = DocArray::new(&text);
+        assert_eq!(docs.num_seqs(), seqs.len());
+        for (i, seq) in seqs.iter().enumerate() {
+            assert_eq!((docs.seq_start(i), docs.seq_len(i)), (seq.offset, seq.len));
+        }
+        let names: Vec<&str> = seqs.iter().map(|seq| seq.name.as_str()).collect();
+        assert_eq!(names, ["chr1", "chr2", "chr3"]);
+
+        let (text, seqs) = Reader::new(fasta).concat_with_revcomp(&alphabet).unwrap();
+        assert_eq!(text, naive(true));
+        let docs = DocArray::new(&text);
+        assert_eq!(docs.num_seqs(), 2 * seqs.len());
+        for (i, seq) in seqs.iter().enumerate() {
+            assert_eq!(
+                (docs.seq_start(2 * i), docs.seq_len(2 * i)),
+                (seq.offset, seq.len)
+            );
+            assert_eq!(docs.seq_len(2 * i + 1), seq.len);
+        }
+
+        // hits in the FMD-Index can be attributed to the records
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let fmd_alphabet = dna::n_alphabet().union(&Alphabet::new(b"acgtn$"));
+        let less = less(&bwt, &fmd_alphabet);
+        let occ = Occ::new(&bwt, 3, &fmd_alphabet);
+        let fmdindex = FMDIndex::from(FMIndex::new(&bwt, &less, &occ));
+        let mut interval = fmdindex.init_interval();
+        for &a in b"CGT".iter().rev() {
+            interval = fmdindex.backward_ext(&interval, a);
+        }
+        let mut hits: Vec<_> = interval
+            .occ_docs(&sa, &docs)
+            .into_iter()
+            .map(|hit| (seqs[hit.doc].name.as_str(), hit.pos, hit.strand))
+            .collect();
+        hits.sort_unstable();
+        assert_eq!(
+            hits,
+            [
+                ("chr1", 0, ReqStrand::Reverse),
+                ("chr1", 1, ReqStrand::Forward),
+                ("chr3", 2, ReqStrand::Reverse)
+            ]
+        );
+
+        let err = Reader::new(&b">ok\nACGT\n>bad\nACXT\n"[..])
+            .concat_with_sentinel(&dna::alphabet())
+            .unwrap_err();
+        match err {
+            Error::InvalidSymbol(id, err) => {
+                assert_eq!((id.as_str(), err.symbol, err.pos), ("bad", b'X', 2))
+            }
+            err => panic!("expected invalid symbol, found {}", err),
+        }
+    }
+
+    #[test]
+    fn test_read_unique() {
+        let duplicate = |fasta: &[u8]| match read_unique(Reader::new(fasta)) {
+            Err(Error::DuplicateId(id, first, second)) => (id, first, second),
+            res => panic!(
+                "expected duplicate id, found {:?}",
+                res.map(|records| records.len())
+            ),
+        };
+        assert_eq!(
+            duplicate(b">chr1\nA\n>chr1\nC\n>chr2\nG\n"),
+            ("chr1".to_owned(), 0, 1)
+        );
+        assert_eq!(
+            duplicate(b">chr1\nA\n>chr2\nC\n>chr3\nG\n>chr2 again\nT\n"),
+            ("chr2".to_owned(), 1, 3)
+        );
+
+        let records = read_unique(Reader::new(FASTA_FILE)).unwrap();
+        let ids: Vec<&str> = records.iter().map(|record| record.id()).collect();
+        assert_eq!(ids, ["id", "id2"]);
+        assert_eq!(records.position("id2"), Some(1));
+        assert_eq!(records.get("id").unwrap().desc(), Some("desc"));
+        assert!(records.get("id3").is_none());
+        assert!(read_unique(Reader::new(&b"ACGT\n"[..])).is_err());
+    }
+
+    #[test]
+    fn test_read_deduplicated() {
+        let fasta = b">chr1 a\nA\n>chr1\nC\n>chr2\nG\n>chr1.2\nT\n>chr1\nAA\n";
+        let (records, renamed) = read_deduplicated(Reader::new(&fasta[..])).unwrap();
+        let ids: Vec<&str> = records.iter().map(|record| record.id()).collect();
+        assert_eq!(ids, ["chr1", "chr1.2", "chr2", "chr1.2.2", "chr1.3"]);
+        let seqs: Vec<&[u8]> = records.iter().map(|record| record.seq()).collect();
+        assert_eq!(seqs, [&b"A"[..], b"C", b"G", b"T", b"AA"]);
+        let renamed: Vec<_> = renamed
+            .iter()
+            .map(|r| (r.record, r.old_id.as_str(), r.new_id.as_str()))
+            .collect();
+        assert_eq!(
+            renamed,
+            [
+                (1, "chr1", "chr1.2"),
+                (3, "chr1.2", "chr1.2.2"),
+                (4, "chr1", "chr1.3")
+            ]
+        );
+        assert_eq!(records.get("chr1").unwrap().full_header(), "chr1 a");
+        assert_eq!(records.get("chr1.3").unwrap().full_header(), "chr1.3");
+
+        let (records, renamed) = read_deduplicated(Reader::new(FASTA_FILE)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(renamed.is_empty());
+    }
+
+    #[test]
+    fn test_writer_width() {
+        let records = [
+            Record::with_attrs("id", Some("desc with  whitespace"), b"ACCGTAGGCTGAT"),
+            Record::with_attrs("id2", None, b""),
+            Record::with_attrs("id3", Some("exact multiple"), b"ACGTACGT"),
+            Record::with_attrs("id4", None, b"A"),
+        ];
+        for width in 0..15 {
+            let mut writer = Writer::new(Vec::new()).width(width);
+            for record in &records {
+                writer.write_record(record).unwrap();
+            }
+            writer.flush().unwrap();
+            let fasta = writer.writer.get_ref().clone();
+
+            let lines: Vec<&[u8]> = fasta.split(|&c| c == b'\n').collect();
+            assert_eq!(lines.last(), Some(&&b""[..]));
+            // no blank lines, and no line longer than the width
+            assert!(lines[..lines.len() - 1].iter().all(|line| !line.is_empty()));
+            if width > 0 {
+                assert!(lines
+                    .iter()
+                    .filter(|line| !line.starts_with(b">"))
+                    .all(|line| line.len() <= width));
+            }
+
+            let mut reader = Reader::new(&fasta[..]);
+            for expected in &records {
+                let mut record = Record::new();
+                reader.read(&mut record).unwrap();
+                assert_eq!(record.id(), expected.id());
+                assert_eq!(record.desc(), expected.desc());
+                assert_eq!(record.seq(), expected.seq());
+            }
+            let mut record = Record::new();
+            reader.read(&mut record).unwrap();
+            assert!(record.is_empty());
+        }
+
+        let mut writer = Writer::new(Vec::new()).width(4);
+        writer.write("id", None, b"ACGTACGT").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.writer.get_ref(), b">id\nACGT\nACGT\n");
+        assert!(writer.write("an id", None, b"ACGT").is_err());
+        assert!(writer.write("id", Some("two\nlines"), b"ACGT").is_err());
+    }
 }
@@ -0,0 +1,1947 @@
+// Copyright 2014-2018 Johannes Köster, Henning Timm.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! FastQ reading and writing.
+//!
+//! # Example
+//!
+//! ```
+//! use std::io;
+//! use bio::io::fastq;
+//! let reader = fastq::Reader::new(io::stdin());
+//! ```
+
+use std::convert::AsRef;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::ops::Range;
+use std::path::Path;
+
+use bio_types::sequence::SequenceRead;
+
+use crate::io::{fasta, sample};
+use crate::stats::{PHREDProb, Prob};
+use crate::utils::TextSlice;
+
+pub mod stats;
+pub mod trim;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Io(err: io::Error) {
+            from()
+            description("error reading FastQ input")
+            display("error reading FastQ input: {}", err)
+            cause(err)
+        }
+        /// Malformed input at the given 1-based line, while parsing the record with the
+        /// given 0-based index. The context is a truncated copy of the offending line.
+        Parse { line: usize, record: usize, msg: &'static str, context: String } {
+            description("malformed FastQ input")
+            display("line {} (record {}): {} {:?}", line, record, msg, context)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum QualError {
+        /// The quality byte at the given position is outside of the range of the given offset.
+        OutOfRange(pos: usize, byte: u8, offset: u8) {
+            description("quality out of range")
+            display("quality {:?} at position {} is out of range for Phred offset {}", *byte as char, pos, offset)
+        }
+    }
+}
+
+/// The offset that is added to Phred scores for encoding them as quality bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PhredOffset {
+    /// Offset 33 ('!'), used by Sanger, Illumina 1.8 and later, and SRA.
+    Phred33,
+    /// Offset 64 ('@'), used by Illumina 1.3 to 1.7.
+    Phred64,
+}
+
+impl PhredOffset {
+    /// Return the offset as byte.
+    pub fn value(self) -> u8 {
+        match self {
+            PhredOffset::Phred33 => 33,
+            PhredOffset::Phred64 => 64,
+        }
+    }
+
+    /// Detect the offset from the range of quality bytes in the given records. Bytes below
+    /// '@' are only valid with offset 33, while bytes above 'J' (a score of 41 with offset
+    /// 33, the maximum emitted by Illumina) indicate offset 64. Returns `None` if the bytes
+    /// fit neither or both offsets, e.g. if all qualities are in the range '@' to 'J'.
+    pub fn detect<'a, I: IntoIterator<Item = &'a Record>>(records: I) -> Option<PhredOffset> {
+        let (mut min, mut max) = (u8::MAX, u8::MIN);
+        for record in records {
+            for &q in record.qual() {
+                min = min.min(q);
+                max = max.max(q);
+            }
+        }
+        if min < 33 || max > 126 || min > max {
+            None
+        } else if min < 64 {
+            Some(PhredOffset::Phred33)
+        } else if max > 74 {
+            Some(PhredOffset::Phred64)
+        } else {
+            None
+        }
+    }
+
+    /// Detect the offset from the first `n` records of the given reader (see
+    /// `PhredOffset::detect`). The inspected records are returned as well, such that they
+    /// can be processed afterwards.
+    pub fn detect_from_reader<R: io::Read>(
+        reader: &mut Reader<R>,
+        n: usize,
+    ) -> Result<(Option<PhredOffset>, Vec<Record>), Error> {
+        let mut records = Vec::new();
+        for _ in 0..n {
+            let mut record = Record::new();
+            reader.read(&mut record)?;
+            if record.is_empty() {
+                break;
+            }
+            records.push(record);
+        }
+
+        Ok((PhredOffset::detect(&records), records))
+    }
+}
+
+quick_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum ClipError {
+        /// The range to keep is not within a read of the given length.
+        InvalidRange(start: usize, end: usize, len: usize) {
+            description("invalid clipping range")
+            display("cannot clip read of length {} to range {}..{}", len, start, end)
+        }
+        /// More bases should be clipped than the read of the given length has.
+        TooLong(n: usize, len: usize) {
+            description("clipping more bases than the read has")
+            display("cannot clip {} bases from read of length {}", n, len)
+        }
+    }
+}
+
+/// Trait for FASTQ readers.
+pub trait FastqRead {
+    fn read(&mut self, record: &mut Record) -> Result<(), Error>;
+}
+
+/// A FastQ reader.
+#[derive(Debug)]
+pub struct Reader<R: io::Read> {
+    reader: io::BufReader<R>,
+    line_buf: String,
+    /// Number of lines read so far.
+    line_no: usize,
+    /// Index of the next record.
+    record_no: usize,
+    lenient: bool,
+    /// Whether lines are skipped until the next header after a corrupt record.
+    resync: bool,
+    /// A line starting with '@' inside of a corrupt record, which is tried as next header.
+    pending_header: Option<String>,
+    dropped_records: usize,
+}
+
+impl Reader<fs::File> {
+    /// Read from a given file. Fails for gzip compressed files.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = Reader::new(fs::File::open(path)?);
+        super::check_uncompressed(&mut reader.reader)?;
+
+        Ok(reader)
+    }
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Read from a given `io::Read`.
+    pub fn new(reader: R) -> Self {
+        Reader {
+            reader: io::BufReader::new(reader),
+            line_buf: String::new(),
+            line_no: 0,
+            record_no: 0,
+            lenient: false,
+            resync: false,
+            pending_header: None,
+            dropped_records: 0,
+        }
+    }
+
+    /// Skip corrupt records instead of failing with `Error::Parse`. After a corrupt record,
+    /// lines are skipped until the next line starting with '@', including such lines inside
+    /// of the corrupt record (e.g. the header of the next record if separator and qualities
+    /// are missing). Such a line is only taken as header if the following line does not start
+    /// with '@' as well, since quality lines may start with '@'. I/O errors are still
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fastq::Reader;
+    ///
+    /// let fastq = b"@r1\nACGT\n+\nII\n@r2\nACGT\nIIII\n@r3\nGG\n+\nII\n";
+    /// let mut records = Reader::new(&fastq[..]).lenient(true).records();
+    /// let ids: Vec<_> = records.by_ref().map(|record| record.unwrap().id().to_owned()).collect();
+    /// assert_eq!(ids, ["r3"]);
+    /// assert_eq!(records.dropped_records(), 2);
+    /// ```
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Return the number of corrupt records that have been skipped in lenient mode.
+    pub fn dropped_records(&self) -> usize {
+        self.dropped_records
+    }
+
+    /// Draw a uniform sample of `n` records in a single pass, see `io::sample::reservoir`.
+    pub fn sample(self, n: usize, seed: u64) -> Result<Vec<Record>, Error> {
+        sample::reservoir(self.records(), n, seed)
+    }
+
+    /// Sample each record with probability `p` in a single pass, see
+    /// `io::sample::fraction`.
+    pub fn sample_frac(self, p: f64, seed: u64) -> Result<Vec<Record>, Error> {
+        sample::fraction(self.records(), p, seed)
+    }
+
+    /// Return an iterator over the records of this FastQ file.
+    pub fn records(self) -> Records<R> {
+        Records {
+            reader: self,
+            error_has_occured: false,
+        }
+    }
+
+    /// Create a parse error for the given line.
+    fn parse_error(&self, line: usize, msg: &'static str, context: &str) -> Error {
+        Error::Parse {
+            line,
+            record: self.record_no,
+            msg,
+            context: super::error_context(context),
+        }
+    }
+
+    /// Create a parse error for a record that ends early.
+    fn incomplete(&self) -> Error {
+        self.parse_error(
+            self.line_no + 1,
+            "Incomplete record. Each FastQ record has to consist \
+             of 4 lines: header, sequence, separator and \
+             qualities.",
+            "",
+        )
+    }
+
+    /// Read the next record, see `FastqRead::read`. Returns `false` if the header turned out
+    /// to be implausible in lenient mode, because the sequence line starts with '@' as well.
+    fn read_record(&mut self, record: &mut Record) -> Result<bool, Error> {
+        record.clear();
+
+        match self.pending_header.take() {
+            Some(line) => self.line_buf = line,
+            // skip blank lines between records, and anything but headers when resynchronizing
+            None => loop {
+                self.line_buf.clear();
+                if super::read_line(&mut self.reader, &mut self.line_no, &mut self.line_buf)? == 0 {
+                    return Ok(true);
+                }
+                if !self.line_buf.trim_end().is_empty()
+                    && (!self.resync || self.line_buf.starts_with('@'))
+                {
+                    break;
+                }
+            },
+        }
+        if !self.line_buf.starts_with('@') {
+            return Err(self.parse_error(
+                self.line_no,
+                "Expected @ at record start.",
+                &self.line_buf,
+            ));
+        }
+        // like for FASTA, the id ends at the first whitespace
+        let header = self.line_buf[1..].trim_end();
+        let (id, desc) = match header.char_indices().find(|&(_, c)| c.is_whitespace()) {
+            Some((i, c)) => (&header[..i], Some(&header[i + c.len_utf8()..])),
+            None => (header, None),
+        };
+        record.id = id.to_owned();
+        record.desc = desc.map(|desc| desc.to_owned());
+        if record.id.is_empty() {
+            return Err(self.parse_error(self.line_no, "Expected id after @.", &self.line_buf));
+        }
+
+        if super::read_line(&mut self.reader, &mut self.line_no, &mut record.seq)? == 0 {
+            return Err(self.incomplete());
+        }
+        super::strip_line_terminator(&mut record.seq);
+        if self.lenient && record.seq.starts_with('@') {
+            self.pending_header = Some(record.seq.clone());
+            return Ok(false);
+        }
+
+        // the sequence may span several lines up to the separator, which usually is the
+        // next line
+        loop {
+            self.line_buf.clear();
+            if super::read_line(&mut self.reader, &mut self.line_no, &mut self.line_buf)? == 0 {
+                return Err(self.incomplete());
+            }
+            if self.line_buf.starts_with('+') {
+                record.plus_header = self.line_buf.trim_end().len() > 1;
+                break;
+            }
+            if self.line_buf.trim_end().is_empty() {
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Unexpected empty line inside record, expected + as separator.",
+                    "",
+                ));
+            }
+            super::strip_line_terminator(&mut self.line_buf);
+            // like the first sequence line, continuation lines may contain any symbol, but a
+            // line starting with '@' is rather the header of the next record
+            if record.seq.is_empty() || self.line_buf.starts_with('@') {
+                self.keep_header_candidate();
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Expected + as separator.",
+                    &self.line_buf,
+                ));
+            }
+            record.seq.push_str(&self.line_buf);
+        }
+
+        // the qualities may span several lines as well, up to the length of the sequence
+        if super::read_line(&mut self.reader, &mut self.line_no, &mut record.qual)? == 0 {
+            return Err(self.incomplete());
+        }
+        super::strip_line_terminator(&mut record.qual);
+        while record.qual.len() < record.seq.len() {
+            if record.qual.is_empty() {
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Unexpected empty line inside record, expected qualities.",
+                    "",
+                ));
+            }
+            self.line_buf.clear();
+            if super::read_line(&mut self.reader, &mut self.line_no, &mut self.line_buf)? == 0 {
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Unequal length of sequence and qualities.",
+                    &record.qual,
+                ));
+            }
+            super::strip_line_terminator(&mut self.line_buf);
+            if self.line_buf.is_empty() {
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Unexpected empty line inside record, expected qualities.",
+                    "",
+                ));
+            }
+            if record.qual.len() + self.line_buf.len() > record.seq.len() {
+                self.keep_header_candidate();
+                return Err(self.parse_error(
+                    self.line_no,
+                    "Unequal length of sequence and qualities.",
+                    &record.qual,
+                ));
+            }
+            record.qual.push_str(&self.line_buf);
+        }
+        if record.qual.len() != record.seq.len() {
+            return Err(self.parse_error(
+                self.line_no,
+                "Unequal length of sequence and qualities.",
+                &record.qual,
+            ));
+        }
+        self.record_no += 1;
+        self.resync = false;
+
+        Ok(true)
+    }
+
+    /// In lenient mode, keep the current line as header candidate if it starts with '@'.
+    fn keep_header_candidate(&mut self) {
+        if self.lenient && self.line_buf.starts_with('@') {
+            self.pending_header = Some(self.line_buf.clone());
+        }
+    }
+}
+
+impl<R> FastqRead for Reader<R>
+where
+    R: io::Read,
+{
+    /// Read the next FASTQ entry into the given `Record`.
+    /// An empty record indicates that no more records can be read.
+    ///
+    /// This method is useful when you want to read records as fast as
+    /// possible because it allows the reuse of a `Record` allocation.
+    ///
+    /// A more ergonomic approach to reading FASTQ records, is the
+    /// [records](Reader::records) iterator.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the record is incomplete,
+    /// syntax is violated or any form of I/O error is encountered. Parse errors
+    /// report the line at which they occurred. Records need a non-empty id and
+    /// as many qualities as bases. Both may span several lines: sequence lines
+    /// (not starting with `@`) are read up to the `+` separator, and quality lines
+    /// up to the length of the sequence, such that quality lines starting with
+    /// `@` are not mistaken for headers. In lenient mode (see
+    /// `Reader::lenient`), corrupt records are skipped instead. Lines may end with
+    /// `\n` or `\r\n`, and blank
+    /// lines between records are skipped, while blank lines inside of a record
+    /// are an error (except for the sequence and qualities of an empty read).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// # use bio::io::fastq::{Reader, FastqRead};
+    /// # use bio::io::fastq::Record;
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// const fastq_file: &'static [u8] = b"@id desc
+    /// AAAA
+    /// +
+    /// IIII
+    /// ";
+    /// let mut reader = Reader::new(fastq_file);
+    /// let mut record = Record::new();
+    ///
+    /// // Check for errors parsing the record
+    /// reader.read(&mut record)?;
+    ///
+    /// assert_eq!(record.id(), "id");
+    /// assert_eq!(record.desc().unwrap(), "desc");
+    /// assert_eq!(record.seq().to_vec(), b"AAAA");
+    /// assert_eq!(record.qual().to_vec(), b"IIII");
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn read(&mut self, record: &mut Record) -> Result<(), Error> {
+        loop {
+            match self.read_record(record) {
+                Ok(true) => return Ok(()),
+                Ok(false) => (),
+                Err(Error::Parse { .. }) if self.lenient => {
+                    self.dropped_records += 1;
+                    self.record_no += 1;
+                    self.resync = true;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// A FastQ record.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Record {
+    id: String,
+    desc: Option<String>,
+    seq: String,
+    qual: String,
+    /// Whether the separator line repeats the header.
+    #[serde(default)]
+    plus_header: bool,
+}
+
+impl Record {
+    /// Create a new, empty FastQ record.
+    pub fn new() -> Self {
+        Record {
+            id: String::new(),
+            desc: None,
+            seq: String::new(),
+            qual: String::new(),
+            plus_header: false,
+        }
+    }
+
+    /// Create a new FastQ record from given attributes.
+    pub fn with_attrs(id: &str, desc: Option<&str>, seq: TextSlice<'_>, qual: &[u8]) -> Self {
+        let desc = match desc {
+            Some(desc) => Some(desc.to_owned()),
+            _ => None,
+        };
+        Record {
+            id: id.to_owned(),
+            desc,
+            seq: String::from_utf8(seq.to_vec()).unwrap(),
+            qual: String::from_utf8(qual.to_vec()).unwrap(),
+            plus_header: false,
+        }
+    }
+
+    /// Check if record is empty.
+    pub fn is_empty(&self) -> bool {
+        self.id.is_empty() && self.desc.is_none() && self.seq.is_empty() && self.qual.is_empty()
+    }
+
+    /// Check validity of FastQ record.
+    pub fn check(&self) -> Result<(), &str> {
+        if self.id().is_empty() {
+            return Err("Expecting id for FastQ record.");
+        }
+        if !self.seq.is_ascii() {
+            return Err("Non-ascii character found in sequence.");
+        }
+        if !self.qual.is_ascii() {
+            return Err("Non-ascii character found in qualities.");
+        }
+        if self.seq().len() != self.qual().len() {
+            return Err("Unequal length of sequence an qualities.");
+        }
+
+        Ok(())
+    }
+
+    /// Return the id of the record.
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// Return descriptions if present.
+    pub fn desc(&self) -> Option<&str> {
+        match self.desc.as_ref() {
+            Some(desc) => Some(&desc),
+            None => None,
+        }
+    }
+
+    /// Return the sequence of the record.
+    pub fn seq(&self) -> TextSlice<'_> {
+        self.seq.trim_end().as_bytes()
+    }
+
+    /// Return the base qualities of the record.
+    pub fn qual(&self) -> &[u8] {
+        self.qual.trim_end().as_bytes()
+    }
+
+    /// Return whether the separator line of the record repeats the header (e.g. `+id desc`
+    /// instead of `+`), as seen by the reader.
+    pub fn repeats_header(&self) -> bool {
+        self.plus_header
+    }
+
+    /// Return the Phred scores of the base qualities, decoded with the given offset. Fails if
+    /// a quality byte is below the offset or above '~'.
+    pub fn qual_scores(&self, offset: PhredOffset) -> Result<Vec<u8>, QualError> {
+        let offset = offset.value();
+        self.qual()
+            .iter()
+            .enumerate()
+            .map(|(pos, &q)| {
+                if q < offset || q > b'~' {
+                    Err(QualError::OutOfRange(pos, q, offset))
+                } else {
+                    Ok(q - offset)
+                }
+            })
+            .collect()
+    }
+
+    /// Return the error probability 10^(-q/10) of each base, assuming Phred scores with
+    /// offset 33. Quality bytes below the offset are treated as a score of 0.
+    pub fn error_probs(&self) -> Vec<f64> {
+        self.qual()
+            .iter()
+            .map(|&q| *Prob::from(PHREDProb(f64::from(q.saturating_sub(33)))))
+            .collect()
+    }
+
+    /// Return the arithmetic mean of the Phred scores (offset 33), or `None` for an empty
+    /// record.
+    pub fn mean_qual(&self) -> Option<f64> {
+        let qual = self.qual();
+        if qual.is_empty() {
+            return None;
+        }
+        let sum: u64 = qual.iter().map(|&q| u64::from(q.saturating_sub(33))).sum();
+        Some(sum as f64 / qual.len() as f64)
+    }
+
+    /// Return the expected number of sequencing errors in the record, i.e. the sum of the
+    /// error probabilities (offset 33), as used for filtering by usearch and fastp.
+    pub fn expected_errors(&self) -> f64 {
+        self.error_probs().iter().sum()
+    }
+
+    /// Convert into a Fasta record with the same id, description and sequence, dropping the
+    /// qualities.
+    pub fn to_fasta(&self) -> fasta::Record {
+        fasta::Record::with_attrs(self.id(), self.desc(), self.seq())
+    }
+
+    /// Hard clip the record to the given range of positions, slicing both sequence and
+    /// qualities. The result keeps track of the position of the range in this record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fastq::Record;
+    ///
+    /// let record = Record::with_attrs("read", None, b"ACGTACGT", b"IIIIJJJJ");
+    /// let clipped = record.clipped(2..7).unwrap();
+    /// assert_eq!(clipped.record().seq(), b"GTACG");
+    /// assert_eq!(clipped.record().qual(), b"IIJJJ");
+    /// assert_eq!(clipped.offset_in_original(), 2);
+    /// assert_eq!(clipped.to_original(1), 3);
+    /// assert!(record.clipped(2..9).is_err());
+    /// ```
+    pub fn clipped(&self, range: Range<usize>) -> Result<ClippedRecord, ClipError> {
+        ClippedRecord::new(self.clone()).clipped(range)
+    }
+
+    /// Hard clip the first `n` bases, see `Record::clipped`.
+    pub fn clip_front(&self, n: usize) -> Result<ClippedRecord, ClipError> {
+        ClippedRecord::new(self.clone()).clip_front(n)
+    }
+
+    /// Hard clip the last `n` bases, see `Record::clipped`.
+    pub fn clip_back(&self, n: usize) -> Result<ClippedRecord, ClipError> {
+        ClippedRecord::new(self.clone()).clip_back(n)
+    }
+
+    /// Clear the record.
+    fn clear(&mut self) {
+        self.id.clear();
+        self.desc = None;
+        self.seq.clear();
+        self.qual.clear();
+        self.plus_header = false;
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "@{} {}\n{}\n+\n{}",
+            self.id,
+            self.desc().unwrap_or_default(),
+            self.seq,
+            self.qual
+        )
+    }
+}
+
+impl SequenceRead for Record {
+    fn name(&self) -> &[u8] {
+        self.id.as_bytes()
+    }
+
+    fn base(&self, i: usize) -> u8 {
+        self.seq.as_bytes()[i]
+    }
+
+    fn base_qual(&self, i: usize) -> u8 {
+        self.qual.as_bytes()[i]
+    }
+
+    fn len(&self) -> usize {
+        self.seq().len()
+    }
+}
+
+/// A hard clipped FastQ record, keeping track of its position in the original record for
+/// mapping coordinates back. Clipping it again refers to the clipped record, while the
+/// bookkeeping stays relative to the original.
+#[derive(Debug, Clone)]
+pub struct ClippedRecord {
+    record: Record,
+    offset: usize,
+    original_len: usize,
+}
+
+impl ClippedRecord {
+    /// Wrap an unclipped record.
+    pub fn new(record: Record) -> Self {
+        let original_len = record.seq().len();
+        ClippedRecord {
+            record,
+            offset: 0,
+            original_len,
+        }
+    }
+
+    /// Return the clipped record.
+    pub fn record(&self) -> &Record {
+        &self.record
+    }
+
+    /// Return the clipped record, dropping the bookkeeping.
+    pub fn into_record(self) -> Record {
+        self.record
+    }
+
+    /// Return the position of the clipped record in the original record.
+    pub fn offset_in_original(&self) -> usize {
+        self.offset
+    }
+
+    /// Return the length of the original record.
+    pub fn original_len(&self) -> usize {
+        self.original_len
+    }
+
+    /// Return the number of bases that have been clipped from the front of the original.
+    pub fn front_clipped(&self) -> usize {
+        self.offset
+    }
+
+    /// Return the number of bases that have been clipped from the back of the original.
+    pub fn back_clipped(&self) -> usize {
+        self.original_len - self.offset - self.record.seq().len()
+    }
+
+    /// Map a position in the clipped record to the original record.
+    pub fn to_original(&self, pos: usize) -> usize {
+        self.offset + pos
+    }
+
+    /// Clip the record further to the given range of its positions.
+    pub fn clipped(&self, range: Range<usize>) -> Result<ClippedRecord, ClipError> {
+        let len = self.record.seq().len();
+        if range.start > range.end || range.end > len {
+            return Err(ClipError::InvalidRange(range.start, range.end, len));
+        }
+        let mut record = Record::with_attrs(
+            self.record.id(),
+            self.record.desc(),
+            &self.record.seq()[range.clone()],
+            &self.record.qual()[range.clone()],
+        );
+        record.plus_header = self.record.plus_header;
+
+        Ok(ClippedRecord {
+            record,
+            offset: self.offset + range.start,
+            original_len: self.original_len,
+        })
+    }
+
+    /// Clip the first `n` bases.
+    pub fn clip_front(&self, n: usize) -> Result<ClippedRecord, ClipError> {
+        let len = self.record.seq().len();
+        if n > len {
+            return Err(ClipError::TooLong(n, len));
+        }
+        self.clipped(n..len)
+    }
+
+    /// Clip the last `n` bases.
+    pub fn clip_back(&self, n: usize) -> Result<ClippedRecord, ClipError> {
+        let len = self.record.seq().len();
+        if n > len {
+            return Err(ClipError::TooLong(n, len));
+        }
+        self.clipped(0..len - n)
+    }
+}
+
+/// An iterator over the records of a FastQ file. It stops after yielding the first error.
+#[derive(Debug)]
+pub struct Records<R: io::Read> {
+    reader: Reader<R>,
+    error_has_occured: bool,
+}
+
+impl<R: io::Read> Records<R> {
+    /// Return the number of corrupt records that have been skipped in lenient mode (see
+    /// `Reader::dropped_records`).
+    pub fn dropped_records(&self) -> usize {
+        self.reader.dropped_records()
+    }
+}
+
+impl<R: io::Read> Iterator for Records<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        if self.error_has_occured {
+            return None;
+        }
+        let mut record = Record::new();
+        match self.reader.read(&mut record) {
+            Ok(()) if record.is_empty() => None,
+            Ok(()) => Some(Ok(record)),
+            Err(err) => {
+                self.error_has_occured = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// The style of the separator line written by a FastQ writer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlusLine {
+    /// A bare `+`.
+    Bare,
+    /// `+` followed by the header, as required by some legacy tools.
+    RepeatHeader,
+}
+
+/// A FastQ writer.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    writer: io::BufWriter<W>,
+    plus_line: Option<PlusLine>,
+}
+
+impl Writer<fs::File> {
+    /// Write to a given file path.
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::create(path).map(Writer::new)
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Write to a given `io::Write`. Output is buffered until `flush` is called or the
+    /// writer is dropped.
+    pub fn new(writer: W) -> Self {
+        Writer {
+            writer: io::BufWriter::new(writer),
+            plus_line: None,
+        }
+    }
+
+    /// Write all separator lines in the given style. By default, records are written with
+    /// the separator line they have been read with (see `Record::repeats_header`), and
+    /// `Writer::write` writes a bare `+`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fastq::{PlusLine, Writer};
+    /// let mut fastq = Vec::new();
+    /// {
+    ///     let mut writer = Writer::new(&mut fastq).plus_line(PlusLine::RepeatHeader);
+    ///     writer.write("id", Some("desc"), b"ACGT", b"IIII").unwrap();
+    /// }
+    /// assert_eq!(fastq, b"@id desc\nACGT\n+id desc\nIIII\n");
+    /// ```
+    pub fn plus_line(mut self, plus_line: PlusLine) -> Self {
+        self.plus_line = Some(plus_line);
+        self
+    }
+
+    /// Directly write a FastQ record.
+    pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        let plus_line = if record.repeats_header() {
+            PlusLine::RepeatHeader
+        } else {
+            PlusLine::Bare
+        };
+        self.write_parts(
+            record.id(),
+            record.desc(),
+            record.seq(),
+            record.qual(),
+            self.plus_line.unwrap_or(plus_line),
+        )
+    }
+
+    /// Write a FastQ record with given id, optional description, sequence and qualities.
+    pub fn write(
+        &mut self,
+        id: &str,
+        desc: Option<&str>,
+        seq: TextSlice<'_>,
+        qual: &[u8],
+    ) -> io::Result<()> {
+        let plus_line = self.plus_line.unwrap_or(PlusLine::Bare);
+        self.write_parts(id, desc, seq, qual, plus_line)
+    }
+
+    fn write_parts(
+        &mut self,
+        id: &str,
+        desc: Option<&str>,
+        seq: TextSlice<'_>,
+        qual: &[u8],
+        plus_line: PlusLine,
+    ) -> io::Result<()> {
+        self.writer.write_all(b"@")?;
+        self.write_header(id, desc)?;
+        self.writer.write_all(seq)?;
+        self.writer.write_all(b"\n+")?;
+        match plus_line {
+            PlusLine::Bare => self.writer.write_all(b"\n")?,
+            PlusLine::RepeatHeader => self.write_header(id, desc)?,
+        }
+        self.writer.write_all(qual)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn write_header(&mut self, id: &str, desc: Option<&str>) -> io::Result<()> {
+        self.writer.write_all(id.as_bytes())?;
+        if let Some(desc) = desc {
+            self.writer.write_all(b" ")?;
+            self.writer.write_all(desc.as_bytes())?;
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flush the writer, ensuring that everything is written.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum PairError {
+        /// Error reading the given file (1 or 2).
+        Read(file: usize, err: Error) {
+            description("error reading paired FastQ files")
+            display("error reading FastQ file {}: {}", file, err)
+            cause(err)
+        }
+        /// The mates of the pair with the given 0-based index have different ids.
+        MateMismatch(pair: usize, id1: String, id2: String) {
+            description("mate ids of paired FastQ records disagree")
+            display("ids {:?} and {:?} of pair {} do not belong to mates", id1, id2, pair)
+        }
+        /// The given file (1 or 2) has no record for the pair with the given 0-based index.
+        MissingMate(pair: usize, file: usize) {
+            description("paired FastQ files have different numbers of records")
+            display("FastQ file {} ends before pair {}", file, pair)
+        }
+        Io(err: io::Error) {
+            from()
+            description("error writing paired FastQ records")
+            display("error writing paired FastQ records: {}", err)
+            cause(err)
+        }
+    }
+}
+
+/// How the ids of mates are compared by a `PairedReader`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MateNames {
+    /// Ids have to be identical. This fits Illumina (CASAVA 1.8 and later) reads, which
+    /// carry the mate number in the description (e.g. `@read 1:N:0:1`), and SRA reads.
+    Identical,
+    /// Ids have to be identical after removing a trailing `/1` from the first and `/2`
+    /// from the second mate, as in older Illumina reads (e.g. `@read#0/1`). Identical ids
+    /// are accepted as well.
+    StripSuffix,
+    /// Ids are not compared.
+    Unchecked,
+}
+
+impl MateNames {
+    fn matches(self, id1: &str, id2: &str) -> bool {
+        match self {
+            MateNames::Identical => id1 == id2,
+            MateNames::StripSuffix => {
+                let id1 = id1.strip_suffix("/1").unwrap_or(id1);
+                let id2 = id2.strip_suffix("/2").unwrap_or(id2);
+                id1 == id2
+            }
+            MateNames::Unchecked => true,
+        }
+    }
+}
+
+/// A reader for paired-end reads stored in two FastQ files, reading the mates of each pair
+/// in lockstep and checking that their ids agree.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fastq::PairedReader;
+///
+/// let r1 = b"@read1/1\nACGT\n+\nIIII\n@read2/1\nGG\n+\nII\n";
+/// let r2 = b"@read1/2\nTTGC\n+\nIIII\n@read2/2\nCA\n+\nII\n";
+/// for pair in PairedReader::new(&r1[..], &r2[..]).records() {
+///     let (mate1, mate2) = pair.unwrap();
+///     assert_eq!(mate1.seq().len(), mate2.seq().len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PairedReader<R1: io::Read, R2: io::Read> {
+    reader1: Reader<R1>,
+    reader2: Reader<R2>,
+    mate_names: MateNames,
+    pair_no: usize,
+}
+
+impl PairedReader<fs::File, fs::File> {
+    /// Read from the two given file paths. Fails for gzip compressed files.
+    pub fn from_files<P1: AsRef<Path>, P2: AsRef<Path>>(path1: P1, path2: P2) -> io::Result<Self> {
+        Ok(PairedReader::from_readers(
+            Reader::from_file(path1)?,
+            Reader::from_file(path2)?,
+        ))
+    }
+}
+
+impl<R1: io::Read, R2: io::Read> PairedReader<R1, R2> {
+    /// Read the first and second mates from the given `io::Read`s. Mate ids are compared
+    /// with `MateNames::StripSuffix`.
+    pub fn new(reader1: R1, reader2: R2) -> Self {
+        PairedReader::from_readers(Reader::new(reader1), Reader::new(reader2))
+    }
+
+    /// Read the first and second mates from the given FastQ readers.
+    pub fn from_readers(reader1: Reader<R1>, reader2: Reader<R2>) -> Self {
+        PairedReader {
+            reader1,
+            reader2,
+            mate_names: MateNames::StripSuffix,
+            pair_no: 0,
+        }
+    }
+
+    /// Set how mate ids are compared.
+    pub fn mate_names(mut self, mate_names: MateNames) -> Self {
+        self.mate_names = mate_names;
+        self
+    }
+
+    /// Read the next pair into the given records, returning `false` if both files have
+    /// been read completely. Fails if only one of the files ends, or if the mate ids do not
+    /// agree.
+    pub fn read(&mut self, mate1: &mut Record, mate2: &mut Record) -> Result<bool, PairError> {
+        self.reader1
+            .read(mate1)
+            .map_err(|err| PairError::Read(1, err))?;
+        self.reader2
+            .read(mate2)
+            .map_err(|err| PairError::Read(2, err))?;
+        match (mate1.is_empty(), mate2.is_empty()) {
+            (true, true) => return Ok(false),
+            (false, true) => return Err(PairError::MissingMate(self.pair_no, 2)),
+            (true, false) => return Err(PairError::MissingMate(self.pair_no, 1)),
+            (false, false) => (),
+        }
+        if !self.mate_names.matches(mate1.id(), mate2.id()) {
+            return Err(PairError::MateMismatch(
+                self.pair_no,
+                mate1.id().to_owned(),
+                mate2.id().to_owned(),
+            ));
+        }
+        self.pair_no += 1;
+
+        Ok(true)
+    }
+
+    /// Draw a uniform sample of `n` pairs, keeping mates together (see
+    /// `io::sample::reservoir`).
+    pub fn sample(self, n: usize, seed: u64) -> Result<Vec<(Record, Record)>, PairError> {
+        sample::reservoir(self.records(), n, seed)
+    }
+
+    /// Sample each pair with probability `p`, keeping mates together (see
+    /// `io::sample::fraction`).
+    pub fn sample_frac(self, p: f64, seed: u64) -> Result<Vec<(Record, Record)>, PairError> {
+        sample::fraction(self.records(), p, seed)
+    }
+
+    /// Return an iterator over the pairs of records. It stops after yielding the first
+    /// error.
+    pub fn records(self) -> PairedRecords<R1, R2> {
+        PairedRecords {
+            reader: self,
+            error_has_occured: false,
+        }
+    }
+}
+
+/// An iterator over the pairs of records of two FastQ files.
+#[derive(Debug)]
+pub struct PairedRecords<R1: io::Read, R2: io::Read> {
+    reader: PairedReader<R1, R2>,
+    error_has_occured: bool,
+}
+
+impl<R1: io::Read, R2: io::Read> Iterator for PairedRecords<R1, R2> {
+    type Item = Result<(Record, Record), PairError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error_has_occured {
+            return None;
+        }
+        let mut mate1 = Record::new();
+        let mut mate2 = Record::new();
+        match self.reader.read(&mut mate1, &mut mate2) {
+            Ok(true) => Some(Ok((mate1, mate2))),
+            Ok(false) => None,
+            Err(err) => {
+                self.error_has_occured = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A writer for paired-end reads, writing the mates of each pair to two FastQ files.
+#[derive(Debug)]
+pub struct PairedWriter<W1: io::Write, W2: io::Write> {
+    writer1: Writer<W1>,
+    writer2: Writer<W2>,
+}
+
+impl PairedWriter<fs::File, fs::File> {
+    /// Write to the two given file paths.
+    pub fn to_files<P1: AsRef<Path>, P2: AsRef<Path>>(path1: P1, path2: P2) -> io::Result<Self> {
+        Ok(PairedWriter::new(
+            fs::File::create(path1)?,
+            fs::File::create(path2)?,
+        ))
+    }
+}
+
+impl<W1: io::Write, W2: io::Write> PairedWriter<W1, W2> {
+    /// Write the first and second mates to the given `io::Write`s.
+    pub fn new(writer1: W1, writer2: W2) -> Self {
+        PairedWriter {
+            writer1: Writer::new(writer1),
+            writer2: Writer::new(writer2),
+        }
+    }
+
+    /// Write a pair of records.
+    pub fn write(&mut self, mate1: &Record, mate2: &Record) -> io::Result<()> {
+        self.writer1.write_record(mate1)?;
+        self.writer2.write_record(mate2)
+    }
+
+    /// Flush both writers, ensuring that everything is written.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer1.flush()?;
+        self.writer2.flush()
+    }
+}
+
+/// Convert FastQ records from the given reader into Fasta records, writing each sequence on
+/// a single line. Returns the number of converted records.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fastq::fastq_to_fasta;
+///
+/// let mut fasta = Vec::new();
+/// fastq_to_fasta(&b"@read1 desc\nACGT\n+\nIIII\n"[..], &mut fasta).unwrap();
+/// assert_eq!(fasta, b">read1 desc\nACGT\n");
+/// ```
+pub fn fastq_to_fasta<R: io::Read, W: io::Write>(reader: R, writer: W) -> Result<usize, Error> {
+    let mut reader = Reader::new(reader);
+    let mut writer = fasta::Writer::new(writer);
+    let mut record = Record::new();
+    let mut count = 0;
+    loop {
+        reader.read(&mut record)?;
+        if record.is_empty() {
+            break;
+        }
+        writer.write(record.id(), record.desc(), record.seq())?;
+        count += 1;
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Interleave the mates from two FastQ files into a single FastQ file, writing the first
+/// mate of each pair followed by the second. Mate ids are validated as in `PairedReader`.
+/// Returns the number of written pairs.
+pub fn interleave<R1: io::Read, R2: io::Read, W: io::Write>(
+    reader1: R1,
+    reader2: R2,
+    writer: W,
+    mate_names: MateNames,
+) -> Result<usize, PairError> {
+    let mut reader = PairedReader::new(reader1, reader2).mate_names(mate_names);
+    let mut writer = Writer::new(writer);
+    let (mut mate1, mut mate2) = (Record::new(), Record::new());
+    let mut count = 0;
+    while reader.read(&mut mate1, &mut mate2)? {
+        writer.write_record(&mate1)?;
+        writer.write_record(&mate2)?;
+        count += 1;
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Split an interleaved FastQ file into two files, taking records alternately as first and
+/// second mate. Mate ids are validated with the given `MateNames`, where
+/// `MateNames::Unchecked` splits by position alone. An odd number of records fails with
+/// `PairError::MissingMate` for the second file. Returns the number of written pairs.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fastq::{deinterleave, interleave, MateNames};
+///
+/// let fastq1 = b"@read1/1\nACGT\n+\nIIII\n";
+/// let fastq2 = b"@read1/2\nTTGC\n+\nIIII\n";
+/// let mut interleaved = Vec::new();
+/// interleave(&fastq1[..], &fastq2[..], &mut interleaved, MateNames::StripSuffix).unwrap();
+///
+/// let (mut out1, mut out2) = (Vec::new(), Vec::new());
+/// deinterleave(&interleaved[..], &mut out1, &mut out2, MateNames::StripSuffix).unwrap();
+/// assert_eq!((&out1[..], &out2[..]), (&fastq1[..], &fastq2[..]));
+/// ```
+pub fn deinterleave<R: io::Read, W1: io::Write, W2: io::Write>(
+    reader: R,
+    writer1: W1,
+    writer2: W2,
+    mate_names: MateNames,
+) -> Result<usize, PairError> {
+    let mut reader = Reader::new(reader);
+    let mut writer = PairedWriter::new(writer1, writer2);
+    let (mut mate1, mut mate2) = (Record::new(), Record::new());
+    let mut count = 0;
+    loop {
+        reader
+            .read(&mut mate1)
+            .map_err(|err| PairError::Read(1, err))?;
+        if mate1.is_empty() {
+            break;
+        }
+        reader
+            .read(&mut mate2)
+            .map_err(|err| PairError::Read(1, err))?;
+        if mate2.is_empty() {
+            return Err(PairError::MissingMate(count, 2));
+        }
+        if !mate_names.matches(mate1.id(), mate2.id()) {
+            return Err(PairError::MateMismatch(
+                count,
+                mate1.id().to_owned(),
+                mate2.id().to_owned(),
+            ));
+        }
+        writer.write(&mate1, &mate2)?;
+        count += 1;
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    const FASTQ_FILE: &'static [u8] = b"@id desc
+ACCGTAGGCTGA
++
+IIIIIIJJJJJJ
+";
+
+    #[test]
+    fn test_reader() {
+        let reader = Reader::new(FASTQ_FILE);
+        let records: Vec<Result<Record, Error>> = reader.records().collect();
+        assert!(records.len() == 1);
+        for res in records {
+            let record = res.ok().unwrap();
+            assert_eq!(record.check(), Ok(()));
+            assert_eq!(record.id(), "id");
+            assert_eq!(record.desc(), Some("desc"));
+            assert_eq!(record.seq(), b"ACCGTAGGCTGA");
+            assert_eq!(record.qual(), b"IIIIIIJJJJJJ");
+        }
+    }
+
+    #[test]
+    fn test_fqread_trait() {
+        let path = "reads.fq.gz";
+        let mut fq_reader: Box<dyn FastqRead> = match path.ends_with(".gz") {
+            true => Box::new(Reader::new(io::BufReader::new(FASTQ_FILE))),
+            false => Box::new(Reader::new(FASTQ_FILE)),
+        };
+        // The read method can be called, since it is implemented by
+        // `Read`. Right now, the records method would not work.
+        let mut record = Record::new();
+        fq_reader.read(&mut record).unwrap();
+        // Check if the returned result is correct.
+        assert_eq!(record.check(), Ok(()));
+        assert_eq!(record.id(), "id");
+        assert_eq!(record.desc(), Some("desc"));
+        assert_eq!(record.seq(), b"ACCGTAGGCTGA");
+        assert_eq!(record.qual(), b"IIIIIIJJJJJJ");
+    }
+
+    #[test]
+    fn test_reader_parse_errors() {
+        let fixtures: [(&[u8], _); 7] = [
+            (
+                b"@r1\nACGT\n+\nIIII\n@r2\nACGTAC\n+\nIIII\n",
+                (8, 1, "IIII"),
+            ),
+            (b"@r1\nA\n+\nI\nr2\nA\n+\nI\n", (5, 1, "r2")),
+            (b"@r1\nA\n+\nI\n\n@r2\nACGT\n\n+\nIIII\n", (8, 1, "")),
+            (b"@r1\r\nACGT\r\n+\r\n\r\n", (4, 0, "")),
+            (b"@ desc\nA\n+\nI\n", (1, 0, "@ desc")),
+            (b"@r1\nA\n-\nI\n", (5, 0, "")),
+            (b"@r1\nA\n+\nI\n@r2\nACGT\n+\n", (8, 1, "")),
+        ];
+        for &(fastq, (line, record, context)) in &fixtures {
+            let mut records = Reader::new(fastq).records();
+            match records.find_map(|record| record.err()) {
+                Some(Error::Parse {
+                    line: l,
+                    record: r,
+                    context: c,
+                    ..
+                }) => assert_eq!((l, r, c), (line, record, context.to_owned())),
+                err => panic!("expected parse error, found {:?}", err),
+            }
+            assert!(records.next().is_none());
+        }
+    }
+
+    #[test]
+    fn test_reader_line_endings() {
+        let unix = b"@r1 desc\nACGT\n+\nIIII\n@r2\n\n+\n\n@r3\nGG\n+r3\nJJ\n";
+        type Fields = (String, Option<String>, Vec<u8>, Vec<u8>);
+        let parse = |fastq: &[u8]| -> Vec<Fields> {
+            Reader::new(fastq)
+                .records()
+                .map(|record| {
+                    let record = record.unwrap();
+                    assert_eq!(record.check(), Ok(()));
+                    (
+                        record.id().to_owned(),
+                        record.desc().map(|desc| desc.to_owned()),
+                        record.seq().to_vec(),
+                        record.qual().to_vec(),
+                    )
+                })
+                .collect()
+        };
+        let expected = parse(unix);
+        assert_eq!(expected.len(), 3);
+        for fastq in &[
+            &b"@r1 desc\r\nACGT\r\n+\r\nIIII\r\n@r2\r\n\r\n+\r\n\r\n@r3\r\nGG\r\n+r3\r\nJJ\r\n"[..],
+            b"@r1 desc\r\nACGT\n+\r\nIIII\n\n@r2\n\r\n+\n\n\r\n\n@r3\nGG\r\n+r3\nJJ",
+            b"\n@r1 desc\nACGT\n+\nIIII\n@r2\n\n+\n\n@r3\nGG\n+r3\nJJ\r\n\r\n\n",
+        ] {
+            assert_eq!(parse(fastq), expected);
+        }
+
+        let mut reader = Reader::new(&b"@r1\r\nACGT\r\n+\r\nIIII\r\n"[..]);
+        let mut record = Record::new();
+        reader.read(&mut record).unwrap();
+        assert_eq!(record.base(3), b'T');
+        assert_eq!(record.to_string(), "@r1 \nACGT\n+\nIIII");
+    }
+
+    #[test]
+    fn test_reader_header_whitespace() {
+        let mut reader = Reader::new(&b"@r1\tdesc with\tspaces\nACGT\n+\nIIII\n"[..]);
+        let mut record = Record::new();
+        reader.read(&mut record).unwrap();
+        assert_eq!(record.id(), "r1");
+        assert_eq!(record.desc(), Some("desc with\tspaces"));
+    }
+
+    #[test]
+    fn test_reader_quality_framing() {
+        // qualities starting with '@' and spanning several lines
+        let fastq = b"@r1\nACGT\n+\n@III\n@r2\nACGTAC\n+\nII\n@I\nII\n@r3\nA\n+\n@\n";
+        let records: Vec<_> = Reader::new(&fastq[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].qual(), b"@III");
+        assert_eq!(records[1].qual(), b"II@III");
+        assert_eq!(records[2].qual(), b"@");
+        assert!(records.iter().all(|record| record.check().is_ok()));
+
+        // qualities longer than the sequence, on a single or several lines
+        for &(fastq, line, context) in &[
+            (&b"@r1\nACGT\n+\nIIIII\n"[..], 4, "IIIII"),
+            (b"@r1\nACGT\n+\nII\nIII\n", 5, "II"),
+        ] {
+            match Reader::new(fastq).records().next() {
+                Some(Err(Error::Parse {
+                    line: l,
+                    record: 0,
+                    context: c,
+                    ..
+                })) => assert_eq!((l, c.as_str()), (line, context)),
+                res => panic!("expected parse error, found {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_multiline() {
+        let fastq = b"@r1 desc\nACGT\n+\nIIII\n\
+@r2\nACGT\nAC\n+\nIIII\n@I\n\
+@r3\nAC\nGT\nA\n+r3\nII\nII\nI\n\
+@r4\nGG\n+\nJJ\n\
+@r5\nACG\nTA\nCG\n+\n@@@@@@@\n\
+@r6\nAC\nG-\n.N\n+\nIIIIII\n";
+        let records: Vec<_> = Reader::new(&fastq[..])
+            .records()
+            .map(|record| {
+                let record = record.unwrap();
+                assert_eq!(record.check(), Ok(()));
+                (
+                    record.id().to_owned(),
+                    record.seq().to_vec(),
+                    record.qual().to_vec(),
+                )
+            })
+            .collect();
+        let expected: [(&str, &[u8], &[u8]); 6] = [
+            ("r1", b"ACGT", b"IIII"),
+            ("r2", b"ACGTAC", b"IIII@I"),
+            ("r3", b"ACGTA", b"IIIII"),
+            ("r4", b"GG", b"JJ"),
+            ("r5", b"ACGTACG", b"@@@@@@@"),
+            ("r6", b"ACG-.N", b"IIIIII"),
+        ];
+        assert_eq!(records.len(), expected.len());
+        for (record, &(id, seq, qual)) in records.iter().zip(&expected) {
+            assert_eq!(
+                (record.0.as_str(), &record.1[..], &record.2[..]),
+                (id, seq, qual)
+            );
+        }
+
+        // malformed wrapped records
+        let fixtures: [(&[u8], _); 4] = [
+            (b"@r1\nAC\nGT\n+\nIII\nII\n", (6, "III")),
+            (b"@r1\nAC\n\nGT\n+\nIIII\n", (3, "")),
+            (b"@r1\nAC\nGT\n@r2\nA\n+\nI\n", (4, "@r2")),
+            (b"@r1\nAC\nGT\n+\nII\n", (5, "II")),
+        ];
+        for &(fastq, (line, context)) in &fixtures {
+            match Reader::new(fastq).records().next() {
+                Some(Err(Error::Parse {
+                    line: l,
+                    record: 0,
+                    context: c,
+                    ..
+                })) => assert_eq!((l, c.as_str()), (line, context)),
+                res => panic!("expected parse error, found {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_lenient() {
+        let ids = |fastq: &[u8]| {
+            let mut strict = Reader::new(fastq).records();
+            assert!(strict.by_ref().any(|record| record.is_err()));
+            let mut records = Reader::new(fastq).lenient(true).records();
+            let ids: Vec<_> = records
+                .by_ref()
+                .map(|record| record.unwrap().id().to_owned())
+                .collect();
+            (ids, records.dropped_records())
+        };
+        let fixtures: [(&[u8], &[&str], usize); 8] = [
+            // qualities shorter than the sequence, the next header is read as quality
+            (b"@r1\nACGT\n+\nII\n@r2\nAC\n+\nII\n", &["r2"], 1),
+            // qualities shorter than the sequence, a quality line starting with '@' is
+            // tried as header
+            (b"@r1\nACGT\n+\nII\n@III\n@r2\nAC\n+\nII\n", &["r2"], 1),
+            // missing separator
+            (b"@r1\nACGT\nIIII\n@r2\nAC\n+\nII\n", &["r2"], 1),
+            // missing separator and qualities
+            (
+                b"@r1\nACGT\n@r2\nAC\n+\nII\n@r3\nA\n+\nI\n",
+                &["r2", "r3"],
+                1,
+            ),
+            // qualities longer than the sequence
+            (
+                b"@r0\nA\n+\nI\n@r1\nACGT\n+\nIIIIII\n@r2\nAC\n+\nII\n",
+                &["r0", "r2"],
+                1,
+            ),
+            // garbage between records and a missing id
+            (
+                b"@r1\nA\n+\nI\nACGT\n+\n@ x\nA\n+\nI\n@r2\nA\n+\nI\n",
+                &["r1", "r2"],
+                2,
+            ),
+            // truncated last record
+            (b"@r1\nA\n+\nI\n@r2\nAC\n+\n", &["r1"], 1),
+            // several corrupt records in a row
+            (b"@r1\nAC\nII\n@r2\nAC\n+\nI\n\n@r3\nA\n+\nI\n", &["r3"], 2),
+        ];
+        for &(fastq, expected_ids, expected_dropped) in &fixtures {
+            let (ids, dropped) = ids(fastq);
+            assert_eq!(ids, expected_ids);
+            assert_eq!(dropped, expected_dropped);
+        }
+    }
+
+    #[test]
+    fn test_record_with_attrs() {
+        let record = Record::with_attrs("id_str", Some("desc"), b"ATGCGGG", b"QQQQQQQ");
+        assert_eq!(record.id(), "id_str");
+        assert_eq!(record.desc(), Some("desc"));
+        assert_eq!(record.seq(), b"ATGCGGG");
+        assert_eq!(record.qual(), b"QQQQQQQ");
+    }
+
+    #[test]
+    fn test_qual_scores() {
+        let record = Record::with_attrs("id", None, b"ACG", b"!J~");
+        assert_eq!(
+            record.qual_scores(PhredOffset::Phred33),
+            Ok(vec![0, 41, 93])
+        );
+        assert_eq!(
+            record.qual_scores(PhredOffset::Phred64),
+            Err(QualError::OutOfRange(0, b'!', 64))
+        );
+        let record = Record::with_attrs("id", None, b"ACG", b"@i~");
+        assert_eq!(
+            record.qual_scores(PhredOffset::Phred64),
+            Ok(vec![0, 41, 62])
+        );
+        let record = Record::with_attrs("id", None, b"AC", b"I\x7f");
+        assert_eq!(
+            record.qual_scores(PhredOffset::Phred33),
+            Err(QualError::OutOfRange(1, 127, 33))
+        );
+    }
+
+    #[test]
+    fn test_error_probs() {
+        let record = Record::with_attrs("id", None, b"ACGT", b"!+5J");
+        let probs = record.error_probs();
+        for (p, expected) in probs.iter().zip(&[1.0, 0.1, 0.01, 10f64.powf(-4.1)]) {
+            assert_relative_eq!(p, expected, epsilon = 1e-12);
+        }
+        assert_relative_eq!(record.mean_qual().unwrap(), 71.0 / 4.0);
+        assert_relative_eq!(
+            record.expected_errors(),
+            1.11 + 10f64.powf(-4.1),
+            epsilon = 1e-12
+        );
+
+        let record = Record::with_attrs("id", None, b"A", b"~");
+        assert_relative_eq!(record.expected_errors(), 10f64.powf(-9.3), epsilon = 1e-20);
+        assert_eq!(Record::new().mean_qual(), None);
+        assert_eq!(Record::new().expected_errors(), 0.0);
+    }
+
+    #[test]
+    fn test_detect_phred_offset() {
+        let phred33 = b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nJ?5#\n@r3\nA\n+\nI\n";
+        let phred64 = b"@r1\nACGT\n+\nhhhh\n@r2\nACGT\n+\nh^TB\n@r3\nA\n+\nh\n";
+        let ambiguous = b"@r1\nACGT\n+\nHIJ@\n";
+
+        let mut reader = Reader::new(&phred33[..]);
+        let (offset, records) = PhredOffset::detect_from_reader(&mut reader, 2).unwrap();
+        assert_eq!(offset, Some(PhredOffset::Phred33));
+        assert_eq!(records.len(), 2);
+        // the remaining records are still available
+        assert_eq!(reader.records().count(), 1);
+
+        let mut reader = Reader::new(&phred64[..]);
+        let (offset, records) = PhredOffset::detect_from_reader(&mut reader, 10).unwrap();
+        assert_eq!(offset, Some(PhredOffset::Phred64));
+        assert_eq!(records.len(), 3);
+        for record in &records {
+            assert!(record.qual_scores(PhredOffset::Phred64).is_ok());
+        }
+
+        let mut reader = Reader::new(&ambiguous[..]);
+        let (offset, _) = PhredOffset::detect_from_reader(&mut reader, 10).unwrap();
+        assert_eq!(offset, None);
+        assert_eq!(PhredOffset::detect(&[]), None);
+    }
+
+    fn pairs(
+        fastq1: &[u8],
+        fastq2: &[u8],
+        mate_names: MateNames,
+    ) -> Vec<Result<(String, String), PairError>> {
+        PairedReader::new(fastq1, fastq2)
+            .mate_names(mate_names)
+            .records()
+            .map(|pair| pair.map(|(mate1, mate2)| (mate1.id().to_owned(), mate2.id().to_owned())))
+            .collect()
+    }
+
+    #[test]
+    fn test_paired_reader() {
+        let illumina1 =
+            b"@M1:8:FC:1:1101:1:2 1:N:0:1\nACGT\n+\nIIII\n@M1:8:FC:1:1101:3:4 1:N:0:1\nGG\n+\nII\n";
+        let illumina2 =
+            b"@M1:8:FC:1:1101:1:2 2:N:0:1\nTTGC\n+\nIIII\n@M1:8:FC:1:1101:3:4 2:N:0:1\nCA\n+\nII\n";
+        let old1 = b"@HWI:6:73:941:1973#0/1\nACGT\n+\nIIII\n@HWI:6:73:941:1974#0/1\nGG\n+\nII\n";
+        let old2 = b"@HWI:6:73:941:1973#0/2\nTTGC\n+\nIIII\n@HWI:6:73:941:1974#0/2\nCA\n+\nII\n";
+        let sra1 = b"@SRR001666.1 071112_SLXA-EAS1_s_7:5:1:817:345 length=4\nACGT\n+\nIIII\n";
+        let sra2 = b"@SRR001666.1 071112_SLXA-EAS1_s_7:5:1:817:345 length=3\nTTG\n+\nIII\n";
+
+        for &mate_names in &[MateNames::Identical, MateNames::StripSuffix] {
+            let illumina = pairs(illumina1, illumina2, mate_names);
+            assert_eq!(illumina.len(), 2);
+            assert!(illumina.iter().all(|pair| pair.is_ok()));
+            let sra = pairs(sra1, sra2, mate_names);
+            assert_eq!(sra.len(), 1);
+            assert!(sra[0].is_ok());
+        }
+        let pairs_old = pairs(old1, old2, MateNames::StripSuffix);
+        assert_eq!(pairs_old.len(), 2);
+        match pairs_old[1] {
+            Ok((ref id1, ref id2)) => {
+                assert_eq!(
+                    (id1.as_str(), id2.as_str()),
+                    ("HWI:6:73:941:1974#0/1", "HWI:6:73:941:1974#0/2")
+                )
+            }
+            Err(ref err) => panic!("unexpected error {}", err),
+        }
+        assert!(matches!(
+            pairs(old1, old2, MateNames::Identical)[0],
+            Err(PairError::MateMismatch(0, _, _))
+        ));
+        // swapped files
+        assert!(matches!(
+            pairs(old2, old1, MateNames::StripSuffix)[0],
+            Err(PairError::MateMismatch(0, _, _))
+        ));
+        assert_eq!(pairs(old1, illumina2, MateNames::Unchecked).len(), 2);
+    }
+
+    #[test]
+    fn test_paired_reader_errors() {
+        let fastq1 = b"@r1/1\nA\n+\nI\n@r2/1\nC\n+\nI\n@r3/1\nG\n+\nI\n@r4/1\nT\n+\nI\n";
+        let fastq2 = b"@r1/2\nA\n+\nI\n@r2/2\nC\n+\nI\n@r4/2\nG\n+\nI\n@r5/2\nT\n+\nI\n";
+        let pairs_mismatch = pairs(fastq1, fastq2, MateNames::StripSuffix);
+        assert_eq!(pairs_mismatch.len(), 3);
+        match pairs_mismatch[2] {
+            Err(PairError::MateMismatch(2, ref id1, ref id2)) => {
+                assert_eq!((id1.as_str(), id2.as_str()), ("r3/1", "r4/2"))
+            }
+            ref res => panic!("expected mismatch, found {:?}", res),
+        }
+
+        // truncated second file, either between records or inside of a record
+        let truncated = &fastq2[..24];
+        let pairs_truncated = pairs(fastq1, truncated, MateNames::StripSuffix);
+        assert_eq!(pairs_truncated.len(), 3);
+        assert!(matches!(
+            pairs_truncated[2],
+            Err(PairError::MissingMate(2, 2))
+        ));
+        let pairs_truncated = pairs(&fastq1[..24], fastq2, MateNames::StripSuffix);
+        assert!(matches!(
+            pairs_truncated[2],
+            Err(PairError::MissingMate(2, 1))
+        ));
+        let pairs_truncated = pairs(&fastq1[..32], fastq2, MateNames::StripSuffix);
+        assert!(matches!(
+            pairs_truncated[2],
+            Err(PairError::Read(1, Error::Parse { line: 11, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_paired_writer() {
+        let mate1 = Record::with_attrs("r1/1", None, b"ACGT", b"IIII");
+        let mate2 = Record::with_attrs("r1/2", Some("desc"), b"TT", b"JJ");
+        let mut writer = PairedWriter::new(Vec::new(), Vec::new());
+        writer.write(&mate1, &mate2).unwrap();
+        writer.flush().unwrap();
+        let fastq1 = writer.writer1.writer.get_ref().clone();
+        let fastq2 = writer.writer2.writer.get_ref().clone();
+        assert_eq!(fastq1, b"@r1/1\nACGT\n+\nIIII\n");
+        assert_eq!(fastq2, b"@r1/2 desc\nTT\n+\nJJ\n");
+        let round_trip = pairs(&fastq1, &fastq2, MateNames::StripSuffix);
+        assert_eq!(round_trip.len(), 1);
+        assert!(round_trip[0].is_ok());
+    }
+
+    #[test]
+    fn test_to_fasta() {
+        let record = Record::with_attrs("read1", Some("length=4"), b"ACGT", b"IIII");
+        let fasta = record.to_fasta();
+        assert_eq!(fasta.id(), "read1");
+        assert_eq!(fasta.desc(), Some("length=4"));
+        assert_eq!(fasta.seq(), b"ACGT");
+
+        let fastq = b"@read1 length=4\nACGT\n+\nIIII\n@read2\nGG\n+\nJJ\n";
+        let mut out = Vec::new();
+        assert_eq!(fastq_to_fasta(&fastq[..], &mut out).unwrap(), 2);
+        assert_eq!(out, b">read1 length=4\nACGT\n>read2\nGG\n");
+        let records: Vec<_> = fasta::Reader::new(&out[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records[1].seq(), b"GG");
+    }
+
+    #[test]
+    fn test_interleave_round_trip() {
+        let files = [
+            (
+                &b"@r1/1\nACGT\n+\nIIII\n@r2/1 desc\nGG\n+\nJJ\n@r3/1\nA\n+\n#\n"[..],
+                &b"@r1/2\nTTGC\n+\nIIII\n@r2/2 desc\nCCA\n+\nJJJ\n@r3/2\nT\n+\n!\n"[..],
+            ),
+            (
+                &b"@read 1:N:0:1\nACGT\n+\nIIII\n"[..],
+                &b"@read 2:N:0:1\nTTGC\n+\nIIII\n"[..],
+            ),
+            (&b""[..], &b""[..]),
+        ];
+        for &(fastq1, fastq2) in &files {
+            let mut interleaved = Vec::new();
+            let pairs = interleave(fastq1, fastq2, &mut interleaved, MateNames::StripSuffix);
+            let pairs = pairs.unwrap();
+            assert_eq!(Reader::new(&interleaved[..]).records().count(), 2 * pairs);
+            for &mate_names in &[MateNames::StripSuffix, MateNames::Unchecked] {
+                let (mut out1, mut out2) = (Vec::new(), Vec::new());
+                let res = deinterleave(&interleaved[..], &mut out1, &mut out2, mate_names);
+                assert_eq!(res.unwrap(), pairs);
+                assert_eq!(out1, fastq1);
+                assert_eq!(out2, fastq2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interleave_errors() {
+        let mut out = Vec::new();
+        let res = interleave(
+            &b"@r1/1\nA\n+\nI\n@r2/1\nA\n+\nI\n"[..],
+            &b"@r1/2\nA\n+\nI\n"[..],
+            &mut out,
+            MateNames::StripSuffix,
+        );
+        assert!(matches!(res, Err(PairError::MissingMate(1, 2))));
+        let res = interleave(
+            &b"@r1/1\nA\n+\nI\n"[..],
+            &b"@r2/2\nA\n+\nI\n"[..],
+            &mut out,
+            MateNames::StripSuffix,
+        );
+        assert!(matches!(res, Err(PairError::MateMismatch(0, _, _))));
+
+        let odd = b"@r1/1\nA\n+\nI\n@r1/2\nA\n+\nI\n@r2/1\nA\n+\nI\n";
+        let (mut out1, mut out2) = (Vec::new(), Vec::new());
+        let res = deinterleave(&odd[..], &mut out1, &mut out2, MateNames::Unchecked);
+        assert!(matches!(res, Err(PairError::MissingMate(1, 2))));
+        let swapped = b"@r1/2\nA\n+\nI\n@r1/1\nA\n+\nI\n";
+        let res = deinterleave(&swapped[..], &mut out1, &mut out2, MateNames::StripSuffix);
+        assert!(matches!(res, Err(PairError::MateMismatch(0, _, _))));
+        let res = deinterleave(&swapped[..], &mut out1, &mut out2, MateNames::Unchecked);
+        assert_eq!(res.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut fastq1 = Vec::new();
+        let mut fastq2 = Vec::new();
+        for i in 0..100 {
+            fastq1.extend(format!("@r{}/1\nACGT\n+\nIIII\n", i).bytes());
+            fastq2.extend(format!("@r{}/2\nTT\n+\nII\n", i).bytes());
+        }
+        let ids = |records: Vec<Record>| -> Vec<String> {
+            records
+                .iter()
+                .map(|record| record.id().to_owned())
+                .collect()
+        };
+        let sample = Reader::new(&fastq1[..]).sample(10, 1).unwrap();
+        assert_eq!(sample.len(), 10);
+        assert_eq!(
+            ids(sample),
+            ids(Reader::new(&fastq1[..]).sample(10, 1).unwrap())
+        );
+        assert_eq!(Reader::new(&fastq1[..]).sample(200, 1).unwrap().len(), 100);
+        let sample = Reader::new(&fastq1[..]).sample_frac(0.5, 1).unwrap();
+        assert!(sample.len() > 30 && sample.len() < 70);
+
+        for pairs in &[
+            PairedReader::new(&fastq1[..], &fastq2[..])
+                .sample(10, 2)
+                .unwrap(),
+            PairedReader::new(&fastq1[..], &fastq2[..])
+                .sample_frac(0.1, 2)
+                .unwrap(),
+        ] {
+            assert!(!pairs.is_empty());
+            for (mate1, mate2) in pairs {
+                assert!(MateNames::StripSuffix.matches(mate1.id(), mate2.id()));
+                assert_eq!(mate2.seq(), b"TT");
+            }
+        }
+        let truncated = &fastq2[..fastq2.len() - 15];
+        assert!(matches!(
+            PairedReader::new(&fastq1[..], truncated).sample(10, 2),
+            Err(PairError::MissingMate(99, 2))
+        ));
+    }
+
+    #[test]
+    fn test_writer_plus_line() {
+        // mixed style input, including a separator that only repeats the id
+        let fastq = b"@r1 desc\nACGT\n+\nIIII\n@r2 desc\nGG\n+r2 desc\nJJ\n@r3\nT\n+r3\nI\n";
+        let write = |plus_line: Option<PlusLine>| {
+            let mut writer = Writer::new(Vec::new());
+            if let Some(plus_line) = plus_line {
+                writer = writer.plus_line(plus_line);
+            }
+            for record in Reader::new(&fastq[..]).records() {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+            writer.flush().unwrap();
+            writer.writer.get_ref().clone()
+        };
+        let records: Vec<_> = Reader::new(&fastq[..])
+            .records()
+            .map(|record| record.unwrap().repeats_header())
+            .collect();
+        assert_eq!(records, [false, true, true]);
+
+        assert_eq!(write(None), &fastq[..]);
+        let bare = write(Some(PlusLine::Bare));
+        assert_eq!(
+            bare,
+            &b"@r1 desc\nACGT\n+\nIIII\n@r2 desc\nGG\n+\nJJ\n@r3\nT\n+\nI\n"[..]
+        );
+        let repeated = write(Some(PlusLine::RepeatHeader));
+        assert_eq!(
+            repeated,
+            &b"@r1 desc\nACGT\n+r1 desc\nIIII\n@r2 desc\nGG\n+r2 desc\nJJ\n@r3\nT\n+r3\nI\n"[..]
+        );
+        // both styles round-trip
+        for output in &[bare, repeated] {
+            let mut writer = Writer::new(Vec::new());
+            for record in Reader::new(&output[..]).records() {
+                writer.write_record(&record.unwrap()).unwrap();
+            }
+            writer.flush().unwrap();
+            assert_eq!(writer.writer.get_ref(), output);
+        }
+    }
+
+    #[test]
+    fn test_clipped() {
+        let record = Record::with_attrs("read", Some("desc"), b"ACGTACGTAC", b"0123456789");
+        let clipped = record.clipped(3..8).unwrap();
+        assert_eq!(clipped.record().seq(), b"TACGT");
+        assert_eq!(clipped.record().qual(), b"34567");
+        assert_eq!(clipped.record().desc(), Some("desc"));
+        assert_eq!((clipped.front_clipped(), clipped.back_clipped()), (3, 2));
+
+        // clipping again keeps the coordinates relative to the original
+        let twice = clipped.clip_front(1).unwrap().clip_back(1).unwrap();
+        assert_eq!(twice.record().seq(), b"ACG");
+        assert_eq!(twice.offset_in_original(), 4);
+        assert_eq!(twice.original_len(), 10);
+        for pos in 0..3 {
+            assert_eq!(
+                record.seq()[twice.to_original(pos)],
+                twice.record().seq()[pos]
+            );
+        }
+
+        assert_eq!(record.clip_front(10).unwrap().record().seq(), b"");
+        assert_eq!(record.clip_back(10).unwrap().back_clipped(), 10);
+        assert_eq!(record.clipped(4..4).unwrap().record().qual(), b"");
+        assert_eq!(
+            record.clipped(4..11).err(),
+            Some(ClipError::InvalidRange(4, 11, 10))
+        );
+        let (start, end) = (5, 4);
+        assert!(record.clipped(start..end).is_err());
+        assert_eq!(
+            record.clip_front(11).err(),
+            Some(ClipError::TooLong(11, 10))
+        );
+        assert_eq!(clipped.clip_back(6).err(), Some(ClipError::TooLong(6, 5)));
+    }
+
+    #[test]
+    fn test_clipped_pairs() {
+        let fastq1 = b"@r1/1\nAAACGTACGT\n+\nIIIIIIIIII\n@r2/1\nAAAC\n+\nIIII\n";
+        let fastq2 = b"@r1/2\nGGTTGCAT\n+\nJJJJJJJJ\n@r2/2\nGGT\n+\nJJJ\n";
+        // remove a primer of length 3 from the first and 2 bases from the second mate
+        let mut clipped = Vec::new();
+        for pair in PairedReader::new(&fastq1[..], &fastq2[..]).records() {
+            let (mate1, mate2) = pair.unwrap();
+            let mate1 = mate1.clip_front(3).unwrap();
+            let mate2 = mate2.clip_front(2).unwrap().clip_back(1).unwrap();
+            clipped.push((mate1, mate2));
+        }
+        assert_eq!(clipped[0].0.record().seq(), b"CGTACGT");
+        assert_eq!(clipped[0].1.record().seq(), b"TTGCA");
+        assert_eq!(clipped[1].0.record().seq(), b"C");
+        // zero-length result
+        assert_eq!(clipped[1].1.record().seq(), b"");
+        assert_eq!(clipped[1].1.offset_in_original(), 2);
+
+        let mut writer = PairedWriter::new(Vec::new(), Vec::new());
+        for (mate1, mate2) in &clipped {
+            writer.write(mate1.record(), mate2.record()).unwrap();
+        }
+        writer.flush().unwrap();
+        let out1 = writer.writer1.writer.get_ref().clone();
+        let out2 = writer.writer2.writer.get_ref().clone();
+        let pairs = pairs(&out1, &out2, MateNames::StripSuffix);
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.iter().all(|pair| pair.is_ok()));
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write("id", Some("desc"), b"ACCGTAGGCTGA", b"IIIIIIJJJJJJ")
+            .ok()
+            .expect("Expected successful write");
+        writer.flush().ok().expect("Expected successful write");
+        assert_eq!(writer.writer.get_ref(), &FASTQ_FILE);
+    }
+}
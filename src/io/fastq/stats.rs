@@ -0,0 +1,321 @@
+// Copyright 2014-2018 Johannes Köster, Henning Timm.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Quality control statistics of FastQ records, as shown in a basic FastQC report. Records
+//! are collected one at a time, with memory bounded by the maximum read length. Qualities
+//! are decoded as Phred scores with offset 33.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::fastq::stats::Collector;
+//! use bio::io::fastq::Reader;
+//!
+//! let fastq = b"@r1\nACGT\n+\nII5+\n@r2\nGG\n+\nI5\n";
+//! let mut collector = Collector::new();
+//! for record in Reader::new(&fastq[..]).records() {
+//!     collector.collect(&record.unwrap());
+//! }
+//! let summary = collector.summary();
+//! assert_eq!((summary.records, summary.bases), (2, 6));
+//! assert_eq!(summary.positions[1].mean_qual, 30.0);
+//! assert_eq!(summary.gc_histogram[100], 1);
+//! ```
+
+use crate::io::fastq::Record;
+
+/// The maximum Phred score that can be encoded with offset 33.
+const MAX_QUAL: usize = 93;
+
+/// Accumulates statistics of FastQ records.
+#[derive(Clone, Debug)]
+pub struct Collector {
+    records: u64,
+    bases: u64,
+    /// Histogram of Phred scores for each position.
+    qual_counts: Vec<[u64; MAX_QUAL + 1]>,
+    /// Counts of A, C, G, T and other bases for each position.
+    base_counts: Vec<[u64; 5]>,
+    length_counts: Vec<u64>,
+    /// Histogram of the GC content of reads in percent.
+    gc_counts: Vec<u64>,
+}
+
+/// Counts of bases at a read position. Lowercase bases are counted as uppercase, and anything
+/// else than A, C, G and T as N.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseCounts {
+    pub a: u64,
+    pub c: u64,
+    pub g: u64,
+    pub t: u64,
+    pub n: u64,
+}
+
+/// Statistics of a read position over all reads that are long enough to cover it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PositionSummary {
+    /// Number of reads covering the position.
+    pub count: u64,
+    pub mean_qual: f64,
+    /// Quartiles of the Phred scores, using the nearest-rank method.
+    pub lower_quartile: u8,
+    pub median_qual: u8,
+    pub upper_quartile: u8,
+    pub base_counts: BaseCounts,
+}
+
+/// Summary of collected statistics.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub records: u64,
+    pub bases: u64,
+    /// Statistics for each 0-based read position.
+    pub positions: Vec<PositionSummary>,
+    /// Number of reads for each read length, up to the maximum.
+    pub length_histogram: Vec<u64>,
+    /// Number of non-empty reads for each GC content, rounded to full percent (0 to 100).
+    pub gc_histogram: Vec<u64>,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Collector::new()
+    }
+}
+
+impl Collector {
+    /// Create a new, empty collector.
+    pub fn new() -> Self {
+        Collector {
+            records: 0,
+            bases: 0,
+            qual_counts: Vec::new(),
+            base_counts: Vec::new(),
+            length_counts: Vec::new(),
+            gc_counts: vec![0; 101],
+        }
+    }
+
+    /// Add the given record to the statistics.
+    pub fn collect(&mut self, record: &Record) {
+        let seq = record.seq();
+        let qual = record.qual();
+        let len = seq.len();
+        self.records += 1;
+        self.bases += len as u64;
+        if self.length_counts.len() <= len {
+            self.length_counts.resize(len + 1, 0);
+        }
+        self.length_counts[len] += 1;
+        if self.base_counts.len() < len {
+            self.base_counts.resize(len, [0; 5]);
+            self.qual_counts.resize(len, [0; MAX_QUAL + 1]);
+        }
+
+        let mut gc = 0;
+        for (i, &base) in seq.iter().enumerate() {
+            let base = match base {
+                b'A' | b'a' => 0,
+                b'C' | b'c' => 1,
+                b'G' | b'g' => 2,
+                b'T' | b't' => 3,
+                _ => 4,
+            };
+            if base == 1 || base == 2 {
+                gc += 1;
+            }
+            self.base_counts[i][base] += 1;
+        }
+        for (i, &q) in qual.iter().enumerate().take(len) {
+            let q = usize::from(q.saturating_sub(33)).min(MAX_QUAL);
+            self.qual_counts[i][q] += 1;
+        }
+        if let Some(percent) = (gc * 100 + len / 2).checked_div(len) {
+            self.gc_counts[percent] += 1;
+        }
+    }
+
+    /// Add the statistics of another collector, e.g. one that collected a different shard of
+    /// the same file.
+    pub fn merge(&mut self, other: Collector) {
+        self.records += other.records;
+        self.bases += other.bases;
+        if self.length_counts.len() < other.length_counts.len() {
+            self.length_counts.resize(other.length_counts.len(), 0);
+        }
+        for (count, other) in self.length_counts.iter_mut().zip(other.length_counts) {
+            *count += other;
+        }
+        if self.base_counts.len() < other.base_counts.len() {
+            self.base_counts.resize(other.base_counts.len(), [0; 5]);
+            self.qual_counts
+                .resize(other.qual_counts.len(), [0; MAX_QUAL + 1]);
+        }
+        for (counts, other) in self.base_counts.iter_mut().zip(other.base_counts) {
+            for (count, other) in counts.iter_mut().zip(other.iter()) {
+                *count += other;
+            }
+        }
+        for (counts, other) in self.qual_counts.iter_mut().zip(other.qual_counts) {
+            for (count, other) in counts.iter_mut().zip(other.iter()) {
+                *count += other;
+            }
+        }
+        for (count, other) in self.gc_counts.iter_mut().zip(other.gc_counts) {
+            *count += other;
+        }
+    }
+
+    /// Return a summary of the collected statistics.
+    pub fn summary(&self) -> Summary {
+        let positions = self
+            .qual_counts
+            .iter()
+            .zip(&self.base_counts)
+            .map(|(qual_counts, base_counts)| {
+                let count: u64 = base_counts.iter().sum();
+                let sum: u64 = qual_counts
+                    .iter()
+                    .enumerate()
+                    .map(|(q, &n)| q as u64 * n)
+                    .sum();
+                PositionSummary {
+                    count,
+                    mean_qual: sum as f64 / count as f64,
+                    lower_quartile: quantile(qual_counts, count, 1, 4),
+                    median_qual: quantile(qual_counts, count, 1, 2),
+                    upper_quartile: quantile(qual_counts, count, 3, 4),
+                    base_counts: BaseCounts {
+                        a: base_counts[0],
+                        c: base_counts[1],
+                        g: base_counts[2],
+                        t: base_counts[3],
+                        n: base_counts[4],
+                    },
+                }
+            })
+            .collect();
+
+        Summary {
+            records: self.records,
+            bases: self.bases,
+            positions,
+            length_histogram: self.length_counts.clone(),
+            gc_histogram: self.gc_counts.clone(),
+        }
+    }
+}
+
+/// Return the `num / denom` quantile of the given histogram with `total` entries, i.e. the
+/// smallest value such that at least `ceil(num / denom * total)` entries are not larger.
+fn quantile(counts: &[u64], total: u64, num: u64, denom: u64) -> u8 {
+    let rank = (num * total).div_ceil(denom).max(1);
+    let mut cumulative = 0;
+    for (q, &n) in counts.iter().enumerate() {
+        cumulative += n;
+        if cumulative >= rank {
+            return q as u8;
+        }
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::fastq::Reader;
+
+    const FASTQ: &[u8] = b"@r1\n\
+ACGTN\n\
++\n\
+II5+!\n\
+@r2\n\
+GGca\n\
++\n\
+5+5+\n\
+@r3\n\
+T\n\
++\n\
+I\n\
+@r4\n\
+\n\
++\n\
+\n";
+
+    fn collect(fastq: &[u8]) -> Collector {
+        let mut collector = Collector::new();
+        for record in Reader::new(fastq).records() {
+            collector.collect(&record.unwrap());
+        }
+        collector
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = collect(FASTQ).summary();
+        assert_eq!(summary.records, 4);
+        assert_eq!(summary.bases, 10);
+        assert_eq!(summary.length_histogram, [1, 1, 0, 0, 1, 1]);
+        let mut gc_histogram = vec![0; 101];
+        // r1 has 2 of 5, r2 has 3 of 4 and r3 0 of 1 bases G or C, r4 is empty
+        gc_histogram[40] = 1;
+        gc_histogram[75] = 1;
+        gc_histogram[0] = 1;
+        assert_eq!(summary.gc_histogram, gc_histogram);
+
+        // 'I' is 40, '5' is 20, '+' is 10 and '!' is 0
+        let expected = [
+            (3, 100.0 / 3.0, 20, 40, 40, [1, 0, 1, 1, 0]),
+            (2, 25.0, 10, 10, 40, [0, 1, 1, 0, 0]),
+            (2, 20.0, 20, 20, 20, [0, 1, 1, 0, 0]),
+            (2, 10.0, 10, 10, 10, [1, 0, 0, 1, 0]),
+            (1, 0.0, 0, 0, 0, [0, 0, 0, 0, 1]),
+        ];
+        assert_eq!(summary.positions.len(), expected.len());
+        for (position, &(count, mean, lower, median, upper, bases)) in
+            summary.positions.iter().zip(&expected)
+        {
+            assert_eq!(position.count, count);
+            assert_relative_eq!(position.mean_qual, mean);
+            assert_eq!(
+                (
+                    position.lower_quartile,
+                    position.median_qual,
+                    position.upper_quartile
+                ),
+                (lower, median, upper)
+            );
+            let counts = position.base_counts;
+            assert_eq!([counts.a, counts.c, counts.g, counts.t, counts.n], bases);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let records: Vec<_> = FASTQ.split(|&c| c == b'@').skip(1).collect();
+        for split in 0..=records.len() {
+            let shard = |records: &[&[u8]]| -> Vec<u8> {
+                records
+                    .iter()
+                    .flat_map(|r| [&b"@"[..], r].concat())
+                    .collect()
+            };
+            let mut collector = collect(&shard(&records[..split]));
+            collector.merge(collect(&shard(&records[split..])));
+            assert_eq!(collector.summary(), collect(FASTQ).summary());
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let summary = Collector::new().summary();
+        assert_eq!((summary.records, summary.bases), (0, 0));
+        assert!(summary.positions.is_empty());
+        assert!(summary.length_histogram.is_empty());
+        assert!(summary.gc_histogram.iter().all(|&n| n == 0));
+    }
+}
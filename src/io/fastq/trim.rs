@@ -0,0 +1,197 @@
+// Copyright 2014-2018 Johannes Köster, Henning Timm.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Quality and adapter trimming of FastQ records. Both trim sequence and qualities at the
+//! 3' end, keeping id and description, and return the trimmed record together with the
+//! number of removed bases.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::fastq::trim::{adapter_trim, quality_trim};
+//! use bio::io::fastq::Record;
+//!
+//! let record = Record::with_attrs("read", None, b"ACGTACAGATCGGA", b"IIIIIIIIIIII##");
+//! let (record, removed) = quality_trim(&record, 20, 1);
+//! assert_eq!((record.seq(), removed), (&b"ACGTACAGATCG"[..], 2));
+//! let (record, removed) = adapter_trim(&record, b"AGATCGGAAGAGC", 3, 0.1);
+//! assert_eq!((record.seq(), removed), (&b"ACGTAC"[..], 6));
+//! ```
+
+use crate::alignment::distance::hamming;
+use crate::io::fastq::Record;
+
+/// Trim low quality bases from the 3' end with a sliding window. Starting at the 3' end,
+/// the window is moved towards the 5' end, removing one base at a time, until the mean
+/// Phred score (offset 33) of the window reaches `min_q`. With a window of 1, all trailing
+/// bases below `min_q` are removed. If no window reaches `min_q`, the result is empty.
+///
+/// # Arguments
+///
+/// * `record` - the record to trim
+/// * `min_q` - the minimum mean Phred score of the window at the new 3' end
+/// * `window` - the window size, at least 1
+pub fn quality_trim(record: &Record, min_q: u8, window: usize) -> (Record, usize) {
+    assert!(window > 0, "window size has to be at least 1");
+    let qual = record.qual();
+    let mut end = qual.len();
+    while end > 0 {
+        let start = end.saturating_sub(window);
+        let sum: usize = qual[start..end]
+            .iter()
+            .map(|&q| usize::from(q.saturating_sub(33)))
+            .sum();
+        if sum >= usize::from(min_q) * (end - start) {
+            break;
+        }
+        end -= 1;
+    }
+
+    truncate(record, end)
+}
+
+/// Trim an adapter from the 3' end. The adapter is searched at each position of the read
+/// from 5' to 3', either completely contained in the read or overlapping its 3' end with a
+/// prefix. At the first position where the overlap is at least `min_overlap` long and has
+/// a hamming distance of at most `max_mismatch_rate` times its length, the read is cut off.
+///
+/// # Arguments
+///
+/// * `record` - the record to trim
+/// * `adapter` - the adapter sequence
+/// * `min_overlap` - the minimum length of an overlap between the read end and the adapter
+/// * `max_mismatch_rate` - the maximum fraction of mismatches in the overlap
+pub fn adapter_trim(
+    record: &Record,
+    adapter: &[u8],
+    min_overlap: usize,
+    max_mismatch_rate: f64,
+) -> (Record, usize) {
+    let seq = record.seq();
+    let min_overlap = min_overlap.max(1);
+    let end = (0..seq.len())
+        .take_while(|&i| (seq.len() - i).min(adapter.len()) >= min_overlap)
+        .find(|&i| {
+            let overlap = (seq.len() - i).min(adapter.len());
+            let max_mismatches = (max_mismatch_rate * overlap as f64) as u64;
+            hamming(&seq[i..i + overlap], &adapter[..overlap]) <= max_mismatches
+        })
+        .unwrap_or(seq.len());
+
+    truncate(record, end)
+}
+
+/// Cut the record to the given length, returning it with the number of removed bases.
+fn truncate(record: &Record, len: usize) -> (Record, usize) {
+    let removed = record.seq().len() - len;
+    let mut trimmed = Record::with_attrs(
+        record.id(),
+        record.desc(),
+        &record.seq()[..len],
+        &record.qual()[..len],
+    );
+    trimmed.plus_header = record.plus_header;
+
+    (trimmed, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADAPTER: &[u8] = b"AGATCGGAAGAGC";
+
+    fn record(seq: &[u8], qual: &[u8]) -> Record {
+        Record::with_attrs("read", Some("desc"), seq, qual)
+    }
+
+    #[test]
+    fn test_quality_trim() {
+        let (trimmed, removed) = quality_trim(&record(b"ACGTACGTAC", b"IIIII#####"), 20, 1);
+        assert_eq!(removed, 5);
+        assert_eq!(trimmed.seq(), b"ACGTA");
+        assert_eq!(trimmed.qual(), b"IIIII");
+        assert_eq!(trimmed.id(), "read");
+        assert_eq!(trimmed.desc(), Some("desc"));
+
+        // '5' is a score of 20, which suffices on average with the preceding base
+        let (trimmed, removed) = quality_trim(&record(b"ACGTAC", b"IIII5#"), 20, 2);
+        assert_eq!((trimmed.qual(), removed), (&b"IIII5"[..], 1));
+        let (trimmed, removed) = quality_trim(&record(b"ACGTAC", b"IIII5#"), 31, 2);
+        assert_eq!((trimmed.qual(), removed), (&b"IIII"[..], 2));
+        // windows larger than the read
+        let (trimmed, removed) = quality_trim(&record(b"ACG", b"I##"), 30, 5);
+        assert_eq!((trimmed.qual(), removed), (&b"I"[..], 2));
+    }
+
+    #[test]
+    fn test_quality_trim_all_low() {
+        for &window in &[1, 2, 4, 10] {
+            let (trimmed, removed) = quality_trim(&record(b"ACGT", b"#+#!"), 20, window);
+            assert_eq!(removed, 4);
+            assert!(trimmed.seq().is_empty());
+            assert!(trimmed.qual().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_quality_trim_noop() {
+        let original = record(b"ACGTACGT", b"II#IIIII");
+        for &window in &[1, 3, 8] {
+            let (trimmed, removed) = quality_trim(&original, 20, window);
+            assert_eq!(removed, 0);
+            assert_eq!(trimmed.seq(), original.seq());
+            assert_eq!(trimmed.qual(), original.qual());
+        }
+        let (trimmed, removed) = quality_trim(&record(b"", b""), 20, 4);
+        assert_eq!((trimmed.seq().len(), removed), (0, 0));
+    }
+
+    #[test]
+    fn test_adapter_trim() {
+        let insert = b"TTGACCAGTACCGATTGCAC";
+        for offset in 0..=insert.len() {
+            // the adapter followed by sequence beyond it
+            let mut seq = insert[..offset].to_vec();
+            seq.extend_from_slice(ADAPTER);
+            seq.extend_from_slice(b"GGGG");
+            let qual = vec![b'I'; seq.len()];
+            let (trimmed, removed) = adapter_trim(&record(&seq, &qual), ADAPTER, 3, 0.0);
+            assert_eq!(trimmed.seq(), &insert[..offset]);
+            assert_eq!(trimmed.qual().len(), offset);
+            assert_eq!(removed, ADAPTER.len() + 4);
+        }
+    }
+
+    #[test]
+    fn test_adapter_trim_partial() {
+        // prefix of the adapter at the 3' end
+        let read = record(b"TTGACCAGTACCAGATC", b"IIIIIIIIIIIIIIIII");
+        let (trimmed, removed) = adapter_trim(&read, ADAPTER, 3, 0.0);
+        assert_eq!((trimmed.seq(), removed), (&b"TTGACCAGTACC"[..], 5));
+        // too short overlap
+        let (trimmed, removed) = adapter_trim(&read, ADAPTER, 6, 0.0);
+        assert_eq!((trimmed.seq(), removed), (read.seq(), 0));
+
+        // one mismatch in the adapter
+        let read = record(b"TTGACCAGATCGCAAGAGC", b"IIIIIIIIIIIIIIIIIII");
+        let (trimmed, removed) = adapter_trim(&read, ADAPTER, 3, 0.0);
+        assert_eq!(removed, 0);
+        assert_eq!(trimmed.seq(), read.seq());
+        let (trimmed, removed) = adapter_trim(&read, ADAPTER, 3, 0.1);
+        assert_eq!((trimmed.seq(), removed), (&b"TTGACC"[..], 13));
+    }
+
+    #[test]
+    fn test_adapter_trim_noop() {
+        let read = record(b"TTGACCAGTACCGATTGCAC", b"IIIIIIIIIIIIIIIIIIII");
+        let (trimmed, removed) = adapter_trim(&read, ADAPTER, 3, 0.1);
+        assert_eq!(removed, 0);
+        assert_eq!(trimmed.seq(), read.seq());
+        assert_eq!(trimmed.qual(), read.qual());
+        let (trimmed, removed) = adapter_trim(&record(b"", b""), ADAPTER, 3, 0.1);
+        assert_eq!((trimmed.seq().len(), removed), (0, 0));
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright 2016 Pierre Marijon.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Gene models from the `ID` and `Parent` attributes of GFF3 records.
+//!
+//! Records without a `Parent` are genes, their children are transcripts, and the `exon` and
+//! `CDS` children of transcripts make up the transcript structure. Other children of
+//! transcripts (e.g. UTRs) and deeper levels are ignored. A record with multiple parents is
+//! added to each of them, and parents may be defined after their children.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::gff;
+//!
+//! let gff = b"chr1\tsrc\texon\t100\t200\t.\t+\t.\tParent=tx1
+//! chr1\tsrc\tgene\t100\t500\t.\t+\t.\tID=gene1
+//! chr1\tsrc\tmRNA\t100\t500\t.\t+\t.\tID=tx1;Parent=gene1
+//! chr1\tsrc\texon\t400\t500\t.\t+\t.\tParent=tx1
+//! ";
+//! let mut builder = gff::HierarchyBuilder::new();
+//! for record in gff::Reader::new(&gff[..], gff::GffType::GFF3).records() {
+//!     builder.push(record.unwrap());
+//! }
+//! let (genes, errors) = builder.build();
+//! assert!(errors.is_empty());
+//! assert_eq!(genes[0].id.as_ref().unwrap(), "gene1");
+//! let exons = &genes[0].transcripts[0].exons;
+//! assert_eq!((exons[0].start, exons[1].start), (99, 399));
+//! ```
+
+use std::collections::HashMap;
+
+use crate::io::gff::Record;
+use crate::utils::{Interval, Strand};
+
+/// A gene with its transcripts.
+#[derive(Debug, Clone)]
+pub struct Gene {
+    pub id: Option<String>,
+    pub seqname: String,
+    pub strand: Strand,
+    pub record: Record,
+    /// Transcripts in the order of the file.
+    pub transcripts: Vec<Transcript>,
+}
+
+/// A transcript with its exons and coding parts. Intervals are 0-based and half-open, and
+/// sorted by their start.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub id: Option<String>,
+    pub record: Record,
+    pub exons: Vec<Interval<u64>>,
+    pub cds: Vec<Interval<u64>>,
+}
+
+quick_error! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum HierarchyError {
+        /// A feature refers to a parent that is not defined. The feature is left out, unless it
+        /// has other parents.
+        MissingParent { feature: String, parent: String } {
+            description("parent feature not found")
+            display("parent {} of feature {} not found", parent, feature)
+        }
+        /// Features are (indirectly) their own parents. They are left out.
+        Cycle { features: Vec<String> } {
+            description("cyclic parent relationship")
+            display("cyclic parent relationship between features {}", features.join(", "))
+        }
+    }
+}
+
+/// Collects GFF3 records and links them into gene models.
+#[derive(Debug, Clone, Default)]
+pub struct HierarchyBuilder {
+    records: Vec<Record>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    Gene,
+    Transcript,
+    Part,
+    Unplaced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Unvisited,
+    Visiting,
+    Done(Role),
+}
+
+impl HierarchyBuilder {
+    pub fn new() -> Self {
+        HierarchyBuilder::default()
+    }
+
+    /// Add a record. Parents may be added after their children.
+    pub fn push(&mut self, record: Record) {
+        self.records.push(record);
+    }
+
+    /// Link all records into genes, in the order of the file. Orphans, i.e. records with an
+    /// undefined parent, and cycles are reported along with the genes. Records below an
+    /// orphan or a cycle are left out, too.
+    pub fn build(self) -> (Vec<Gene>, Vec<HierarchyError>) {
+        let mut ids = HashMap::new();
+        for (i, record) in self.records.iter().enumerate() {
+            if let Some(id) = record.attributes().get("ID") {
+                ids.entry(id.to_owned()).or_insert(i);
+            }
+        }
+
+        let mut errors = Vec::new();
+        let mut parents = Vec::with_capacity(self.records.len());
+        for record in &self.records {
+            let mut resolved = Vec::new();
+            for parent in record.attributes().get_vec("Parent").unwrap_or_default() {
+                match ids.get(parent) {
+                    Some(&j) => resolved.push(j),
+                    None => errors.push(HierarchyError::MissingParent {
+                        feature: describe(record),
+                        parent: parent.to_owned(),
+                    }),
+                }
+            }
+            let is_root = !record.attributes().contains_key("Parent");
+            parents.push((is_root, resolved));
+        }
+
+        let mut resolver = Resolver {
+            records: &self.records,
+            parents: &parents,
+            states: vec![State::Unvisited; self.records.len()],
+            path: Vec::new(),
+            errors: &mut errors,
+        };
+        let roles: Vec<Role> = (0..self.records.len())
+            .map(|i| resolver.resolve(i))
+            .collect();
+
+        let mut genes = Vec::new();
+        let mut gene_index = HashMap::new();
+        for (i, record) in self.records.iter().enumerate() {
+            if roles[i] == Role::Gene {
+                gene_index.insert(i, genes.len());
+                genes.push(Gene {
+                    id: id(record),
+                    seqname: record.seqname().to_owned(),
+                    strand: record.strand(),
+                    record: record.clone(),
+                    transcripts: Vec::new(),
+                });
+            }
+        }
+        // transcripts indexed by record and gene
+        let mut transcript_index = HashMap::new();
+        for (i, record) in self.records.iter().enumerate() {
+            if roles[i] == Role::Transcript {
+                for &parent in &parents[i].1 {
+                    if let Some(&g) = gene_index.get(&parent) {
+                        let transcripts = &mut genes[g].transcripts;
+                        transcript_index
+                            .entry(i)
+                            .or_insert_with(Vec::new)
+                            .push((g, transcripts.len()));
+                        transcripts.push(Transcript {
+                            id: id(record),
+                            record: record.clone(),
+                            exons: Vec::new(),
+                            cds: Vec::new(),
+                        });
+                    }
+                }
+            }
+        }
+        for (i, record) in self.records.iter().enumerate() {
+            if roles[i] != Role::Part {
+                continue;
+            }
+            let interval = Interval::new(record.start().saturating_sub(1)..*record.end())
+                .unwrap_or_else(|_| Interval::from(*record.end()..*record.end()));
+            for parent in &parents[i].1 {
+                for &(g, t) in transcript_index.get(parent).into_iter().flatten() {
+                    let transcript = &mut genes[g].transcripts[t];
+                    match record.feature_type() {
+                        "exon" => transcript.exons.push(interval.clone()),
+                        "CDS" => transcript.cds.push(interval.clone()),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        for transcript in genes.iter_mut().flat_map(|gene| &mut gene.transcripts) {
+            transcript.exons.sort_by_key(|exon| (exon.start, exon.end));
+            transcript.cds.sort_by_key(|cds| (cds.start, cds.end));
+        }
+
+        (genes, errors)
+    }
+}
+
+/// Determines the roles of records by following their parents, detecting cycles.
+struct Resolver<'a> {
+    records: &'a [Record],
+    parents: &'a [(bool, Vec<usize>)],
+    states: Vec<State>,
+    path: Vec<usize>,
+    errors: &'a mut Vec<HierarchyError>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, i: usize) -> Role {
+        match self.states[i] {
+            State::Done(role) => return role,
+            State::Visiting => {
+                let start = self.path.iter().position(|&j| j == i).unwrap();
+                let cycle = self.path[start..].to_vec();
+                self.errors.push(HierarchyError::Cycle {
+                    features: cycle.iter().map(|&j| describe(&self.records[j])).collect(),
+                });
+                for j in cycle {
+                    self.states[j] = State::Done(Role::Unplaced);
+                }
+                return Role::Unplaced;
+            }
+            State::Unvisited => (),
+        }
+
+        self.states[i] = State::Visiting;
+        self.path.push(i);
+        let (is_root, ref parents) = self.parents[i];
+        let role = if is_root {
+            Role::Gene
+        } else {
+            let mut role = Role::Unplaced;
+            for &parent in parents {
+                match self.resolve(parent) {
+                    Role::Gene => role = Role::Transcript,
+                    Role::Transcript if role != Role::Transcript => role = Role::Part,
+                    _ => (),
+                }
+            }
+            role
+        };
+        self.path.pop();
+        // records of a cycle that was closed below have already been marked
+        if self.states[i] == State::Visiting {
+            self.states[i] = State::Done(role);
+            role
+        } else {
+            Role::Unplaced
+        }
+    }
+}
+
+fn id(record: &Record) -> Option<String> {
+    record.attributes().get("ID").map(|id| id.to_owned())
+}
+
+/// Describe a record by its ID, or by its type and location if it has none.
+fn describe(record: &Record) -> String {
+    match record.attributes().get("ID") {
+        Some(id) => id.to_owned(),
+        None => format!(
+            "{} at {}:{}-{}",
+            record.feature_type(),
+            record.seqname(),
+            record.start(),
+            record.end()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::gff::{GffType, Reader};
+
+    fn build(gff: &[u8]) -> (Vec<Gene>, Vec<HierarchyError>) {
+        let mut builder = HierarchyBuilder::new();
+        for record in Reader::new(gff, GffType::GFF3).records() {
+            builder.push(record.unwrap());
+        }
+        builder.build()
+    }
+
+    fn intervals(intervals: &[Interval<u64>]) -> Vec<(u64, u64)> {
+        intervals.iter().map(|i| (i.start, i.end)).collect()
+    }
+
+    #[test]
+    fn test_multi_transcript_gene() {
+        let gff = b"chr1\tsrc\tgene\t1000\t9000\t.\t-\t.\tID=gene1
+chr1\tsrc\tmRNA\t1000\t9000\t.\t-\t.\tID=tx1;Parent=gene1
+chr1\tsrc\texon\t5000\t5500\t.\t-\t.\tParent=tx1
+chr1\tsrc\texon\t1000\t1200\t.\t-\t.\tParent=tx1
+chr1\tsrc\tCDS\t5000\t5300\t.\t-\t0\tID=cds1;Parent=tx1
+chr1\tsrc\tCDS\t1100\t1200\t.\t-\t1\tID=cds1;Parent=tx1
+chr1\tsrc\tfive_prime_UTR\t5301\t5500\t.\t-\t.\tParent=tx1
+chr1\tsrc\tmRNA\t1000\t9000\t.\t-\t.\tID=tx2;Parent=gene1
+chr1\tsrc\texon\t8000\t9000\t.\t-\t.\tParent=tx2
+chr1\tsrc\texon\t1000\t1200\t.\t-\t.\tParent=tx2
+chr2\tsrc\tgene\t1\t100\t.\t+\t.\tID=gene2
+";
+        let (genes, errors) = build(gff);
+        assert!(errors.is_empty());
+        assert_eq!(genes.len(), 2);
+        let gene = &genes[0];
+        assert_eq!(gene.id, Some("gene1".to_owned()));
+        assert_eq!(
+            (gene.seqname.as_str(), gene.strand),
+            ("chr1", Strand::Reverse)
+        );
+        assert_eq!(gene.transcripts.len(), 2);
+        let tx1 = &gene.transcripts[0];
+        assert_eq!(tx1.id, Some("tx1".to_owned()));
+        assert_eq!(intervals(&tx1.exons), [(999, 1200), (4999, 5500)]);
+        assert_eq!(intervals(&tx1.cds), [(1099, 1200), (4999, 5300)]);
+        let tx2 = &gene.transcripts[1];
+        assert_eq!(intervals(&tx2.exons), [(999, 1200), (7999, 9000)]);
+        assert!(tx2.cds.is_empty());
+        assert_eq!(genes[1].id, Some("gene2".to_owned()));
+        assert!(genes[1].transcripts.is_empty());
+    }
+
+    #[test]
+    fn test_multiple_parents() {
+        // an exon shared by two transcripts, and a trans-spliced transcript of two genes
+        let gff = b"chr1\tsrc\tgene\t1\t1000\t.\t+\t.\tID=geneA
+chr2\tsrc\tgene\t1\t1000\t.\t+\t.\tID=geneB
+chr1\tsrc\tmRNA\t1\t1000\t.\t+\t.\tID=txA;Parent=geneA
+chr1\tsrc\tmRNA\t1\t1000\t.\t+\t.\tID=txAB;Parent=geneA,geneB
+chr1\tsrc\texon\t1\t100\t.\t+\t.\tParent=txA,txAB
+chr2\tsrc\texon\t500\t600\t.\t+\t.\tParent=txAB
+";
+        let (genes, errors) = build(gff);
+        assert!(errors.is_empty());
+        assert_eq!(genes.len(), 2);
+        let ids = |gene: &Gene| -> Vec<String> {
+            gene.transcripts
+                .iter()
+                .map(|t| t.id.clone().unwrap())
+                .collect()
+        };
+        assert_eq!(ids(&genes[0]), ["txA", "txAB"]);
+        assert_eq!(ids(&genes[1]), ["txAB"]);
+        assert_eq!(intervals(&genes[0].transcripts[0].exons), [(0, 100)]);
+        for transcript in &[&genes[0].transcripts[1], &genes[1].transcripts[0]] {
+            assert_eq!(intervals(&transcript.exons), [(0, 100), (499, 600)]);
+        }
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let gff = b"chr1\tsrc\tCDS\t150\t200\t.\t+\t0\tParent=tx1
+chr1\tsrc\texon\t300\t400\t.\t+\t.\tParent=tx1
+chr1\tsrc\texon\t100\t200\t.\t+\t.\tParent=tx1
+chr1\tsrc\tmRNA\t100\t400\t.\t+\t.\tID=tx1;Parent=gene1
+chr1\tsrc\tgene\t100\t400\t.\t+\t.\tID=gene1
+";
+        let (genes, errors) = build(gff);
+        assert!(errors.is_empty());
+        assert_eq!(genes.len(), 1);
+        let transcript = &genes[0].transcripts[0];
+        assert_eq!(intervals(&transcript.exons), [(99, 200), (299, 400)]);
+        assert_eq!(intervals(&transcript.cds), [(149, 200)]);
+    }
+
+    #[test]
+    fn test_orphans_and_cycles() {
+        let gff = b"chr1\tsrc\tgene\t1\t1000\t.\t+\t.\tID=gene1
+chr1\tsrc\tmRNA\t1\t1000\t.\t+\t.\tID=tx1;Parent=gene1,gene9
+chr1\tsrc\texon\t1\t100\t.\t+\t.\tParent=tx9
+chr1\tsrc\tmRNA\t1\t1000\t.\t+\t.\tID=loopA;Parent=loopB
+chr1\tsrc\tmRNA\t1\t1000\t.\t+\t.\tID=loopB;Parent=loopA
+chr1\tsrc\texon\t1\t100\t.\t+\t.\tParent=loopA
+chr1\tsrc\tgene\t1\t1000\t.\t+\t.\tID=self;Parent=self
+";
+        let (genes, errors) = build(gff);
+        assert_eq!(genes.len(), 1);
+        assert_eq!(genes[0].transcripts.len(), 1);
+        assert_eq!(
+            errors,
+            [
+                HierarchyError::MissingParent {
+                    feature: "tx1".to_owned(),
+                    parent: "gene9".to_owned()
+                },
+                HierarchyError::MissingParent {
+                    feature: "exon at chr1:1-100".to_owned(),
+                    parent: "tx9".to_owned()
+                },
+                HierarchyError::Cycle {
+                    features: vec!["loopA".to_owned(), "loopB".to_owned()]
+                },
+                HierarchyError::Cycle {
+                    features: vec!["self".to_owned()]
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,1537 @@
+// Copyright 2016 Pierre Marijon.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! GFF3, GFF2 and GTF2 format reading and writing.
+//!
+//! GFF2 definition : http://gmod.org/wiki/GFF2#The_GFF2_File_Format
+//! GTF2 definition : http://mblab.wustl.edu/GTF2.html
+//! GFF3 definition : http://gmod.org/wiki/GFF3#GFF3_Format
+//!
+//! Records read in one format can be written in another. When GFF3 records are written as
+//! GTF2, the `gene_id` and `transcript_id` attributes are derived from their `ID` and
+//! `Parent` attributes (see `Writer::write`).
+//!
+//! Attributes are kept in their original order, including duplicate keys. In GFF3, they are
+//! percent-decoded and multiple values are split at commas. In GFF2 and GTF2, values may be
+//! quoted, and quoted values may contain semicolons and escaped quotes.
+//!
+//! Comment and pragma lines are skipped by the reader and collected as header lines, and
+//! `##sequence-region` pragmas are parsed.
+//!
+//! # Example
+//!
+//! ```
+//! use std::io;
+//! use bio::io::gff;
+//! let reader = gff::Reader::new(io::stdin(), gff::GffType::GFF3);
+//! ```
+//!
+//! ```
+//! use bio::io::gff;
+//! let gff = b"chr1\tsrc\tgene\t1\t100\t.\t+\t.\tID=gene1;Name=BRCA%3B1;Dbxref=x,y\n";
+//! let record = gff::Reader::new(&gff[..], gff::GffType::GFF3)
+//!     .records()
+//!     .next()
+//!     .unwrap()
+//!     .unwrap();
+//! assert_eq!(record.attributes().get("Name"), Some("BRCA;1"));
+//! assert_eq!(record.attributes().get_vec("Dbxref"), Some(vec!["x", "y"]));
+//! ```
+
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::convert::AsRef;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use csv;
+
+use crate::io::CoordinateError;
+use crate::utils::{Strand, StrandError};
+
+mod hierarchy;
+pub use self::hierarchy::{Gene, HierarchyBuilder, HierarchyError, Transcript};
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Csv(err: csv::Error) {
+            from()
+            description("error reading GFF input")
+            display("error reading GFF input: {}", err)
+            cause(err)
+        }
+        /// Start or end at the given 1-based line is not a non-negative integer.
+        InvalidCoordinate { line: u64, field: &'static str, value: String } {
+            description("invalid coordinate in GFF input")
+            display("line {}: expected non-negative integer {}, found {:?}", line, field, value)
+        }
+        /// Invalid strand column at the given 1-based line.
+        InvalidStrand { line: u64, err: StrandError } {
+            description("invalid strand in GFF input")
+            display("line {}: {}", line, err)
+            cause(err)
+        }
+        /// Malformed `##sequence-region` pragma at the given 1-based line.
+        InvalidSequenceRegion { line: u64, context: String } {
+            description("invalid sequence-region pragma in GFF input")
+            display("line {}: expected ##sequence-region with seqid, start and end: {:?}",
+                line, context)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Csv(err) => err.into(),
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// `GffType`
+///
+/// We have three format in the GFF family.
+/// The change is in the last field of GFF.
+/// For each type we have key value separator and field separator
+#[derive(Debug, Clone, Copy)]
+pub enum GffType {
+    /// Attribute format is: key1=value; key2=value1,value2
+    GFF3,
+    /// Attribute format is: key1 value; key2 value1; key2 value2
+    GFF2,
+    /// Same as GFF2 just possible keyword and possible value change
+    GTF2,
+    /// Any, first field of tuple separates key from value,
+    /// second field separates multiple key value pairs, and
+    /// third field separates multiple values for the same key
+    Any(u8, u8, u8),
+}
+
+impl GffType {
+    #[inline]
+    /// First field is key value separator.
+    /// Second field terminates a key value pair.
+    /// Third field
+    fn separator(self) -> (u8, u8, u8) {
+        match self {
+            GffType::GFF3 => (b'=', b';', b','),
+            GffType::GFF2 => (b' ', b';', 0u8),
+            GffType::GTF2 => (b' ', b';', 0u8),
+            GffType::Any(x, y, z) => (x, y, z),
+        }
+    }
+}
+
+/// A GFF reader. Comment and pragma lines starting with `#`, and `track` and `browser` lines of
+/// the UCSC genome browser are skipped, and collected as header lines, also between records.
+#[derive(Debug)]
+pub struct Reader<R: io::Read> {
+    inner: csv::Reader<R>,
+    gff_type: GffType,
+    header_lines: Vec<String>,
+    sequence_regions: Vec<SequenceRegion>,
+}
+
+impl Reader<fs::File> {
+    /// Read GFF from given file path in given format.
+    pub fn from_file<P: AsRef<Path>>(path: P, fileformat: GffType) -> io::Result<Self> {
+        fs::File::open(path).map(|f| Reader::new(f, fileformat))
+    }
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Create a new GFF reader given an instance of `io::Read`, in given format.
+    pub fn new(reader: R, fileformat: GffType) -> Self {
+        Reader {
+            inner: csv::ReaderBuilder::new()
+                .delimiter(b'\t')
+                .has_headers(false)
+                .flexible(true)
+                .quoting(false)
+                .from_reader(reader),
+            gff_type: fileformat,
+            header_lines: Vec::new(),
+            sequence_regions: Vec::new(),
+        }
+    }
+
+    /// Iterate over all records.
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records {
+            inner: self.inner.records(),
+            gff_type: self.gff_type,
+            header_lines: &mut self.header_lines,
+            sequence_regions: &mut self.sequence_regions,
+        }
+    }
+
+    /// The comment, pragma, track and browser lines that have been skipped so far, without
+    /// line terminator.
+    pub fn header_lines(&self) -> &[String] {
+        &self.header_lines
+    }
+
+    /// The `##sequence-region` pragmas that have been read so far.
+    pub fn sequence_regions(&self) -> &[SequenceRegion] {
+        &self.sequence_regions
+    }
+}
+
+/// The extent of a sequence as given by a `##sequence-region` pragma, with 1-based start and
+/// end, the end included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceRegion {
+    pub seqname: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl SequenceRegion {
+    /// Parse the given `##sequence-region` pragma, or return `None` if it is malformed.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("##sequence-region") {
+            return None;
+        }
+        let region = SequenceRegion {
+            seqname: fields.next()?.to_owned(),
+            start: fields.next()?.parse().ok()?,
+            end: fields.next()?.parse().ok()?,
+        };
+        match fields.next() {
+            None => Some(region),
+            Some(_) => None,
+        }
+    }
+}
+
+type GffRecordInner = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+);
+
+/// An iterator over the records of a GFF file.
+pub struct Records<'a, R: io::Read> {
+    inner: csv::StringRecordsIter<'a, R>,
+    gff_type: GffType,
+    header_lines: &'a mut Vec<String>,
+    sequence_regions: &'a mut Vec<SequenceRegion>,
+}
+
+impl<'a, R: io::Read> Iterator for Records<'a, R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        let res = loop {
+            match self.inner.next()? {
+                Ok(ref fields) if fields.get(0).is_some_and(super::is_header_line) => {
+                    let header_line = fields.iter().join("\t");
+                    if header_line.starts_with("##sequence-region") {
+                        let line = fields.position().map_or(0, |pos| pos.line());
+                        match SequenceRegion::parse(&header_line) {
+                            Some(region) => self.sequence_regions.push(region),
+                            None => {
+                                return Some(Err(Error::InvalidSequenceRegion {
+                                    line,
+                                    context: super::error_context(&header_line),
+                                }))
+                            }
+                        }
+                    }
+                    self.header_lines.push(header_line);
+                }
+                res => break res,
+            }
+        };
+
+        Some(res.map_err(Error::from).and_then(|fields| {
+            let (seqname, source, feature_type, start, end, score, strand, frame, raw_attributes) =
+                fields.deserialize::<GffRecordInner>(None)?;
+            let line = fields.position().map_or(0, |pos| pos.line());
+            let coordinate = |field, value: String| {
+                value
+                    .parse()
+                    .map_err(|_| Error::InvalidCoordinate { line, field, value })
+            };
+            let start = coordinate("start", start)?;
+            let end = coordinate("end", end)?;
+            strand
+                .parse::<Strand>()
+                .map_err(|err| Error::InvalidStrand { line, err })?;
+            let attributes = Attributes::parse(&raw_attributes, self.gff_type);
+
+            Ok(Record {
+                seqname,
+                source,
+                feature_type,
+                start,
+                end,
+                score,
+                strand,
+                frame,
+                attributes,
+            })
+        }))
+    }
+}
+
+/// A GFF writer.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    inner: csv::Writer<W>,
+    gff_type: GffType,
+    header_written: bool,
+    /// The `gene_id` and `transcript_id` of the written GFF3 records by their `ID`, when
+    /// writing GTF2.
+    gtf_ids: HashMap<String, (String, Option<String>)>,
+}
+
+impl Writer<fs::File> {
+    /// Write to a given file path in given format.
+    pub fn to_file<P: AsRef<Path>>(path: P, fileformat: GffType) -> io::Result<Self> {
+        fs::File::create(path).map(|f| Writer::new(f, fileformat))
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Write to a given writer. For GFF3, the `##gff-version 3` header is written before the
+    /// first record.
+    pub fn new(writer: W, fileformat: GffType) -> Self {
+        Writer {
+            inner: csv::WriterBuilder::new()
+                .delimiter(b'\t')
+                .flexible(true)
+                .quote_style(csv::QuoteStyle::Never)
+                .from_writer(writer),
+            gff_type: fileformat,
+            header_written: false,
+            gtf_ids: HashMap::new(),
+        }
+    }
+
+    /// Write the given header lines, e.g. the ones collected by
+    /// [`Reader::header_lines`](struct.Reader.html#method.header_lines). For GFF3, the
+    /// `##gff-version 3` header is written first, unless it is the first of the given lines.
+    pub fn write_header_lines<I, S>(&mut self, lines: I) -> csv::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut lines = lines.into_iter().peekable();
+        if !self.header_written {
+            let has_version = lines
+                .peek()
+                .is_some_and(|line| line.as_ref().starts_with("##gff-version"));
+            if let (GffType::GFF3, false) = (self.gff_type, has_version) {
+                self.inner.write_record(["##gff-version 3"])?;
+            }
+            self.header_written = true;
+        }
+        for line in lines {
+            self.inner.write_record([line.as_ref()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a given GFF record. Attributes are written in their order, in the syntax of the
+    /// format of the writer, so that records read in another format are converted. Missing
+    /// score and frame are written as `.`, as are unknown strands unless given as `?`.
+    ///
+    /// When writing GTF2, records without a `gene_id` that were not read as GFF2 or GTF2
+    /// (e.g. read from GFF3 or built with `Record::new`) get the `gene_id` and
+    /// `transcript_id` attributes in front, derived from the `ID` and `Parent` attributes
+    /// like in `HierarchyBuilder`: records without a parent are genes, their
+    /// children are transcripts, and the children of transcripts (e.g. exons) belong to
+    /// them. Parents have to be written before their children, and only the first parent
+    /// of a record is considered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::gff;
+    ///
+    /// let gff3 = b"chr1\tsrc\tmRNA\t1\t90\t.\t+\t.\tID=tx1;Parent=gene1
+    /// chr1\tsrc\texon\t1\t90\t.\t+\t.\tParent=tx1
+    /// ";
+    /// let mut writer = gff::Writer::new(vec![], gff::GffType::GTF2);
+    /// let mut gene = gff::Record::new();
+    /// gene.attributes_mut().insert("ID", "gene1");
+    /// writer.write(&gene).unwrap();
+    /// for record in gff::Reader::new(&gff3[..], gff::GffType::GFF3).records() {
+    ///     writer.write(&record.unwrap()).unwrap();
+    /// }
+    /// ```
+    pub fn write(&mut self, record: &Record) -> csv::Result<()> {
+        if !self.header_written {
+            if let GffType::GFF3 = self.gff_type {
+                self.inner.write_record(["##gff-version 3"])?;
+            }
+            self.header_written = true;
+        }
+        let or_dot = |field: &str| if field.is_empty() { "." } else { field }.to_owned();
+        // keep a questionmark for an unknown strand
+        let strand = match record.strand.as_str() {
+            "?" => "?",
+            _ => record.strand().symbol(),
+        };
+        let attributes = match self.gff_type {
+            GffType::GTF2
+                if !record.attributes.gtf && !record.attributes.contains_key("gene_id") =>
+            {
+                let mut attributes = Attributes::new();
+                if let Some((gene_id, transcript_id)) = self.gtf_ids(record) {
+                    attributes.push("gene_id", vec![gene_id]);
+                    if let Some(transcript_id) = transcript_id {
+                        attributes.push("transcript_id", vec![transcript_id]);
+                    }
+                }
+                attributes
+                    .entries
+                    .extend(record.attributes.entries.iter().cloned());
+                attributes.format(self.gff_type)
+            }
+            _ => record.attributes.format(self.gff_type),
+        };
+
+        self.inner.serialize((
+            &record.seqname,
+            &record.source,
+            &record.feature_type,
+            record.start,
+            record.end,
+            or_dot(&record.score),
+            strand,
+            or_dot(&record.frame),
+            attributes,
+        ))
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Derive the `gene_id` and `transcript_id` of the given GFF3 record from its `ID` and
+    /// the already written record its `Parent` refers to (see `write`).
+    fn gtf_ids(&mut self, record: &Record) -> Option<(String, Option<String>)> {
+        let id = record.attributes.get("ID");
+        let ids = match record.attributes.get("Parent") {
+            None => id.map(|id| (id.to_owned(), None)),
+            Some(parent) => match self.gtf_ids.get(parent) {
+                // a transcript
+                Some((gene_id, None)) => Some((gene_id.clone(), id.map(|id| id.to_owned()))),
+                Some(ids) => Some(ids.clone()),
+                None => None,
+            },
+        };
+        if let (Some(id), Some(ids)) = (id, &ids) {
+            self.gtf_ids.insert(id.to_owned(), ids.clone());
+        }
+
+        ids
+    }
+}
+
+/// A GFF record
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct Record {
+    seqname: String,
+    source: String,
+    feature_type: String,
+    start: u64,
+    end: u64,
+    score: String,
+    strand: String,
+    frame: String,
+    attributes: Attributes,
+}
+
+impl Record {
+    /// Create a new GFF record.
+    pub fn new() -> Self {
+        Record {
+            seqname: "".to_owned(),
+            source: "".to_owned(),
+            feature_type: "".to_owned(),
+            start: 0,
+            end: 0,
+            score: ".".to_owned(),
+            strand: ".".to_owned(),
+            frame: "".to_owned(),
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Sequence name of the feature.
+    pub fn seqname(&self) -> &str {
+        &self.seqname
+    }
+
+    /// Source of the feature.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Type of the feature.
+    pub fn feature_type(&self) -> &str {
+        &self.feature_type
+    }
+
+    /// Start position of feature (1-based).
+    pub fn start(&self) -> &u64 {
+        &self.start
+    }
+
+    /// End position of feature (1-based, included).
+    pub fn end(&self) -> &u64 {
+        &self.end
+    }
+
+    /// Start and end in the 0-based, half-open coordinates of BED. Following the GFF3
+    /// specification, a feature with start equal to end has 1 bp. Zero-length features such
+    /// as insertion sites are written the same way, and cannot be told apart.
+    ///
+    /// ```
+    /// use bio::io::gff;
+    ///
+    /// let mut record = gff::Record::new();
+    /// *record.start_mut() = 1;
+    /// *record.end_mut() = 1;
+    /// assert_eq!(record.to_bed_coords(), Ok((0, 1)));
+    /// ```
+    pub fn to_bed_coords(&self) -> Result<(u64, u64), CoordinateError> {
+        if self.start == 0 {
+            return Err(CoordinateError::ZeroStart);
+        }
+        if self.start > self.end {
+            return Err(CoordinateError::StartAfterEnd {
+                start: self.start,
+                end: self.end,
+            });
+        }
+
+        Ok((self.start - 1, self.end))
+    }
+
+    /// Score of feature
+    pub fn score(&self) -> Option<u64> {
+        match self.score.as_ref() {
+            "." => None,
+            _ => self.score.parse::<u64>().ok(),
+        }
+    }
+
+    /// Strand of the feature. Anything else than `+` or `-` is an unknown strand.
+    pub fn strand(&self) -> Strand {
+        self.strand.parse().unwrap_or(Strand::Unknown)
+    }
+
+    /// Frame of the feature.
+    pub fn frame(&self) -> &str {
+        &self.frame
+    }
+
+    /// Attribute of feature
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Get mutable reference on seqname of feature.
+    pub fn seqname_mut(&mut self) -> &mut String {
+        &mut self.seqname
+    }
+
+    /// Get mutable reference on source of feature.
+    pub fn source_mut(&mut self) -> &mut String {
+        &mut self.source
+    }
+
+    /// Get mutable reference on type of feature.
+    pub fn feature_type_mut(&mut self) -> &mut String {
+        &mut self.feature_type
+    }
+
+    /// Get mutable reference on start of feature.
+    pub fn start_mut(&mut self) -> &mut u64 {
+        &mut self.start
+    }
+
+    /// Get mutable reference on end of feature.
+    pub fn end_mut(&mut self) -> &mut u64 {
+        &mut self.end
+    }
+
+    /// Get mutable reference on score of feature.
+    pub fn score_mut(&mut self) -> &mut String {
+        &mut self.score
+    }
+
+    /// Set strand of feature.
+    pub fn set_strand(&mut self, strand: Strand) {
+        self.strand = strand.to_string();
+    }
+
+    /// Get mutable reference on strand of feature.
+    #[deprecated(since = "0.28.3", note = "use `set_strand` instead")]
+    pub fn strand_mut(&mut self) -> &mut String {
+        &mut self.strand
+    }
+
+    /// Get mutable reference on frame of feature.
+    pub fn frame_mut(&mut self) -> &mut String {
+        &mut self.frame
+    }
+
+    /// Get mutable reference on attributes of feature.
+    pub fn attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.attributes
+    }
+}
+
+/// A single attribute with its values, as it occurred in the attribute column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Attribute {
+    key: String,
+    values: Vec<String>,
+    /// Whether the value is quoted when written as GFF2 or GTF2. Only GTF values that were
+    /// read without quotes are written without quotes again.
+    quoted: bool,
+}
+
+/// The attributes of a GFF record, as an ordered multimap. Attributes keep the order in which
+/// they were read or inserted, and keys may occur more than once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Attributes {
+    entries: Vec<Attribute>,
+    /// Whether the attributes were read as GFF2 or GTF2, in which case no `gene_id` and
+    /// `transcript_id` are derived when writing them as GTF2.
+    #[serde(skip)]
+    gtf: bool,
+}
+
+impl Attributes {
+    /// Create a new, empty attribute map.
+    pub fn new() -> Self {
+        Attributes::default()
+    }
+
+    /// Number of attributes, counting repeated keys separately.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|entry| entry.key == key)
+    }
+
+    /// First value of the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.key == key)
+            .flat_map(|entry| entry.values.first())
+            .map(|value| value.as_str())
+            .next()
+    }
+
+    /// All values of the given key in their order, or `None` if the key does not occur.
+    pub fn get_vec(&self, key: &str) -> Option<Vec<&str>> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .filter(|entry| entry.key == key)
+                .flat_map(|entry| entry.values.iter().map(|value| value.as_str()))
+                .collect(),
+        )
+    }
+
+    /// Add a value to the given key. If the key already occurs, the value is appended to its
+    /// first occurrence (i.e. written as `key=a,b` in GFF3), otherwise the key is added at the
+    /// end.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        match self.entries.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => entry.values.push(value.to_owned()),
+            None => self.push(key, vec![value.to_owned()]),
+        }
+    }
+
+    /// Add the key with the given values at the end, even if the key already occurs.
+    pub fn push(&mut self, key: &str, values: Vec<String>) {
+        self.entries.push(Attribute {
+            key: key.to_owned(),
+            values,
+            quoted: true,
+        });
+    }
+
+    /// Remove all occurrences of the given key, returning their values.
+    pub fn remove(&mut self, key: &str) -> Option<Vec<String>> {
+        let (removed, kept) = self.entries.drain(..).partition(|entry| entry.key == key);
+        self.entries = kept;
+        let removed: Vec<Attribute> = removed;
+        if removed.is_empty() {
+            None
+        } else {
+            Some(removed.into_iter().flat_map(|entry| entry.values).collect())
+        }
+    }
+
+    /// Iterate over all key-value pairs in their order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value.as_str())))
+    }
+
+    /// Iterate over all attributes with their values in their order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.values.as_slice()))
+    }
+
+    /// Parse the attribute column in the given format.
+    fn parse(raw: &str, gff_type: GffType) -> Self {
+        match gff_type {
+            GffType::GFF3 => Attributes::parse_gff3(raw),
+            GffType::GFF2 | GffType::GTF2 => Attributes::parse_gtf(raw),
+            GffType::Any(..) => {
+                let (delim, term, vdelim) = gff_type.separator();
+                let trim_quotes = |s: &str| s.trim_matches('\'').trim_matches('"').to_owned();
+                let mut attributes = Attributes::new();
+                for attribute in raw.split(term as char) {
+                    let mut parts = attribute.trim_start().splitn(2, delim as char);
+                    if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                        let values = if vdelim == 0 {
+                            vec![trim_quotes(value)]
+                        } else {
+                            value.split(vdelim as char).map(trim_quotes).collect()
+                        };
+                        attributes.push(&trim_quotes(key), values);
+                    }
+                }
+                attributes
+            }
+        }
+    }
+
+    fn parse_gff3(raw: &str) -> Self {
+        let mut attributes = Attributes::new();
+        for attribute in raw.split(';') {
+            let attribute = attribute.trim_start();
+            if attribute.is_empty() {
+                continue;
+            }
+            let mut parts = attribute.splitn(2, '=');
+            let key = percent_decode(parts.next().unwrap());
+            let values = match parts.next() {
+                Some(values) => values.split(',').map(percent_decode).collect(),
+                None => Vec::new(),
+            };
+            attributes.push(&key, values);
+        }
+        attributes
+    }
+
+    /// Parse GTF style attributes, i.e. `key value;` pairs where the value may be quoted.
+    fn parse_gtf(raw: &str) -> Self {
+        let mut attributes = Attributes {
+            gtf: true,
+            ..Attributes::default()
+        };
+        let mut chars = raw.chars().peekable();
+        loop {
+            while chars.peek().is_some_and(|c| c.is_whitespace() || *c == ';') {
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                break;
+            }
+            let mut key = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == ';' {
+                    break;
+                }
+                key.push(c);
+                chars.next();
+            }
+            while chars.peek().is_some_and(|c| *c == ' ' || *c == '\t') {
+                chars.next();
+            }
+            let mut value = String::new();
+            let quoted = chars.peek() == Some(&'"');
+            if quoted {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+                // skip anything up to the terminating semicolon
+                while chars.peek().is_some_and(|c| *c != ';') {
+                    chars.next();
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == ';' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                value.truncate(value.trim_end().len());
+            }
+            attributes.entries.push(Attribute {
+                key,
+                values: vec![value],
+                quoted,
+            });
+        }
+        attributes
+    }
+
+    /// Format the attributes for the attribute column in the given format.
+    fn format(&self, gff_type: GffType) -> String {
+        match gff_type {
+            GffType::GFF3 => self
+                .entries
+                .iter()
+                .map(|entry| {
+                    if entry.values.is_empty() {
+                        percent_encode(&entry.key)
+                    } else {
+                        format!(
+                            "{}={}",
+                            percent_encode(&entry.key),
+                            entry
+                                .values
+                                .iter()
+                                .map(|value| percent_encode(value))
+                                .join(",")
+                        )
+                    }
+                })
+                .join(";"),
+            GffType::GFF2 | GffType::GTF2 => self
+                .entries
+                .iter()
+                .flat_map(|entry| {
+                    entry.values.iter().map(move |value| {
+                        if entry.quoted || needs_quotes(value) {
+                            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                            format!("{} \"{}\"", entry.key, escaped)
+                        } else {
+                            format!("{} {}", entry.key, value)
+                        }
+                    })
+                })
+                .join("; "),
+            GffType::Any(..) => {
+                let (delim, term, vdelim) = gff_type.separator();
+                let vdelim = if vdelim == 0 {
+                    // without a value delimiter, every value is written as its own pair
+                    None
+                } else {
+                    Some((vdelim as char).to_string())
+                };
+                self.entries
+                    .iter()
+                    .flat_map(|entry| {
+                        match vdelim {
+                            Some(ref vdelim) => vec![entry.values.join(vdelim)],
+                            None => entry.values.clone(),
+                        }
+                        .into_iter()
+                        .map(move |value| format!("{}{}{}", entry.key, delim as char, value))
+                    })
+                    .join(&(term as char).to_string())
+            }
+        }
+    }
+}
+
+/// Attributes are equal if they have the same keys and values in the same order, regardless of
+/// how the values were quoted.
+impl PartialEq for Attributes {
+    fn eq(&self, other: &Attributes) -> bool {
+        self.entries().eq(other.entries())
+    }
+}
+
+impl Eq for Attributes {}
+
+/// Whether a GTF value has to be quoted to be read back unchanged.
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c == ';' || c == '"' || c == '\\')
+}
+
+/// Decode `%XX` escapes. Invalid escapes are kept as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Encode the characters with a reserved meaning in GFF3 attributes (`;`, `=`, `&`, `,` and
+/// `%`) and control characters such as tabs and newlines as `%XX`.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ';' | '=' | '&' | ',' | '%' => encoded.push_str(&format!("%{:02X}", c as u8)),
+            c if c.is_ascii_control() => encoded.push_str(&format!("%{:02X}", c as u8)),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::bed;
+
+    const GFF3_HEADER: &[u8] = b"##gff-version 3\n";
+    const GFF_FILE: &'static [u8] = b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\t\
+Note=Removed,Obsolete;ID=test
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tNote=ATP-dependent protease subunit HslV;\
+ID=PRO_0000148105";
+    const GFF_FILE_WITH_COMMENT: &'static [u8] = b"#comment
+P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\t\
+Note=Removed,Obsolete;ID=test
+#comment
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tNote=ATP-dependent protease subunit HslV;\
+ID=PRO_0000148105";
+    //required because MultiMap iter on element randomly
+    const GFF_FILE_ONE_ATTRIB: &'static [u8] =
+        b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\tNote=Removed
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID=PRO_0000148105
+";
+
+    const GTF_FILE: &'static [u8] =
+        b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\tNote Removed;ID test
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tNote ATP-dependent;ID PRO_0000148105
+";
+
+    // Another variant of GTF file, modified from a published GENCODE GTF file.
+    const GTF_FILE_2: &'static [u8] = b"chr1\tHAVANA\tgene\t11869\t14409\t.\t+\t.\t\
+gene_id \"ENSG00000223972.5\"; gene_type \"transcribed_unprocessed_pseudogene\";
+chr1\tHAVANA\ttranscript\t11869\t14409\t.\t+\t.\tgene_id \"ENSG00000223972.5\";\
+transcript_id \"ENST00000456328.2\"; gene_type \"transcribed_unprocessed_pseudogene\"";
+
+    // GTF file with duplicate attribute keys, taken from a published GENCODE GTF file.
+    const GTF_FILE_DUP_ATTR_KEYS: &'static [u8] = b"chr1\tENSEMBL\ttranscript\t182393\t\
+184158\t.\t+\t.\tgene_id \"ENSG00000279928.1\"; transcript_id \"ENST00000624431.1\";\
+gene_type \"protein_coding\"; gene_status \"KNOWN\"; gene_name \"FO538757.2\";\
+transcript_type \"protein_coding\"; transcript_status \"KNOWN\";\
+transcript_name \"FO538757.2-201\"; level 3; protein_id \"ENSP00000485457.1\";\
+transcript_support_level \"1\"; tag \"basic\"; tag \"appris_principal_1\";";
+
+    //required because MultiMap iter on element randomly
+    const GTF_FILE_ONE_ATTRIB: &'static [u8] =
+        b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\tNote Removed
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID PRO_0000148105
+";
+
+    const GFF_FILE_WITH_PRAGMAS: &[u8] = b"##gff-version 3
+##sequence-region chr1 1 248956422
+##sequence-region   chrM\t1 16569
+chr1\tsrc\tgene\t11869\t14409\t.\t+\t.\tID=gene1
+# a comment between records
+###
+chrM\tsrc\tgene\t3307\t4262\t.\t+\t.\tID=gene2
+";
+
+    #[test]
+    fn test_reader_gff3() {
+        let seqname = ["P0A7B8", "P0A7B8"];
+        let source = ["UniProtKB", "UniProtKB"];
+        let feature_type = ["Initiator methionine", "Chain"];
+        let starts = [1, 2];
+        let ends = [1, 176];
+        let scores = [None, Some(50)];
+        let strand = [Strand::Unknown, Strand::Forward];
+        let frame = [".", "."];
+        let mut attributes = [Attributes::new(), Attributes::new()];
+        attributes[0].insert("Note", "Removed");
+        attributes[0].insert("Note", "Obsolete");
+        attributes[0].insert("ID", "test");
+        attributes[1].insert("Note", "ATP-dependent protease subunit HslV");
+        attributes[1].insert("ID", "PRO_0000148105");
+
+        let mut reader = Reader::new(GFF_FILE, GffType::GFF3);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.unwrap();
+            assert_eq!(record.seqname(), seqname[i]);
+            assert_eq!(record.source(), source[i]);
+            assert_eq!(record.feature_type(), feature_type[i]);
+            assert_eq!(*record.start(), starts[i]);
+            assert_eq!(*record.end(), ends[i]);
+            assert_eq!(record.score(), scores[i]);
+            assert_eq!(record.strand(), strand[i]);
+            assert_eq!(record.frame(), frame[i]);
+            assert_eq!(record.attributes(), &attributes[i]);
+        }
+
+        let mut reader = Reader::new(GFF_FILE_WITH_COMMENT, GffType::GFF3);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.unwrap();
+            assert_eq!(record.seqname(), seqname[i]);
+            assert_eq!(record.source(), source[i]);
+            assert_eq!(record.feature_type(), feature_type[i]);
+            assert_eq!(*record.start(), starts[i]);
+            assert_eq!(*record.end(), ends[i]);
+            assert_eq!(record.score(), scores[i]);
+            assert_eq!(record.strand(), strand[i]);
+            assert_eq!(record.frame(), frame[i]);
+            assert_eq!(record.attributes(), &attributes[i]);
+        }
+    }
+
+    #[test]
+    fn test_reader_gtf2() {
+        let seqname = ["P0A7B8", "P0A7B8"];
+        let source = ["UniProtKB", "UniProtKB"];
+        let feature_type = ["Initiator methionine", "Chain"];
+        let starts = [1, 2];
+        let ends = [1, 176];
+        let scores = [None, Some(50)];
+        let strand = [Strand::Unknown, Strand::Forward];
+        let frame = [".", "."];
+        let mut attributes = [Attributes::new(), Attributes::new()];
+        attributes[0].insert("Note", "Removed");
+        attributes[0].insert("ID", "test");
+        attributes[1].insert("Note", "ATP-dependent");
+        attributes[1].insert("ID", "PRO_0000148105");
+
+        let mut reader = Reader::new(GTF_FILE, GffType::GTF2);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.unwrap();
+            assert_eq!(record.seqname(), seqname[i]);
+            assert_eq!(record.source(), source[i]);
+            assert_eq!(record.feature_type(), feature_type[i]);
+            assert_eq!(*record.start(), starts[i]);
+            assert_eq!(*record.end(), ends[i]);
+            assert_eq!(record.score(), scores[i]);
+            assert_eq!(record.strand(), strand[i]);
+            assert_eq!(record.frame(), frame[i]);
+            assert_eq!(record.attributes(), &attributes[i]);
+        }
+    }
+
+    #[test]
+    fn test_reader_gtf2_2() {
+        let seqname = ["chr1", "chr1"];
+        let source = ["HAVANA", "HAVANA"];
+        let feature_type = ["gene", "transcript"];
+        let starts = [11869, 11869];
+        let ends = [14409, 14409];
+        let scores = [None, None];
+        let strand = [Strand::Forward, Strand::Forward];
+        let frame = [".", "."];
+        let mut attributes = [Attributes::new(), Attributes::new()];
+        attributes[0].insert("gene_id", "ENSG00000223972.5");
+        attributes[0].insert("gene_type", "transcribed_unprocessed_pseudogene");
+        attributes[1].insert("gene_id", "ENSG00000223972.5");
+        attributes[1].insert("transcript_id", "ENST00000456328.2");
+        attributes[1].insert("gene_type", "transcribed_unprocessed_pseudogene");
+
+        let mut reader = Reader::new(GTF_FILE_2, GffType::GTF2);
+        for (i, r) in reader.records().enumerate() {
+            let record = r.unwrap();
+            assert_eq!(record.seqname(), seqname[i]);
+            assert_eq!(record.source(), source[i]);
+            assert_eq!(record.feature_type(), feature_type[i]);
+            assert_eq!(*record.start(), starts[i]);
+            assert_eq!(*record.end(), ends[i]);
+            assert_eq!(record.score(), scores[i]);
+            assert_eq!(record.strand(), strand[i]);
+            assert_eq!(record.frame(), frame[i]);
+            assert_eq!(record.attributes(), &attributes[i]);
+        }
+    }
+
+    #[test]
+    fn test_reader_gtf2_dup_attr_keys() {
+        let mut reader = Reader::new(GTF_FILE_DUP_ATTR_KEYS, GffType::GTF2);
+        let mut records = reader.records().collect::<Vec<_>>();
+        assert_eq!(records.len(), 1);
+        let record = records.pop().unwrap().expect("expected one record");
+        assert_eq!(record.attributes.get("tag"), Some("basic"));
+        assert_eq!(
+            record.attributes.get_vec("tag"),
+            Some(vec!["basic", "appris_principal_1"])
+        );
+        // both occurrences are kept in their original position
+        let keys: Vec<_> = record.attributes.entries().map(|(key, _)| key).collect();
+        assert_eq!(keys.len(), 13);
+        assert_eq!(&keys[10..], ["transcript_support_level", "tag", "tag"]);
+        assert_eq!(record.attributes.get("level"), Some("3"));
+    }
+
+    #[test]
+    fn test_attributes_gff3() {
+        let gff = b"chr1\tsrc\tgene\t1\t100\t.\t+\t.\t\
+ID=gene1;Name=a%3Bb%2Cc%3Dd;Dbxref=x,y;Note=\"quoted\" 50%25 %tab%09
+chr1\tsrc\tmRNA\t1\t100\t.\t+\t.\tParent=gene1;ID=tx1;Parent=gene2
+";
+        let records: Vec<Record> = Reader::new(&gff[..], GffType::GFF3)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        let attributes = records[0].attributes();
+        assert_eq!(attributes.get("Name"), Some("a;b,c=d"));
+        assert_eq!(attributes.get_vec("Dbxref"), Some(vec!["x", "y"]));
+        assert_eq!(attributes.get("Note"), Some("\"quoted\" 50% %tab\t"));
+        assert_eq!(attributes.get("Missing"), None);
+        assert_eq!(attributes.get_vec("Missing"), None);
+        let pairs: Vec<_> = records[1].attributes().iter().collect();
+        assert_eq!(
+            pairs,
+            [("Parent", "gene1"), ("ID", "tx1"), ("Parent", "gene2")]
+        );
+
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        let written = writer.inner.into_inner().unwrap();
+        // the invalid escape is encoded on writing, the rest is unchanged
+        assert_eq!(
+            String::from_utf8(written.clone()).unwrap(),
+            String::from_utf8([GFF3_HEADER, &gff[..]].concat())
+                .unwrap()
+                .replace("%tab", "%25tab")
+        );
+        let reread: Vec<Record> = Reader::new(&written[..], GffType::GFF3)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        for (record, reread) in records.iter().zip(&reread) {
+            assert_eq!(record.attributes(), reread.attributes());
+        }
+    }
+
+    #[test]
+    fn test_attributes_gtf2() {
+        let gtf = b"chr1\tsrc\texon\t1\t100\t.\t+\t.\t\
+gene_id \"g1\"; note \"a;b \\\"c\\\"\"; exon_number 1; tag \"x\"; tag \"y\"
+";
+        let records: Vec<Record> = Reader::new(&gtf[..], GffType::GTF2)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        let attributes = records[0].attributes();
+        assert_eq!(attributes.len(), 5);
+        assert_eq!(attributes.get("gene_id"), Some("g1"));
+        assert_eq!(attributes.get("note"), Some("a;b \"c\""));
+        assert_eq!(attributes.get("exon_number"), Some("1"));
+        assert_eq!(attributes.get_vec("tag"), Some(vec!["x", "y"]));
+
+        let mut writer = Writer::new(vec![], GffType::GTF2);
+        writer.write(&records[0]).unwrap();
+        assert_eq!(writer.inner.into_inner().unwrap(), &gtf[..]);
+    }
+
+    #[test]
+    fn test_attributes_edit() {
+        let mut attributes = Attributes::new();
+        attributes.insert("ID", "a");
+        attributes.insert("Dbxref", "x");
+        attributes.insert("Dbxref", "y");
+        attributes.push("ID", vec!["b".to_owned()]);
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes.get_vec("ID"), Some(vec!["a", "b"]));
+        assert_eq!(attributes.format(GffType::GFF3), "ID=a;Dbxref=x,y;ID=b");
+        assert_eq!(
+            attributes.format(GffType::GTF2),
+            "ID \"a\"; Dbxref \"x\"; Dbxref \"y\"; ID \"b\""
+        );
+        assert_eq!(
+            attributes.remove("ID"),
+            Some(vec!["a".to_owned(), "b".to_owned()])
+        );
+        assert_eq!(attributes.remove("ID"), None);
+        assert!(!attributes.contains_key("ID"));
+        assert_eq!(attributes.format(GffType::GFF3), "Dbxref=x,y");
+    }
+
+    #[test]
+    fn test_writer_gff3() {
+        let mut reader = Reader::new(GFF_FILE_ONE_ATTRIB, GffType::GFF3);
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        for r in reader.records() {
+            writer
+                .write(&r.ok().expect("Error reading record"))
+                .ok()
+                .expect("Error writing record");
+        }
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            [GFF3_HEADER, GFF_FILE_ONE_ATTRIB].concat()
+        )
+    }
+
+    #[test]
+    fn test_writer_gtf2() {
+        let mut reader = Reader::new(GTF_FILE_ONE_ATTRIB, GffType::GTF2);
+        let mut writer = Writer::new(vec![], GffType::GTF2);
+        for r in reader.records() {
+            writer
+                .write(&r.ok().expect("Error reading record"))
+                .ok()
+                .expect("Error writing record");
+        }
+        assert_eq!(writer.inner.into_inner().unwrap(), GTF_FILE_ONE_ATTRIB)
+    }
+
+    #[test]
+    fn test_convert_gtf2_to_gff3() {
+        let mut reader = Reader::new(GTF_FILE_ONE_ATTRIB, GffType::GTF2);
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        for r in reader.records() {
+            writer
+                .write(&r.ok().expect("Error reading record"))
+                .ok()
+                .expect("Error writing record");
+        }
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            [GFF3_HEADER, GFF_FILE_ONE_ATTRIB].concat()
+        )
+    }
+
+    #[test]
+    fn test_reader_pragmas() {
+        let mut reader = Reader::new(GFF_FILE_WITH_PRAGMAS, GffType::GFF3);
+        let records: Vec<_> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].seqname(), "chrM");
+        assert_eq!(records[1].attributes().get("ID"), Some("gene2"));
+        assert_eq!(
+            reader.sequence_regions(),
+            [
+                SequenceRegion {
+                    seqname: "chr1".to_owned(),
+                    start: 1,
+                    end: 248956422,
+                },
+                SequenceRegion {
+                    seqname: "chrM".to_owned(),
+                    start: 1,
+                    end: 16569,
+                },
+            ]
+        );
+        assert_eq!(reader.header_lines().len(), 5);
+        assert_eq!(
+            reader.header_lines()[2],
+            "##sequence-region   chrM\t1 16569"
+        );
+        assert_eq!(reader.header_lines()[4], "###");
+
+        // headers are only known up to the records read so far
+        let mut reader = Reader::new(GFF_FILE_WITH_PRAGMAS, GffType::GFF3);
+        reader.records().next().unwrap().unwrap();
+        assert_eq!(reader.header_lines().len(), 3);
+    }
+
+    #[test]
+    fn test_reader_invalid_coordinates() {
+        for &(start, end, field, value) in &[
+            ("-1", "10", "start", "-1"),
+            ("one", "10", "start", "one"),
+            ("1", "10.5", "end", "10.5"),
+            ("1", "", "end", ""),
+        ] {
+            let gff = format!(
+                "##gff-version 3\nchr1\tsrc\tgene\t{}\t{}\t.\t+\t.\tID=gene1\n",
+                start, end
+            );
+            match Reader::new(gff.as_bytes(), GffType::GFF3).records().next() {
+                Some(Err(err @ Error::InvalidCoordinate { .. })) => assert_eq!(
+                    err.to_string(),
+                    format!(
+                        "line 2: expected non-negative integer {}, found {:?}",
+                        field, value
+                    )
+                ),
+                r => panic!("expected invalid coordinate, got {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bed_coords() {
+        let record = |start, end| {
+            let mut record = Record::new();
+            *record.start_mut() = start;
+            *record.end_mut() = end;
+            record
+        };
+        assert_eq!(record(1, 1).to_bed_coords(), Ok((0, 1)));
+        assert_eq!(record(1, 100).to_bed_coords(), Ok((0, 100)));
+        assert_eq!(record(100, 100).to_bed_coords(), Ok((99, 100)));
+        assert_eq!(
+            record(0, 10).to_bed_coords(),
+            Err(CoordinateError::ZeroStart)
+        );
+        assert_eq!(
+            record(10, 9).to_bed_coords(),
+            Err(CoordinateError::StartAfterEnd { start: 10, end: 9 })
+        );
+
+        // round trips over features of 1 bp, at the first base, and longer ones
+        for &(start, end) in &[(1, 1), (1, 2), (2, 2), (1, 1000), (999, 1000)] {
+            let (bed_start, bed_end) = record(start, end).to_bed_coords().unwrap();
+            let mut bed_record = bed::Record::new();
+            bed_record.set_start(bed_start);
+            bed_record.set_end(bed_end);
+            assert_eq!(bed_record.to_gff_coords(), Ok((start, end)));
+        }
+        for &(start, end) in &[(0, 1), (0, 2), (1, 2), (0, 1000), (999, 1000)] {
+            let mut bed_record = bed::Record::new();
+            bed_record.set_start(start);
+            bed_record.set_end(end);
+            let (gff_start, gff_end) = bed_record.to_gff_coords().unwrap();
+            assert_eq!(record(gff_start, gff_end).to_bed_coords(), Ok((start, end)));
+        }
+        // an empty BED feature becomes 1 bp long
+        let mut bed_record = bed::Record::new();
+        bed_record.set_start(10);
+        bed_record.set_end(10);
+        let (gff_start, gff_end) = bed_record.to_gff_coords().unwrap();
+        assert_eq!(record(gff_start, gff_end).to_bed_coords(), Ok((9, 10)));
+    }
+
+    #[test]
+    fn test_reader_invalid_sequence_region() {
+        let gff = b"##gff-version 3\n##sequence-region chr1 1\n";
+        match Reader::new(&gff[..], GffType::GFF3).records().next() {
+            Some(Err(Error::InvalidSequenceRegion { line, .. })) => assert_eq!(line, 2),
+            r => panic!("expected invalid sequence-region, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_writer_header_lines() {
+        let mut reader = Reader::new(GFF_FILE_WITH_PRAGMAS, GffType::GFF3);
+        let records: Vec<_> = reader.records().map(|r| r.unwrap()).collect();
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        writer.write_header_lines(reader.header_lines()).unwrap();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        // comments between records are written at the start
+        assert_eq!(
+            String::from_utf8(writer.inner.into_inner().unwrap()).unwrap(),
+            "##gff-version 3
+##sequence-region chr1 1 248956422
+##sequence-region   chrM\t1 16569
+# a comment between records
+###
+chr1\tsrc\tgene\t11869\t14409\t.\t+\t.\tID=gene1
+chrM\tsrc\tgene\t3307\t4262\t.\t+\t.\tID=gene2
+"
+        );
+
+        // the version header is added for GFF3 only
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        writer.write_header_lines(["#comment"]).unwrap();
+        writer.write(&records[0]).unwrap();
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            &b"##gff-version 3\n#comment\nchr1\tsrc\tgene\t11869\t14409\t.\t+\t.\tID=gene1\n"[..]
+        );
+        let mut writer = Writer::new(vec![], GffType::GTF2);
+        writer.write_header_lines(["#comment"]).unwrap();
+        assert_eq!(writer.inner.into_inner().unwrap(), b"#comment\n");
+    }
+
+    #[test]
+    fn test_writer_gff2() {
+        let gff2 = b"chr1\tsrc\tgene\t1\t100\t.\t+\t.\tgene_id \"g1\"; level 2\n";
+        let mut reader = Reader::new(&gff2[..], GffType::GFF2);
+        let mut writer = Writer::new(vec![], GffType::GFF2);
+        for r in reader.records() {
+            writer.write(&r.unwrap()).unwrap();
+        }
+        assert_eq!(writer.inner.into_inner().unwrap(), &gff2[..]);
+    }
+
+    #[test]
+    fn test_writer_missing_fields() {
+        let mut record = Record::new();
+        *record.seqname_mut() = "chr1".to_owned();
+        *record.source_mut() = "src".to_owned();
+        *record.feature_type_mut() = "gene".to_owned();
+        *record.start_mut() = 11;
+        *record.end_mut() = 20;
+        record.attributes_mut().insert("ID", "gene1");
+        record.score_mut().clear();
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        writer.write(&record).unwrap();
+        writer.write(&record).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            &b"##gff-version 3
+chr1\tsrc\tgene\t11\t20\t.\t.\t.\tID=gene1
+chr1\tsrc\tgene\t11\t20\t.\t.\t.\tID=gene1
+"[..]
+        );
+    }
+
+    #[test]
+    fn test_convert_gff3_to_gtf2() {
+        let gff3 = b"##gff-version 3
+chr1\tsrc\tgene\t1000\t2000\t.\t+\t.\tID=gene1;Name=A%3BB
+chr1\tsrc\tmRNA\t1000\t2000\t.\t+\t.\tID=tx1;Parent=gene1;Dbxref=x,y
+chr1\tsrc\texon\t1000\t1200\t.\t+\t.\tParent=tx1
+chr1\tsrc\tCDS\t1100\t1200\t.\t+\t0\tParent=tx1
+";
+        let mut reader = Reader::new(&gff3[..], GffType::GFF3);
+        let mut writer = Writer::new(vec![], GffType::GTF2);
+        for r in reader.records() {
+            writer.write(&r.unwrap()).unwrap();
+        }
+        let gtf = writer.inner.into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(gtf.clone()).unwrap(),
+            "chr1\tsrc\tgene\t1000\t2000\t.\t+\t.\tgene_id \"gene1\"; ID \"gene1\"; Name \"A;B\"
+chr1\tsrc\tmRNA\t1000\t2000\t.\t+\t.\tgene_id \"gene1\"; transcript_id \"tx1\"; \
+             ID \"tx1\"; Parent \"gene1\"; Dbxref \"x\"; Dbxref \"y\"
+chr1\tsrc\texon\t1000\t1200\t.\t+\t.\tgene_id \"gene1\"; transcript_id \"tx1\"; Parent \"tx1\"
+chr1\tsrc\tCDS\t1100\t1200\t.\t+\t0\tgene_id \"gene1\"; transcript_id \"tx1\"; Parent \"tx1\"
+"
+        );
+
+        // the attributes survive the conversion
+        let gff3_records: Vec<_> = Reader::new(&gff3[..], GffType::GFF3)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        let gtf_records: Vec<_> = Reader::new(&gtf[..], GffType::GTF2)
+            .records()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(gtf_records.len(), 4);
+        for (i, gtf) in gtf_records.iter().enumerate() {
+            assert_eq!(gtf.attributes().get("gene_id"), Some("gene1"));
+            let transcript_id = if i == 0 { None } else { Some("tx1") };
+            assert_eq!(gtf.attributes().get("transcript_id"), transcript_id);
+        }
+        for (gff3, gtf) in gff3_records.iter().zip(&gtf_records) {
+            assert_eq!(gff3.start(), gtf.start());
+            assert_eq!(gff3.end(), gtf.end());
+            assert_eq!(gff3.frame(), gtf.frame());
+            for key in &["ID", "Name", "Parent", "Dbxref"] {
+                assert_eq!(
+                    gff3.attributes().get_vec(key),
+                    gtf.attributes().get_vec(key)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_strand() {
+        let gff = b"chr1\ttest\tgene\t1\t10\t.\t+\t.\tID=a
+chr1\ttest\tgene\t1\t10\t.\t-\t.\tID=b
+chr1\ttest\tgene\t1\t10\t.\t.\t.\tID=c
+";
+        let mut reader = Reader::new(&gff[..], GffType::GFF3);
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        let mut strands = Vec::new();
+        for r in reader.records() {
+            let record = r.unwrap();
+            strands.push(record.strand());
+            writer.write(&record).unwrap();
+        }
+        assert_eq!(strands, [Strand::Forward, Strand::Reverse, Strand::Unknown]);
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            [GFF3_HEADER, &gff[..]].concat()
+        );
+
+        let mut record = Record::new();
+        assert_eq!(record.strand(), Strand::Unknown);
+        record.set_strand(Strand::Forward.opposite());
+        assert!(record.strand().is_reverse());
+
+        // a questionmark is an unknown strand, too
+        let gff = b"chr1\ttest\tgene\t1\t10\t.\t?\t.\tID=a\n";
+        let record = Reader::new(&gff[..], GffType::GFF3)
+            .records()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.strand(), Strand::Unknown);
+        let mut writer = Writer::new(vec![], GffType::GFF3);
+        writer.write(&record).unwrap();
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            [GFF3_HEADER, &gff[..]].concat()
+        );
+    }
+
+    #[test]
+    fn test_invalid_strand() {
+        let gff = b"#comment
+chr1\ttest\tgene\t1\t10\t.\t+\t.\tID=a
+chr1\ttest\tgene\t1\t10\t.\tforward\t.\tID=b
+";
+        let mut reader = Reader::new(&gff[..], GffType::GFF3);
+        let mut records = reader.records();
+        assert!(records.next().unwrap().is_ok());
+        match records.next().unwrap() {
+            Err(Error::InvalidStrand { line, err }) => {
+                assert_eq!(line, 3);
+                assert_eq!(err, StrandError::InvalidSymbol("forward".to_owned()));
+            }
+            res => panic!("expected invalid strand, got {:?}", res),
+        }
+    }
+}
@@ -1,6 +1,165 @@
 //! Readers and writers for common bioinformatics file formats.
 
+use std::io;
+use std::io::prelude::*;
+
 pub mod bed;
+pub mod bedgraph;
 pub mod fasta;
 pub mod fastq;
 pub mod gff;
+pub mod sample;
+pub mod twobit;
+
+quick_error! {
+    /// Coordinates that cannot be converted between the 0-based, half-open coordinates of BED
+    /// and the 1-based, inclusive coordinates of GFF.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum CoordinateError {
+        StartAfterEnd { start: u64, end: u64 } {
+            description("start after end")
+            display("start {} is after end {}", start, end)
+        }
+        /// GFF positions start at 1.
+        ZeroStart {
+            description("GFF start 0")
+            display("start 0 is not a valid 1-based position")
+        }
+        /// An empty BED feature at position 0 lies before the first base, which GFF cannot
+        /// express.
+        EmptyAtOrigin {
+            description("empty feature at position 0")
+            display("an empty feature before the first base cannot be converted to GFF")
+        }
+    }
+}
+
+/// The magic bytes starting gzip (and bgzf) compressed files.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Fail with a descriptive error if the given reader starts with the gzip magic bytes,
+/// since compressed input is not supported. The bytes are only peeked at in the buffer of
+/// the reader, such that nothing is consumed and non-seekable input like pipes or
+/// `/dev/stdin` is supported.
+fn check_uncompressed<R: BufRead>(reader: &mut R) -> io::Result<()> {
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Input is gzip compressed, which is not supported. Decompress it first.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Maximum number of characters of an offending line that are kept in parse errors.
+const MAX_CONTEXT_LEN: usize = 60;
+
+/// Read a line into the given buffer, counting it in `line_no` unless the end of the input
+/// has been reached.
+fn read_line<R: BufRead>(
+    reader: &mut R,
+    line_no: &mut usize,
+    buf: &mut String,
+) -> io::Result<usize> {
+    let n = reader.read_line(buf)?;
+    if n > 0 {
+        *line_no += 1;
+    }
+
+    Ok(n)
+}
+
+/// Remove a trailing line terminator, i.e. `\n` or `\r\n`, from the given line.
+fn strip_line_terminator(line: &mut String) {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+}
+
+/// Copy the given line without line terminator for use in an error message, truncating it
+/// to `MAX_CONTEXT_LEN` characters.
+fn error_context(line: &str) -> String {
+    let line = line.trim_end_matches(&['\n', '\r'][..]);
+    match line.char_indices().nth(MAX_CONTEXT_LEN) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_owned(),
+    }
+}
+
+/// Whether the given line is a comment, or a track or browser line of the UCSC genome browser,
+/// rather than a record.
+fn is_header_line(line: &str) -> bool {
+    line.starts_with('#')
+        || matches!(
+            line.split_whitespace().next(),
+            Some("track") | Some("browser")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_uncompressed() {
+        for &data in &[&b""[..], b">", b">id\nACGT\n", b"\x1f", b"\x8b\x1f"] {
+            // a slice cannot be rewound, such that nothing may be consumed
+            let mut reader = io::BufReader::new(data);
+            assert!(check_uncompressed(&mut reader).is_ok());
+            let mut read = Vec::new();
+            reader.read_to_end(&mut read).unwrap();
+            assert_eq!(read, data);
+        }
+        // gzip header of an empty member
+        let mut reader = io::BufReader::new(&b"\x1f\x8b\x08\x00\x00\x00\x00\x00\x00\x03"[..]);
+        assert_eq!(
+            check_uncompressed(&mut reader).err().map(|err| err.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_strip_line_terminator() {
+        for &(line, stripped) in &[
+            ("ACGT\n", "ACGT"),
+            ("ACGT\r\n", "ACGT"),
+            ("ACGT", "ACGT"),
+            ("\r\n", ""),
+            ("AC\rGT\r", "AC\rGT\r"),
+        ] {
+            let mut line = line.to_owned();
+            strip_line_terminator(&mut line);
+            assert_eq!(line, stripped);
+        }
+    }
+
+    #[test]
+    fn test_error_context() {
+        assert_eq!(error_context("ACGT\r\n"), "ACGT");
+        assert_eq!(error_context(""), "");
+        let line = "ä".repeat(MAX_CONTEXT_LEN);
+        assert_eq!(error_context(&line), line);
+        let long = format!("{}A\n", line);
+        assert_eq!(error_context(&long), format!("{}...", line));
+    }
+
+    #[test]
+    fn test_is_header_line() {
+        for &line in &[
+            "#comment",
+            "##gff-version 3",
+            "track name=reads description=\"aligned reads\"",
+            "track",
+            "browser position chr7:127471196-127495720",
+        ] {
+            assert!(is_header_line(line));
+        }
+        for &line in &["chr1\t0\t10", "tracks\t0\t10", "", " #comment"] {
+            assert!(!is_header_line(line));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2014-2018 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Random subsampling of records in a single pass, e.g. for taking a subset of reads for
+//! quality control. Sampling is reproducible for a given seed, and the sampled records are
+//! returned in their original order. Errors of the underlying iterator are returned
+//! immediately.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::fastq;
+//!
+//! let fastq = b"@r1\nACGT\n+\nIIII\n@r2\nGG\n+\nII\n@r3\nT\n+\nI\n";
+//! let ids = |reads: Vec<fastq::Record>| -> Vec<String> {
+//!     reads.iter().map(|read| read.id().to_owned()).collect()
+//! };
+//! let reads = fastq::Reader::new(&fastq[..]).sample(2, 42).unwrap();
+//! assert_eq!(reads.len(), 2);
+//! assert_eq!(ids(reads), ids(fastq::Reader::new(&fastq[..]).sample(2, 42).unwrap()));
+//! ```
+
+/// The SplitMix64 random number generator (Steele et al. 2014). It is small and fast, and
+/// since it is part of this crate, samples stay the same for a given seed across versions of
+/// any dependency.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform integer in [0, n), for n > 0, by Lemire's multiply-and-reject method.
+    fn below(&mut self, n: u64) -> u64 {
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let product = u128::from(self.next_u64()) * u128::from(n);
+            if product as u64 >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+
+    /// A uniform float in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Draw a uniform sample of `n` records with reservoir sampling (Vitter's algorithm R),
+/// keeping at most `n` records in memory. If there are at most `n` records, all of them are
+/// returned.
+pub fn reservoir<T, E, I>(records: I, n: usize, seed: u64) -> Result<Vec<T>, E>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    let mut rng = Rng::new(seed);
+    let mut sample = Vec::with_capacity(n);
+    for (i, record) in records.into_iter().enumerate() {
+        let record = record?;
+        if i < n {
+            sample.push((i, record));
+        } else {
+            let j = rng.below(i as u64 + 1) as usize;
+            if j < n {
+                sample[j] = (i, record);
+            }
+        }
+    }
+    sample.sort_unstable_by_key(|&(i, _)| i);
+
+    Ok(sample.into_iter().map(|(_, record)| record).collect())
+}
+
+/// Keep each record independently with probability `p`, such that on average a fraction `p`
+/// of the records is returned.
+pub fn fraction<T, E, I>(records: I, p: f64, seed: u64) -> Result<Vec<T>, E>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    assert!(
+        (0.0..=1.0).contains(&p),
+        "sampling fraction has to be between 0 and 1"
+    );
+    let mut rng = Rng::new(seed);
+    let mut sample = Vec::new();
+    for record in records {
+        let record = record?;
+        if rng.unit() < p {
+            sample.push(record);
+        }
+    }
+
+    Ok(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(len: usize) -> Vec<Result<usize, ()>> {
+        (0..len).map(Ok).collect()
+    }
+
+    #[test]
+    fn test_reservoir() {
+        let sample = reservoir(records(1000), 10, 7).unwrap();
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample, reservoir(records(1000), 10, 7).unwrap());
+        assert_ne!(sample, reservoir(records(1000), 10, 8).unwrap());
+
+        // n larger than the input
+        assert_eq!(reservoir(records(5), 10, 7).unwrap(), [0, 1, 2, 3, 4]);
+        assert!(reservoir(records(5), 0, 7).unwrap().is_empty());
+        assert!(reservoir(records(0), 3, 7).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reservoir_uniform() {
+        // each record should be sampled with probability n / len
+        let mut counts = [0; 20];
+        for seed in 0..2000 {
+            for i in reservoir(records(20), 5, seed).unwrap() {
+                counts[i] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&count| count > 400 && count < 600));
+    }
+
+    #[test]
+    fn test_fraction() {
+        let sample = fraction(records(10000), 0.1, 3).unwrap();
+        assert!(sample.len() > 900 && sample.len() < 1100);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample, fraction(records(10000), 0.1, 3).unwrap());
+        assert!(fraction(records(100), 0.0, 3).unwrap().is_empty());
+        assert_eq!(fraction(records(100), 1.0, 3).unwrap().len(), 100);
+    }
+
+    #[test]
+    fn test_rng() {
+        // reference outputs of SplitMix64
+        let mut rng = Rng::new(1_234_567);
+        assert_eq!(rng.next_u64(), 6_457_827_717_110_365_317);
+        assert_eq!(rng.next_u64(), 3_203_168_211_198_807_973);
+        for n in 1..100 {
+            assert!(rng.below(n) < n);
+            let x = rng.unit();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let records = vec![Ok(1), Err("corrupt"), Ok(3)];
+        assert_eq!(reservoir(records.clone(), 1, 0), Err("corrupt"));
+        assert_eq!(fraction(records, 1.0, 0), Err("corrupt"));
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright 2014-2018 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading of the UCSC 2bit format for reference genomes
+//! (see <https://genome.ucsc.edu/FAQ/FAQformat.html#format7>). Sequences are stored with two
+//! bits per base, together with the blocks of `N` and the blocks of soft-masked (lowercase)
+//! bases, which are restored when fetching.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use bio::io::twobit;
+//! let mut reader = twobit::Reader::from_file("hg38.2bit").unwrap();
+//! for name in reader.sequence_names() {
+//!     println!("{}", name);
+//! }
+//! let seq = reader.fetch("chr1", 10_000, 10_100).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::convert::AsRef;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// The signature at the start of a 2bit file.
+const SIGNATURE: u32 = 0x1A41_2743;
+
+/// The bases encoded by the 2-bit values 0 to 3.
+const BASES: [u8; 4] = *b"TCAG";
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Io(err: io::Error) {
+            from()
+            description("error reading 2bit file")
+            display("error reading 2bit file: {}", err)
+            cause(err)
+        }
+        InvalidSignature(signature: u32) {
+            description("not a 2bit file")
+            display("invalid 2bit signature {:#010x}", signature)
+        }
+        UnsupportedVersion(version: u32) {
+            description("unsupported 2bit version")
+            display("unsupported 2bit version {}", version)
+        }
+        UnknownSequence(name: String) {
+            description("unknown sequence name")
+            display("sequence {:?} is not part of the 2bit file", name)
+        }
+        InvalidInterval(start: u64, end: u64, len: u64) {
+            description("invalid interval")
+            display("interval {}..{} is invalid for a sequence of length {}", start, end, len)
+        }
+    }
+}
+
+/// A reader for 2bit files, which parses the header and the sequence index on creation and
+/// reads sequences on demand.
+#[derive(Debug)]
+pub struct Reader<R: io::Read + io::Seek> {
+    reader: io::BufReader<R>,
+    big_endian: bool,
+    names: Vec<String>,
+    offsets: Vec<u64>,
+    name_to_idx: HashMap<String, usize>,
+    /// The headers of the sequences that have been accessed, by sequence index.
+    headers: Vec<Option<SeqHeader>>,
+    soft_mask: bool,
+}
+
+impl Reader<fs::File> {
+    /// Read from a given file path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Reader::new(fs::File::open(path)?)
+    }
+}
+
+impl<R: io::Read + io::Seek> Reader<R> {
+    /// Read from a given `io::Read + io::Seek`, parsing the header and the sequence index.
+    /// Files of both endiannesses and of version 0 and 1 (with 64-bit offsets) are
+    /// supported.
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut reader = io::BufReader::new(reader);
+        reader.seek(io::SeekFrom::Start(0))?;
+        let signature = read_u32(&mut reader, false)?;
+        let big_endian = match signature {
+            SIGNATURE => false,
+            _ if signature.swap_bytes() == SIGNATURE => true,
+            _ => return Err(Error::InvalidSignature(signature)),
+        };
+        let version = read_u32(&mut reader, big_endian)?;
+        if version > 1 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let count = read_u32(&mut reader, big_endian)? as usize;
+        read_u32(&mut reader, big_endian)?;
+
+        let mut names = Vec::with_capacity(count);
+        let mut offsets = Vec::with_capacity(count);
+        let mut name_to_idx = HashMap::with_capacity(count);
+        for i in 0..count {
+            let mut len = [0; 1];
+            reader.read_exact(&mut len)?;
+            let mut name = vec![0; len[0] as usize];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let offset = if version == 0 {
+                u64::from(read_u32(&mut reader, big_endian)?)
+            } else {
+                read_u64(&mut reader, big_endian)?
+            };
+            name_to_idx.insert(name.clone(), i);
+            names.push(name);
+            offsets.push(offset);
+        }
+
+        Ok(Reader {
+            reader,
+            big_endian,
+            names,
+            offsets,
+            name_to_idx,
+            headers: (0..count).map(|_| None).collect(),
+            soft_mask: false,
+        })
+    }
+
+    /// Return soft-masked bases in lowercase when fetching. By default, all bases are
+    /// returned in uppercase.
+    pub fn soft_mask(mut self, soft_mask: bool) -> Self {
+        self.soft_mask = soft_mask;
+        self
+    }
+
+    /// Return the names of the sequences in file order.
+    pub fn sequence_names(&self) -> Vec<&str> {
+        self.names.iter().map(|name| name.as_str()).collect()
+    }
+
+    /// Return the length of the sequence with the given name.
+    pub fn sequence_len(&mut self, name: &str) -> Result<u64, Error> {
+        let idx = self.seq_header(name)?;
+        Ok(self.header(idx).len)
+    }
+
+    /// Fetch the interval [start, end) of the sequence with the given name, restoring the
+    /// blocks of `N` and, if enabled with `soft_mask`, the lowercase bases.
+    pub fn fetch(&mut self, name: &str, start: u64, end: u64) -> Result<Vec<u8>, Error> {
+        let idx = self.seq_header(name)?;
+        let header = self.header(idx);
+        if start > end || end > header.len {
+            return Err(Error::InvalidInterval(start, end, header.len));
+        }
+
+        let first = start / 4;
+        let mut packed = vec![0; (end.div_ceil(4) - first) as usize];
+        let dna_offset = header.dna_offset;
+        self.reader.seek(io::SeekFrom::Start(dna_offset + first))?;
+        self.reader.read_exact(&mut packed)?;
+        let header = self.header(idx);
+        let mut seq: Vec<u8> = (start..end)
+            .map(|i| {
+                let byte = packed[(i / 4 - first) as usize];
+                BASES[(byte >> (6 - 2 * (i % 4)) & 0b11) as usize]
+            })
+            .collect();
+
+        for_overlaps(&header.n_blocks, start, end, |i| seq[i] = b'N');
+        if self.soft_mask {
+            for_overlaps(&header.mask_blocks, start, end, |i| {
+                seq[i].make_ascii_lowercase()
+            });
+        }
+
+        Ok(seq)
+    }
+
+    /// Fetch the whole sequence with the given name.
+    pub fn fetch_all(&mut self, name: &str) -> Result<Vec<u8>, Error> {
+        let len = self.sequence_len(name)?;
+        self.fetch(name, 0, len)
+    }
+
+    /// Return the index of the sequence with the given name, reading its header unless it
+    /// has been read before.
+    fn seq_header(&mut self, name: &str) -> Result<usize, Error> {
+        let idx = match self.name_to_idx.get(name) {
+            Some(&idx) => idx,
+            None => return Err(Error::UnknownSequence(name.to_owned())),
+        };
+        if self.headers[idx].is_some() {
+            return Ok(idx);
+        }
+        self.reader.seek(io::SeekFrom::Start(self.offsets[idx]))?;
+        let len = u64::from(self.read_u32()?);
+        let n_blocks = self.read_blocks()?;
+        let mask_blocks = self.read_blocks()?;
+        self.read_u32()?;
+        let dna_offset = self.reader.stream_position()?;
+
+        self.headers[idx] = Some(SeqHeader {
+            len,
+            n_blocks,
+            mask_blocks,
+            dna_offset,
+        });
+
+        Ok(idx)
+    }
+
+    /// The header of the sequence with the given index, which has to be read before with
+    /// `seq_header`.
+    fn header(&self, idx: usize) -> &SeqHeader {
+        self.headers[idx]
+            .as_ref()
+            .expect("bug: sequence header not read")
+    }
+
+    /// Read a list of blocks, given by their count, their starts and their sizes, sorted by
+    /// their starts.
+    fn read_blocks(&mut self) -> io::Result<Vec<(u64, u64)>> {
+        let count = self.read_u32()? as usize;
+        let mut starts = Vec::with_capacity(count);
+        for _ in 0..count {
+            starts.push(u64::from(self.read_u32()?));
+        }
+        let mut blocks = Vec::with_capacity(count);
+        for start in starts {
+            blocks.push((start, start + u64::from(self.read_u32()?)));
+        }
+        blocks.sort_unstable();
+
+        Ok(blocks)
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        read_u32(&mut self.reader, self.big_endian)
+    }
+}
+
+/// Length, blocks (as intervals [start, end)) and offset of the packed bases of a sequence.
+#[derive(Debug)]
+struct SeqHeader {
+    len: u64,
+    n_blocks: Vec<(u64, u64)>,
+    mask_blocks: Vec<(u64, u64)>,
+    dna_offset: u64,
+}
+
+/// Call `f` with the position relative to `start` of each base of [start, end) that is
+/// covered by one of the given sorted, non-overlapping blocks.
+fn for_overlaps<F: FnMut(usize)>(blocks: &[(u64, u64)], start: u64, end: u64, mut f: F) {
+    let first = blocks.partition_point(|&(_, block_end)| block_end <= start);
+    for &(block_start, block_end) in blocks[first..]
+        .iter()
+        .take_while(|&&(block_start, _)| block_start < end)
+    {
+        for i in block_start.max(start)..block_end.min(end) {
+            f((i - start) as usize);
+        }
+    }
+}
+
+fn read_u32<R: io::Read>(reader: &mut R, big_endian: bool) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(if big_endian {
+        u32::from_be_bytes(buf)
+    } else {
+        u32::from_le_bytes(buf)
+    })
+}
+
+fn read_u64<R: io::Read>(reader: &mut R, big_endian: bool) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(if big_endian {
+        u64::from_be_bytes(buf)
+    } else {
+        u64::from_le_bytes(buf)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEQS: [(&str, &[u8]); 4] = [
+        ("chr1", b"ACGTNNNNacgtACGTTTGCAnnAGGTCAN"),
+        ("chrM", b"GATTACA"),
+        ("empty", b""),
+        ("gap", b"NNNNNnnccc"),
+    ];
+
+    /// Encode the given sequences as a 2bit file, with N-blocks for runs of `N`/`n` and
+    /// mask blocks for runs of lowercase bases.
+    fn encode(seqs: &[(&str, &[u8])], big_endian: bool, version: u32) -> Vec<u8> {
+        let u32_bytes = |v: u32| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+        let runs = |seq: &[u8], is_in: &dyn Fn(u8) -> bool| {
+            let mut starts = Vec::new();
+            let mut sizes = Vec::new();
+            for (i, &c) in seq.iter().enumerate() {
+                if is_in(c) {
+                    if i == 0 || !is_in(seq[i - 1]) {
+                        starts.push(i as u32);
+                        sizes.push(0);
+                    }
+                    *sizes.last_mut().unwrap() += 1;
+                }
+            }
+            (starts, sizes)
+        };
+
+        let mut records = Vec::new();
+        for &(_, seq) in seqs {
+            let mut record = Vec::new();
+            record.extend(&u32_bytes(seq.len() as u32));
+            let is_n = |c: u8| c == b'N' || c == b'n';
+            let is_lower = |c: u8| c.is_ascii_lowercase();
+            for (starts, sizes) in &[runs(seq, &is_n), runs(seq, &is_lower)] {
+                record.extend(&u32_bytes(starts.len() as u32));
+                for &v in starts.iter().chain(sizes) {
+                    record.extend(&u32_bytes(v));
+                }
+            }
+            record.extend(&u32_bytes(0));
+            for chunk in seq.chunks(4) {
+                let mut byte = 0;
+                for (j, &c) in chunk.iter().enumerate() {
+                    let code = match c.to_ascii_uppercase() {
+                        b'C' => 1,
+                        b'A' => 2,
+                        b'G' => 3,
+                        _ => 0,
+                    };
+                    byte |= code << (6 - 2 * j);
+                }
+                record.push(byte);
+            }
+            records.push(record);
+        }
+
+        let mut file = Vec::new();
+        for &v in &[SIGNATURE, version, seqs.len() as u32, 0] {
+            file.extend(&u32_bytes(v));
+        }
+        let offset_len = if version == 0 { 4 } else { 8 };
+        let index_len: usize = seqs
+            .iter()
+            .map(|&(name, _)| 1 + name.len() + offset_len)
+            .sum();
+        let mut offset = (file.len() + index_len) as u64;
+        for (&(name, _), record) in seqs.iter().zip(&records) {
+            file.push(name.len() as u8);
+            file.extend(name.as_bytes());
+            if version == 0 {
+                file.extend(&u32_bytes(offset as u32));
+            } else if big_endian {
+                file.extend(&offset.to_be_bytes());
+            } else {
+                file.extend(&offset.to_le_bytes());
+            }
+            offset += record.len() as u64;
+        }
+        for record in &records {
+            file.extend(record);
+        }
+        file
+    }
+
+    #[test]
+    fn test_fetch_literal() {
+        let file: &[u8] = b"\x43\x27\x41\x1a\0\0\0\0\x01\0\0\0\0\0\0\0\
+            \x04chr1\x19\0\0\0\
+            \x05\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\
+            \x1b\xc0";
+        let mut reader = Reader::new(io::Cursor::new(file)).unwrap();
+        assert_eq!(reader.sequence_names(), ["chr1"]);
+        assert_eq!(reader.fetch_all("chr1").unwrap(), b"TCAGG");
+        assert_eq!(encode(&[("chr1", b"TCAGG")], false, 0), file);
+    }
+
+    #[test]
+    fn test_fetch() {
+        for &big_endian in &[false, true] {
+            for &version in &[0, 1] {
+                let file = encode(&SEQS, big_endian, version);
+                let mut reader = Reader::new(io::Cursor::new(&file[..]))
+                    .unwrap()
+                    .soft_mask(true);
+                assert_eq!(reader.sequence_names(), ["chr1", "chrM", "empty", "gap"]);
+                for &(name, seq) in &SEQS {
+                    assert_eq!(reader.sequence_len(name).unwrap(), seq.len() as u64);
+                    assert_eq!(reader.fetch_all(name).unwrap(), seq);
+                    for start in 0..=seq.len() {
+                        for end in start..=seq.len() {
+                            assert_eq!(
+                                reader.fetch(name, start as u64, end as u64).unwrap(),
+                                &seq[start..end]
+                            );
+                        }
+                    }
+                }
+
+                let mut reader = Reader::new(io::Cursor::new(&file[..])).unwrap();
+                for &(name, seq) in &SEQS {
+                    assert_eq!(reader.fetch_all(name).unwrap(), seq.to_ascii_uppercase());
+                }
+                assert_eq!(reader.fetch("chr1", 10, 14).unwrap(), b"GTAC");
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_overlaps() {
+        let blocks = [(2, 4), (4, 5), (7, 10), (12, 13)];
+        for start in 0..15 {
+            for end in start..15 {
+                let mut covered = Vec::new();
+                for_overlaps(&blocks, start, end, |i| covered.push(start + i as u64));
+                let expected: Vec<u64> = (start..end)
+                    .filter(|&i| blocks.iter().any(|&(s, e)| s <= i && i < e))
+                    .collect();
+                assert_eq!(covered, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let file = encode(&SEQS, false, 0);
+        let mut reader = Reader::new(io::Cursor::new(&file[..])).unwrap();
+        match reader.fetch("chr2", 0, 1) {
+            Err(Error::UnknownSequence(name)) => assert_eq!(name, "chr2"),
+            res => panic!("expected unknown sequence, found {:?}", res),
+        }
+        assert!(matches!(
+            reader.fetch("chrM", 0, 8),
+            Err(Error::InvalidInterval(0, 8, 7))
+        ));
+        assert!(matches!(
+            reader.fetch("chrM", 5, 4),
+            Err(Error::InvalidInterval(5, 4, 7))
+        ));
+        assert!(matches!(
+            reader.fetch("empty", 0, 1),
+            Err(Error::InvalidInterval(0, 1, 0))
+        ));
+
+        let mut file = encode(&SEQS, false, 0);
+        file[4] = 2;
+        assert!(matches!(
+            Reader::new(io::Cursor::new(&file[..])),
+            Err(Error::UnsupportedVersion(2))
+        ));
+        file[0] = 0;
+        assert!(matches!(
+            Reader::new(io::Cursor::new(&file[..])),
+            Err(Error::InvalidSignature(0x1A41_2700))
+        ));
+        assert!(matches!(
+            Reader::new(io::Cursor::new(&file[..10])),
+            Err(Error::InvalidSignature(_))
+        ));
+        let file = encode(&SEQS, false, 0);
+        assert!(matches!(
+            Reader::new(io::Cursor::new(&file[..20])),
+            Err(Error::Io(_))
+        ));
+    }
+}
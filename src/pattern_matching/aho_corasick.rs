@@ -0,0 +1,313 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Algorithm of Aho and Corasick for finding multiple patterns at once.
+//! Constructs a trie of the patterns, with failure links pointing from each node to the node
+//! of its longest proper suffix that is also in the trie. Following the failure links, the
+//! transitions of the trie are completed to a deterministic automaton, which scans linearly
+//! over a text of length n. Complexity: O(n + z) for z matches, after building the automaton
+//! in O(m * |A|) for patterns of total length m over an alphabet A.
+//!
+//! All matches are reported, including overlapping and nested ones, and duplicate patterns
+//! match at the same positions. By default, the automaton has transitions for all 256 byte
+//! values. For many patterns, restricting the transitions to a smaller alphabet saves
+//! memory.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::aho_corasick::AhoCorasick;
+//! let text = b"ACGTTACGATAC";
+//! let patterns: &[&[u8]] = &[b"TAC", b"ACG", b"AC"];
+//! let ac = AhoCorasick::new(patterns);
+//! let occ: Vec<(usize, usize)> = ac.find_all(text).collect();
+//! assert_eq!(occ, [(2, 0), (1, 0), (0, 4), (2, 5), (1, 5), (0, 9), (2, 10)]);
+//! ```
+
+use std::iter::Enumerate;
+use std::slice;
+
+use crate::alphabets::Alphabet;
+
+/// Marker for a missing transition while building the automaton.
+const NONE: usize = usize::MAX;
+
+/// Aho-Corasick algorithm.
+pub struct AhoCorasick {
+    /// Column of each byte in the transition table, or `None` for bytes outside the alphabet.
+    columns: Vec<Option<usize>>,
+    n_columns: usize,
+    /// Transitions of all states, row by row.
+    delta: Vec<usize>,
+    /// Indices of the patterns ending in each state.
+    matches: Vec<Vec<usize>>,
+    /// The nearest state along the failure links that has matches.
+    output_links: Vec<Option<usize>>,
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Create a new instance for the given patterns, with transitions for all bytes.
+    /// Panics if a pattern is empty.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        Self::build(patterns, (0..=255).map(Some).collect(), 256)
+    }
+
+    /// Create a new instance for the given patterns, with transitions restricted to the
+    /// symbols of the given alphabet. Text symbols outside of the alphabet are skipped, since
+    /// they cannot be part of a match. Panics if a pattern is empty or contains symbols outside
+    /// of the alphabet.
+    pub fn with_alphabet<P: AsRef<[u8]>>(patterns: &[P], alphabet: &Alphabet) -> Self {
+        let mut columns = vec![None; 256];
+        for (column, a) in alphabet.iter().enumerate() {
+            columns[a as usize] = Some(column);
+        }
+        Self::build(patterns, columns, alphabet.len())
+    }
+
+    fn build<P: AsRef<[u8]>>(
+        patterns: &[P],
+        columns: Vec<Option<usize>>,
+        n_columns: usize,
+    ) -> Self {
+        let mut ac = AhoCorasick {
+            columns,
+            n_columns,
+            delta: vec![NONE; n_columns],
+            matches: vec![vec![]],
+            output_links: vec![None],
+            lens: Vec::with_capacity(patterns.len()),
+        };
+
+        // trie of the patterns
+        for (i, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "Pattern is empty");
+            let mut q = 0;
+            for &a in pattern {
+                let column = ac.columns[a as usize].unwrap_or_else(|| {
+                    panic!("Unexpected symbol {:?} not in alphabet.", a as char)
+                });
+                if ac.delta[q * n_columns + column] == NONE {
+                    ac.delta[q * n_columns + column] = ac.matches.len();
+                    ac.delta.extend((0..n_columns).map(|_| NONE));
+                    ac.matches.push(vec![]);
+                    ac.output_links.push(None);
+                }
+                q = ac.delta[q * n_columns + column];
+            }
+            ac.matches[q].push(i);
+            ac.lens.push(pattern.len());
+        }
+
+        // failure links in breadth-first order, completing the transitions of each state with
+        // those of its failure state, which is closer to the root
+        let mut fail = vec![0; ac.matches.len()];
+        let mut queue = Vec::with_capacity(ac.matches.len());
+        for column in 0..n_columns {
+            match ac.delta[column] {
+                NONE => ac.delta[column] = 0,
+                child => queue.push(child),
+            }
+        }
+        let mut head = 0;
+        while head < queue.len() {
+            let q = queue[head];
+            head += 1;
+            for column in 0..n_columns {
+                let fallback = ac.delta[fail[q] * n_columns + column];
+                match ac.delta[q * n_columns + column] {
+                    NONE => ac.delta[q * n_columns + column] = fallback,
+                    child => {
+                        fail[child] = fallback;
+                        ac.output_links[child] = if ac.matches[fallback].is_empty() {
+                            ac.output_links[fallback]
+                        } else {
+                            Some(fallback)
+                        };
+                        queue.push(child);
+                    }
+                }
+            }
+        }
+
+        ac
+    }
+
+    fn delta(&self, q: usize, a: u8) -> usize {
+        match self.columns[a as usize] {
+            Some(column) => self.delta[q * self.n_columns + column],
+            None => 0,
+        }
+    }
+
+    /// Find all matches of the patterns in a given text. Matches are returned as iterator
+    /// over pairs of pattern index and start position, ordered by end position, and matches
+    /// ending at the same position by decreasing length.
+    pub fn find_all<'a>(&'a self, text: &'a [u8]) -> Matches<'a> {
+        Matches {
+            ac: self,
+            q: 0,
+            text: text.iter().enumerate(),
+            pos: 0,
+            output: None,
+            output_idx: 0,
+        }
+    }
+}
+
+/// Iterator over pairs of pattern index and start position of matches.
+pub struct Matches<'a> {
+    ac: &'a AhoCorasick,
+    q: usize,
+    text: Enumerate<slice::Iter<'a, u8>>,
+    /// The last position of the text that has been read.
+    pos: usize,
+    /// The state whose matches are reported next, and the index of the next match.
+    output: Option<usize>,
+    output_idx: usize,
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            if let Some(q) = self.output {
+                if let Some(&pattern) = self.ac.matches[q].get(self.output_idx) {
+                    self.output_idx += 1;
+                    return Some((pattern, self.pos + 1 - self.ac.lens[pattern]));
+                }
+                self.output = self.ac.output_links[q];
+                self.output_idx = 0;
+                continue;
+            }
+
+            let (i, &a) = self.text.next()?;
+            self.q = self.ac.delta(self.q, a);
+            self.pos = i;
+            self.output = if self.ac.matches[self.q].is_empty() {
+                self.ac.output_links[self.q]
+            } else {
+                Some(self.q)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    fn brute_force(patterns: &[Vec<u8>], text: &[u8]) -> Vec<(usize, usize)> {
+        let mut occ = vec![];
+        for end in 1..=text.len() {
+            let mut ending: Vec<_> = patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| text[..end].ends_with(p))
+                .map(|(i, p)| (i, end - p.len()))
+                .collect();
+            ending.sort_by_key(|&(i, start)| (start, i));
+            occ.extend(ending);
+        }
+        occ
+    }
+
+    #[test]
+    fn test_find_all() {
+        let patterns: &[&[u8]] = &[b"he", b"she", b"his", b"hers"];
+        let ac = AhoCorasick::new(patterns);
+        assert_eq!(
+            ac.find_all(b"ushers").collect_vec(),
+            [(1, 1), (0, 2), (3, 2)]
+        );
+        assert_eq!(ac.find_all(b"").count(), 0);
+        assert_eq!(ac.find_all(b"xyz").count(), 0);
+    }
+
+    #[test]
+    fn test_duplicates_and_prefixes() {
+        let patterns: &[&[u8]] = &[b"AA", b"A", b"AAA", b"A", b"CA"];
+        let ac = AhoCorasick::new(patterns);
+        assert_eq!(
+            ac.find_all(b"CAAA").collect_vec(),
+            [
+                (4, 0),
+                (1, 1),
+                (3, 1),
+                (0, 1),
+                (1, 2),
+                (3, 2),
+                (2, 1),
+                (0, 2),
+                (1, 3),
+                (3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_alphabet() {
+        let alphabet = Alphabet::new(b"ACGT");
+        let patterns: &[&[u8]] = &[b"ACG", b"GT"];
+        let ac = AhoCorasick::with_alphabet(patterns, &alphabet);
+        // symbols outside the alphabet interrupt matches
+        assert_eq!(
+            ac.find_all(b"ACGTNACNGT").collect_vec(),
+            [(0, 0), (1, 2), (1, 8)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not in alphabet")]
+    fn test_with_alphabet_invalid_pattern() {
+        AhoCorasick::with_alphabet(&[b"ACGN"], &Alphabet::new(b"ACGT"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pattern is empty")]
+    fn test_empty_pattern() {
+        AhoCorasick::new(&[&b"AC"[..], b""]);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::from_seed(&[3]);
+        let alphabet = Alphabet::new(b"ACGT");
+        for _ in 0..100 {
+            // short patterns over few symbols, such that duplicates and prefixes are common
+            let patterns: Vec<Vec<u8>> = (0..rng.gen_range(1, 30))
+                .map(|_| {
+                    (0..rng.gen_range(1, 7))
+                        .map(|_| b"ACGT"[rng.gen_range(0, 4)])
+                        .collect()
+                })
+                .collect();
+            let text: Vec<u8> = (0..rng.gen_range(0, 200))
+                .map(|_| b"ACGTN"[rng.gen_range(0, 5)])
+                .collect();
+            let expected = brute_force(&patterns, &text);
+            let ac = AhoCorasick::new(&patterns);
+            assert_eq!(sorted(ac.find_all(&text)), expected);
+            let ac = AhoCorasick::with_alphabet(&patterns, &alphabet);
+            assert_eq!(sorted(ac.find_all(&text)), expected);
+        }
+    }
+
+    /// Sort matches by end position and start, and duplicates by pattern index, checking that
+    /// they are already ordered by end and start.
+    fn sorted(matches: Matches<'_>) -> Vec<(usize, usize)> {
+        let ac = matches.ac;
+        let mut occ = matches
+            .map(|(i, start)| (start + ac.lens[i], start, i))
+            .collect_vec();
+        assert!(occ.windows(2).all(|w| (w[0].0, w[0].1) <= (w[1].0, w[1].1)));
+        occ.sort();
+        occ.into_iter().map(|(_, start, i)| (i, start)).collect()
+    }
+}
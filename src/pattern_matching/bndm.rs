@@ -19,6 +19,7 @@
 //! ```
 
 use crate::pattern_matching::shift_and::masks;
+use crate::pattern_matching::Matcher;
 use crate::utils::TextSlice;
 use std::borrow::Borrow;
 
@@ -57,6 +58,18 @@ impl BNDM {
     }
 }
 
+impl Matcher for BNDM {
+    type Matches<'a> = Matches<'a>;
+
+    fn pattern_len(&self) -> usize {
+        self.m
+    }
+
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Matches<'a> {
+        BNDM::find_all(self, text)
+    }
+}
+
 /// Iterator over start positions of matches.
 pub struct Matches<'a> {
     bndm: &'a BNDM,
@@ -18,6 +18,7 @@
 //! assert_eq!(occ, [8, 25]);
 //! ```
 
+use crate::pattern_matching::Matcher;
 use crate::utils::TextSlice;
 use std::borrow::Borrow;
 use std::cmp::Ord;
@@ -105,6 +106,18 @@ impl BOM {
     }
 }
 
+impl Matcher for BOM {
+    type Matches<'a> = Matches<'a>;
+
+    fn pattern_len(&self) -> usize {
+        self.m
+    }
+
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Matches<'a> {
+        BOM::find_all(self, text)
+    }
+}
+
 /// Iterator over start positions of matches.
 pub struct Matches<'a> {
     bom: &'a BOM,
@@ -38,11 +38,13 @@
 //! assert_eq!(occ, [8, 25]);
 //! ```
 
+use crate::pattern_matching::Matcher;
 use crate::utils::TextSlice;
 
 /// Algorithm of Horspool.
 pub struct Horspool<'a> {
     shift: Vec<usize>,
+    rev_shift: Vec<usize>,
     m: usize,
     pattern: TextSlice<'a>,
 }
@@ -57,29 +59,59 @@ impl<'a> Horspool<'a> {
         for (j, &a) in pattern[..m - 1].iter().enumerate() {
             shift[a as usize] = m - 1 - j;
         }
+        // the same for searching from the end of the text, with the window aligned at the
+        // first symbol of the pattern
+        let mut rev_shift = vec![m; 256];
+        for (j, &a) in pattern.iter().enumerate().skip(1).rev() {
+            rev_shift[a as usize] = j;
+        }
 
-        Horspool { m, shift, pattern }
+        Horspool {
+            m,
+            shift,
+            rev_shift,
+            pattern,
+        }
     }
 
     /// Find all matches with a given text. Matches are returned as an iterator over start
-    /// positions.
+    /// positions, which can also be consumed from the end of the text.
     pub fn find_all<'b>(&'b self, text: TextSlice<'b>) -> Matches<'_> {
         Matches {
             horspool: self,
             text,
-            n: text.len(),
             last: self.m - 1,
+            end: text.len(),
+            pattern_first: self.pattern[0],
             pattern_last: self.pattern[self.m - 1],
         }
     }
 }
 
+impl<'p> Matcher for Horspool<'p> {
+    type Matches<'a>
+        = Matches<'a>
+    where
+        Self: 'a;
+
+    fn pattern_len(&self) -> usize {
+        self.m
+    }
+
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Matches<'a> {
+        Horspool::find_all(self, text)
+    }
+}
+
 /// Iterator over start positions of matches.
 pub struct Matches<'a> {
     horspool: &'a Horspool<'a>,
     text: TextSlice<'a>,
-    n: usize,
+    /// Last position of the next window from the front.
     last: usize,
+    /// End (exclusive) of the next window from the back.
+    end: usize,
+    pattern_first: u8,
     pattern_last: u8,
 }
 
@@ -89,11 +121,11 @@ impl<'a> Iterator for Matches<'a> {
     fn next(&mut self) -> Option<usize> {
         loop {
             // shift until the last symbol matches
-            while self.last < self.n && self.text[self.last] != self.pattern_last {
+            while self.last < self.end && self.text[self.last] != self.pattern_last {
                 self.last += self.horspool.shift[self.text[self.last] as usize];
             }
-            // stop if end of text is reached
-            if self.last >= self.n {
+            // stop if end of text or the windows searched from the back are reached
+            if self.last >= self.end {
                 return None;
             }
 
@@ -111,6 +143,34 @@ impl<'a> Iterator for Matches<'a> {
     }
 }
 
+impl<'a> DoubleEndedIterator for Matches<'a> {
+    fn next_back(&mut self) -> Option<usize> {
+        let m = self.horspool.m;
+        loop {
+            // shift until the first symbol matches
+            while self.last < self.end && self.text[self.end - m] != self.pattern_first {
+                let shift = self.horspool.rev_shift[self.text[self.end - m] as usize];
+                self.end = self.end.saturating_sub(shift);
+            }
+            // stop if start of text or the windows searched from the front are reached
+            if self.last >= self.end {
+                return None;
+            }
+
+            // putative start position
+            let i = self.end - m;
+
+            self.end = self
+                .end
+                .saturating_sub(self.horspool.rev_shift[self.pattern_first as usize]);
+
+            if self.text[i + 1..i + m] == self.horspool.pattern[1..] {
+                return Some(i);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Horspool;
@@ -133,4 +193,25 @@ mod tests {
         let horspool = Horspool::new(pattern);
         assert_eq!(horspool.find_all(text).collect_vec(), [8]);
     }
+
+    #[test]
+    fn test_find_all_rev() {
+        let text = b"AACBAACBCAACBAACA";
+        let horspool = Horspool::new(b"AACBAAC");
+        assert_eq!(horspool.find_all(text).collect_vec(), [0, 9]);
+        assert_eq!(horspool.find_all(text).rev().collect_vec(), [9, 0]);
+        assert_eq!(horspool.rev_shift[b'A' as usize], 1);
+        assert_eq!(horspool.rev_shift[b'C' as usize], 2);
+        assert_eq!(horspool.rev_shift[b'X' as usize], 7);
+
+        // both ends meet without reporting a match twice
+        let horspool = Horspool::new(b"AA");
+        let mut matches = horspool.find_all(b"AAAAA");
+        assert_eq!(matches.next(), Some(0));
+        assert_eq!(matches.next_back(), Some(3));
+        assert_eq!(matches.next_back(), Some(2));
+        assert_eq!(matches.next(), Some(1));
+        assert_eq!(matches.next(), None);
+        assert_eq!(matches.next_back(), None);
+    }
 }
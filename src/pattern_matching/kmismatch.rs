@@ -0,0 +1,277 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bit-parallel pattern matching with at most k mismatches (Hamming distance), without
+//! insertions or deletions. This is the Shift-Add algorithm of Baeza-Yates and Gonnet (1992),
+//! which extends Shift-And by a small counter per pattern position instead of a single bit.
+//! The counter of position j holds the number of mismatches between the pattern prefix of
+//! length j + 1 and the text ending at the current position. Counters are just wide enough
+//! to hold k, with an extra bit marking overflow. Patterns that need more counters than fit
+//! into a 64 bit word are split into blocks of words.
+//! Complexity: O(n * ⌈m log(k) / w⌉) with text length n, pattern length m and word size w.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::kmismatch::KMismatch;
+//! let pattern = b"GATTACA";
+//! let text = b"ACGATTACAGTGATAACATT";
+//! let kmismatch = KMismatch::new(pattern, 1);
+//! let occ: Vec<(usize, usize)> = kmismatch.find_all(text).collect();
+//! assert_eq!(occ, [(2, 0), (11, 1)]);
+//! ```
+
+use std::borrow::Borrow;
+use std::iter::Enumerate;
+
+use crate::utils::TextSlice;
+
+/// Bit-parallel k-mismatch algorithm.
+pub struct KMismatch {
+    m: usize,
+    k: usize,
+    /// Bits per counter, including the overflow bit.
+    width: u32,
+    /// Counters per word.
+    per_word: usize,
+    n_words: usize,
+    /// Mismatch masks of all symbols, `n_words` words per symbol, with a one in the counter
+    /// of each pattern position that does not match the symbol.
+    masks: Vec<u64>,
+    /// The bits of a word used by its counters.
+    used: u64,
+    /// The overflow bits of all counters in a word.
+    overflow_bits: u64,
+}
+
+impl KMismatch {
+    /// Create a new instance for a given non-empty pattern and maximum number of mismatches.
+    pub fn new(pattern: TextSlice<'_>, k: usize) -> Self {
+        let m = pattern.len();
+        assert!(m > 0, "Expecting non-empty pattern.");
+        // more than m mismatches are impossible
+        let k = k.min(m);
+        let width = 64 - (k as u64).leading_zeros() + 1;
+        let per_word = (64 / width) as usize;
+        let n_words = m.div_ceil(per_word);
+
+        let counter = |j: usize| (j / per_word, (j % per_word) as u32 * width);
+        let mut masks = vec![0; 256 * n_words];
+        for a in 0..256 {
+            let symbol_masks = &mut masks[a * n_words..(a + 1) * n_words];
+            for (j, &b) in pattern.iter().enumerate() {
+                if b as usize != a {
+                    let (word, shift) = counter(j);
+                    symbol_masks[word] |= 1 << shift;
+                }
+            }
+        }
+        let used_bits = per_word as u32 * width;
+        let used = if used_bits == 64 {
+            !0
+        } else {
+            (1 << used_bits) - 1
+        };
+        let overflow_bits = (0..per_word as u32)
+            .map(|f| 1 << (f * width + width - 1))
+            .fold(0, |bits, bit| bits | bit);
+
+        KMismatch {
+            m,
+            k,
+            width,
+            per_word,
+            n_words,
+            masks,
+            used,
+            overflow_bits,
+        }
+    }
+
+    /// Find all matches of the pattern with at most k mismatches in the given text. Matches
+    /// are returned as an iterator over pairs of start position and number of mismatches.
+    pub fn find_all<C, T>(&self, text: T) -> Matches<'_, C, T::IntoIter>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+    {
+        Matches {
+            kmismatch: self,
+            counters: vec![0; self.n_words],
+            overflows: vec![0; self.n_words],
+            text: text.into_iter().enumerate(),
+        }
+    }
+}
+
+/// Iterator over pairs of start position and number of mismatches of matches.
+pub struct Matches<'a, C, T>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    kmismatch: &'a KMismatch,
+    /// Mismatch counters without their overflow bits.
+    counters: Vec<u64>,
+    /// Overflow bits of the counters.
+    overflows: Vec<u64>,
+    text: Enumerate<T>,
+}
+
+impl<'a, C, T> Iterator for Matches<'a, C, T>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let km = self.kmismatch;
+        let top = (km.per_word as u32 - 1) * km.width;
+        let (last_word, last_shift) = (
+            (km.m - 1) / km.per_word,
+            ((km.m - 1) % km.per_word) as u32 * km.width,
+        );
+
+        for (i, c) in self.text.by_ref() {
+            let a = *c.borrow() as usize;
+            let masks = &km.masks[a * km.n_words..(a + 1) * km.n_words];
+            // shift all counters by one position, carrying the top counter of each word
+            // into the next one, and add the mismatches
+            let (mut carry, mut overflow_carry) = (0, 0);
+            for (word, &mask) in masks.iter().enumerate() {
+                let (counters, overflows) = (self.counters[word], self.overflows[word]);
+                // counters are below their overflow bit, so adding one cannot carry into the
+                // next counter
+                let shifted = ((counters << km.width) & km.used | carry) + mask;
+                self.overflows[word] = ((overflows << km.width) & km.used)
+                    | overflow_carry
+                    | (shifted & km.overflow_bits);
+                self.counters[word] = shifted & !km.overflow_bits;
+                carry = counters >> top;
+                overflow_carry = overflows >> top;
+            }
+
+            if i + 1 >= km.m
+                && (self.overflows[last_word] >> last_shift) & (1 << (km.width - 1)) == 0
+            {
+                let mismatches =
+                    ((self.counters[last_word] >> last_shift) & ((1 << km.width) - 1)) as usize;
+                if mismatches <= km.k {
+                    return Some((i + 1 - km.m, mismatches));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_matching::horspool::Horspool;
+    use crate::pattern_matching::kmp::KMP;
+    use itertools::Itertools;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    fn naive_find_all(pattern: &[u8], text: &[u8], k: usize) -> Vec<(usize, usize)> {
+        (0..(text.len() + 1).saturating_sub(pattern.len()))
+            .map(|i| {
+                let window = &text[i..i + pattern.len()];
+                let mismatches = window.iter().zip(pattern).filter(|(a, b)| a != b).count();
+                (i, mismatches)
+            })
+            .filter(|&(_, mismatches)| mismatches <= k)
+            .collect()
+    }
+
+    fn random_seq(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        (0..len).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect()
+    }
+
+    #[test]
+    fn test_find_all() {
+        let kmismatch = KMismatch::new(b"ACGT", 1);
+        assert_eq!(
+            kmismatch.find_all(b"ACGTTCGTACCTAGGT").collect_vec(),
+            [(0, 0), (4, 1), (8, 1), (12, 1)]
+        );
+        assert_eq!(kmismatch.find_all(b"AGGAACTT").collect_vec(), [(4, 1)]);
+        assert_eq!(kmismatch.find_all(b"ACG").count(), 0);
+        let kmismatch = KMismatch::new(b"ACGT", 2);
+        assert_eq!(
+            kmismatch.find_all(b"AGGAACTT").collect_vec(),
+            [(0, 2), (3, 2), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn test_k_at_least_m() {
+        let text = b"TTTTTAC";
+        for &k in &[3, 4, 100] {
+            let kmismatch = KMismatch::new(b"ACG", k);
+            assert_eq!(
+                kmismatch.find_all(text).collect_vec(),
+                naive_find_all(b"ACG", text, k)
+            );
+            assert_eq!(kmismatch.find_all(text).count(), 5);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "non-empty pattern")]
+    fn test_empty_pattern() {
+        KMismatch::new(b"", 1);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::from_seed(&[12]);
+        for _ in 0..300 {
+            // patterns spanning several words, for k with counters of different widths
+            let m = rng.gen_range(1, 150);
+            let k = [0, 1, 2, 3, 4, 7, 8, 20][rng.gen_range(0, 8)];
+            let pattern = random_seq(&mut rng, m);
+            let n = rng.gen_range(0, 400);
+            let mut text = random_seq(&mut rng, n);
+            // plant mutated copies of the pattern
+            for _ in 0..3 {
+                let mut copy = pattern.clone();
+                for _ in 0..rng.gen_range(0, k + 2) {
+                    copy[rng.gen_range(0, m)] = b"ACGT"[rng.gen_range(0, 4)];
+                }
+                let pos = rng.gen_range(0, text.len() + 1);
+                text.splice(pos..pos, copy);
+            }
+            let kmismatch = KMismatch::new(&pattern, k);
+            assert_eq!(
+                kmismatch.find_all(&text).collect_vec(),
+                naive_find_all(&pattern, &text, k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_exact() {
+        let mut rng = StdRng::from_seed(&[13]);
+        for _ in 0..200 {
+            let (m, n) = (rng.gen_range(1, 100), rng.gen_range(0, 300));
+            let pattern = random_seq(&mut rng, m);
+            let mut text = random_seq(&mut rng, n);
+            let pos = rng.gen_range(0, text.len() + 1);
+            text.splice(pos..pos, pattern.clone());
+            let kmismatch = KMismatch::new(&pattern, 0);
+            let occ = kmismatch.find_all(&text).collect_vec();
+            assert!(occ.iter().all(|&(_, mismatches)| mismatches == 0));
+            let starts = occ.into_iter().map(|(i, _)| i).collect_vec();
+            assert_eq!(starts, KMP::new(&pattern).find_all(&text).collect_vec());
+            assert_eq!(
+                starts,
+                Horspool::new(&pattern).find_all(&text).collect_vec()
+            );
+        }
+    }
+}
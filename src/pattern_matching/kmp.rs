@@ -24,7 +24,9 @@
 
 use std::borrow::Borrow;
 use std::iter::{repeat, Enumerate};
+use std::slice;
 
+use crate::pattern_matching::Matcher;
 use crate::utils::TextSlice;
 
 type LPS = Vec<usize>;
@@ -71,6 +73,21 @@ impl<'a> KMP<'a> {
     }
 }
 
+impl<'p> Matcher for KMP<'p> {
+    type Matches<'a>
+        = Matches<'a, &'a u8, slice::Iter<'a, u8>>
+    where
+        Self: 'a;
+
+    fn pattern_len(&self) -> usize {
+        self.m
+    }
+
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Self::Matches<'a> {
+        KMP::find_all(self, text)
+    }
+}
+
 fn lps(pattern: &[u8]) -> LPS {
     let (m, mut q) = (pattern.len(), 0);
     let mut lps: LPS = repeat(0).take(m).collect();
@@ -8,20 +8,223 @@
 //! "Algorithmen auf Sequenzen", Kopczynski, Marschall, Martin and Rahmann, 2008 - 2015.
 //!
 //! * Algorithm of Horspool: fastest for a sufficiently large alphabet
-//! * Shift And algorithm: fast for patterns with less than 64 symbols and very small alphabets.
+//! * Shift And algorithm: fast for patterns with at most 128 symbols and very small alphabets,
+//!   also with character classes like IUPAC codes.
 //! * BNDM algorithm: fast for patterns with less than 64 symbols.
 //! * BOM algorithm: fast for long patterns and small alphabet.
 //! * KMP algorithm: the classical ancestor.
+//! * Aho-Corasick algorithm: finding many patterns at once.
+//! * k-mismatch algorithm: bit-parallel matching with a bounded Hamming distance.
 //! * Ukkonens algorithm: approximate pattern matching with dynamic programming.
 //! * Myers algorithm: linear-time approximate pattern matching with edit distance for small patterns
 //!
 //! Another fast pattern matching algorithm is available in the twoway crate: https://crates.io/crates/twoway
+//!
+//! The exact matchers implement the [`Matcher`](trait.Matcher.html) trait, which finds either
+//! all, possibly overlapping, matches or greedily non-overlapping ones.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::horspool::Horspool;
+//! use bio::pattern_matching::kmp::KMP;
+//! use bio::pattern_matching::Matcher;
+//!
+//! fn count<M: Matcher>(matcher: &M, text: &[u8]) -> (usize, usize) {
+//!     (
+//!         matcher.find_all(text).count(),
+//!         matcher.find_all_disjoint(text).count(),
+//!     )
+//! }
+//!
+//! let text = b"ACAAAAAGTAAA";
+//! assert_eq!(count(&Horspool::new(b"AAA"), text), (4, 2));
+//! assert_eq!(count(&KMP::new(b"AAA"), text), (4, 2));
+//! ```
+
+use crate::utils::TextSlice;
 
+pub mod aho_corasick;
 pub mod bndm;
 pub mod bom;
 pub mod horspool;
+pub mod kmismatch;
 pub mod kmp;
 pub mod myers;
 pub mod pssm;
 pub mod shift_and;
 pub mod ukkonen;
+
+/// An exact pattern matcher for a single pattern.
+pub trait Matcher {
+    /// Iterator over start positions of matches.
+    type Matches<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    /// Length of the pattern.
+    fn pattern_len(&self) -> usize;
+
+    /// Find all matches of the pattern in the given text, including overlapping ones. Matches
+    /// are returned as an iterator over start positions in increasing order.
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Self::Matches<'a>;
+
+    /// Find non-overlapping matches of the pattern in the given text. From left to right,
+    /// each match is reported unless it overlaps the previously reported one.
+    fn find_all_disjoint<'a>(&'a self, text: TextSlice<'a>) -> DisjointMatches<Self::Matches<'a>> {
+        DisjointMatches {
+            matches: self.find_all(text),
+            pattern_len: self.pattern_len(),
+            next: 0,
+        }
+    }
+}
+
+/// Iterator over start positions of non-overlapping matches, see
+/// [`Matcher::find_all_disjoint`](trait.Matcher.html#method.find_all_disjoint).
+pub struct DisjointMatches<I> {
+    matches: I,
+    pattern_len: usize,
+    /// The first position after the last reported match.
+    next: usize,
+}
+
+impl<I: Iterator<Item = usize>> Iterator for DisjointMatches<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let next = self.next;
+        let i = self.matches.find(|&i| i >= next)?;
+        self.next = i + self.pattern_len;
+
+        Some(i)
+    }
+}
+
+/// Start positions of the pattern in the text, comparing each window. This is the reference
+/// for testing the matchers.
+#[cfg(test)]
+pub(crate) fn naive_find_all(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    (0..(text.len() + 1).saturating_sub(pattern.len()))
+        .filter(|&i| text[i..].starts_with(pattern))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bndm::BNDM;
+    use super::bom::BOM;
+    use super::horspool::Horspool;
+    use super::kmp::KMP;
+    use super::shift_and::ShiftAnd;
+    use super::*;
+    use itertools::Itertools;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    /// Pattern, text, all matches and disjoint matches.
+    type Fixture = (
+        &'static [u8],
+        &'static [u8],
+        &'static [usize],
+        &'static [usize],
+    );
+
+    const FIXTURES: &[Fixture] = &[
+        (b"AAA", b"AAAAAA", &[0, 1, 2, 3], &[0, 3]),
+        (b"AAA", b"AAAAA", &[0, 1, 2], &[0]),
+        (b"AAA", b"AACAAAAGAA", &[3, 4], &[3]),
+        (b"ABAB", b"ABABABXABAB", &[0, 2, 7], &[0, 7]),
+        (b"A", b"CAAC", &[1, 2], &[1, 2]),
+        (b"ACGT", b"ACG", &[], &[]),
+        (b"ACGT", b"", &[], &[]),
+        (b"ACGT", b"ACGT", &[0], &[0]),
+        (b"qnnnannan", b"dhjalkjwqnnnannanaflkjdklfj", &[8], &[8]),
+    ];
+
+    fn naive_find_all_disjoint(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+        let mut occ: Vec<usize> = vec![];
+        for i in naive_find_all(pattern, text) {
+            if occ.last().is_none_or(|&last| i >= last + pattern.len()) {
+                occ.push(i);
+            }
+        }
+        occ
+    }
+
+    fn check<M: Matcher>(matcher: &M, text: &[u8], all: &[usize], disjoint: &[usize]) {
+        assert_eq!(matcher.find_all(text).collect_vec(), all);
+        assert_eq!(matcher.find_all_disjoint(text).collect_vec(), disjoint);
+    }
+
+    fn check_all(pattern: &[u8], text: &[u8], all: &[usize], disjoint: &[usize]) {
+        check(&Horspool::new(pattern), text, all, disjoint);
+        check(&KMP::new(pattern), text, all, disjoint);
+        check(&BOM::new(pattern), text, all, disjoint);
+        check(&BNDM::new(pattern), text, all, disjoint);
+        check(&ShiftAnd::new(pattern), text, all, disjoint);
+
+        let horspool = Horspool::new(pattern);
+        let mut rev = horspool.find_all(text).rev().collect_vec();
+        rev.reverse();
+        assert_eq!(rev, all);
+    }
+
+    #[test]
+    fn test_fixtures() {
+        for &(pattern, text, all, disjoint) in FIXTURES {
+            assert_eq!(naive_find_all(pattern, text), all);
+            assert_eq!(naive_find_all_disjoint(pattern, text), disjoint);
+            check_all(pattern, text, all, disjoint);
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::from_seed(&[10]);
+        for _ in 0..500 {
+            let pattern: Vec<u8> = (0..rng.gen_range(1, 8))
+                .map(|_| b"AC"[rng.gen_range(0, 2)])
+                .collect();
+            let text: Vec<u8> = (0..rng.gen_range(0, 100))
+                .map(|_| b"ACG"[rng.gen_range(0, 3)])
+                .collect();
+            check_all(
+                &pattern,
+                &text,
+                &naive_find_all(&pattern, &text),
+                &naive_find_all_disjoint(&pattern, &text),
+            );
+        }
+    }
+
+    #[test]
+    fn test_double_ended() {
+        let mut rng = StdRng::from_seed(&[11]);
+        for _ in 0..500 {
+            let pattern: Vec<u8> = (0..rng.gen_range(1, 5))
+                .map(|_| b"AC"[rng.gen_range(0, 2)])
+                .collect();
+            let text: Vec<u8> = (0..rng.gen_range(0, 60))
+                .map(|_| b"AC"[rng.gen_range(0, 2)])
+                .collect();
+            let horspool = Horspool::new(&pattern);
+            // alternate randomly between both ends
+            let mut matches = horspool.find_all(&text);
+            let (mut front, mut back) = (vec![], vec![]);
+            loop {
+                let found = if rng.gen() {
+                    matches.next().map(|i| front.push(i))
+                } else {
+                    matches.next_back().map(|i| back.push(i))
+                };
+                if found.is_none() {
+                    break;
+                }
+            }
+            // the other end has to be exhausted as well
+            front.extend(matches.by_ref());
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, naive_find_all(&pattern, &text));
+        }
+    }
+}
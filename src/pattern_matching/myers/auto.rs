@@ -0,0 +1,256 @@
+//! Myers algorithm for patterns of any length, which uses the fast single-word implementation
+//! if the pattern fits into a 64 bit bit-vector, and the block-based implementation of the
+//! [`long`](../long/index.html) module otherwise. Distances are always reported as `usize`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::myers::auto::Myers;
+//!
+//! let text = b"CGGTCCTGAGGGATTAGCAC";
+//! let pattern = b"TCCTAGGGC";
+//! let myers = Myers::new(pattern);
+//! assert!(!myers.is_long());
+//! assert_eq!(myers.find_all_end(text, 2).collect::<Vec<_>>(), [(11, 2), (12, 2)]);
+//!
+//! let pattern = [&b"TCCTAGGGC"[..]; 10].concat();
+//! let text = [&b"ACGT"[..], &pattern, b"ACGT"].concat();
+//! let myers = Myers::new(&pattern);
+//! assert!(myers.is_long());
+//! assert_eq!(myers.find_best_end(&text), (93, 0));
+//! ```
+
+use std::borrow::Borrow;
+
+use crate::alignment::Alignment;
+use crate::pattern_matching::myers::{self, long};
+
+/// Myers algorithm, using one bit-vector for patterns of up to 64 symbols and blocks of
+/// bit-vectors for longer patterns.
+// the single-word version is not boxed, as it is the fast path
+#[allow(clippy::large_enum_variant)]
+pub enum Myers {
+    Short(myers::Myers<u64>),
+    Long(long::Myers<u64>),
+}
+
+impl Myers {
+    /// Create a new instance of Myers algorithm for a given non-empty pattern of any length.
+    pub fn new<P, C>(pattern: P) -> Self
+    where
+        C: Borrow<u8>,
+        P: IntoIterator<Item = C>,
+        P::IntoIter: ExactSizeIterator,
+    {
+        let pattern = pattern.into_iter();
+        if pattern.len() <= 64 {
+            Myers::Short(myers::Myers::new(pattern))
+        } else {
+            Myers::Long(long::Myers::new(pattern))
+        }
+    }
+
+    /// Whether the block-based implementation is used.
+    pub fn is_long(&self) -> bool {
+        match self {
+            Myers::Short(_) => false,
+            Myers::Long(_) => true,
+        }
+    }
+
+    /// Calculate the global distance of the pattern to the given text.
+    pub fn distance<C, I>(&self, text: I) -> usize
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+    {
+        match self {
+            Myers::Short(myers) => myers.distance(text) as usize,
+            Myers::Long(myers) => myers.distance(text),
+        }
+    }
+
+    /// Finds all matches of pattern in the given text up to a given maximum distance.
+    /// Matches are returned as an iterator over pairs of end position and distance.
+    pub fn find_all_end<C, I>(&self, text: I, max_dist: usize) -> Matches<'_, C, I::IntoIter>
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+    {
+        match self {
+            Myers::Short(myers) => Matches::Short(myers.find_all_end(text, short_dist(max_dist))),
+            Myers::Long(myers) => Matches::Long(myers.find_all_end(text, max_dist)),
+        }
+    }
+
+    /// Find the best match of the pattern in the given text.
+    /// if multiple end positions have the same distance, the first is returned.
+    pub fn find_best_end<C, I>(&self, text: I) -> (usize, usize)
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+    {
+        match self {
+            Myers::Short(myers) => {
+                let (end, dist) = myers.find_best_end(text);
+                (end, dist as usize)
+            }
+            Myers::Long(myers) => myers.find_best_end(text),
+        }
+    }
+
+    /// Finds all matches of pattern in the given text up to a given maximum distance.
+    /// Matches are returned as an iterator over ranges of `(start, end, distance)`, see
+    /// `myers::Myers::find_all`.
+    pub fn find_all<C, I>(&mut self, text: I, max_dist: usize) -> FullMatches<'_, C, I::IntoIter>
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        match self {
+            Myers::Short(myers) => FullMatches::Short(myers.find_all(text, short_dist(max_dist))),
+            Myers::Long(myers) => FullMatches::Long(myers.find_all(text, max_dist)),
+        }
+    }
+
+    /// Updates the given `Alignment` with the position, path and distance of the hit ending at
+    /// `end_pos`, if its distance is at most `max_dist`, see `myers::Myers::alignment_at`.
+    pub fn alignment_at(
+        &mut self,
+        text: &[u8],
+        end_pos: usize,
+        max_dist: usize,
+        aln: &mut Alignment,
+    ) -> bool {
+        match self {
+            Myers::Short(myers) => myers.alignment_at(text, end_pos, short_dist(max_dist), aln),
+            Myers::Long(myers) => myers.alignment_at(text, end_pos, max_dist, aln),
+        }
+    }
+}
+
+/// The maximum distance for the single-word implementation. Its patterns are at most 64
+/// symbols long, such that no larger distance than 64 can occur.
+fn short_dist(max_dist: usize) -> u8 {
+    max_dist.min(u8::MAX as usize) as u8
+}
+
+/// Iterator over pairs of end positions and distance of matches.
+pub enum Matches<'a, C, I>
+where
+    C: Borrow<u8>,
+    I: Iterator<Item = C>,
+{
+    Short(myers::Matches<'a, u64, C, I>),
+    Long(long::Matches<'a, u64, C, I>),
+}
+
+impl<'a, C, I> Iterator for Matches<'a, C, I>
+where
+    C: Borrow<u8>,
+    I: Iterator<Item = C>,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        match self {
+            Matches::Short(matches) => matches.next().map(|(end, dist)| (end, dist as usize)),
+            Matches::Long(matches) => matches.next(),
+        }
+    }
+}
+
+/// Iterator over tuples of starting position, end position and distance of matches.
+pub enum FullMatches<'a, C, I>
+where
+    C: Borrow<u8>,
+    I: Iterator<Item = C> + ExactSizeIterator,
+{
+    Short(myers::FullMatches<'a, u64, C, I>),
+    Long(long::FullMatches<'a, u64, C, I>),
+}
+
+impl<'a, C, I> FullMatches<'a, C, I>
+where
+    C: Borrow<u8>,
+    I: Iterator<Item = C> + ExactSizeIterator,
+{
+    /// Searches the next match and updates the given `Alignment` with its position
+    /// and alignment path if found. The distance is stored in `Alignment::score`.
+    /// If no next hit is found, `false` is returned and `aln` remains unchanged.
+    pub fn next_alignment(&mut self, aln: &mut Alignment) -> bool {
+        match self {
+            FullMatches::Short(matches) => matches.next_alignment(aln),
+            FullMatches::Long(matches) => matches.next_alignment(aln),
+        }
+    }
+}
+
+impl<'a, C, I> Iterator for FullMatches<'a, C, I>
+where
+    C: Borrow<u8>,
+    I: Iterator<Item = C> + ExactSizeIterator,
+{
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize, usize)> {
+        match self {
+            FullMatches::Short(matches) => matches
+                .next()
+                .map(|(start, end, dist)| (start, end, dist as usize)),
+            FullMatches::Long(matches) => matches.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    #[test]
+    fn test_short_and_long() {
+        let mut rng = StdRng::from_seed(&[14]);
+        for _ in 0..200 {
+            let m = rng.gen_range(1, 200);
+            let k = rng.gen_range(0, 20);
+            let pattern: Vec<u8> = (0..m).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect();
+            let text: Vec<u8> = (0..rng.gen_range(0, 400))
+                .map(|_| b"ACGT"[rng.gen_range(0, 4)])
+                .collect();
+            let mut myers = Myers::new(&pattern);
+            assert_eq!(myers.is_long(), m > 64);
+            let mut long = long::Myers::<u64>::new(&pattern);
+            assert_eq!(
+                myers.find_all_end(&text, k).collect_vec(),
+                long.find_all_end(&text, k).collect_vec()
+            );
+            assert_eq!(myers.distance(&text), long.distance(&text));
+            assert_eq!(
+                myers.find_all(&text, k).collect_vec(),
+                long.find_all(&text, k).collect_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_alignment() {
+        let text = b"CGGTCCTGAGGGATTAGCAC";
+        let mut myers = Myers::new(b"TCCTAGGGC");
+        let mut aln = Alignment::default();
+        assert!(myers.alignment_at(text, 11, 2, &mut aln));
+        assert_eq!((aln.ystart, aln.yend, aln.score), (3, 12, 2));
+
+        let mut matches = myers.find_all(text, 2);
+        assert!(matches.next_alignment(&mut aln));
+        assert_eq!((aln.ystart, aln.yend, aln.score), (3, 12, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pattern is empty")]
+    fn test_empty_pattern() {
+        Myers::new(b"");
+    }
+}
@@ -248,6 +248,93 @@ macro_rules! impl_tests {
             assert_eq!(max_dist, 64);
         }
 
+        #[test]
+        fn test_alignment_at() {
+            let text = b"CGGTCCTGAGGGATTAGCAC";
+            let pattern = b"TCCTAGGGC";
+
+            let mut myers = Myers::<$bitvec>::new(pattern);
+            let mut aln = Alignment::default();
+            assert!(myers.alignment_at(text, 11, 2, &mut aln));
+            assert_eq!((aln.ystart, aln.yend, aln.ylen, aln.score), (3, 12, 20, 2));
+            assert_eq!(
+                aln.operations,
+                &[Match, Match, Match, Match, Del, Match, Match, Match, Match, Ins]
+            );
+            // too distant, or not searched
+            assert!(!myers.alignment_at(text, 11, 1, &mut aln));
+            assert!(!myers.alignment_at(text, 20, 2, &mut aln));
+            assert_eq!((aln.ystart, aln.yend), (3, 12));
+        }
+
+        #[test]
+        fn test_random_hits_and_alignments() {
+            use crate::alignment::distance::levenshtein;
+            use rand::{Rng, SeedableRng, StdRng};
+
+            let mut rng = StdRng::from_seed(&[11]);
+            let mut aln = Alignment::default();
+            for _ in 0..200 {
+                let mut random_seq = |max_len| -> Vec<u8> {
+                    (0..rng.gen_range(1, max_len))
+                        .map(|_| b"ACGT"[rng.gen_range(0, 4)])
+                        .collect()
+                };
+                let pattern = random_seq(41);
+                let text = random_seq(100);
+                let k = rng.gen_range(0, 10);
+                let mut myers = Myers::<$bitvec>::new(&pattern);
+                let ends = myers.find_all_end(&text, k as $dist_type).collect_vec();
+                let hits = myers.find_all(&text, k as $dist_type).collect_vec();
+                assert_eq!(ends.len(), hits.len());
+                for (&(end, dist), &(start, hit_end, hit_dist)) in ends.iter().zip(&hits) {
+                    assert_eq!((hit_end, hit_dist), (end + 1, dist));
+                    assert_eq!(levenshtein(&pattern, &text[start..hit_end]), dist as u32);
+                    // no other start gives a smaller distance
+                    assert!(
+                        (0..=end).all(|s| levenshtein(&pattern, &text[s..hit_end]) >= dist as u32)
+                    );
+
+                    assert!(myers.alignment_at(&text, end, k as $dist_type, &mut aln));
+                    assert_eq!((aln.yend, aln.ylen), (end + 1, text.len()));
+                    assert_eq!(aln.score, dist as i32);
+                    // the operations have to describe an alignment with the given distance
+                    let (mut i, mut j) = (0, aln.ystart);
+                    let mut edits = 0;
+                    for op in &aln.operations {
+                        match *op {
+                            Match => {
+                                assert_eq!(pattern[i], text[j]);
+                                i += 1;
+                                j += 1;
+                            }
+                            Subst => {
+                                assert_ne!(pattern[i], text[j]);
+                                i += 1;
+                                j += 1;
+                                edits += 1;
+                            }
+                            Ins => {
+                                i += 1;
+                                edits += 1;
+                            }
+                            Del => {
+                                j += 1;
+                                edits += 1;
+                            }
+                            _ => panic!("unexpected operation {:?}", op),
+                        }
+                    }
+                    assert_eq!((i, j, edits), (pattern.len(), end + 1, dist as usize));
+                }
+                for end in 0..text.len() {
+                    if !ends.iter().any(|&(e, _)| e == end) {
+                        assert!(!myers.alignment_at(&text, end, k as $dist_type, &mut aln));
+                    }
+                }
+            }
+        }
+
         // macro end
     };
 }
@@ -5,7 +5,7 @@
 //! of the hits. Apart from that, the `Myers` object in this module provides exactly the same
 //! API as the 'simple' version `bio::pattern_matching::myers::Myers`.
 //! For short patterns, the 'simple' version is still to be preferred, as the block-based
-//! algorithm is slower.
+//! algorithm is slower. Both report the same matches and distances.
 
 use std::borrow::Borrow;
 use std::cmp::{max, min};
@@ -219,7 +219,7 @@ where
 
         let w = word_size::<T>();
         let last_dist = self.states[last_block].dist;
-        if (last_dist as i8 - carry as i8) as usize <= max_dist
+        if (last_dist as isize - carry as isize) as usize <= max_dist
             && last_block < self.max_block
             && (peq[last_block + 1].peq[a as usize] & T::one() == T::one() || carry < 0)
         {
@@ -227,7 +227,7 @@ where
             self.add_state(-carry as i8);
             advance_block(&mut self.states[last_block], &peq[last_block], a, carry);
         } else {
-            while last_block > 0 && self.states[last_block].dist >= max_dist + w {
+            while last_block > 0 && self.states[last_block].dist >= max_dist.saturating_add(w) {
                 last_block -= 1;
             }
             self.states.truncate(last_block + 1);
@@ -492,4 +492,83 @@ impl_myers!(
 #[cfg(test)]
 mod tests {
     impl_tests!(super, u8, usize, build_64);
+
+    use rand::{Rng, SeedableRng, StdRng};
+
+    /// End positions and distances of all semiglobal matches with at most `k` edits,
+    /// computed with the full DP matrix.
+    fn dp_find_all_end(pattern: &[u8], text: &[u8], k: usize) -> Vec<(usize, usize)> {
+        let mut col: Vec<usize> = (0..=pattern.len()).collect();
+        let mut hits = vec![];
+        for (j, &c) in text.iter().enumerate() {
+            let mut diag = col[0];
+            for i in 1..=pattern.len() {
+                let value = (diag + (pattern[i - 1] != c) as usize)
+                    .min(col[i] + 1)
+                    .min(col[i - 1] + 1);
+                diag = col[i];
+                col[i] = value;
+            }
+            if col[pattern.len()] <= k {
+                hits.push((j, col[pattern.len()]));
+            }
+        }
+        hits
+    }
+
+    /// A random DNA sequence with a copy of the pattern with up to `k` random edits.
+    fn random_text(rng: &mut StdRng, pattern: &[u8], k: usize) -> Vec<u8> {
+        let mut text: Vec<u8> = (0..rng.gen_range(0, 200))
+            .map(|_| b"ACGT"[rng.gen_range(0, 4)])
+            .collect();
+        let mut copy = pattern.to_vec();
+        for _ in 0..rng.gen_range(0, k + 1) {
+            let i = rng.gen_range(0, copy.len());
+            match rng.gen_range(0, 3) {
+                0 => copy[i] = b"ACGT"[rng.gen_range(0, 4)],
+                1 => copy.insert(i, b"ACGT"[rng.gen_range(0, 4)]),
+                _ => {
+                    copy.remove(i);
+                }
+            }
+        }
+        text.extend(copy);
+        text.extend((0..rng.gen_range(0, 200)).map(|_| b"ACGT"[rng.gen_range(0, 4)]));
+        text
+    }
+
+    #[test]
+    fn test_random_long_patterns() {
+        let mut rng = StdRng::from_seed(&[42]);
+        for _ in 0..40 {
+            let m = rng.gen_range(1, 501);
+            let k = rng.gen_range(0, 21);
+            let pattern: Vec<u8> = (0..m).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect();
+            let text = random_text(&mut rng, &pattern, k);
+            let expected = dp_find_all_end(&pattern, &text, k);
+            let myers = Myers::<u64>::new(&pattern);
+            assert_eq!(myers.find_all_end(&text, k).collect_vec(), expected);
+            let myers = Myers::<u8>::new(&pattern);
+            assert_eq!(myers.find_all_end(&text, k).collect_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn test_random_short_patterns() {
+        // the block-based version has to agree with the simple one
+        let mut rng = StdRng::from_seed(&[43]);
+        for _ in 0..100 {
+            let m = rng.gen_range(1, 65);
+            let k = rng.gen_range(0, 21);
+            let pattern: Vec<u8> = (0..m).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect();
+            let text = random_text(&mut rng, &pattern, k);
+            let simple = crate::pattern_matching::myers::Myers::<u64>::new(&pattern);
+            let expected = simple
+                .find_all_end(&text, k as u8)
+                .map(|(end, dist)| (end, dist as usize))
+                .collect_vec();
+            let myers = Myers::<u64>::new(&pattern);
+            assert_eq!(myers.find_all_end(&text, k).collect_vec(), expected);
+        }
+    }
 }
@@ -78,6 +78,20 @@
 //! ```
 //! Note that `u8` just used for demonstration, using `u64` is still the best in most cases.
 //!
+//! If the pattern length is not known in advance, [`auto::Myers`](auto/enum.Myers.html) uses
+//! `Myers::<u64>` for patterns that fit and `long::Myers::<u64>` for longer ones:
+//!
+//! ```
+//! # extern crate bio;
+//! use bio::pattern_matching::myers::auto;
+//!
+//! # fn main() {
+//! let text = b"CGGTCCTGAGGGATTAGCAC";
+//! let myers = auto::Myers::new(b"TCCTAGGGC");
+//! assert_eq!(myers.find_all_end(text, 2).collect::<Vec<_>>(), [(11, 2), (12, 2)]);
+//! # }
+//! ```
+//!
 //! # Obtaining the starting position of a match
 //!
 //! The `Myers::find_all` method provides an iterator over tuples of `(start, end, distance)`.
@@ -122,7 +136,7 @@
 //! let mut matches = myers.find_all(text, 3);
 //! while matches.next_alignment(&mut aln) {
 //!     //println!("Hit fond in range: {}..{} (distance: {})", aln.ystart, aln.yend, aln.score);
-//!     //println!("{}", aln.pretty(pattern, text));
+//!     //println!("{}", aln.pretty(pattern, text, 80));
 //! }
 //! # }
 //! ```
@@ -153,6 +167,9 @@
 //!
 //! </pre>
 //!
+//! If only the alignment of a hit with a known end position is needed, `Myers::alignment_at()`
+//! computes it from the part of the text that a hit within the maximum distance can span.
+//!
 //! **Note** that the [`Alignment`](../../alignment/struct.Alignment.html) instance is only created
 //! once and then reused. Because the Myers algorithm is very fast, the allocation necessary for
 //! `Alignment::operations` can have a non-negligible impact on performance; and thus, recycling
@@ -192,7 +209,7 @@
 //! // now calculate the alignment
 //! matches.alignment_at(best_end, &mut aln);
 //! println!("Best alignment at {}..{} (distance: {})", aln.ystart, aln.yend, aln.score);
-//! println!("{}", aln.pretty(pattern, text));
+//! println!("{}", aln.pretty(pattern, text, 80));
 //! # }
 //! ```
 //!
@@ -232,6 +249,7 @@
 
 #[macro_use]
 mod myers_impl;
+pub mod auto;
 mod builder;
 mod helpers;
 #[cfg(test)]
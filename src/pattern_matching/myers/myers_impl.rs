@@ -218,6 +218,37 @@ impl<T: BitVec> $Myers {
     {
         LazyMatches::new(self, text.into_iter(), max_dist)
     }
+
+    /// Updates the given `Alignment` with the position, path and distance of the hit ending at
+    /// `end_pos` (as returned by `find_all_end`), if its distance is at most `max_dist`.
+    /// Otherwise, `false` is returned and `aln` remains unchanged. Only the `m + max_dist`
+    /// symbols of the text up to the end position are searched, since no hit within the
+    /// maximum distance can be longer, thus memory does not depend on the text length.
+    pub fn alignment_at(
+        &mut self,
+        text: &[u8],
+        end_pos: usize,
+        max_dist: $DistType,
+        aln: &mut Alignment,
+    ) -> bool {
+        if end_pos >= text.len() {
+            return false;
+        }
+        let window_len = self.m.to_usize().unwrap().saturating_add(max_dist.to_usize().unwrap());
+        let window_start = (end_pos + 1).saturating_sub(window_len);
+        let mut matches = self.find_all_lazy(&text[window_start..=end_pos], max_dist);
+        // the end position is a hit if it is the last one of the window
+        match matches.by_ref().last() {
+            Some((pos, _)) if pos == end_pos - window_start => {
+                matches.alignment_at(end_pos - window_start, aln);
+                aln.ystart += window_start;
+                aln.yend += window_start;
+                aln.ylen = text.len();
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Iterator over pairs of end positions and distance of matches.
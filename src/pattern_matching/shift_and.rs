@@ -4,7 +4,8 @@
 // except according to those terms.
 
 //! `ShiftAnd` algorithm for pattern matching.
-//! Patterns may contain at most 64 symbols.
+//! Patterns may contain at most 128 symbols. Pattern positions may match classes of text
+//! symbols, e.g. IUPAC codes in primers.
 //! Complexity: O(n) with text length n.
 //!
 //! # Example
@@ -16,16 +17,25 @@
 //! let shiftand = shift_and::ShiftAnd::new(pattern);
 //! let occ = shiftand.find_all(text).next().unwrap();
 //! assert_eq!(occ, 8);
+//!
+//! let primer = b"GGCTRG";
+//! let shiftand = shift_and::ShiftAnd::with_iupac(primer);
+//! assert_eq!(shiftand.find_all(text).collect::<Vec<_>>(), [2, 12]);
 //! ```
 
 use std::borrow::Borrow;
 use std::iter::Enumerate;
+use std::slice;
+
+use crate::alphabets::dna;
+use crate::pattern_matching::Matcher;
+use crate::utils::TextSlice;
 
 /// `ShiftAnd` algorithm.
 pub struct ShiftAnd {
     m: usize,
-    masks: [u64; 256],
-    accept: u64,
+    masks: [u128; 256],
+    accept: u128,
 }
 
 impl ShiftAnd {
@@ -35,15 +45,45 @@ impl ShiftAnd {
         P::IntoIter: ExactSizeIterator,
         C: Borrow<u8>,
         P: IntoIterator<Item = C>,
+    {
+        Self::with_classes(pattern, |a| [a])
+    }
+
+    /// Create new ShiftAnd instance from a given pattern, where each pattern symbol matches
+    /// the text symbols returned by `class` for it.
+    pub fn with_classes<C, P, F, S>(pattern: P, mut class: F) -> Self
+    where
+        P::IntoIter: ExactSizeIterator,
+        C: Borrow<u8>,
+        P: IntoIterator<Item = C>,
+        F: FnMut(u8) -> S,
+        S: AsRef<[u8]>,
     {
         let pattern = pattern.into_iter();
         let m = pattern.len();
-        assert!(m <= 64, "Expecting a pattern of at most 64 symbols.");
-        let (masks, accept) = masks(pattern);
+        assert!(m <= 128, "Expecting a pattern of at most 128 symbols.");
+        let mut masks = [0; 256];
+        for (i, c) in pattern.enumerate() {
+            for &a in class(*c.borrow()).as_ref() {
+                masks[a as usize] |= 1 << i;
+            }
+        }
+        let accept = if m == 0 { 0 } else { 1 << (m - 1) };
 
         ShiftAnd { m, masks, accept }
     }
 
+    /// Create new ShiftAnd instance from a given DNA pattern with IUPAC codes, which match
+    /// the bases they represent (see [`iupac_expand`](../../alphabets/dna/fn.iupac_expand.html)).
+    pub fn with_iupac<C, P>(pattern: P) -> Self
+    where
+        P::IntoIter: ExactSizeIterator,
+        C: Borrow<u8>,
+        P: IntoIterator<Item = C>,
+    {
+        Self::with_classes(pattern, dna::iupac_expand)
+    }
+
     /// Find all matches of pattern in the given text. Matches are returned as an iterator
     /// over start positions.
     pub fn find_all<C, T>(&self, text: T) -> Matches<'_, C, T::IntoIter>
@@ -59,8 +99,20 @@ impl ShiftAnd {
     }
 }
 
-/// Calculate ShiftAnd masks. This function is called automatically when instantiating
-/// a new ShiftAnd for a given pattern.
+impl Matcher for ShiftAnd {
+    type Matches<'a> = Matches<'a, &'a u8, slice::Iter<'a, u8>>;
+
+    fn pattern_len(&self) -> usize {
+        self.m
+    }
+
+    fn find_all<'a>(&'a self, text: TextSlice<'a>) -> Self::Matches<'a> {
+        ShiftAnd::find_all(self, text)
+    }
+}
+
+/// Calculate ShiftAnd masks for a pattern of at most 64 symbols, as used by the BNDM
+/// algorithm.
 pub fn masks<C, P>(pattern: P) -> ([u64; 256], u64)
 where
     C: Borrow<u8>,
@@ -84,7 +136,7 @@ where
     T: Iterator<Item = C>,
 {
     shiftand: &'a ShiftAnd,
-    active: u64,
+    active: u128,
     text: Enumerate<T>,
 }
 
@@ -99,7 +151,7 @@ where
         for (i, c) in self.text.by_ref() {
             self.active = ((self.active << 1) | 1) & self.shiftand.masks[*c.borrow() as usize];
             if self.active & self.shiftand.accept > 0 {
-                return Some(i - self.shiftand.m + 1);
+                return Some(i + 1 - self.shiftand.m);
             }
         }
 
@@ -110,7 +162,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pattern_matching::naive_find_all;
     use itertools::Itertools;
+    use rand::{Rng, SeedableRng, StdRng};
 
     #[test]
     fn test_find_all() {
@@ -119,4 +173,66 @@ mod tests {
         let shiftand = ShiftAnd::new(pattern);
         assert_eq!(shiftand.find_all(text).collect_vec(), [8]);
     }
+
+    #[test]
+    fn test_iupac() {
+        let text = b"TTGACGTAGGGACATAGTTGATGTAGC";
+        // R matches A and G, N matches anything
+        let shiftand = ShiftAnd::with_iupac(b"TGAYRTAG");
+        assert_eq!(shiftand.find_all(&text[..]).collect_vec(), [1, 18]);
+        let shiftand = ShiftAnd::with_iupac(b"GNNAT");
+        assert_eq!(shiftand.find_all(&text[..]).collect_vec(), [10]);
+        // concrete bases only match themselves
+        let shiftand = ShiftAnd::with_iupac(b"TGACGTAG");
+        assert_eq!(shiftand.find_all(&text[..]).collect_vec(), [1]);
+    }
+
+    #[test]
+    fn test_with_classes() {
+        let shiftand = ShiftAnd::with_classes(b"ab", |a| vec![a, a.to_ascii_uppercase()]);
+        assert_eq!(shiftand.find_all(b"abAbaBABxb").collect_vec(), [0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_long_pattern() {
+        let mut rng = StdRng::from_seed(&[8]);
+        let random_seq = |rng: &mut StdRng, len| -> Vec<u8> {
+            (0..len).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect()
+        };
+        for &m in &[65, 100, 127, 128] {
+            let pattern = random_seq(&mut rng, m);
+            let mut text = random_seq(&mut rng, 300);
+            text.extend_from_slice(&pattern);
+            text.extend(random_seq(&mut rng, 50));
+            text.extend_from_slice(&pattern);
+            let shiftand = ShiftAnd::new(&pattern);
+            let occ = shiftand.find_all(&text).collect_vec();
+            assert_eq!(occ, naive_find_all(&pattern, &text));
+            assert_eq!(occ, [300, 350 + m]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 128 symbols")]
+    fn test_pattern_too_long() {
+        ShiftAnd::new(&[b'A'; 129][..]);
+    }
+
+    #[test]
+    fn test_random_short_patterns() {
+        let mut rng = StdRng::from_seed(&[9]);
+        for _ in 0..200 {
+            let pattern: Vec<u8> = (0..rng.gen_range(1, 65))
+                .map(|_| b"AC"[rng.gen_range(0, 2)])
+                .collect();
+            let text: Vec<u8> = (0..rng.gen_range(0, 200))
+                .map(|_| b"AC"[rng.gen_range(0, 2)])
+                .collect();
+            let shiftand = ShiftAnd::new(&pattern);
+            assert_eq!(
+                shiftand.find_all(&text).collect_vec(),
+                naive_find_all(&pattern, &text)
+            );
+        }
+    }
 }
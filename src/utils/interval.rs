@@ -22,7 +22,7 @@ impl<N: Ord + Clone> From<Range<N>> for Interval<N> {
     fn from(r: Range<N>) -> Self {
         match Interval::new(r) {
             Ok(interval) => interval,
-            Err(IntervalError::InvalidRange) => {
+            Err(_) => {
                 panic!("Cannot convert negative width range to interval")
             }
         }
@@ -35,7 +35,7 @@ impl<'a, N: Ord + Clone> From<&'a Range<N>> for Interval<N> {
     fn from(r: &Range<N>) -> Self {
         match Interval::new(r.clone()) {
             Ok(interval) => interval,
-            Err(IntervalError::InvalidRange) => {
+            Err(_) => {
                 panic!("Cannot convert negative width range to interval")
             }
         }
@@ -52,11 +52,14 @@ impl<N: Ord + Clone> Deref for Interval<N> {
 }
 
 quick_error! {
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq)]
     pub enum IntervalError {
         InvalidRange {
             description("An Interval must have a Range with a positive width")
         }
+        EmptyRange {
+            description("An empty Range is not allowed here")
+        }
     }
 }
 
@@ -0,0 +1,139 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bio_types::strand;
+
+/// The strand of a feature, as given in the strand column of BED and GFF files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Strand {
+    Forward,
+    Reverse,
+    #[default]
+    Unknown,
+}
+
+impl Strand {
+    /// Symbol of the strand, i.e. `+`, `-` or `.`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Strand::Forward => "+",
+            Strand::Reverse => "-",
+            Strand::Unknown => ".",
+        }
+    }
+
+    pub fn is_forward(self) -> bool {
+        self == Strand::Forward
+    }
+
+    pub fn is_reverse(self) -> bool {
+        self == Strand::Reverse
+    }
+
+    pub fn is_unknown(self) -> bool {
+        self == Strand::Unknown
+    }
+
+    /// The opposite strand. The opposite of an unknown strand is unknown.
+    pub fn opposite(self) -> Strand {
+        match self {
+            Strand::Forward => Strand::Reverse,
+            Strand::Reverse => Strand::Forward,
+            Strand::Unknown => Strand::Unknown,
+        }
+    }
+}
+
+/// Parse a strand from `+`, `-`, or `.` and `?` for an unknown strand.
+impl FromStr for Strand {
+    type Err = StrandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Strand::Forward),
+            "-" => Ok(Strand::Reverse),
+            "." | "?" => Ok(Strand::Unknown),
+            _ => Err(StrandError::InvalidSymbol(s.to_owned())),
+        }
+    }
+}
+
+/// Display the strand as its symbol. An unknown strand is displayed as `.`, also when it was
+/// parsed from `?`.
+impl fmt::Display for Strand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl From<strand::Strand> for Strand {
+    fn from(strand: strand::Strand) -> Self {
+        match strand {
+            strand::Strand::Forward => Strand::Forward,
+            strand::Strand::Reverse => Strand::Reverse,
+            strand::Strand::Unknown => Strand::Unknown,
+        }
+    }
+}
+
+impl From<Strand> for strand::Strand {
+    fn from(strand: Strand) -> Self {
+        match strand {
+            Strand::Forward => strand::Strand::Forward,
+            Strand::Reverse => strand::Strand::Reverse,
+            Strand::Unknown => strand::Strand::Unknown,
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum StrandError {
+        InvalidSymbol(symbol: String) {
+            description("invalid strand symbol")
+            display("invalid strand symbol {:?}, expected one of +, -, . or ?", symbol)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_display() {
+        for &(symbol, strand) in &[
+            ("+", Strand::Forward),
+            ("-", Strand::Reverse),
+            (".", Strand::Unknown),
+        ] {
+            assert_eq!(symbol.parse::<Strand>().unwrap(), strand);
+            assert_eq!(strand.to_string(), symbol);
+        }
+        assert_eq!("?".parse::<Strand>().unwrap(), Strand::Unknown);
+        for &symbol in &["", "++", "f", "(+)", "1"] {
+            assert_eq!(
+                symbol.parse::<Strand>(),
+                Err(StrandError::InvalidSymbol(symbol.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_opposite() {
+        assert_eq!(Strand::Forward.opposite(), Strand::Reverse);
+        assert_eq!(Strand::Reverse.opposite(), Strand::Forward);
+        assert_eq!(Strand::Unknown.opposite(), Strand::Unknown);
+        assert!(Strand::Reverse.is_reverse());
+        assert!(!Strand::Unknown.is_reverse());
+        assert!(!Strand::Unknown.is_forward());
+    }
+
+    #[test]
+    fn test_bio_types_conversion() {
+        for &strand in &[Strand::Forward, Strand::Reverse, Strand::Unknown] {
+            let converted: strand::Strand = strand.into();
+            assert_eq!(Strand::from(converted), strand);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2014-2016 Johannes Köster.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Allocation-free reading of FASTA records with `fasta::Reader::read_next`. This test lives in
+//! its own test binary because it replaces the global allocator.
+
+use bio::io::fasta::{Reader, Record};
+
+/// Counting the allocations of the current thread, to check for allocation-free reading.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+#[test]
+fn test_read_next() {
+    let mut fasta = Vec::new();
+    for i in 0..10_000 {
+        fasta.extend_from_slice(format!(">read{} desc {}\n", i % 10, i % 7).as_bytes());
+        for _ in 0..i % 5 {
+            fasta.extend_from_slice(&b"ACGTTGCAACGTGCAT"[..i % 17]);
+            fasta.push(b'\n');
+        }
+    }
+    let expected: usize = Reader::new(&fasta[..])
+        .records()
+        .map(|record| record.unwrap().seq().len())
+        .sum();
+
+    let mut reader = Reader::new(&fasta[..]);
+    let mut record = Record::new();
+    let mut bases = 0;
+    let mut records = 0;
+    // warm up the buffers with the longest record
+    while records < 100 && reader.read_next(&mut record).unwrap() {
+        bases += record.seq().len();
+        records += 1;
+    }
+    let before = allocations();
+    while reader.read_next(&mut record).unwrap() {
+        bases += record.seq().len();
+        records += 1;
+    }
+    assert_eq!(allocations(), before);
+    assert_eq!(records, 10_000);
+    assert_eq!(bases, expected);
+    assert!(record.is_empty());
+    assert!(!reader.read_next(&mut record).unwrap());
+}